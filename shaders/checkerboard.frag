@@ -0,0 +1,14 @@
+#version 450
+
+layout(location = 0) out vec4 f_color;
+
+// Size of each square, in window pixels.
+const int SQUARE_SIZE = 16;
+
+// Light and dark squares drawn behind the canvas output, so transparency can be told apart from black.
+void main()
+{
+    ivec2 square = ivec2(gl_FragCoord.xy) / SQUARE_SIZE;
+    float shade = ((square.x + square.y) & 1) == 0 ? 0.6f : 0.3f;
+    f_color = vec4(vec3(shade), 1.0f);
+}
@@ -9,6 +9,9 @@ layout(set = 0, binding = 0) uniform Uniforms {
     float u_time_delta;
     uint u_frame_num;
     uint u_num_textures;
+    vec4 u_mouse_delta;
+    vec4 u_jitter;
+    float u_aspect;
 };
 
 layout(set = 0, binding = 1) uniform CustomUniforms { bool vertical_wipe; };
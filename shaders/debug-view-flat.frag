@@ -0,0 +1,9 @@
+#version 450
+
+layout(location = 0) out vec4 f_color;
+
+// Coverage of the canvas geometry. Wireframe and overdraw views scale it with their blend state.
+void main()
+{
+    f_color = vec4(1.0f);
+}
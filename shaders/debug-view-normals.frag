@@ -0,0 +1,22 @@
+#version 450
+
+layout(location = 0) in vec2 v_uv;
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler sampler_0;
+layout(set = 0, binding = 1) uniform texture2D texture_0;
+
+float height(vec2 uv) {
+    vec3 color = texture(sampler2D(texture_0, sampler_0), uv).rgb;
+    return dot(color, vec3(0.2126f, 0.7152f, 0.0722f));
+}
+
+// Normals of the output treated as a height field of its luminance, mapped from [-1, 1] to [0, 1].
+void main()
+{
+    vec2 texel = 1.0f / vec2(textureSize(sampler2D(texture_0, sampler_0), 0));
+    float dx = height(v_uv + vec2(texel.x, 0.0f)) - height(v_uv - vec2(texel.x, 0.0f));
+    float dy = height(v_uv + vec2(0.0f, texel.y)) - height(v_uv - vec2(0.0f, texel.y));
+    vec3 normal = normalize(vec3(-dx, -dy, 2.0f * max(texel.x, texel.y) * 16.0f));
+    f_color = vec4(normal * 0.5f + 0.5f, 1.0f);
+}
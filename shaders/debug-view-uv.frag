@@ -0,0 +1,10 @@
+#version 450
+
+layout(location = 0) in vec2 v_uv;
+layout(location = 0) out vec4 f_color;
+
+// Texture coordinates of the canvas geometry as red and green.
+void main()
+{
+    f_color = vec4(fract(v_uv), 0.0f, 1.0f);
+}
@@ -0,0 +1,17 @@
+#version 450
+
+layout (location = 0) out vec2 outUV;
+
+// Full-screen triangle mirrored by the instance index: bit 0 flips horizontally, bit 1 vertically.
+void main()
+{
+    outUV = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2);
+    vec2 position = outUV * 2.0f + -1.0f;
+    if ((gl_InstanceIndex & 1) != 0) {
+        position.x = -position.x;
+    }
+    if ((gl_InstanceIndex & 2) != 0) {
+        position.y = -position.y;
+    }
+    gl_Position = vec4(position, 0.0f, 1.0f);
+}
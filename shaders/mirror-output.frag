@@ -0,0 +1,28 @@
+#version 450
+
+layout(location = 0) in vec2 v_canvas_uv;
+layout(location = 1) in vec2 v_output_uv;
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler sampler_0;
+layout(set = 0, binding = 1) uniform texture2D texture_0;
+layout(set = 0, binding = 2) uniform MirrorBlend {
+    // Width of the blend region at the left, right, top and bottom edge, in normalized output coordinates.
+    vec4 u_blend_widths;
+    // x: exponent applied to the falloff.
+    vec4 u_blend_params;
+};
+
+float edge_falloff(float distance_to_edge, float width) {
+    return width > 0.0f ? smoothstep(0.0f, width, distance_to_edge) : 1.0f;
+}
+
+void main()
+{
+    f_color = texture(sampler2D(texture_0, sampler_0), v_canvas_uv);
+    float weight = edge_falloff(v_output_uv.x, u_blend_widths.x)
+        * edge_falloff(1.0f - v_output_uv.x, u_blend_widths.y)
+        * edge_falloff(v_output_uv.y, u_blend_widths.z)
+        * edge_falloff(1.0f - v_output_uv.y, u_blend_widths.w);
+    f_color.rgb *= pow(weight, u_blend_params.x);
+}
@@ -0,0 +1,16 @@
+#version 450
+
+// Warped mesh covering a mirror window, see src/canvas/mirror.rs.
+layout(location = 0) in vec2 a_position;
+layout(location = 1) in vec2 a_canvas_uv;
+layout(location = 2) in vec2 a_output_uv;
+
+layout(location = 0) out vec2 v_canvas_uv;
+layout(location = 1) out vec2 v_output_uv;
+
+void main()
+{
+    v_canvas_uv = a_canvas_uv;
+    v_output_uv = a_output_uv;
+    gl_Position = vec4(a_position, 0.0f, 1.0f);
+}
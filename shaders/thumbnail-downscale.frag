@@ -0,0 +1,30 @@
+#version 450
+
+layout(location = 0) in vec2 v_uv;
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler sampler_0;
+layout(set = 0, binding = 1) uniform texture2D texture_0;
+
+// Most bilinear taps per axis. Each averages 2 x 2 texels, so footprints of up to 32 texels are filtered exactly,
+// larger ones are sampled more sparsely.
+const int MAX_TAPS = 16;
+
+// Box filter over all the source texels a target pixel covers, so large outputs shrink without aliasing.
+void main()
+{
+    vec2 source_size = vec2(textureSize(sampler2D(texture_0, sampler_0), 0));
+    // Extent of this pixel in the source, in UV and in texels.
+    vec2 pixel_uv = fwidth(v_uv);
+    vec2 footprint = max(pixel_uv * source_size, vec2(1.0f));
+    ivec2 taps = clamp(ivec2(ceil(footprint / 2.0f)), ivec2(1), ivec2(MAX_TAPS));
+    vec2 step_uv = pixel_uv / vec2(taps);
+    vec2 first_uv = v_uv - 0.5f * pixel_uv + 0.5f * step_uv;
+    vec4 sum = vec4(0.0f);
+    for (int y = 0; y < taps.y; y++) {
+        for (int x = 0; x < taps.x; x++) {
+            sum += texture(sampler2D(texture_0, sampler_0), first_uv + vec2(x, y) * step_uv);
+        }
+    }
+    f_color = sum / float(taps.x * taps.y);
+}
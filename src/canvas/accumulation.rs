@@ -0,0 +1,84 @@
+use super::{Canvas, PAINTING_TEXTURE_FORMAT};
+pub use crate::renderer::accumulation_blend_state;
+use crate::renderer::encode_average_blend;
+use crate::utils::texture_bytes;
+use crate::vector::UIntVector2;
+use wgpu::Extent3d;
+
+/// Running average of successive canvas frames, used to converge noisy shaders into clean stills.
+pub struct Accumulation {
+    /// Holds the average of all frames accumulated so far, in [PAINTING_TEXTURE_FORMAT].
+    pub texture: wgpu::Texture,
+    pub size: UIntVector2,
+    /// Number of frames averaged into [Self::texture].
+    pub samples: u32,
+}
+
+impl Canvas {
+    /// Accumulation only runs while time is frozen and jitter varies the samples, otherwise the average would blur motion.
+    pub fn accumulation_active(&self) -> bool {
+        self.accumulation_enabled && self.time_frozen && self.jitter_enabled && !self.jitter_frozen
+    }
+
+    /// Throw away the accumulated frames. The next frame starts a new average.
+    pub fn reset_accumulation(&mut self) {
+        if self.accumulation.take().is_some() {
+            self.vram.set("Accumulation", 0);
+        }
+    }
+
+    /// Blend `frame` into the running average, creating the accumulation texture first if needed.
+    /// Returns the texture holding the updated average.
+    pub fn accumulate_frame(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        frame: &wgpu::TextureView,
+    ) -> &wgpu::Texture {
+        let size = self.render_size();
+        let stale = match &self.accumulation {
+            Some(accumulation) => accumulation.size.x != size.x || accumulation.size.y != size.y,
+            None => true,
+        };
+        if stale {
+            let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+                size: Extent3d {
+                    width: size.x,
+                    height: size.y,
+                    depth_or_array_layers: 1,
+                },
+                format: PAINTING_TEXTURE_FORMAT,
+                usage: wgpu::TextureUsage::RENDER_ATTACHMENT
+                    | wgpu::TextureUsage::SAMPLED
+                    | wgpu::TextureUsage::COPY_SRC,
+                label: Some("Accumulation"),
+                dimension: wgpu::TextureDimension::D2,
+                mip_level_count: 1,
+                sample_count: 1,
+            });
+            self.accumulation = Some(Accumulation {
+                texture,
+                size,
+                samples: 0,
+            });
+            self.vram.set(
+                "Accumulation",
+                texture_bytes(size.x, size.y, PAINTING_TEXTURE_FORMAT),
+            );
+        }
+
+        let accumulation = self.accumulation.as_mut().unwrap();
+        let view = accumulation
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        encode_average_blend(
+            &self.device,
+            encoder,
+            &self.accumulation_pipeline,
+            frame,
+            &view,
+            accumulation.samples,
+        );
+        accumulation.samples += 1;
+        &accumulation.texture
+    }
+}
@@ -0,0 +1,90 @@
+use super::{Canvas, VS_MODULE_BYTES};
+use crate::utils::compile_builtin_shader;
+
+static CHECKERBOARD_FS_SOURCE: &str = include_str!("../../shaders/checkerboard.frag");
+
+/// Draws a checkerboard behind the canvas output in its window, so transparent areas are visible.
+/// Only affects the canvas window, paintings, movies, mirrors and clipboard copies keep their true alpha.
+pub struct CheckerboardPipelines {
+    pub checkerboard: wgpu::RenderPipeline,
+    /// Draws the output over the checkerboard by its alpha.
+    pub over: wgpu::RenderPipeline,
+}
+
+impl CheckerboardPipelines {
+    fn new(device: &wgpu::Device, sc_tex_format: wgpu::TextureFormat) -> CheckerboardPipelines {
+        let vs_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Vertex Shader"),
+            source: wgpu::util::make_spirv(VS_MODULE_BYTES),
+            flags: wgpu::ShaderFlags::VALIDATION,
+        });
+        let fs_data = compile_builtin_shader(
+            CHECKERBOARD_FS_SOURCE,
+            shaderc::ShaderKind::Fragment,
+            "checkerboard.frag",
+        )
+        .expect("Error compiling checkerboard fragment shader.");
+        let fs_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Checkerboard Fragment Shader"),
+            source: wgpu::util::make_spirv(&fs_data),
+            flags: wgpu::ShaderFlags::VALIDATION,
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Checkerboard Pipeline Layout"),
+            push_constant_ranges: &[],
+            bind_group_layouts: &[],
+        });
+        let checkerboard = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Checkerboard Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &vs_module,
+                entry_point: "main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &fs_module,
+                entry_point: "main",
+                targets: &[wgpu::ColorTargetState {
+                    format: sc_tex_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrite::ALL,
+                }],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+        });
+        CheckerboardPipelines {
+            checkerboard,
+            over: crate::utils::create_blit_pipeline(
+                device,
+                &vs_module,
+                sc_tex_format,
+                wgpu::BlendState::ALPHA_BLENDING,
+            ),
+        }
+    }
+}
+
+impl Canvas {
+    /// Show a checkerboard behind the output in the canvas window, or the output as is.
+    pub fn set_checkerboard(&mut self, enabled: bool) {
+        self.checkerboard =
+            match enabled {
+                true => Some(self.checkerboard.take().unwrap_or_else(|| {
+                    CheckerboardPipelines::new(&self.device, self.sc_desc.format)
+                })),
+                false => None,
+            };
+        self.request_paused_redraw();
+    }
+}
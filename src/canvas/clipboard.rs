@@ -0,0 +1,168 @@
+use super::{Canvas, VS_MODULE_BYTES};
+use crate::texture::default_color_sampler;
+use crate::vector::UIntVector2;
+use futures::executor::block_on;
+use log::{error, info};
+use std::borrow::Cow;
+use std::num::NonZeroU32;
+use wgpu::{BindGroupDescriptor, BindGroupEntry, BindingResource, Extent3d, LoadOp, Operations};
+
+/// Clipboard images are 8-bit RGBA, stored as displayed.
+static CLIPBOARD_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Copy of one frame of the output, waiting for the GPU to finish before it can be read.
+pub struct ClipboardCopy {
+    buffer: wgpu::Buffer,
+    size: UIntVector2,
+    /// Buffer rows are padded to [wgpu::COPY_BYTES_PER_ROW_ALIGNMENT].
+    padded_row_bytes: u32,
+}
+
+impl Canvas {
+    /// Put the next frame shown on screen on the clipboard. Also works while paused, the current frame is rendered again.
+    pub fn request_clipboard_copy(&mut self) {
+        self.clipboard_copy_requested = true;
+        if self.paused {
+            self.window.request_redraw();
+        }
+    }
+
+    /// Encode a copy of `output` at the on-screen render size, if one was requested.
+    /// Pass the result to [Self::finish_clipboard_copy] once the encoder has been submitted.
+    pub fn encode_clipboard_copy(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        output: &wgpu::TextureView,
+    ) -> Option<ClipboardCopy> {
+        if !std::mem::replace(&mut self.clipboard_copy_requested, false) {
+            return None;
+        }
+        let size = self.render_size();
+        let extent = Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        };
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Clipboard Copy"),
+            size: extent,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: CLIPBOARD_TEXTURE_FORMAT,
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+        });
+        let row_bytes = size.x * 4;
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row_bytes = row_bytes.div_ceil(alignment) * alignment;
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Clipboard Copy Buffer"),
+            size: (padded_row_bytes * size.y) as u64,
+            usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        // The output is in the canvas' render format, convert it to the clipboard's by drawing it.
+        let vs_module = self
+            .device
+            .create_shader_module(&wgpu::ShaderModuleDescriptor {
+                label: Some("Vertex Shader"),
+                source: wgpu::util::make_spirv(VS_MODULE_BYTES),
+                flags: wgpu::ShaderFlags::VALIDATION,
+            });
+        let pipeline = crate::utils::create_swap_chain_pipeline(
+            &self.device,
+            &vs_module,
+            CLIPBOARD_TEXTURE_FORMAT,
+        );
+        let sampler = default_color_sampler(&self.device);
+        let bind_group = self.device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Clipboard Copy Bind Group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Sampler(&sampler),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::TextureView(output),
+                },
+            ],
+        });
+        {
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Clipboard Copy Render Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(self.clear_color),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.set_pipeline(&pipeline);
+            render_pass.draw(0..3, 0..1);
+        }
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                texture: &texture,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    bytes_per_row: NonZeroU32::new(padded_row_bytes),
+                    offset: 0,
+                    rows_per_image: NonZeroU32::new(size.y),
+                },
+            },
+            extent,
+        );
+        Some(ClipboardCopy {
+            buffer,
+            size,
+            padded_row_bytes,
+        })
+    }
+
+    /// Wait for the copied frame and put it on the system clipboard.
+    pub fn finish_clipboard_copy(&self, copy: ClipboardCopy) {
+        let ClipboardCopy {
+            buffer,
+            size,
+            padded_row_bytes,
+        } = copy;
+        let slice = buffer.slice(..);
+        let mapping = slice.map_async(wgpu::MapMode::Read);
+        self.device.poll(wgpu::Maintain::Wait);
+        if let Err(e) = block_on(mapping) {
+            error!("Error reading back frame for clipboard: {}", e);
+            return;
+        }
+        let row_bytes = (size.x * 4) as usize;
+        let mut pixels = Vec::with_capacity(row_bytes * size.y as usize);
+        {
+            let mapped = slice.get_mapped_range();
+            for row in mapped.chunks(padded_row_bytes as usize) {
+                pixels.extend_from_slice(&row[..row_bytes]);
+            }
+        }
+        buffer.unmap();
+
+        let image = arboard::ImageData {
+            width: size.x as usize,
+            height: size.y as usize,
+            bytes: Cow::Owned(pixels),
+        };
+        match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_image(image)) {
+            Ok(()) => info!("Copied {} x {} frame to clipboard", size.x, size.y),
+            Err(e) => error!("Error copying frame to clipboard: {}", e),
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use super::{
+    accumulation_blend_state, Canvas, CanvasMessage, RENDER_TEXTURE_FORMAT, VS_MODULE_BYTES,
+};
+use crate::texture::default_color_sampler;
+use log::info;
+use wgpu::{BindGroupDescriptor, BindGroupEntry, BindingResource, Extent3d, LoadOp, Operations};
+
+/// How the reference frame is shown against the live render.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CompareMode {
+    /// The reference covers the canvas from the left edge up to a vertical line.
+    Wipe,
+    /// The reference is blended over the whole canvas.
+    OnionSkin,
+}
+
+impl CompareMode {
+    pub const ALL: [CompareMode; 2] = [CompareMode::Wipe, CompareMode::OnionSkin];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CompareMode::Wipe => "Wipe",
+            CompareMode::OnionSkin => "Onion Skin",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CompareSettings {
+    pub mode: CompareMode,
+    /// For [CompareMode::Wipe] the fraction of the width showing the reference,
+    /// for [CompareMode::OnionSkin] the opacity of the reference.
+    pub amount: f32,
+}
+
+/// Frame captured from the on-screen output to compare later tweaks against.
+/// Only drawn to the canvas window, so paintings, movies, mirrors and clipboard copies never include it.
+pub struct ReferenceFrame {
+    texture: wgpu::Texture,
+    copy_pipeline: wgpu::RenderPipeline,
+    /// Mixes in the reference by the blend constant, like the master fade.
+    blend_pipeline: wgpu::RenderPipeline,
+}
+
+impl Canvas {
+    /// Store the next frame shown on screen as the reference. Also works while paused.
+    pub fn request_reference_capture(&mut self) {
+        self.reference_capture_requested = true;
+        self.request_paused_redraw();
+    }
+
+    pub fn clear_reference(&mut self) {
+        self.reference = None;
+        self.vram.set("Reference frame", 0);
+        self.request_paused_redraw();
+    }
+
+    /// Show the reference over the live render, or hide it with `None`.
+    pub fn set_compare(&mut self, compare: Option<CompareSettings>) {
+        self.compare = compare;
+        self.request_paused_redraw();
+    }
+
+    /// Copy `output` into the reference frame if a capture was requested.
+    pub fn encode_reference_capture(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        output: &wgpu::TextureView,
+    ) {
+        if !std::mem::replace(&mut self.reference_capture_requested, false) {
+            return;
+        }
+        let size = self.render_size();
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Reference Frame"),
+            size: Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: RENDER_TEXTURE_FORMAT,
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+        });
+        let reference = match self.reference.take() {
+            // Pipelines don't depend on the size, so keep them from the previous capture.
+            Some(previous) => ReferenceFrame {
+                texture,
+                ..previous
+            },
+            None => {
+                let vs_module = self
+                    .device
+                    .create_shader_module(&wgpu::ShaderModuleDescriptor {
+                        label: Some("Vertex Shader"),
+                        source: wgpu::util::make_spirv(VS_MODULE_BYTES),
+                        flags: wgpu::ShaderFlags::VALIDATION,
+                    });
+                ReferenceFrame {
+                    texture,
+                    copy_pipeline: crate::utils::create_swap_chain_pipeline(
+                        &self.device,
+                        &vs_module,
+                        RENDER_TEXTURE_FORMAT,
+                    ),
+                    blend_pipeline: crate::utils::create_blit_pipeline(
+                        &self.device,
+                        &vs_module,
+                        RENDER_TEXTURE_FORMAT,
+                        accumulation_blend_state(),
+                    ),
+                }
+            }
+        };
+        let view = reference
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = self.compare_bind_group(&reference.copy_pipeline, output);
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Reference Capture Render Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(self.clear_color),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.set_pipeline(&reference.copy_pipeline);
+            render_pass.draw(0..3, 0..1);
+        }
+        self.vram.set(
+            "Reference frame",
+            crate::utils::texture_bytes(size.x, size.y, RENDER_TEXTURE_FORMAT),
+        );
+        info!("Captured {} x {} reference frame", size.x, size.y);
+        self.transmitter
+            .send(CanvasMessage::ReferenceCaptured(size))
+            .unwrap();
+        self.reference = Some(reference);
+    }
+
+    /// Write `input` with the reference overlaid into a new texture, if comparing.
+    /// Returns `None` when there is nothing to overlay, in which case `input` should be shown as is.
+    pub fn encode_reference_compare(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::TextureView,
+    ) -> Option<wgpu::TextureView> {
+        let (reference, compare) = match (&self.reference, self.compare) {
+            (Some(reference), Some(compare)) => (reference, compare),
+            _ => return None,
+        };
+        let size = self.render_size();
+        let output = self
+            .device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("Reference Compare"),
+                size: Extent3d {
+                    width: size.x,
+                    height: size.y,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: RENDER_TEXTURE_FORMAT,
+                usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let reference_view = reference
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let live_bind_group = self.compare_bind_group(&reference.copy_pipeline, input);
+        // Bind groups have to outlive the render pass using them.
+        let reference_pipeline = match compare.mode {
+            CompareMode::Wipe => &reference.copy_pipeline,
+            CompareMode::OnionSkin => &reference.blend_pipeline,
+        };
+        let reference_bind_group = self.compare_bind_group(reference_pipeline, &reference_view);
+        let amount = compare.amount.clamp(0.0, 1.0);
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Reference Compare Render Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &output,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(self.clear_color),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_bind_group(0, &live_bind_group, &[]);
+            render_pass.set_pipeline(&reference.copy_pipeline);
+            render_pass.draw(0..3, 0..1);
+
+            // A reference of another size is stretched to the current one.
+            match compare.mode {
+                CompareMode::Wipe => {
+                    let width = (size.x as f32 * amount).round() as u32;
+                    if width > 0 {
+                        render_pass.set_scissor_rect(0, 0, width, size.y);
+                        render_pass.set_bind_group(0, &reference_bind_group, &[]);
+                        render_pass.draw(0..3, 0..1);
+                    }
+                }
+                CompareMode::OnionSkin => {
+                    let level = amount as f64;
+                    render_pass.set_blend_constant(wgpu::Color {
+                        r: level,
+                        g: level,
+                        b: level,
+                        a: level,
+                    });
+                    render_pass.set_bind_group(0, &reference_bind_group, &[]);
+                    render_pass.set_pipeline(&reference.blend_pipeline);
+                    render_pass.draw(0..3, 0..1);
+                }
+            }
+        }
+        Some(output)
+    }
+
+    fn compare_bind_group(
+        &self,
+        pipeline: &wgpu::RenderPipeline,
+        view: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        let sampler = default_color_sampler(&self.device);
+        self.device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Reference Compare Bind Group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Sampler(&sampler),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::TextureView(view),
+                },
+            ],
+        })
+    }
+}
@@ -0,0 +1,317 @@
+use super::{Canvas, RENDER_TEXTURE_FORMAT, VS_MODULE_BYTES};
+use crate::texture::default_color_sampler;
+use crate::utils::compile_builtin_shader;
+use log::warn;
+use wgpu::{
+    BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor, BindGroupLayoutEntry,
+    BindingResource, BlendState, Extent3d, LoadOp, Operations,
+};
+
+static DEBUG_FLAT_FS_SOURCE: &str = include_str!("../../shaders/debug-view-flat.frag");
+static DEBUG_UV_FS_SOURCE: &str = include_str!("../../shaders/debug-view-uv.frag");
+static DEBUG_NORMALS_FS_SOURCE: &str = include_str!("../../shaders/debug-view-normals.frag");
+/// Brightness each draw over a pixel adds in [DebugView::Overdraw].
+const OVERDRAW_STEP: f64 = 0.25;
+
+/// Alternative views of the canvas for debugging geometry and shading. Only shown in the canvas window.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DebugView {
+    Off,
+    /// Edges of the canvas geometry drawn over the output. Requires [wgpu::Features::NON_FILL_POLYGON_MODE].
+    Wireframe,
+    /// Normals of the output's luminance as a height field.
+    Normals,
+    /// Texture coordinates of the canvas geometry.
+    Uv,
+    /// Number of times each pixel is shaded, a quarter brighter per draw.
+    Overdraw,
+}
+
+impl DebugView {
+    pub const ALL: [DebugView; 5] = [
+        DebugView::Off,
+        DebugView::Wireframe,
+        DebugView::Normals,
+        DebugView::Uv,
+        DebugView::Overdraw,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DebugView::Off => "Off",
+            DebugView::Wireframe => "Wireframe",
+            DebugView::Normals => "Normals",
+            DebugView::Uv => "UV",
+            DebugView::Overdraw => "Overdraw",
+        }
+    }
+}
+
+/// Built the first time a debug view is selected.
+pub struct DebugViewPipelines {
+    copy: wgpu::RenderPipeline,
+    /// Only exists if the device supports line polygon mode.
+    wireframe: Option<wgpu::RenderPipeline>,
+    overdraw: wgpu::RenderPipeline,
+    uv: wgpu::RenderPipeline,
+    normals: wgpu::RenderPipeline,
+}
+
+impl DebugViewPipelines {
+    fn new(device: &wgpu::Device) -> DebugViewPipelines {
+        let vs_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Vertex Shader"),
+            source: wgpu::util::make_spirv(VS_MODULE_BYTES),
+            flags: wgpu::ShaderFlags::VALIDATION,
+        });
+        let fs_module = |source: &str, name: &str| {
+            let data = compile_builtin_shader(source, shaderc::ShaderKind::Fragment, name)
+                .expect("Error compiling debug view fragment shader.");
+            device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+                label: Some(name),
+                source: wgpu::util::make_spirv(&data),
+                flags: wgpu::ShaderFlags::VALIDATION,
+            })
+        };
+        let flat_module = fs_module(DEBUG_FLAT_FS_SOURCE, "debug-view-flat.frag");
+        let uv_module = fs_module(DEBUG_UV_FS_SOURCE, "debug-view-uv.frag");
+        let normals_module = fs_module(DEBUG_NORMALS_FS_SOURCE, "debug-view-normals.frag");
+        let replace = BlendState {
+            color: wgpu::BlendComponent::REPLACE,
+            alpha: wgpu::BlendComponent::REPLACE,
+        };
+        let additive_component = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Constant,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+        let additive = BlendState {
+            color: additive_component,
+            alpha: additive_component,
+        };
+        let wireframe = match device
+            .features()
+            .contains(wgpu::Features::NON_FILL_POLYGON_MODE)
+        {
+            true => Some(create_debug_pipeline(
+                device,
+                &vs_module,
+                &flat_module,
+                replace,
+                wgpu::PolygonMode::Line,
+            )),
+            false => None,
+        };
+        DebugViewPipelines {
+            copy: crate::utils::create_swap_chain_pipeline(
+                device,
+                &vs_module,
+                RENDER_TEXTURE_FORMAT,
+            ),
+            wireframe,
+            overdraw: create_debug_pipeline(
+                device,
+                &vs_module,
+                &flat_module,
+                additive,
+                wgpu::PolygonMode::Fill,
+            ),
+            uv: create_debug_pipeline(
+                device,
+                &vs_module,
+                &uv_module,
+                replace,
+                wgpu::PolygonMode::Fill,
+            ),
+            normals: create_debug_pipeline(
+                device,
+                &vs_module,
+                &normals_module,
+                replace,
+                wgpu::PolygonMode::Fill,
+            ),
+        }
+    }
+}
+
+impl Canvas {
+    /// Whether [DebugView::Wireframe] can be shown on this device.
+    pub fn wireframe_supported(&self) -> bool {
+        self.device
+            .features()
+            .contains(wgpu::Features::NON_FILL_POLYGON_MODE)
+    }
+
+    pub fn set_debug_view(&mut self, view: DebugView) {
+        if view == DebugView::Wireframe && !self.wireframe_supported() {
+            warn!("Wireframe view is not supported by this device.");
+            return;
+        }
+        if view != DebugView::Off && self.debug_view_pipelines.is_none() {
+            self.debug_view_pipelines = Some(DebugViewPipelines::new(&self.device));
+        }
+        self.debug_view = view;
+        self.request_paused_redraw();
+    }
+
+    /// Write the selected debug view of `input` into a new texture.
+    /// Returns `None` when no debug view is selected, in which case `input` should be shown as is.
+    pub fn encode_debug_view(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::TextureView,
+    ) -> Option<wgpu::TextureView> {
+        let pipelines = match (self.debug_view, &self.debug_view_pipelines) {
+            (DebugView::Off, _) | (_, None) => return None,
+            (_, Some(pipelines)) => pipelines,
+        };
+        let size = self.render_size();
+        let output = self
+            .device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("Debug View"),
+                size: Extent3d {
+                    width: size.x,
+                    height: size.y,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: RENDER_TEXTURE_FORMAT,
+                usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        // Every pipeline has the blit layout, so the input is bound even where it's unused.
+        let sampler = default_color_sampler(&self.device);
+        let bind_group = |pipeline: &wgpu::RenderPipeline| {
+            self.device.create_bind_group(&BindGroupDescriptor {
+                label: Some("Debug View Bind Group"),
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::Sampler(&sampler),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::TextureView(input),
+                    },
+                ],
+            })
+        };
+        let pipeline = match self.debug_view {
+            DebugView::Off => return None,
+            DebugView::Wireframe => &pipelines.copy,
+            DebugView::Normals => &pipelines.normals,
+            DebugView::Uv => &pipelines.uv,
+            DebugView::Overdraw => &pipelines.overdraw,
+        };
+        let main_bind_group = bind_group(pipeline);
+        let wireframe_bind_group = pipelines.wireframe.as_ref().map(bind_group);
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Debug View Render Pass"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: &output,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        if self.debug_view == DebugView::Overdraw {
+            render_pass.set_blend_constant(wgpu::Color {
+                r: OVERDRAW_STEP,
+                g: OVERDRAW_STEP,
+                b: OVERDRAW_STEP,
+                a: 1.0,
+            });
+        }
+        render_pass.set_bind_group(0, &main_bind_group, &[]);
+        render_pass.set_pipeline(pipeline);
+        render_pass.draw(0..3, 0..1);
+        // The wireframe is drawn over a copy of the output.
+        if let (DebugView::Wireframe, Some(wireframe), Some(wireframe_bind_group)) =
+            (self.debug_view, &pipelines.wireframe, &wireframe_bind_group)
+        {
+            render_pass.set_bind_group(0, wireframe_bind_group, &[]);
+            render_pass.set_pipeline(wireframe);
+            render_pass.draw(0..3, 0..1);
+        }
+        drop(render_pass);
+        Some(output)
+    }
+}
+
+/// Pipeline drawing the canvas geometry with a debug fragment shader, using the same bind group layout as
+/// [crate::utils::create_blit_pipeline].
+fn create_debug_pipeline(
+    device: &wgpu::Device,
+    vs_module: &wgpu::ShaderModule,
+    fs_module: &wgpu::ShaderModule,
+    blend: BlendState,
+    polygon_mode: wgpu::PolygonMode,
+) -> wgpu::RenderPipeline {
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Debug View Pipeline Layout"),
+        push_constant_ranges: &[],
+        bind_group_layouts: &[
+            &device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        count: None,
+                        visibility: wgpu::ShaderStage::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler {
+                            filtering: true,
+                            comparison: false,
+                        },
+                    },
+                    BindGroupLayoutEntry {
+                        binding: 1,
+                        count: None,
+                        visibility: wgpu::ShaderStage::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                    },
+                ],
+            }),
+        ],
+    });
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Debug View Pipeline"),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: vs_module,
+            entry_point: "main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: fs_module,
+            entry_point: "main",
+            targets: &[wgpu::ColorTargetState {
+                format: RENDER_TEXTURE_FORMAT,
+                blend: Some(blend),
+                write_mask: wgpu::ColorWrite::ALL,
+            }],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            cull_mode: None,
+            polygon_mode,
+            ..Default::default()
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+    })
+}
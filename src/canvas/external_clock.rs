@@ -0,0 +1,80 @@
+use super::Canvas;
+use log::{info, warn};
+use std::net::UdpSocket;
+
+/// Frames are advanced by ticks received over UDP instead of the local timer, so several instances can run in lockstep.
+/// Any datagram is a tick. One whose payload is a number, e.g. `12.5`, also sets the time to that many seconds,
+/// which lets instances that joined late line up with the rest.
+pub struct ExternalClock {
+    socket: UdpSocket,
+    /// Time added per tick, unit: seconds.
+    tick_duration: f32,
+    /// Unit: seconds
+    time: f32,
+}
+
+impl ExternalClock {
+    /// Listen for ticks on the given UDP port. `rate` is the expected number of ticks per second.
+    pub fn bind(port: u16, rate: f32) -> std::io::Result<ExternalClock> {
+        let socket = UdpSocket::bind(("0.0.0.0", port))?;
+        socket.set_nonblocking(true)?;
+        Ok(ExternalClock {
+            socket,
+            tick_duration: 1.0 / rate.max(f32::EPSILON),
+            time: 0.0,
+        })
+    }
+
+    /// Consume every tick received since the last call. Returns whether there were any.
+    fn poll(&mut self) -> bool {
+        let mut buf = [0u8; 64];
+        let mut ticked = false;
+        loop {
+            match self.socket.recv(&mut buf) {
+                Ok(len) => {
+                    ticked = true;
+                    let payload = std::str::from_utf8(&buf[..len]).unwrap_or("");
+                    match payload.trim().parse::<f32>() {
+                        Ok(time) => self.time = time,
+                        Err(_) => self.time += self.tick_duration,
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    warn!("Error receiving external clock tick: {}", e);
+                    break;
+                }
+            }
+        }
+        ticked
+    }
+
+    /// Unit: seconds
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+}
+
+impl Canvas {
+    /// Stop free-running and render a frame per tick received on `port`, see [ExternalClock].
+    pub fn sync_to_external_clock(&mut self, port: u16, rate: f32) -> std::io::Result<()> {
+        self.external_clock = Some(ExternalClock::bind(port, rate)?);
+        info!(
+            "Waiting for external clock ticks on UDP port {}, {} per second.",
+            port, rate
+        );
+        Ok(())
+    }
+
+    pub fn external_clock_active(&self) -> bool {
+        self.external_clock.is_some()
+    }
+
+    /// Whether the external clock ticked since the last call, in which case a frame should be rendered.
+    pub fn external_clock_ticked(&mut self) -> bool {
+        match self.external_clock.as_mut() {
+            Some(clock) => clock.poll(),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use super::{accumulation_blend_state, Canvas, RENDER_TEXTURE_FORMAT};
+use crate::texture::default_color_sampler;
+use std::num::NonZeroU32;
+use std::time::Instant;
+use wgpu::{
+    BindGroupDescriptor, BindGroupEntry, BindingResource, Extent3d, ImageCopyTexture,
+    ImageDataLayout, LoadOp, Operations, Origin3d,
+};
+
+/// Fades the final on-screen output towards a solid color, e.g. to end a live set.
+pub struct MasterFade {
+    /// 0 shows the canvas, 1 shows only [Self::color].
+    pub level: f32,
+    target: f32,
+    /// Change in [Self::level] per second.
+    rate: f32,
+    last_step: Instant,
+    /// 1x1 texture holding the fade color, blended over the output.
+    color_texture: wgpu::Texture,
+    /// Copies the output into the fade target before the color is blended over it.
+    copy_pipeline: wgpu::RenderPipeline,
+    blend_pipeline: wgpu::RenderPipeline,
+}
+
+impl MasterFade {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        vs_module: &wgpu::ShaderModule,
+        render_format: wgpu::TextureFormat,
+    ) -> MasterFade {
+        let color_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Master Fade Color"),
+            size: Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: RENDER_TEXTURE_FORMAT,
+            usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+        });
+        let fade = MasterFade {
+            level: 0.0,
+            target: 0.0,
+            rate: 0.0,
+            last_step: Instant::now(),
+            color_texture,
+            copy_pipeline: crate::utils::create_swap_chain_pipeline(
+                device,
+                vs_module,
+                render_format,
+            ),
+            // Mixes in the color by the blend constant, just like accumulation mixes in new frames.
+            blend_pipeline: crate::utils::create_blit_pipeline(
+                device,
+                vs_module,
+                render_format,
+                accumulation_blend_state(),
+            ),
+        };
+        fade.set_color([0.0, 0.0, 0.0], queue);
+        fade
+    }
+
+    pub fn set_color(&self, color: [f32; 3], queue: &wgpu::Queue) {
+        let texel: Vec<u8> = [color[0], color[1], color[2], 1.0]
+            .iter()
+            .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+            .collect();
+        queue.write_texture(
+            ImageCopyTexture {
+                origin: Origin3d::ZERO,
+                mip_level: 0,
+                texture: &self.color_texture,
+            },
+            &texel,
+            ImageDataLayout {
+                bytes_per_row: NonZeroU32::new(4),
+                offset: 0,
+                rows_per_image: NonZeroU32::new(1),
+            },
+            Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// Level the fade is heading towards, or holding.
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// Move [Self::level] to `target` over `duration` seconds. A zero duration jumps straight there.
+    pub fn fade_to(&mut self, target: f32, duration: f32) {
+        self.target = target.clamp(0.0, 1.0);
+        if duration <= 0.0 {
+            self.level = self.target;
+        }
+        self.rate = (self.target - self.level).abs() / duration.max(f32::EPSILON);
+        self.last_step = Instant::now();
+    }
+
+    /// Advance towards the target by the real time passed, independent of pause and frozen time.
+    /// Returns whether the level changed.
+    pub fn step(&mut self) -> bool {
+        let now = Instant::now();
+        let delta = now.duration_since(self.last_step).as_secs_f32();
+        self.last_step = now;
+        if self.level == self.target {
+            return false;
+        }
+        let step = self.rate * delta;
+        if (self.target - self.level).abs() <= step {
+            self.level = self.target;
+        } else if self.target > self.level {
+            self.level += step;
+        } else {
+            self.level -= step;
+        }
+        true
+    }
+}
+
+impl Canvas {
+    /// Write `input` with the fade color mixed over it into `output`.
+    pub fn apply_master_fade(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::TextureView,
+        output: &wgpu::TextureView,
+    ) {
+        let fade = &self.master_fade;
+        let sampler = default_color_sampler(&self.device);
+        let color_view = fade
+            .color_texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = |pipeline: &wgpu::RenderPipeline, view: &wgpu::TextureView| {
+            self.device.create_bind_group(&BindGroupDescriptor {
+                label: Some("Master Fade Bind Group"),
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::Sampler(&sampler),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::TextureView(view),
+                    },
+                ],
+            })
+        };
+        let copy_bind_group = bind_group(&fade.copy_pipeline, input);
+        let blend_bind_group = bind_group(&fade.blend_pipeline, &color_view);
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Master Fade Render Pass"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(self.clear_color),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_bind_group(0, &copy_bind_group, &[]);
+        render_pass.set_pipeline(&fade.copy_pipeline);
+        render_pass.draw(0..3, 0..1);
+
+        let level = fade.level as f64;
+        render_pass.set_blend_constant(wgpu::Color {
+            r: level,
+            g: level,
+            b: level,
+            a: level,
+        });
+        render_pass.set_bind_group(0, &blend_bind_group, &[]);
+        render_pass.set_pipeline(&fade.blend_pipeline);
+        render_pass.draw(0..3, 0..1);
+    }
+}
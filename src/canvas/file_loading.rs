@@ -1,10 +1,14 @@
-use crate::uniforms::load_uniforms_from_json;
+use crate::uniforms::{load_ramps_from_json, load_uniforms_from_json};
 use std::sync::mpsc::channel;
 
 use super::message::CanvasMessage;
-use super::{Canvas, PAINTING_TEXTURE_FORMAT, RENDER_TEXTURE_FORMAT, VS_MODULE_BYTES};
+use super::{Canvas, PAINTING_TEXTURE_FORMAT, VS_MODULE_BYTES};
 use crate::postprocessing::PostProcess;
 use crate::recording::MOVIE_TEXTURE_FORMAT;
+use crate::renderer::create_texture_bindings;
+use crate::texture::AssetTexture;
+use crate::vector::{IntVector2, Vector4};
+use image::GenericImageView;
 use log::{error, info, warn};
 use notify::{DebouncedEvent, Watcher};
 
@@ -15,72 +19,8 @@ impl Canvas {
         match event {
             DebouncedEvent::Create(path_buf) | DebouncedEvent::Write(path_buf) => {
                 let file = path_buf.to_str().unwrap();
-                let fs_spirv_data = match crate::utils::load_shader(file) {
-                    Ok(data) => data,
-                    Err(e) => {
-                        error!("Error compiling shader: {}", e);
-                        self.transmitter
-                            .send(CanvasMessage::ShaderCompilationFailed(e.to_string()))
-                            .unwrap();
-                        return;
-                    }
-                };
-                let fs_module = self
-                    .device
-                    .create_shader_module(&wgpu::ShaderModuleDescriptor {
-                        label: Some("Vertex Shader"),
-                        source: wgpu::util::make_spirv(&fs_spirv_data),
-                        flags: wgpu::ShaderFlags::VALIDATION,
-                    });
-                let vs_module = self
-                    .device
-                    .create_shader_module(&wgpu::ShaderModuleDescriptor {
-                        label: Some("Vertex Shader"),
-                        source: wgpu::util::make_spirv(VS_MODULE_BYTES),
-                        flags: wgpu::ShaderFlags::VALIDATION,
-                    });
-
-                let layouts = [&self.bind_group_layouts[0], &self.bind_group_layouts[1]];
-                // let mut constants_for_pipeline = vec![];
-                // if let Some(constants) = self.push_constants.as_ref() {
-                //     let mut size = 0;
-                //     for a_constant in constants {
-                //         size += a_constant.size();
-                //     }
-                //     constants_for_pipeline.push(wgpu::PushConstantRange {
-                //         stages: wgpu::ShaderStage::FRAGMENT,
-                //         range: 0..(size as u32),
-                //     });
-                // }
-                let render_pipeline_layout =
-                    self.device
-                        .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                            label: Some("Canvas Pipeline Layout"),
-                            bind_group_layouts: &layouts,
-                            // push_constant_ranges: &constants_for_pipeline,
-                            push_constant_ranges: &[],
-                        });
-                let (render_pipeline, painting_pipeline, movie_pipeline) =
-                    crate::utils::create_pipelines(
-                        &self.device,
-                        &render_pipeline_layout,
-                        &vs_module,
-                        &fs_module,
-                        (
-                            RENDER_TEXTURE_FORMAT,
-                            PAINTING_TEXTURE_FORMAT,
-                            MOVIE_TEXTURE_FORMAT,
-                        ),
-                    );
-
-                self.render_pipeline = render_pipeline;
-                self.painting_pipeline = painting_pipeline;
-                self.movie_pipeline = movie_pipeline;
-
-                self.transmitter
-                    .send(CanvasMessage::ShaderCompilationSucceeded)
-                    .unwrap();
                 info!("Detected shader file changed, reloading {}", file);
+                self.reload_shader(file);
             }
             DebouncedEvent::Remove(path_buf) => {
                 info!(
@@ -113,11 +53,203 @@ impl Canvas {
         }
     }
 
+    /// Compile the given shader file and rebuild the render pipelines with it.
+    /// On failure the current pipelines are kept and the error is sent to the Dashboard.
+    pub fn reload_shader(&mut self, file: &str) {
+        match self.compile_shader(file, &self.bind_group_layouts[1]) {
+            Ok(compiled) => self.use_compiled_shader(compiled),
+            Err(e) => {
+                error!("Error compiling shader: {}", e);
+                self.shader_error = true;
+                self.transmitter
+                    .send(CanvasMessage::ShaderCompilationFailed(e))
+                    .unwrap();
+            }
+        }
+    }
+
+    /// Compile the given shader file into render pipelines whose set 1 has `texture_layout`.
+    /// Nothing is replaced, see [Self::use_compiled_shader].
+    fn compile_shader(
+        &self,
+        file: &str,
+        texture_layout: &wgpu::BindGroupLayout,
+    ) -> Result<CompiledShader, String> {
+        let fs_spirv_data =
+            crate::utils::load_shader(file, self.aa_samples, &self.entry_point, &self.include_dirs)
+                .map_err(|e| e.to_string())?;
+        let fs_module = self
+            .device
+            .create_shader_module(&wgpu::ShaderModuleDescriptor {
+                label: Some("Vertex Shader"),
+                source: wgpu::util::make_spirv(&fs_spirv_data),
+                flags: wgpu::ShaderFlags::VALIDATION,
+            });
+        let vs_module = self
+            .device
+            .create_shader_module(&wgpu::ShaderModuleDescriptor {
+                label: Some("Vertex Shader"),
+                source: wgpu::util::make_spirv(VS_MODULE_BYTES),
+                flags: wgpu::ShaderFlags::VALIDATION,
+            });
+
+        let layouts = [&self.bind_group_layouts[0], texture_layout];
+        // let mut constants_for_pipeline = vec![];
+        // if let Some(constants) = self.push_constants.as_ref() {
+        //     let mut size = 0;
+        //     for a_constant in constants {
+        //         size += a_constant.size();
+        //     }
+        //     constants_for_pipeline.push(wgpu::PushConstantRange {
+        //         stages: wgpu::ShaderStage::FRAGMENT,
+        //         range: 0..(size as u32),
+        //     });
+        // }
+        let render_pipeline_layout =
+            self.device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Canvas Pipeline Layout"),
+                    bind_group_layouts: &layouts,
+                    // push_constant_ranges: &constants_for_pipeline,
+                    push_constant_ranges: &[],
+                });
+        let (render_pipeline, painting_pipeline, movie_pipeline) = crate::utils::create_pipelines(
+            &self.device,
+            &render_pipeline_layout,
+            &vs_module,
+            &fs_module,
+            crate::utils::fragment_entry_point(file, &self.entry_point),
+            (
+                self.render_format.texture_format(),
+                PAINTING_TEXTURE_FORMAT,
+                MOVIE_TEXTURE_FORMAT,
+            ),
+        );
+        Ok(CompiledShader {
+            fs_spirv: fs_spirv_data,
+            render_pipeline,
+            painting_pipeline,
+            movie_pipeline,
+        })
+    }
+
+    /// Render with a shader from [Self::compile_shader] from now on.
+    fn use_compiled_shader(&mut self, compiled: CompiledShader) {
+        self.render_pipeline = compiled.render_pipeline;
+        self.painting_pipeline = compiled.painting_pipeline;
+        self.movie_pipeline = compiled.movie_pipeline;
+        // Restart the jitter sequence so accumulation in the new shader starts fresh.
+        self.uniforms.jitter = Vector4::zero();
+        self.reset_accumulation();
+
+        self.shader_error = false;
+        self.transmitter
+            .send(CanvasMessage::ShaderCompilationSucceeded)
+            .unwrap();
+        self.fs_spirv = compiled.fs_spirv;
+        self.update_uniform_layout();
+    }
+
+    /// Keep showing the last frame of the last shader that compiled while the current one doesn't, or clear the
+    /// window instead with `hold` off.
+    pub fn set_hold_frame_on_error(&mut self, hold: bool) {
+        self.hold_frame_on_error = hold;
+        self.request_paused_redraw();
+    }
+
+    /// Load an image from disk and bind it as the first texture (`texture_0`) for the shader to process.
+    /// Replaces the first image given at launch, or adds one if there were none.
+    /// The painting resolution in the GUI is set to the image size so the processed result can be captured as is.
+    pub fn load_input_image(&mut self, file: &str) {
+        let image = match image::open(file) {
+            Ok(image) => image::DynamicImage::ImageRgba8(image.to_rgba8()),
+            Err(e) => {
+                error!("Error loading input image {}: {}", file, e);
+                self.transmitter
+                    .send(CanvasMessage::InputImageFailed(e.to_string()))
+                    .unwrap();
+                return;
+            }
+        };
+        // The bindings and pipelines are all built before anything is replaced,
+        // so the current image stays if the shader doesn't compile against the new layout.
+        let mut textures = std::mem::take(&mut self.textures);
+        let texture = AssetTexture::new_with_image(&image, &self.device, &self.queue);
+        let previous = if textures.is_empty() {
+            textures.push(texture);
+            None
+        } else {
+            Some(std::mem::replace(&mut textures[0], texture))
+        };
+        let (layout, bind_group) = create_texture_bindings(
+            &self.device,
+            &textures,
+            &self.ramp_textures,
+            &self.noise_texture,
+        );
+        // Pipelines are tied to the bind group layouts, so rebuild them.
+        let compiled = match self.compile_shader(&self.shader_file, &layout) {
+            Ok(compiled) => compiled,
+            Err(e) => {
+                match previous {
+                    Some(previous) => textures[0] = previous,
+                    None => textures.clear(),
+                }
+                self.textures = textures;
+                error!("Error compiling shader for input image {}: {}", file, e);
+                self.transmitter
+                    .send(CanvasMessage::InputImageFailed(format!(
+                        "The shader does not compile with the image bound: {}",
+                        e
+                    )))
+                    .unwrap();
+                return;
+            }
+        };
+        if previous.is_none() {
+            warn!("No images were loaded at launch, ramps move down one binding to make room for the input image.");
+            self.uniforms.num_textures = 1;
+        }
+        self.textures = textures;
+        self.update_texture_vram();
+        self.bind_group_layouts[1] = layout;
+        self.bind_groups[1] = bind_group;
+        self.use_compiled_shader(compiled);
+
+        info!("Loaded input image {}", file);
+        self.transmitter
+            .send(CanvasMessage::InputImageLoaded(String::from(file)))
+            .unwrap();
+        self.transmitter
+            .send(CanvasMessage::UpdatePaintingResolutioninGUI(
+                IntVector2::new(image.width() as i32, image.height() as i32),
+            ))
+            .unwrap();
+    }
+
+    /// Switch to another fragment shader, e.g. one just created from a template.
+    /// The shader file watcher, if any, follows the new file.
+    pub fn open_shader(&mut self, file: &str) {
+        if let Some(watcher) = self.shader_file_watcher.as_mut() {
+            let _ = watcher.unwatch(&self.shader_file);
+            if let Err(e) = watcher.watch(file, notify::RecursiveMode::NonRecursive) {
+                warn!("Could not watch shader file {}: {}", file, e);
+            }
+        }
+        self.shader_file = String::from(file);
+        self.reload_shader(file);
+        info!("Opened shader {}", file);
+        self.transmitter
+            .send(CanvasMessage::ShaderOpened(String::from(file)))
+            .unwrap();
+    }
+
     pub fn add_post_processing_shader(&mut self, shader_data: Vec<u8>) {
         let postprocess = PostProcess::new(
             &self.device,
             shader_data,
             self.user_uniforms_buffer.is_some(),
+            self.render_format.texture_format(),
         );
         // We have a default included post-processing stage that is run in the painting pipeline
         // for doing sRGB conversion. That must always run last.
@@ -163,6 +295,26 @@ impl Canvas {
                     std::fs::read_to_string(file).expect("Error reading uniforms from file.");
                 let json_data = json::parse(&text).expect("Error parsing JSON");
                 self.user_uniforms = load_uniforms_from_json(&json_data);
+                self.transmitter.send(CanvasMessage::ClearUniforms).unwrap();
+                self.transmitter
+                    .send(CanvasMessage::UniformsForGUI(
+                        self.user_uniforms.iter().cloned().collect(),
+                    ))
+                    .unwrap();
+                self.update_uniform_layout();
+                // Ramps are bound at startup, so only ones we already have a texture for can be reloaded.
+                for a_ramp in load_ramps_from_json(&json_data) {
+                    if self.update_ramp(&a_ramp) {
+                        self.transmitter
+                            .send(CanvasMessage::RampForGUI(a_ramp))
+                            .unwrap();
+                    } else {
+                        warn!(
+                            "Ramp {} was added after launch and will not be bound until restart.",
+                            a_ramp.name
+                        );
+                    }
+                }
                 // self.push_constants = Some(load_push_constants_from_json(&json_data));
             }
             DebouncedEvent::Remove(path_buf) => {
@@ -196,3 +348,11 @@ impl Canvas {
         }
     }
 }
+
+/// Pipelines built from a fragment shader, and the SPIR-V they were built from.
+struct CompiledShader {
+    fs_spirv: Vec<u8>,
+    render_pipeline: wgpu::RenderPipeline,
+    painting_pipeline: wgpu::RenderPipeline,
+    movie_pipeline: wgpu::RenderPipeline,
+}
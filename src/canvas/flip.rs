@@ -0,0 +1,146 @@
+use super::{Canvas, PAINTING_TEXTURE_FORMAT};
+use crate::recording::MOVIE_TEXTURE_FORMAT;
+use crate::texture::default_color_sampler;
+use crate::utils::compile_builtin_shader;
+use crate::vector::UIntVector2;
+use std::ops::Range;
+use wgpu::{BindGroupDescriptor, BindGroupEntry, BindingResource, Extent3d, LoadOp, Operations};
+
+static FLIP_VS_SOURCE: &str = include_str!("../../shaders/full-screen-quad-flip.vert");
+
+/// Mirroring of the canvas output, e.g. for rear projection.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Flip {
+    pub horizontal: bool,
+    pub vertical: bool,
+}
+
+impl Flip {
+    pub fn is_none(&self) -> bool {
+        !self.horizontal && !self.vertical
+    }
+
+    /// Instances to draw the full-screen triangle with, the flip vertex shader reads the flip from the index.
+    pub fn instances(&self) -> Range<u32> {
+        let index = self.horizontal as u32 | (self.vertical as u32) << 1;
+        index..index + 1
+    }
+}
+
+/// Blits drawing a texture mirrored by the current [Flip], one per target they are used for.
+pub struct FlipPipelines {
+    /// Replaces the swap chain pipeline in the canvas window.
+    pub window: wgpu::RenderPipeline,
+    /// Draws the output over the transparency checkerboard.
+    pub window_over: wgpu::RenderPipeline,
+    painting: wgpu::RenderPipeline,
+    movie: wgpu::RenderPipeline,
+}
+
+impl FlipPipelines {
+    fn new(device: &wgpu::Device, sc_tex_format: wgpu::TextureFormat) -> FlipPipelines {
+        let vs_data = compile_builtin_shader(
+            FLIP_VS_SOURCE,
+            shaderc::ShaderKind::Vertex,
+            "full-screen-quad-flip.vert",
+        )
+        .expect("Error compiling flip vertex shader.");
+        let vs_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Flip Vertex Shader"),
+            source: wgpu::util::make_spirv(&vs_data),
+            flags: wgpu::ShaderFlags::VALIDATION,
+        });
+        let blit =
+            |format, blend| crate::utils::create_blit_pipeline(device, &vs_module, format, blend);
+        FlipPipelines {
+            window: blit(sc_tex_format, wgpu::BlendState::REPLACE),
+            window_over: blit(sc_tex_format, wgpu::BlendState::ALPHA_BLENDING),
+            painting: blit(PAINTING_TEXTURE_FORMAT, wgpu::BlendState::REPLACE),
+            movie: blit(MOVIE_TEXTURE_FORMAT, wgpu::BlendState::REPLACE),
+        }
+    }
+}
+
+impl Canvas {
+    /// Mirror the output in the canvas window, and in paintings and movies if `captures` is set.
+    pub fn set_flip(&mut self, flip: Flip, captures: bool) {
+        self.flip = flip;
+        self.flip_captures = captures;
+        self.flip_pipelines = match flip.is_none() {
+            true => None,
+            false => Some(
+                self.flip_pipelines
+                    .take()
+                    .unwrap_or_else(|| FlipPipelines::new(&self.device, self.sc_desc.format)),
+            ),
+        };
+        self.request_paused_redraw();
+    }
+
+    /// Encode a mirrored copy of a finished painting or movie frame, if captures are flipped.
+    /// `texture` must be sampled, and have [PAINTING_TEXTURE_FORMAT] or [MOVIE_TEXTURE_FORMAT].
+    pub fn encode_capture_flip(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        format: wgpu::TextureFormat,
+        resolution: UIntVector2,
+    ) -> Option<wgpu::Texture> {
+        let pipelines = match &self.flip_pipelines {
+            Some(pipelines) if self.flip_captures => pipelines,
+            _ => return None,
+        };
+        let pipeline = match format == MOVIE_TEXTURE_FORMAT {
+            true => &pipelines.movie,
+            false => &pipelines.painting,
+        };
+        let flipped = self.device.create_texture(&wgpu::TextureDescriptor {
+            size: Extent3d {
+                width: resolution.x,
+                height: resolution.y,
+                depth_or_array_layers: 1,
+            },
+            format,
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+            label: Some("Flipped Capture"),
+            dimension: wgpu::TextureDimension::D2,
+            mip_level_count: 1,
+            sample_count: 1,
+        });
+        let input_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let output_view = flipped.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = default_color_sampler(&self.device);
+        let bind_group = self.device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Flip Bind Group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Sampler(&sampler),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::TextureView(&input_view),
+                },
+            ],
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Flip Render Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &output_view,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(self.clear_color),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.set_pipeline(pipeline);
+            render_pass.draw(0..3, self.flip.instances());
+        }
+        Some(flipped)
+    }
+}
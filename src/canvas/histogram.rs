@@ -0,0 +1,220 @@
+use super::{Canvas, CanvasMessage, MapFuture, VS_MODULE_BYTES};
+use crate::texture::default_color_sampler;
+use futures::FutureExt;
+use std::num::NonZeroU32;
+use wgpu::{
+    BindGroupDescriptor, BindGroupEntry, BindingResource, Extent3d, LoadOp, Operations, Origin3d,
+};
+
+/// Width and height of the downsampled copy of the output the histogram is computed from.
+const HISTOGRAM_READBACK_SIZE: u32 = 128;
+/// Number of bins per channel.
+pub const HISTOGRAM_BINS: usize = 64;
+/// Stored as displayed, so the bins match what is seen on screen.
+static HISTOGRAM_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Distribution of the on-screen output's values, each bin is the fraction of pixels that fall in it.
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    pub red: Vec<f32>,
+    pub green: Vec<f32>,
+    pub blue: Vec<f32>,
+}
+
+impl Histogram {
+    /// Bin RGBA8 pixel data. Alpha is ignored.
+    fn from_rgba(pixels: &[u8]) -> Histogram {
+        let mut histogram = Histogram {
+            red: vec![0.0; HISTOGRAM_BINS],
+            green: vec![0.0; HISTOGRAM_BINS],
+            blue: vec![0.0; HISTOGRAM_BINS],
+        };
+        let pixel_count = (pixels.len() / 4).max(1) as f32;
+        let bin = |value: u8| value as usize * HISTOGRAM_BINS / 256;
+        for pixel in pixels.chunks_exact(4) {
+            histogram.red[bin(pixel[0])] += 1.0 / pixel_count;
+            histogram.green[bin(pixel[1])] += 1.0 / pixel_count;
+            histogram.blue[bin(pixel[2])] += 1.0 / pixel_count;
+        }
+        histogram
+    }
+}
+
+/// Copies a downsampled frame of the output to the CPU, one readback at a time.
+pub struct HistogramReadback {
+    texture: wgpu::Texture,
+    buffer: wgpu::Buffer,
+    pipeline: wgpu::RenderPipeline,
+    /// Set while the buffer holds a frame being mapped.
+    mapping: Option<MapFuture>,
+}
+
+impl HistogramReadback {
+    pub fn new(device: &wgpu::Device) -> HistogramReadback {
+        let vs_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Vertex Shader"),
+            source: wgpu::util::make_spirv(VS_MODULE_BYTES),
+            flags: wgpu::ShaderFlags::VALIDATION,
+        });
+        HistogramReadback {
+            texture: device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Histogram Readback"),
+                size: Extent3d {
+                    width: HISTOGRAM_READBACK_SIZE,
+                    height: HISTOGRAM_READBACK_SIZE,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: HISTOGRAM_TEXTURE_FORMAT,
+                usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+            }),
+            buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Histogram Readback Buffer"),
+                size: (HISTOGRAM_READBACK_SIZE * HISTOGRAM_READBACK_SIZE * 4) as u64,
+                usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
+                mapped_at_creation: false,
+            }),
+            pipeline: crate::utils::create_swap_chain_pipeline(
+                device,
+                &vs_module,
+                HISTOGRAM_TEXTURE_FORMAT,
+            ),
+            mapping: None,
+        }
+    }
+
+    /// GPU memory held while the histogram is enabled.
+    pub fn byte_size(&self) -> u64 {
+        2 * crate::utils::texture_bytes(
+            HISTOGRAM_READBACK_SIZE,
+            HISTOGRAM_READBACK_SIZE,
+            HISTOGRAM_TEXTURE_FORMAT,
+        )
+    }
+
+    /// Whether the previous readback has been consumed, so a new frame can be copied.
+    pub fn idle(&self) -> bool {
+        self.mapping.is_none()
+    }
+}
+
+impl Canvas {
+    /// Turn the histogram readback on or off. Its textures are only allocated while it's on.
+    pub fn set_histogram_enabled(&mut self, enabled: bool) {
+        self.histogram = match enabled {
+            true => Some(HistogramReadback::new(&self.device)),
+            false => None,
+        };
+        let bytes = self.histogram.as_ref().map_or(0, |h| h.byte_size());
+        self.vram.set("Histogram", bytes);
+    }
+
+    /// Encode a downsampled copy of `output` into the readback buffer, if the previous one has been consumed.
+    /// Returns whether a copy was encoded, in which case [Self::start_histogram_readback] must follow the submit.
+    pub fn encode_histogram_copy(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        output: &wgpu::TextureView,
+    ) -> bool {
+        let readback = match &self.histogram {
+            Some(readback) if readback.idle() => readback,
+            _ => return false,
+        };
+        let view = readback
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = default_color_sampler(&self.device);
+        let bind_group = self.device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Histogram Bind Group"),
+            layout: &readback.pipeline.get_bind_group_layout(0),
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Sampler(&sampler),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::TextureView(output),
+                },
+            ],
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Histogram Render Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(self.clear_color),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.set_pipeline(&readback.pipeline);
+            render_pass.draw(0..3, 0..1);
+        }
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                texture: &readback.texture,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &readback.buffer,
+                layout: wgpu::ImageDataLayout {
+                    bytes_per_row: NonZeroU32::new(HISTOGRAM_READBACK_SIZE * 4),
+                    offset: 0,
+                    rows_per_image: NonZeroU32::new(HISTOGRAM_READBACK_SIZE),
+                },
+            },
+            Extent3d {
+                width: HISTOGRAM_READBACK_SIZE,
+                height: HISTOGRAM_READBACK_SIZE,
+                depth_or_array_layers: 1,
+            },
+        );
+        true
+    }
+
+    /// Start mapping the copy encoded by [Self::encode_histogram_copy]. Call after submitting it.
+    pub fn start_histogram_readback(&mut self) {
+        if let Some(readback) = self.histogram.as_mut() {
+            let mapping = readback.buffer.slice(..).map_async(wgpu::MapMode::Read);
+            readback.mapping = Some(Box::pin(mapping));
+        }
+    }
+
+    /// Send the histogram of the last copied frame to the Dashboard once its readback has finished.
+    pub fn poll_histogram(&mut self) {
+        let readback = match self.histogram.as_mut() {
+            Some(readback) => readback,
+            None => return,
+        };
+        let result = match readback.mapping.as_mut() {
+            Some(mapping) => {
+                self.device.poll(wgpu::Maintain::Poll);
+                match mapping.as_mut().now_or_never() {
+                    Some(result) => result,
+                    None => return,
+                }
+            }
+            None => return,
+        };
+        readback.mapping = None;
+        if result.is_err() {
+            return;
+        }
+        let histogram = {
+            let pixels = readback.buffer.slice(..).get_mapped_range();
+            Histogram::from_rgba(&pixels)
+        };
+        readback.buffer.unmap();
+        self.transmitter
+            .send(CanvasMessage::Histogram(histogram))
+            .unwrap();
+    }
+}
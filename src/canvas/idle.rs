@@ -0,0 +1,59 @@
+use super::Canvas;
+use log::info;
+
+/// Seconds the output takes to dim when the canvas goes idle, and to come back when it wakes.
+const IDLE_FADE_DURATION: f32 = 2.0;
+
+/// Low-power state of an unattended canvas, see [crate::dashboard::DashboardMessage::EnterIdle].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IdleSettings {
+    /// Frames per second rendered while idle. Ignored while frames are driven by an external clock.
+    pub framerate: f32,
+    /// Master fade level held while idle, towards the current fade color. 0 doesn't dim.
+    pub dim: f32,
+}
+
+/// Canvas state to restore when waking from idle.
+#[derive(Debug, Clone, Copy)]
+pub struct Idle {
+    pub settings: IdleSettings,
+    /// Master fade level the Dashboard had asked for before going idle.
+    fade_target: f32,
+}
+
+impl Canvas {
+    pub fn enter_idle(&mut self, settings: IdleSettings) {
+        let fade_target = match self.idle {
+            Some(idle) => idle.fade_target,
+            None => self.master_fade.target(),
+        };
+        info!(
+            "Going idle at {} fps, dimmed to {:.0}%",
+            settings.framerate,
+            settings.dim * 100.0
+        );
+        // Never brighten an output that was already faded further.
+        self.master_fade
+            .fade_to(settings.dim.max(fade_target), IDLE_FADE_DURATION);
+        self.idle = Some(Idle {
+            settings,
+            fade_target,
+        });
+    }
+
+    pub fn exit_idle(&mut self) {
+        if let Some(idle) = self.idle.take() {
+            info!("Waking from idle");
+            self.master_fade
+                .fade_to(idle.fade_target, IDLE_FADE_DURATION);
+        }
+    }
+
+    /// Milliseconds between frames: `active_interval`, or longer while idle.
+    pub fn frame_interval_ms(&self, active_interval: u128) -> u128 {
+        match self.idle {
+            Some(idle) => active_interval.max((1000.0 / idle.settings.framerate.max(0.1)) as u128),
+            None => active_interval,
+        }
+    }
+}
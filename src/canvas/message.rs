@@ -1,4 +1,7 @@
-use crate::uniforms::UserUniform;
+use super::{Histogram, VariationSheetLayout};
+use crate::layout::UniformSlot;
+use crate::uniforms::{RampUniform, UserUniform};
+use crate::utils::{PaintingLayer, VramTracker};
 use crate::vector::{IntVector2, UIntVector2, Vector2};
 
 /// Message Enums used by [crate::canvas::Canvas] to send messages to interested parties.
@@ -6,22 +9,30 @@ pub enum CanvasMessage {
     /// Signifies a new frame has been dispatch for rendering (not a painting draw call)
     RenderPassSubmitted,
     /// Mouse has moved to a new location in the window.
+    /// While the mouse is captured this is the movement since the last event instead.
     MouseMoved(Vector2),
-    /// Frame has been rendered
-    FrameStep,
+    /// Frame has been rendered, at the contained time point.
+    FrameStep(std::time::Instant),
     /// Error with swapchain.
     SwapChainFrameError(wgpu::SwapChainError),
     /// Contains new window size.
     WindowResized(IntVector2),
     /// A painting render operation has been dispatched.
-    /// The buffer will contain the painting data once rendering finishes.
-    /// The IntVector2 is the resolution of the painting.
+    /// The UIntVector2 is the resolution of the painting.
     /// The Instant is the time point at which this render operation started.
-    PaintingStarted(wgpu::Buffer, UIntVector2, std::time::Instant),
+    PaintingStarted(UIntVector2, std::time::Instant),
+    /// The painting has finished rendering. The buffers are mapped and hold its data, ready to be written.
+    /// There is one layer per pass if all passes were requested, otherwise only the final output.
+    PaintingRendered(Vec<PaintingLayer>, UIntVector2),
+    /// The cells of a variation sheet have finished rendering. The buffer is mapped and holds them as laid out.
+    VariationSheetRendered(wgpu::Buffer, VariationSheetLayout),
+    /// The painting could not be read back from the GPU. Contains the error message.
+    PaintingFailed(String),
     /// A movie frame render operation has been dispatched.
     /// The buffer will contain the frame data once rendering finishes.
     /// The IntVector2 is the resolution of the frame.
     /// The Instant is the time point at which this render operation started.
+    /// Also acknowledges the [crate::dashboard::DashboardMessage::MovieRenderRequested] it answers.
     MovieFrameStarted(wgpu::Buffer, UIntVector2, std::time::Instant),
     /// Signifies shader reloaded from disk, recompiled, and render pipeline has been updated.
     ShaderCompilationSucceeded,
@@ -29,8 +40,47 @@ pub enum CanvasMessage {
     ShaderCompilationFailed(String),
     /// Indication pause play state changed from canvas window.
     PausePlayChanged,
-    /// Used by Canvas to tell Dashboard how to build the editor GUI for a given custom uniform.
-    UniformForGUI(UserUniform),
+    /// The uniforms the Dashboard shows are no longer bound, drop their controls and any edits not sent yet.
+    /// Sent before the uniforms file is reloaded, followed by [Self::UniformsForGUI] with the new set.
+    ClearUniforms,
+    /// Used by Canvas to tell Dashboard how to build the editor GUI for its custom uniforms.
+    /// Contains the whole set, sent on startup and whenever the uniforms file is reloaded.
+    UniformsForGUI(Vec<UserUniform>),
+    /// Used by Canvas to tell Dashboard which color ramps it can edit.
+    RampForGUI(RampUniform),
+    /// The input image at the contained path is now bound as the first texture.
+    InputImageLoaded(String),
+    /// Error loading the input image, contains error message.
+    InputImageFailed(String),
+    /// Current level of the master fade, sent while it changes. 0 is no fade, 1 is only the fade color.
+    MasterFadeLevel(f32),
+    /// Histogram of a recent on-screen frame, sent while the histogram is enabled.
+    Histogram(Histogram),
+    /// Downscaled copy of a recent on-screen frame, sent while thumbnails are enabled.
+    /// Tightly packed RGBA8 pixels with straight alpha, and their size.
+    Thumbnail(Vec<u8>, UIntVector2),
+    /// Number of frames averaged so far while accumulating.
+    AccumulationSamples(u32),
+    /// Shared record of GPU allocations, sent once at startup.
+    VramTrackerCreated(VramTracker),
+    /// Path of the shader being rendered, sent once at startup.
+    ShaderOpened(String),
+    /// Largest texture dimension the canvas device supports, sent once at startup.
+    MaxTextureDimension(u32),
     /// Change the resolution of the painting in the GUI.
     UpdatePaintingResolutioninGUI(IntVector2),
+    /// A reference frame of this size has been stored for comparison.
+    ReferenceCaptured(UIntVector2),
+    /// Whether the canvas device can draw [crate::canvas::DebugView::Wireframe], sent once at startup.
+    WireframeSupported(bool),
+    /// Functionality disabled because the canvas device lacks an optional feature, sent once at startup.
+    MissingCapabilities(Vec<&'static str>),
+    /// Number of points now touching the canvas window.
+    TouchCount(usize),
+    /// Where Easel's and the user uniforms are in their buffers, followed by pins that could not be honored and
+    /// mismatches with the layout the shader declares. Sent whenever the layout, the pins or the shader changes.
+    UniformLayout {
+        slots: Vec<UniformSlot>,
+        warnings: Vec<String>,
+    },
 }
@@ -0,0 +1,608 @@
+use super::Canvas;
+use crate::texture::default_color_sampler;
+use crate::utils::{compile_builtin_shader, texture_bytes};
+use crate::vector::Vector2;
+use bytemuck::{Pod, Zeroable};
+use std::time::Instant;
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+use wgpu::{
+    BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor, BindGroupLayoutEntry,
+    BindingResource, LoadOp, Operations,
+};
+use winit::window::{Fullscreen, Window, WindowId};
+
+static MIRROR_VS_SOURCE: &str = include_str!("../../shaders/mirror-output.vert");
+static MIRROR_FS_SOURCE: &str = include_str!("../../shaders/mirror-output.frag");
+/// Quads per side of the mesh a mirror is drawn with, enough for smooth warps.
+const MIRROR_MESH_RESOLUTION: u32 = 32;
+/// Largest number of grid warp control points per side.
+pub const MAX_WARP_GRID_SIZE: usize = 5;
+/// Present modes a mirror can use, from least to most latency.
+pub const MIRROR_PRESENT_MODES: [wgpu::PresentMode; 3] = [
+    wgpu::PresentMode::Immediate,
+    wgpu::PresentMode::Mailbox,
+    wgpu::PresentMode::Fifo,
+];
+
+pub fn present_mode_name(mode: wgpu::PresentMode) -> &'static str {
+    match mode {
+        wgpu::PresentMode::Immediate => "Immediate",
+        wgpu::PresentMode::Mailbox => "Mailbox",
+        wgpu::PresentMode::Fifo => "Fifo",
+    }
+}
+
+fn present_mode_from_name(name: &str) -> Option<wgpu::PresentMode> {
+    MIRROR_PRESENT_MODES
+        .iter()
+        .copied()
+        .find(|mode| present_mode_name(*mode).eq_ignore_ascii_case(name))
+}
+
+/// Which part of the canvas a [MirrorOutput] shows, and how it is mapped onto the window.
+#[derive(Debug, Clone)]
+pub struct MirrorSettings {
+    /// Top-left corner of the shown region, in normalized canvas coordinates.
+    pub offset: Vector2,
+    /// Extent of the shown region, in normalized canvas coordinates.
+    pub size: Vector2,
+    /// Borderless fullscreen on the monitor the window is currently on.
+    pub fullscreen: bool,
+    /// Where the corners of the image land, in normalized window coordinates.
+    /// Order: top-left, top-right, bottom-right, bottom-left.
+    pub corners: [Vector2; 4],
+    /// Control points per side of the grid warp. 2 places them only at the corners.
+    pub warp_grid_size: usize,
+    /// Displacement of each control point, row-major, in normalized window coordinates.
+    pub warp_offsets: Vec<Vector2>,
+    /// Width of the falloff at the left, right, top and bottom edge, in normalized image coordinates.
+    pub blend_widths: [f32; 4],
+    /// Exponent applied to the falloff, to match the response of the projectors.
+    pub blend_exponent: f32,
+    /// Show the canvas window's debug view and reference overlay, e.g. on an operator's preview monitor.
+    /// Otherwise the clean output is shown.
+    pub show_overlays: bool,
+    pub present_mode: wgpu::PresentMode,
+    /// Most frames drawn to this window per second, 0 draws every canvas frame.
+    pub max_framerate: f32,
+}
+
+impl Default for MirrorSettings {
+    fn default() -> Self {
+        let mut settings = Self {
+            offset: Vector2::new(0.0, 0.0),
+            size: Vector2::new(1.0, 1.0),
+            fullscreen: false,
+            corners: [Vector2::zero(); 4],
+            warp_grid_size: 2,
+            warp_offsets: vec![],
+            blend_widths: [0.0; 4],
+            blend_exponent: 1.0,
+            show_overlays: false,
+            present_mode: wgpu::PresentMode::Mailbox,
+            max_framerate: 0.0,
+        };
+        settings.reset_warp();
+        settings
+    }
+}
+
+fn lerp(a: Vector2, b: Vector2, t: f32) -> Vector2 {
+    Vector2::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+}
+
+fn vector2_to_json(v: &Vector2) -> json::JsonValue {
+    json::array![v.x, v.y]
+}
+
+fn vector2_from_json(data: &json::JsonValue) -> Option<Vector2> {
+    Some(Vector2::new(data[0].as_f32()?, data[1].as_f32()?))
+}
+
+impl MirrorSettings {
+    /// Change the number of grid warp control points per side. Clears the grid offsets.
+    pub fn set_warp_grid_size(&mut self, size: usize) {
+        self.warp_grid_size = size.clamp(2, MAX_WARP_GRID_SIZE);
+        self.warp_offsets = vec![Vector2::zero(); self.warp_grid_size * self.warp_grid_size];
+    }
+
+    /// Undo the corner pin and grid warp.
+    pub fn reset_warp(&mut self) {
+        self.corners = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(1.0, 0.0),
+            Vector2::new(1.0, 1.0),
+            Vector2::new(0.0, 1.0),
+        ];
+        self.set_warp_grid_size(self.warp_grid_size);
+    }
+
+    /// Window position of the image point `(s, t)`: the corner pin followed by the grid warp.
+    fn warp(&self, s: f32, t: f32) -> Vector2 {
+        let c = &self.corners;
+        let mut position = lerp(lerp(c[0], c[1], s), lerp(c[3], c[2], s), t);
+        // Interpolate the offsets of the four control points around the point.
+        let n = self.warp_grid_size;
+        let (gx, gy) = (s * (n - 1) as f32, t * (n - 1) as f32);
+        let (x0, y0) = ((gx as usize).min(n - 2), (gy as usize).min(n - 2));
+        let (fx, fy) = (gx - x0 as f32, gy - y0 as f32);
+        let at = |x: usize, y: usize| self.warp_offsets[y * n + x];
+        let offset = lerp(
+            lerp(at(x0, y0), at(x0 + 1, y0), fx),
+            lerp(at(x0, y0 + 1), at(x0 + 1, y0 + 1), fx),
+            fy,
+        );
+        position.x += offset.x;
+        position.y += offset.y;
+        position
+    }
+
+    /// Triangle list covering the warped image.
+    fn mesh(&self) -> Vec<MirrorVertex> {
+        let vertex = |i: u32, j: u32| {
+            let (s, t) = (
+                i as f32 / MIRROR_MESH_RESOLUTION as f32,
+                j as f32 / MIRROR_MESH_RESOLUTION as f32,
+            );
+            let position = self.warp(s, t);
+            MirrorVertex {
+                position: [2.0 * position.x - 1.0, 1.0 - 2.0 * position.y],
+                canvas_uv: [
+                    self.offset.x + s * self.size.x,
+                    self.offset.y + t * self.size.y,
+                ],
+                output_uv: [s, t],
+            }
+        };
+        let mut vertices = Vec::with_capacity((MIRROR_MESH_RESOLUTION.pow(2) * 6) as usize);
+        for j in 0..MIRROR_MESH_RESOLUTION {
+            for i in 0..MIRROR_MESH_RESOLUTION {
+                vertices.extend_from_slice(&[
+                    vertex(i, j),
+                    vertex(i, j + 1),
+                    vertex(i + 1, j),
+                    vertex(i + 1, j),
+                    vertex(i, j + 1),
+                    vertex(i + 1, j + 1),
+                ]);
+            }
+        }
+        vertices
+    }
+
+    pub fn to_json(&self) -> json::JsonValue {
+        let mut data = json::JsonValue::new_object();
+        data["offset"] = vector2_to_json(&self.offset);
+        data["size"] = vector2_to_json(&self.size);
+        data["fullscreen"] = self.fullscreen.into();
+        data["corners"] = self
+            .corners
+            .iter()
+            .map(vector2_to_json)
+            .collect::<Vec<_>>()
+            .into();
+        data["warp_grid_size"] = self.warp_grid_size.into();
+        data["warp_offsets"] = self
+            .warp_offsets
+            .iter()
+            .map(vector2_to_json)
+            .collect::<Vec<_>>()
+            .into();
+        data["blend_widths"] = self.blend_widths.to_vec().into();
+        data["blend_exponent"] = self.blend_exponent.into();
+        data["show_overlays"] = self.show_overlays.into();
+        data["present_mode"] = present_mode_name(self.present_mode).into();
+        data["max_framerate"] = self.max_framerate.into();
+        data
+    }
+
+    /// Inverse of [Self::to_json]. Missing keys keep their defaults.
+    pub fn from_json(data: &json::JsonValue) -> Self {
+        let mut settings = Self::default();
+        if let Some(offset) = vector2_from_json(&data["offset"]) {
+            settings.offset = offset;
+        }
+        if let Some(size) = vector2_from_json(&data["size"]) {
+            settings.size = size;
+        }
+        if let Some(fullscreen) = data["fullscreen"].as_bool() {
+            settings.fullscreen = fullscreen;
+        }
+        for (i, corner) in data["corners"].members().take(4).enumerate() {
+            if let Some(corner) = vector2_from_json(corner) {
+                settings.corners[i] = corner;
+            }
+        }
+        if let Some(size) = data["warp_grid_size"].as_usize() {
+            settings.set_warp_grid_size(size);
+            let offsets: Vec<Vector2> = data["warp_offsets"]
+                .members()
+                .filter_map(vector2_from_json)
+                .collect();
+            if offsets.len() == settings.warp_offsets.len() {
+                settings.warp_offsets = offsets;
+            }
+        }
+        for (i, width) in data["blend_widths"].members().take(4).enumerate() {
+            if let Some(width) = width.as_f32() {
+                settings.blend_widths[i] = width;
+            }
+        }
+        if let Some(exponent) = data["blend_exponent"].as_f32() {
+            settings.blend_exponent = exponent;
+        }
+        if let Some(show_overlays) = data["show_overlays"].as_bool() {
+            settings.show_overlays = show_overlays;
+        }
+        if let Some(mode) = data["present_mode"]
+            .as_str()
+            .and_then(present_mode_from_name)
+        {
+            settings.present_mode = mode;
+        }
+        if let Some(framerate) = data["max_framerate"].as_f32() {
+            settings.max_framerate = framerate.max(0.0);
+        }
+        settings
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+struct MirrorVertex {
+    /// Clip space position.
+    position: [f32; 2],
+    canvas_uv: [f32; 2],
+    /// Position within the image, used for the edge blend.
+    output_uv: [f32; 2],
+}
+
+/// Layout of the `MirrorBlend` block in `shaders/mirror-output.frag`.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+struct MirrorBlendUniform {
+    widths: [f32; 4],
+    params: [f32; 4],
+}
+
+/// Additional window that shows the canvas, e.g. one per projector in an installation.
+/// Each is drawn through its own corner pin, grid warp and edge blend, see [MirrorSettings].
+pub struct MirrorOutput {
+    pub window: Window,
+    surface: wgpu::Surface,
+    sc_desc: wgpu::SwapChainDescriptor,
+    swap_chain: wgpu::SwapChain,
+    settings: MirrorSettings,
+    /// Warped mesh built from [Self::settings].
+    vertex_buffer: wgpu::Buffer,
+    vertex_count: u32,
+    /// Holds a [MirrorBlendUniform].
+    blend_buffer: wgpu::Buffer,
+    /// When a frame was last drawn, for [MirrorSettings::max_framerate].
+    last_frame: Option<Instant>,
+}
+
+impl MirrorOutput {
+    /// Whether enough time has passed since the last frame for [MirrorSettings::max_framerate].
+    fn frame_due(&self, now: Instant) -> bool {
+        match self.last_frame {
+            Some(last) if self.settings.max_framerate > 0.0 => {
+                (now - last).as_secs_f32() >= 1.0 / self.settings.max_framerate
+            }
+            _ => true,
+        }
+    }
+
+    /// The swap chain is recreated here whenever the window size has changed since the last frame.
+    fn current_frame(&mut self, device: &wgpu::Device) -> Option<wgpu::SwapChainFrame> {
+        let size = self.window.inner_size();
+        if size.width == 0 || size.height == 0 {
+            return None;
+        }
+        if size.width != self.sc_desc.width || size.height != self.sc_desc.height {
+            self.sc_desc.width = size.width;
+            self.sc_desc.height = size.height;
+            self.swap_chain = device.create_swap_chain(&self.surface, &self.sc_desc);
+        }
+        self.swap_chain.get_current_frame().ok()
+    }
+
+    /// Rebuild the mesh and blend uniform from [Self::settings].
+    fn apply_settings(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let (vertex_buffer, vertex_count) = create_mesh_buffer(device, &self.settings);
+        self.vertex_buffer = vertex_buffer;
+        self.vertex_count = vertex_count;
+        let blend = MirrorBlendUniform {
+            widths: self.settings.blend_widths,
+            params: [self.settings.blend_exponent, 0.0, 0.0, 0.0],
+        };
+        queue.write_buffer(&self.blend_buffer, 0, bytemuck::bytes_of(&blend));
+    }
+}
+
+/// Vertex buffer holding [MirrorSettings::mesh], and its vertex count.
+fn create_mesh_buffer(device: &wgpu::Device, settings: &MirrorSettings) -> (wgpu::Buffer, u32) {
+    let vertices = settings.mesh();
+    let buffer = device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("Mirror Mesh"),
+        contents: bytemuck::cast_slice(&vertices),
+        usage: wgpu::BufferUsage::VERTEX,
+    });
+    (buffer, vertices.len() as u32)
+}
+
+/// Pipeline that draws the canvas through a mirror's warped mesh, applying the edge blend.
+fn create_mirror_pipeline(
+    device: &wgpu::Device,
+    sc_tex_format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    let vs_data = compile_builtin_shader(
+        MIRROR_VS_SOURCE,
+        shaderc::ShaderKind::Vertex,
+        "mirror-output.vert",
+    )
+    .expect("Error compiling mirror vertex shader.");
+    let fs_data = compile_builtin_shader(
+        MIRROR_FS_SOURCE,
+        shaderc::ShaderKind::Fragment,
+        "mirror-output.frag",
+    )
+    .expect("Error compiling mirror fragment shader.");
+    let vs_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+        label: Some("Mirror Vertex Shader"),
+        source: wgpu::util::make_spirv(&vs_data),
+        flags: wgpu::ShaderFlags::VALIDATION,
+    });
+    let fs_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+        label: Some("Mirror Fragment Shader"),
+        source: wgpu::util::make_spirv(&fs_data),
+        flags: wgpu::ShaderFlags::VALIDATION,
+    });
+
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Mirror Pipeline Layout"),
+        push_constant_ranges: &[],
+        bind_group_layouts: &[
+            &device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        count: None,
+                        visibility: wgpu::ShaderStage::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler {
+                            filtering: true,
+                            comparison: false,
+                        },
+                    },
+                    BindGroupLayoutEntry {
+                        binding: 1,
+                        count: None,
+                        visibility: wgpu::ShaderStage::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                    },
+                    BindGroupLayoutEntry {
+                        binding: 2,
+                        count: None,
+                        visibility: wgpu::ShaderStage::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                    },
+                ],
+            }),
+        ],
+    });
+
+    let attributes = [
+        wgpu::VertexAttribute {
+            format: wgpu::VertexFormat::Float32x2,
+            offset: 0,
+            shader_location: 0,
+        },
+        wgpu::VertexAttribute {
+            format: wgpu::VertexFormat::Float32x2,
+            offset: 8,
+            shader_location: 1,
+        },
+        wgpu::VertexAttribute {
+            format: wgpu::VertexFormat::Float32x2,
+            offset: 16,
+            shader_location: 2,
+        },
+    ];
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Mirror Pipeline"),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: &vs_module,
+            entry_point: "main",
+            buffers: &[wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<MirrorVertex>() as wgpu::BufferAddress,
+                step_mode: wgpu::InputStepMode::Vertex,
+                attributes: &attributes,
+            }],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &fs_module,
+            entry_point: "main",
+            targets: &[wgpu::ColorTargetState {
+                format: sc_tex_format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrite::ALL,
+            }],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            cull_mode: None,
+            ..Default::default()
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+    })
+}
+
+impl Canvas {
+    /// Start mirroring the canvas to `window`.
+    pub fn add_mirror(&mut self, window: Window) {
+        if self.mirror_pipeline.is_none() {
+            self.mirror_pipeline = Some(create_mirror_pipeline(&self.device, self.sc_desc.format));
+        }
+        let surface = unsafe { self.instance.create_surface(&window) };
+        let size = window.inner_size();
+        let sc_desc = wgpu::SwapChainDescriptor {
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
+            format: self.sc_desc.format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: MirrorSettings::default().present_mode,
+        };
+        let swap_chain = self.device.create_swap_chain(&surface, &sc_desc);
+        let blend_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Mirror Blend Uniforms"),
+            size: std::mem::size_of::<MirrorBlendUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let settings = MirrorSettings::default();
+        let (vertex_buffer, vertex_count) = create_mesh_buffer(&self.device, &settings);
+        let mut mirror = MirrorOutput {
+            window,
+            surface,
+            sc_desc,
+            swap_chain,
+            settings,
+            vertex_buffer,
+            vertex_count,
+            blend_buffer,
+            last_frame: None,
+        };
+        mirror.apply_settings(&self.device, &self.queue);
+        self.mirrors.push(mirror);
+        self.update_mirror_vram();
+    }
+
+    /// Close the mirror shown in the window with the given id.
+    pub fn remove_mirror(&mut self, id: WindowId) {
+        self.mirrors.retain(|mirror| mirror.window.id() != id);
+        self.update_mirror_vram();
+    }
+
+    pub fn set_mirror_settings(&mut self, id: WindowId, settings: MirrorSettings) {
+        if let Some(mirror) = self.mirrors.iter_mut().find(|m| m.window.id() == id) {
+            if mirror.settings.fullscreen != settings.fullscreen {
+                mirror.window.set_fullscreen(match settings.fullscreen {
+                    true => Some(Fullscreen::Borderless(mirror.window.current_monitor())),
+                    false => None,
+                });
+            }
+            if mirror.sc_desc.present_mode != settings.present_mode {
+                mirror.sc_desc.present_mode = settings.present_mode;
+                mirror.swap_chain = self
+                    .device
+                    .create_swap_chain(&mirror.surface, &mirror.sc_desc);
+            }
+            mirror.settings = settings;
+            mirror.apply_settings(&self.device, &self.queue);
+        }
+    }
+
+    fn update_mirror_vram(&self) {
+        // Assume double buffering.
+        let bytes = self
+            .mirrors
+            .iter()
+            .map(|m| 2 * texture_bytes(m.sc_desc.width, m.sc_desc.height, m.sc_desc.format))
+            .sum();
+        self.vram.set("Mirror swap chains", bytes);
+    }
+
+    /// Draw the final canvas image to every mirror window due a frame, or `overlay_view`, the image shown
+    /// in the canvas window, to those with [MirrorSettings::show_overlays].
+    /// The returned frames are presented when dropped, so keep them until the encoder has been submitted.
+    pub fn render_mirrors(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        canvas_view: &wgpu::TextureView,
+        overlay_view: &wgpu::TextureView,
+    ) -> Vec<wgpu::SwapChainFrame> {
+        let pipeline = match &self.mirror_pipeline {
+            Some(pipeline) => pipeline,
+            None => return vec![],
+        };
+        let sampler = default_color_sampler(&self.device);
+        let layout = pipeline.get_bind_group_layout(0);
+        let mut frames = Vec::with_capacity(self.mirrors.len());
+        let mut resized = false;
+        let now = Instant::now();
+        for mirror in &mut self.mirrors {
+            if !mirror.frame_due(now) {
+                continue;
+            }
+            let (width, height) = (mirror.sc_desc.width, mirror.sc_desc.height);
+            let frame = match mirror.current_frame(&self.device) {
+                Some(frame) => frame,
+                None => continue,
+            };
+            resized |= width != mirror.sc_desc.width || height != mirror.sc_desc.height;
+            let bind_group = self.device.create_bind_group(&BindGroupDescriptor {
+                label: Some("Mirror Bind Group"),
+                layout: &layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::Sampler(&sampler),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::TextureView(
+                            match mirror.settings.show_overlays {
+                                true => overlay_view,
+                                false => canvas_view,
+                            },
+                        ),
+                    },
+                    BindGroupEntry {
+                        binding: 2,
+                        resource: mirror.blend_buffer.as_entire_binding(),
+                    },
+                ],
+            });
+            {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Mirror Render Pass"),
+                    color_attachments: &[wgpu::RenderPassColorAttachment {
+                        view: &frame.output.view,
+                        resolve_target: None,
+                        ops: Operations {
+                            // Black outside the warped image, so nothing is projected there.
+                            load: LoadOp::Clear(wgpu::Color::BLACK),
+                            store: true,
+                        },
+                    }],
+                    depth_stencil_attachment: None,
+                });
+                render_pass.set_bind_group(0, &bind_group, &[]);
+                render_pass.set_pipeline(pipeline);
+                render_pass.set_vertex_buffer(0, mirror.vertex_buffer.slice(..));
+                render_pass.draw(0..mirror.vertex_count, 0..1);
+            }
+            mirror.last_frame = Some(now);
+            frames.push(frame);
+        }
+        if resized {
+            self.update_mirror_vram();
+        }
+        frames
+    }
+}
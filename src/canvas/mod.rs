@@ -1,21 +1,25 @@
 // use crate::drawable::Drawable;
-use crate::texture::{default_color_sampler, AssetTexture};
-use crate::uniforms::{Uniforms, UserUniform};
+use crate::layout::UniformLayout;
+use crate::renderer::{create_texture_bindings, create_uniform_bindings};
+use crate::texture::{AssetTexture, NoiseTexture2D, NoiseType, RampTexture};
+use crate::uniforms::{RampUniform, Uniforms, UserUniform};
+use crate::utils::{self, texture_bytes, VramTracker};
 use crate::vector::{IntVector2, IntVector4, UIntVector2, Vector2, Vector4};
 use crate::{dashboard::DashboardMessage, recording::MOVIE_TEXTURE_FORMAT};
 use chrono::Datelike;
-use std::collections::HashSet;
+use log::{error, info, warn};
+use std::collections::{HashMap, HashSet};
 use std::vec::Vec;
 use std::{
-    num::NonZeroU64,
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
     sync::mpsc::{Receiver, Sender},
+    sync::Arc,
 };
 use stopwatch::Stopwatch;
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
-use wgpu::{
-    BindGroupEntry, BindGroupLayoutEntry, BindingResource, BufferBinding, PowerPreference,
-    RequestAdapterOptions,
-};
+use wgpu::PowerPreference;
 use winit::{event::*, window::Window};
 
 mod message;
@@ -24,18 +28,56 @@ mod rendering;
 pub use self::rendering::*;
 mod file_loading;
 pub use self::file_loading::*;
+mod accumulation;
+pub use self::accumulation::*;
+mod mirror;
+pub use self::mirror::*;
+mod fade;
+pub use self::fade::*;
+mod histogram;
+pub use self::histogram::*;
+mod thumbnail;
+pub use self::thumbnail::*;
+mod clipboard;
+mod external_clock;
+pub use self::external_clock::*;
+mod compare;
+pub use self::compare::*;
+mod debug_view;
+pub use self::debug_view::*;
+mod variation_sheet;
+pub use self::variation_sheet::*;
+mod idle;
+pub use self::idle::*;
+mod render_format;
+pub use self::render_format::*;
+mod checkerboard;
+pub use self::checkerboard::*;
+mod flip;
+pub use self::flip::*;
+mod touch;
+pub use self::touch::*;
+mod mouse_smoothing;
+pub use self::mouse_smoothing::*;
+mod stereo;
+mod uniform_layout;
+pub use self::stereo::*;
+mod time_scale;
+pub use self::time_scale::*;
 
 use crate::postprocessing::PostProcess;
 use notify::{DebouncedEvent, RecommendedWatcher};
 
-/// Pre-compile vertex shader that renders a full-screen quad.
-pub static VS_MODULE_BYTES: &[u8] = include_bytes!("../../shaders/vert.spv");
-/// The [wgpu::TextureFormat] used when rendering to screen.
+pub use crate::renderer::VS_MODULE_BYTES;
+/// The [wgpu::TextureFormat] used when rendering to screen with [RenderFormat::Standard].
 /// We render to linear color as so that post-process ops are correctly applied in linear space.
 /// A final render pass is done before presenting to screen to convert to sRGB.
 pub static RENDER_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
 /// The [wgpu::TextureFormat] used when rendering off-screen painting to write to disk.
 pub static PAINTING_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Pending [wgpu::BufferSlice::map_async], completed by polling the Canvas' device.
+type MapFuture = Pin<Box<dyn Future<Output = Result<(), wgpu::BufferAsyncError>> + Send>>;
 /// Built-in shader used as a post-processing effect to apply gamma sRGB conversion for painting.
 /// This is needed as the [PAINTING_TEXTURE_FORMAT] does not perform automatic sRGB conversion for us.
 static POST_PROCESS_SRGB_SHADER_BYTES: &[u8] =
@@ -57,6 +99,14 @@ pub struct Canvas {
     pub device: wgpu::Device,
     /// Handle to command dispatch queue on device.
     pub queue: wgpu::Queue,
+    /// Path of the fragment shader, used to recompile it when compile settings change.
+    shader_file: String,
+    /// Name of the fragment function, see [crate::utils::load_shader].
+    entry_point: String,
+    /// Directories searched for `#include`d files, see [crate::utils::load_shader].
+    include_dirs: Vec<String>,
+    /// Anti-aliasing quality, injected into the shader and used as the painting supersampling factor.
+    aa_samples: u32,
     /// Descriptor is kept around for window resizing events.
     sc_desc: wgpu::SwapChainDescriptor,
     /// Handle to swap chain for on-screen rendering.
@@ -69,8 +119,16 @@ pub struct Canvas {
     /// Render pipeline use for off-screen rendering of movie frames.
     /// May also include other post-processing effects, if provided.
     movie_pipeline: wgpu::RenderPipeline,
+    /// Precision of on-screen and movie rendering, fixed for the canvas' lifetime.
+    render_format: RenderFormat,
+    /// Writes [RenderFormat::HighPrecision] movie frames to [MOVIE_TEXTURE_FORMAT].
+    movie_convert_pipeline: wgpu::RenderPipeline,
     /// The pipeline use to render output of [Self::render_pipeline] to screen.
     swap_chain_pipeline: wgpu::RenderPipeline,
+    /// Pipeline used to halve supersampled paintings down to their final resolution.
+    downsample_pipeline: wgpu::RenderPipeline,
+    /// Blends frames into [Self::accumulation].
+    accumulation_pipeline: wgpu::RenderPipeline,
     /// Color with which to [wgpu::LoadOp::Clear] attachments to render passes.
     clear_color: wgpu::Color,
     /// Resolution of render canvas.
@@ -86,22 +144,105 @@ pub struct Canvas {
     user_uniforms_buffer_size: Option<usize>,
     /// Optional list of user-provided uniforms from JSON file.
     user_uniforms: HashSet<UserUniform>,
+    /// Where each user uniform is written in [Self::user_uniforms_buffer].
+    uniform_layout: UniformLayout,
+    /// Offsets of user uniforms pinned in the shader settings, by name.
+    uniform_pins: HashMap<String, usize>,
+    /// Binary data of the fragment shader currently in the pipelines, reflected to check [Self::uniform_layout].
+    fs_spirv: Vec<u8>,
     /// Optional list of user-provided push constants from JSON file.
     // push_constants: Option<Vec<Box<dyn PushConstant>>>,
     bind_groups: [wgpu::BindGroup; 2],
     bind_group_layouts: [wgpu::BindGroupLayout; 2],
 
     /// List of texture handles and their destination binding locations in the shader.
-    #[allow(dead_code)]
+    /// The first is replaced by the input image, see [Self::load_input_image].
     textures: Vec<AssetTexture>,
+    /// List of color ramps editable in the GUI, bound after [Self::textures].
+    ramp_textures: Vec<RampTexture>,
+    /// Seeded noise bound after [Self::ramp_textures].
+    noise_texture: NoiseTexture2D,
     /// List of post-processing shaders.
     postprocess_ops: Vec<PostProcess>,
     /// Shader to apply sRGB Gamma for paintings.
     srgb_postprocess: PostProcess,
     /// Stopwatch used for calculating time elapsed and other uniforms.
     stop_watch: Stopwatch,
+    /// How fast time advances relative to [Self::stop_watch].
+    time_scale: TimeScale,
     /// Pause/Play state. Also pauses [Self::stop_watch], which sets time data in [Self::uniforms].
     paused: bool,
+    /// Stops time and frame count like [Self::paused], but keeps rendering so uniform edits stay visible.
+    time_frozen: bool,
+    /// Stops only time, frames keep counting and accumulation stays off, e.g. for a held segment of a recording.
+    time_held: bool,
+    /// Whether [Uniforms::jitter] advances through the R2 sequence each frame.
+    jitter_enabled: bool,
+    /// Hold the current jitter offset, e.g. for stills.
+    jitter_frozen: bool,
+    /// Average successive frames while time is frozen, see [Self::accumulation_active].
+    accumulation_enabled: bool,
+    accumulation: Option<Accumulation>,
+    /// Applied to the on-screen output after post-processing, so mirrors fade too.
+    master_fade: MasterFade,
+    /// Set while the Dashboard has seen no input for its idle timeout. Renders slower and dims the output.
+    idle: Option<Idle>,
+    /// Points touching the window, bound as `u_touches`.
+    touches: TouchPoints,
+    /// Filter between the mouse position reported by the window and the one bound to the shader.
+    mouse_smoothing: MouseSmoothing,
+    /// Extra windows the canvas is mirrored to.
+    mirrors: Vec<MirrorOutput>,
+    /// Built when the first mirror is added.
+    mirror_pipeline: Option<wgpu::RenderPipeline>,
+    /// Only exists while the Dashboard shows the histogram, since reading frames back has a cost.
+    histogram: Option<HistogramReadback>,
+    /// Only exists while the Dashboard shows the preview, see [Self::set_thumbnail_size].
+    thumbnail: Option<ThumbnailReadback>,
+    /// Render at this size instead of the window size, letterboxed in the window. See [Self::lock_resolution].
+    locked_resolution: Option<UIntVector2>,
+    /// Width to height ratio of the region rendered in the window, letterboxed. See [Self::set_framing].
+    framing: Option<UIntVector2>,
+    /// Set until the next rendered frame has been copied to the clipboard.
+    clipboard_copy_requested: bool,
+    /// Set until the next rendered frame has been stored as [Self::reference].
+    reference_capture_requested: bool,
+    reference: Option<ReferenceFrame>,
+    /// How [Self::reference] is overlaid on the canvas window, hidden with `None`.
+    compare: Option<CompareSettings>,
+    /// Set when a paused canvas should render once more to show a change to the window-only overlays.
+    paused_redraw_requested: bool,
+    /// Set while the shader fails to compile. The pipelines of the last shader that compiled are kept meanwhile.
+    shader_error: bool,
+    /// Keep showing the last good frame while [Self::shader_error] is set, rather than clearing the window.
+    hold_frame_on_error: bool,
+    /// Shown in the canvas window instead of the output, see [DebugView].
+    debug_view: DebugView,
+    debug_view_pipelines: Option<DebugViewPipelines>,
+    /// Only exists while the canvas window shows a checkerboard behind transparent areas.
+    checkerboard: Option<CheckerboardPipelines>,
+    /// Mirroring of the canvas window, see [Self::set_flip].
+    flip: Flip,
+    /// Whether paintings and movies are mirrored like the canvas window.
+    flip_captures: bool,
+    /// Only exists while the output is flipped.
+    flip_pipelines: Option<FlipPipelines>,
+    /// Paintings and movie frames render an eye per half when set, see [Self::encode_capture_shader_pass].
+    stereo: Option<StereoSettings>,
+    /// Painting rendered on the GPU and waiting to be read back, see [Self::poll_painting].
+    painting_readback: Option<PaintingReadback>,
+    /// Time bound to the painting being encoded instead of the live time, unit: seconds.
+    capture_time: Option<f32>,
+    /// Replaces [Self::stop_watch] as the source of time when frames are driven by external ticks.
+    external_clock: Option<ExternalClock>,
+    /// Whether the window currently has a zero-sized client area (e.g. minimized).
+    /// No swap chain exists for such a size, so rendering is skipped until it is restored.
+    minimized: bool,
+    /// Whether the cursor is grabbed and mouse movement is reported as deltas.
+    /// Shared with the main thread so that Escape releases the mouse rather than quitting.
+    mouse_captured: Arc<AtomicBool>,
+    /// Shared with the Dashboard, which displays the total.
+    vram: VramTracker,
     /// Time of last update. Use to calculate time deltas in [Self::uniforms].
     last_update: std::time::Instant,
 
@@ -111,6 +252,8 @@ pub struct Canvas {
     receiver: Receiver<DashboardMessage>,
     /// Whether to show the window titlebar.
     show_titlebar: bool,
+    /// Hides the window border, titlebar and cursor, see [Self::set_borderless].
+    borderless: bool,
 
     /// Optional file watcher used to watch the fragment shader.
     shader_file_watcher: Option<RecommendedWatcher>,
@@ -122,22 +265,33 @@ pub struct Canvas {
     json_file_watcher_receiver: Option<Receiver<DebouncedEvent>>,
     /// Painting Resolution
     painting_resolution: UIntVector2,
+    /// Whether paintings capture every pass as a layer, see [Self::create_painting].
+    painting_all_passes: bool,
+    /// Frames rendered and averaged into each painting, see [Self::encode_warm_up_average].
+    painting_warm_up_frames: u32,
 }
 
 impl Canvas {
     /// Construct a new Canvas object
     /// * `window` - [winit::window::Window] to render to. Takes ownership
+    /// * `shader_file` - Path of the fragment shader `fs_spirv_data` was loaded from.
     /// * `fs_spirv_data` - Binary data of compiled fragment shader
     /// * `images` - Optional array of images to bind to shader. Images are bound in the same order as specified here.
     /// * `user_uniforms` - Optional array of user-specified uniforms to bind in shader. Uniforms are bound in same order as specified here.
+    /// * `ramps` - Color ramps to bind as 1D textures after the images. Ramps are bound in the same order as specified here.
     /// * `push_constants` - Optional array of push constants to bind in shader. Constants are bound in same order as specified here.
     /// * `transmitter` - [std::sync::mpsc::Sender] object used for sending [CanvasMessage]s to interested parties.
     /// * `receiver` - [std::sync::mpsc::Receiver] object used to received messages from [crate::dashboard::Dashboard]
     pub async fn new(
         window: Window,
+        shader_file: String,
         fs_spirv_data: Vec<u8>,
         images: Option<Vec<image::DynamicImage>>,
         user_uniforms: Option<HashSet<UserUniform>>,
+        ramps: Vec<RampUniform>,
+        render_format: RenderFormat,
+        entry_point: String,
+        include_dirs: Vec<String>,
         // push_constants: Option<Vec<Box<dyn PushConstant>>>,
         transmitter: Sender<CanvasMessage>,
         receiver: Receiver<DashboardMessage>,
@@ -150,13 +304,20 @@ impl Canvas {
             surface = instance.create_surface(&window);
         }
 
-        let adapter = instance
-            .request_adapter(&RequestAdapterOptions {
-                compatible_surface: Some(&surface),
-                power_preference: PowerPreference::HighPerformance,
-            })
-            .await
-            .unwrap();
+        let adapter = match utils::request_adapter(
+            &instance,
+            &surface,
+            PowerPreference::HighPerformance,
+            "Canvas",
+        )
+        .await
+        {
+            Some(adapter) => adapter,
+            None => {
+                error!("No GPU adapter is available to render the canvas. Check that a Vulkan, Metal or DirectX 12 driver is installed.");
+                std::process::exit(1);
+            }
+        };
         // From: https://docs.rs/wgpu/0.6.2/wgpu/struct.Limits.html#structfield.max_push_constant_size
         let max_push_constant_size = match wgpu::BackendBit::PRIMARY {
             wgpu::BackendBit::VULKAN => 256,
@@ -164,22 +325,21 @@ impl Canvas {
             wgpu::BackendBit::METAL => 4096,
             _ => 128,
         };
+        // Request the adapter's full texture size so paintings and recordings can be as large as the GPU allows.
+        let max_texture_dimension_2d = adapter.limits().max_texture_dimension_2d;
         let limits = wgpu::Limits {
             max_push_constant_size,
+            max_texture_dimension_2d,
             ..Default::default()
         };
-        let device_desc = wgpu::DeviceDescriptor {
-            label: None,
-            features: adapter.features(),
-            limits,
-        };
-
-        let (device, queue) = adapter.request_device(&device_desc, None).await.unwrap();
+        let (device, queue) = utils::request_device(&adapter, limits, "Canvas").await;
+        // The device may have fallen back to default limits.
+        let max_texture_dimension_2d = device.limits().max_texture_dimension_2d;
 
         //------------------------------------------------------------------------------------------
         // Create uniforms, device buffer, and bindings.
         let mut uniforms = Uniforms::new();
-        uniforms.resolution = Vector4::new(size.width as f32, size.height as f32, 0.0, 0.0);
+        uniforms.set_resolution(size.width, size.height);
         uniforms.num_textures = match &images {
             Some(vec) => vec.len() as u32,
             None => 0,
@@ -195,17 +355,13 @@ impl Canvas {
         // Bind custom uniforms, if provided
         let mut custom_uniforms_buffer = None;
         let mut custom_uniforms_buffer_size = 0;
+        let uniform_layout = match &user_uniforms {
+            Some(dem_uniforms) => UniformLayout::new(dem_uniforms, &HashMap::new()),
+            None => UniformLayout::default(),
+        };
         if let Some(dem_uniforms) = &user_uniforms {
-            let mut total_size = 0;
-            for a_uniform in dem_uniforms {
-                total_size += a_uniform.bytes.len();
-            }
-
-            custom_uniforms_buffer_size = total_size;
-            let mut bytes = Vec::with_capacity(total_size);
-            for a_uniform in dem_uniforms {
-                bytes.extend_from_slice(&a_uniform.bytes);
-            }
+            let bytes = uniform_layout.pack(dem_uniforms);
+            custom_uniforms_buffer_size = bytes.len();
 
             let desc = BufferInitDescriptor {
                 label: Some("Custom Uniforms Buffer"),
@@ -224,6 +380,11 @@ impl Canvas {
                 asset_textures.push(AssetTexture::new_with_image(an_image, &device, &queue));
             }
         }
+        let mut ramp_textures = Vec::<RampTexture>::new();
+        for a_ramp in &ramps {
+            ramp_textures.push(RampTexture::new(a_ramp, &device, &queue));
+        }
+        let noise_texture = NoiseTexture2D::new(&device, &queue, NoiseType::White, 0);
 
         //------------------------------------------------------------------------------------------
         // Setup swap chain
@@ -250,136 +411,19 @@ impl Canvas {
         });
 
         //------------------------------------------------------------------------------------------
-        // Create the bind group layout and entries.
-        // Uniforms and our generated textures are set 0
-        let primary_bind_group_layout: wgpu::BindGroupLayout;
-        {
-            let mut bind_group_layout_entries = Vec::<wgpu::BindGroupLayoutEntry>::new();
-            // Uniforms are first.
-            bind_group_layout_entries.push(wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStage::FRAGMENT,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            });
-            if let Some(_) = custom_uniforms_buffer {
-                bind_group_layout_entries.push(wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStage::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                });
-            }
-            primary_bind_group_layout =
-                device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    label: None,
-                    entries: &bind_group_layout_entries,
-                });
-        }
-
-        // In set 1, bind provided textures.
-        let secondary_bind_group_layout: wgpu::BindGroupLayout;
-        {
-            let mut bind_group_layout_entries = Vec::<wgpu::BindGroupLayoutEntry>::new();
-            // For now, we only have 1 sampler per set
-            bind_group_layout_entries.push(BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStage::FRAGMENT,
-                ty: wgpu::BindingType::Sampler {
-                    filtering: true,
-                    comparison: false,
-                },
-                count: None,
-            });
-            for i in 1..=asset_textures.len() {
-                bind_group_layout_entries.push(BindGroupLayoutEntry {
-                    binding: i as u32,
-                    visibility: wgpu::ShaderStage::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        multisampled: false,
-                    },
-                    count: None,
-                });
-            }
-            // Create the Bind Group Layout.
-            secondary_bind_group_layout =
-                device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    label: None,
-                    entries: &bind_group_layout_entries,
-                });
-        }
-
-        //------------------------------------------------------------------------------------------
-        // Create Bind Groups from layouts.
-        let primary_bind_group: wgpu::BindGroup;
-        {
-            let mut primary_bind_group_entries: Vec<BindGroupEntry> = Vec::new();
-            // Provided Uniforms first.
-            primary_bind_group_entries.push(wgpu::BindGroupEntry {
-                binding: 0,
-                resource: BindingResource::Buffer(BufferBinding {
-                    buffer: &u_buffer,
-                    offset: 0,
-                    size: Some(NonZeroU64::new(std::mem::size_of_val(&uniforms) as u64).unwrap()),
-                }),
-            });
-            // Custom Uniforms next, if enabled.
-            if let Some(cu_buffer) = &custom_uniforms_buffer {
-                primary_bind_group_entries.push(wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: BindingResource::Buffer(BufferBinding {
-                        buffer: &cu_buffer,
-                        offset: 0,
-                        size: Some(NonZeroU64::new(custom_uniforms_buffer_size as u64).unwrap()),
-                    }),
-                });
-            }
-
-            // Finally create the bind group.
-            primary_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Primary Bind Group"),
-                layout: &primary_bind_group_layout,
-                entries: &primary_bind_group_entries,
-            });
-        }
+        // Create the bind group layouts and bind groups.
+        // Uniforms and custom uniforms are set 0.
+        let (primary_bind_group_layout, primary_bind_group) = create_uniform_bindings(
+            &device,
+            (&u_buffer, std::mem::size_of_val(&uniforms)),
+            custom_uniforms_buffer
+                .as_ref()
+                .map(|buffer| (buffer, custom_uniforms_buffer_size)),
+        );
 
-        let secondary_bind_group: wgpu::BindGroup;
-        {
-            let mut secondary_bind_group_entries: Vec<BindGroupEntry> = Vec::new();
-            let default_sampler = default_color_sampler(&device);
-            secondary_bind_group_entries.push(BindGroupEntry {
-                binding: 0,
-                resource: BindingResource::Sampler(&default_sampler),
-            });
-            // Create texture views.
-            let mut tex_views = Vec::<wgpu::TextureView>::new();
-            for tex in &asset_textures {
-                let texture_view = tex.get_view(0);
-                tex_views.push(texture_view);
-            }
-            // Add texture view bindings.
-            for tex_bind_idx in 1..=tex_views.len() {
-                secondary_bind_group_entries.push(BindGroupEntry {
-                    binding: tex_bind_idx as u32,
-                    resource: BindingResource::TextureView(&tex_views[tex_bind_idx - 1]),
-                });
-            }
-            secondary_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Secondary Bind Group"),
-                layout: &secondary_bind_group_layout,
-                entries: &secondary_bind_group_entries,
-            });
-        }
+        // In set 1, bind provided textures followed by the ramps and the noise texture.
+        let (secondary_bind_group_layout, secondary_bind_group) =
+            create_texture_bindings(&device, &asset_textures, &ramp_textures, &noise_texture);
 
         //------------------------------------------------------------------------------------------
         // Create render pipeline.
@@ -406,8 +450,9 @@ impl Canvas {
             &render_pipeline_layout,
             &vs_module,
             &fs_module,
+            crate::utils::fragment_entry_point(&shader_file, &entry_point),
             (
-                RENDER_TEXTURE_FORMAT,
+                render_format.texture_format(),
                 PAINTING_TEXTURE_FORMAT,
                 MOVIE_TEXTURE_FORMAT,
             ),
@@ -415,6 +460,20 @@ impl Canvas {
         // Swap chain pipeline will never change and is separate from others.
         let swap_chain_pipeline =
             crate::utils::create_swap_chain_pipeline(&device, &vs_module, sc_desc.format);
+        // Linear filtering at texel centers of a half-sized target is an exact 2x2 box filter.
+        let downsample_pipeline =
+            crate::utils::create_swap_chain_pipeline(&device, &vs_module, PAINTING_TEXTURE_FORMAT);
+        let accumulation_pipeline = crate::utils::create_blit_pipeline(
+            &device,
+            &vs_module,
+            PAINTING_TEXTURE_FORMAT,
+            accumulation_blend_state(),
+        );
+        let master_fade =
+            MasterFade::new(&device, &queue, &vs_module, render_format.texture_format());
+        // Converts high precision movie frames to the movie's format as the last step.
+        let movie_convert_pipeline =
+            crate::utils::create_swap_chain_pipeline(&device, &vs_module, MOVIE_TEXTURE_FORMAT);
         let mut custom_size = None;
         if custom_uniforms_buffer_size > 0 {
             custom_size = Some(custom_uniforms_buffer_size);
@@ -426,11 +485,48 @@ impl Canvas {
                 IntVector2::new(size.width as i32, size.height as i32),
             ))
             .unwrap();
-        Self {
+        for a_ramp in ramps {
+            transmitter.send(CanvasMessage::RampForGUI(a_ramp)).unwrap();
+        }
+        if let Some(uniforms) = &user_uniforms {
+            transmitter
+                .send(CanvasMessage::UniformsForGUI(
+                    uniforms.iter().cloned().collect(),
+                ))
+                .unwrap();
+        }
+        transmitter
+            .send(CanvasMessage::MaxTextureDimension(max_texture_dimension_2d))
+            .unwrap();
+        transmitter
+            .send(CanvasMessage::WireframeSupported(
+                device
+                    .features()
+                    .contains(wgpu::Features::NON_FILL_POLYGON_MODE),
+            ))
+            .unwrap();
+        transmitter
+            .send(CanvasMessage::MissingCapabilities(
+                utils::missing_capabilities(&device),
+            ))
+            .unwrap();
+        let vram = VramTracker::default();
+        transmitter
+            .send(CanvasMessage::VramTrackerCreated(vram.clone()))
+            .unwrap();
+        transmitter
+            .send(CanvasMessage::ShaderOpened(shader_file.clone()))
+            .unwrap();
+        vram.set(
+            "Uniform buffers",
+            (std::mem::size_of::<Uniforms>() + custom_uniforms_buffer_size) as u64,
+        );
+        let mut canvas = Self {
             srgb_postprocess: PostProcess::new(
                 &device,
                 Vec::from(POST_PROCESS_SRGB_SHADER_BYTES),
                 custom_uniforms_buffer.is_some(),
+                render_format.texture_format(),
             ),
             window,
             instance,
@@ -438,12 +534,20 @@ impl Canvas {
             adapter,
             device,
             queue,
+            shader_file,
+            entry_point,
+            include_dirs,
+            aa_samples: 1,
             sc_desc,
             swap_chain,
             render_pipeline,
             painting_pipeline,
             movie_pipeline,
+            render_format,
+            movie_convert_pipeline,
             swap_chain_pipeline,
+            downsample_pipeline,
+            accumulation_pipeline,
             clear_color: wgpu::Color {
                 r: 0.1,
                 g: 0.2,
@@ -458,35 +562,180 @@ impl Canvas {
                 Some(uni) => uni,
                 None => HashSet::new(),
             },
+            uniform_layout,
+            uniform_pins: HashMap::new(),
+            fs_spirv: fs_spirv_data,
             // push_constants,
             uniforms_device_buffer: u_buffer,
             bind_groups: [primary_bind_group, secondary_bind_group],
             bind_group_layouts: [primary_bind_group_layout, secondary_bind_group_layout],
             textures: asset_textures,
+            ramp_textures,
+            noise_texture,
             postprocess_ops: vec![],
 
             stop_watch: Stopwatch::start_new(),
+            time_scale: TimeScale::new(),
             paused: false,
+            minimized: false,
+            time_frozen: false,
+            time_held: false,
+            jitter_enabled: false,
+            jitter_frozen: false,
+            accumulation_enabled: false,
+            accumulation: None,
+            master_fade,
+            idle: None,
+            touches: TouchPoints::new(),
+            mouse_smoothing: MouseSmoothing::new(),
+            mirrors: Vec::new(),
+            mirror_pipeline: None,
+            histogram: None,
+            thumbnail: None,
+            reference_capture_requested: false,
+            reference: None,
+            compare: None,
+            paused_redraw_requested: false,
+            shader_error: false,
+            hold_frame_on_error: true,
+            debug_view: DebugView::Off,
+            debug_view_pipelines: None,
+            checkerboard: None,
+            flip: Flip::default(),
+            flip_captures: false,
+            flip_pipelines: None,
+            stereo: None,
+            painting_readback: None,
+            capture_time: None,
+            locked_resolution: None,
+            framing: None,
+            clipboard_copy_requested: false,
+            external_clock: None,
+            mouse_captured: Arc::new(AtomicBool::new(false)),
             last_update: std::time::Instant::now(),
             transmitter,
             receiver,
             show_titlebar: true,
+            borderless: false,
             shader_file_watcher: None,
             shader_file_watcher_receiver: None,
             json_file_watcher: None,
             json_file_watcher_receiver: None,
             painting_resolution: UIntVector2::zero(),
+            painting_all_passes: false,
+            painting_warm_up_frames: 0,
+            vram,
+        };
+        canvas.update_texture_vram();
+        canvas.update_render_target_vram();
+        canvas.update_uniform_layout();
+        canvas
+    }
+
+    /// Record the size of the images and ramps bound to the shader.
+    fn update_texture_vram(&self) {
+        let image_bytes = self.textures.iter().map(|tex| tex.byte_size()).sum();
+        self.vram.set("Input textures", image_bytes);
+        self.vram.set(
+            "Color ramps",
+            self.ramp_textures.len() as u64
+                * texture_bytes(crate::uniforms::RAMP_TEXTURE_WIDTH, 1, RampTexture::FORMAT),
+        );
+        self.vram
+            .set("Noise texture", self.noise_texture.byte_size());
+    }
+
+    /// Record the size of the swap chain and the textures each frame renders through, which depend on the window size.
+    fn update_render_target_vram(&self) {
+        // Assume double buffering.
+        self.vram.set(
+            "Canvas swap chain",
+            2 * texture_bytes(self.size.width, self.size.height, self.sc_desc.format),
+        );
+        // Shader output plus the two post-processing targets.
+        let render_size = self.render_size();
+        self.vram.set(
+            "Canvas render targets",
+            3 * texture_bytes(
+                render_size.x,
+                render_size.y,
+                self.render_format.texture_format(),
+            ),
+        );
+    }
+
+    /// Size the shader renders at on screen: the locked resolution if there is one,
+    /// otherwise the largest region of the framing's aspect that fits the window, or the whole window.
+    pub fn render_size(&self) -> UIntVector2 {
+        match (self.locked_resolution, self.framing) {
+            (Some(resolution), _) => resolution,
+            (None, Some(aspect)) => {
+                let scale = (self.size.width as f32 / aspect.x as f32)
+                    .min(self.size.height as f32 / aspect.y as f32);
+                UIntVector2::new(
+                    ((aspect.x as f32 * scale).round() as u32).max(1),
+                    ((aspect.y as f32 * scale).round() as u32).max(1),
+                )
+            }
+            (None, None) => UIntVector2::new(self.size.width, self.size.height),
         }
     }
 
+    /// Region of the window the render is shown in as `[x, y, width, height]`, unit: pixels.
+    /// A locked resolution is scaled to fit and centered, leaving bars on two sides.
+    fn viewport(&self) -> [f32; 4] {
+        let (window_width, window_height) = (self.size.width as f32, self.size.height as f32);
+        let render_size = self.render_size();
+        let scale = (window_width / render_size.x as f32).min(window_height / render_size.y as f32);
+        let (width, height) = (render_size.x as f32 * scale, render_size.y as f32 * scale);
+        [
+            (window_width - width) / 2.0,
+            (window_height - height) / 2.0,
+            width,
+            height,
+        ]
+    }
+
+    /// Render at a fixed resolution regardless of window resizes, e.g. while recording, or follow the window again with `None`.
+    pub fn lock_resolution(&mut self, resolution: Option<UIntVector2>) {
+        self.locked_resolution = resolution;
+        self.render_size_changed();
+    }
+
+    /// Render only a region of the window with the given width to height ratio, letterboxed, or the whole window with `None`.
+    /// The region follows window resizes, unlike [Self::lock_resolution].
+    pub fn set_framing(&mut self, aspect: Option<UIntVector2>) {
+        self.framing = aspect;
+        self.render_size_changed();
+    }
+
+    /// Point `u_resolution` and `u_aspect` at the new [Self::render_size] and start accumulating over.
+    fn render_size_changed(&mut self) {
+        let render_size = self.render_size();
+        self.uniforms.set_resolution(render_size.x, render_size.y);
+        self.reset_accumulation();
+        self.update_render_target_vram();
+    }
+
     /// Expected to be called from main thread when user resizes canvas window.
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        // Creating a zero-sized swap chain is invalid, so wait until the window is restored.
+        self.minimized = new_size.width == 0 || new_size.height == 0;
+        if self.minimized {
+            return;
+        }
         self.size = new_size;
+        self.reset_accumulation();
+        self.update_render_target_vram();
         self.sc_desc.width = new_size.width;
         self.sc_desc.height = new_size.height;
         self.swap_chain = self.device.create_swap_chain(&self.surface, &self.sc_desc);
-        self.uniforms.resolution.x = new_size.width as f32;
-        self.uniforms.resolution.y = new_size.height as f32;
+        let render_size = self.render_size();
+        self.uniforms.set_resolution(render_size.x, render_size.y);
+        // The new swap chain starts out blank, so draw the held frame again at the new size.
+        if self.shader_error && self.hold_frame_on_error {
+            self.request_paused_redraw();
+        }
         self.transmitter
             .send(CanvasMessage::WindowResized(IntVector2::new(
                 new_size.width as i32,
@@ -495,41 +744,198 @@ impl Canvas {
             .unwrap();
     }
 
+    /// Move [Uniforms::jitter] to the next offset in the R2 sequence.
+    fn advance_jitter(&mut self) {
+        let index = self.uniforms.jitter.z as u32;
+        let (x, y) = crate::utils::r2_sequence(index);
+        self.uniforms.jitter = Vector4::new(x, y, (index + 1) as f32, 0.0);
+    }
+
+    /// Time only advances while neither paused, frozen nor held.
+    fn update_stop_watch(&mut self) {
+        if self.paused || self.time_frozen || self.time_held {
+            self.stop_watch.stop();
+        } else {
+            self.stop_watch.start();
+        }
+    }
+
+    /// The titlebar and border show when the titlebar is shown and the window isn't borderless.
+    fn update_decorations(&mut self) {
+        self.window
+            .set_decorations(self.show_titlebar && !self.borderless);
+    }
+
+    /// Drop the window's border, titlebar and the cursor over it, so screen recordings of the window show only the output.
+    /// Leaving restores the titlebar as it was set.
+    fn set_borderless(&mut self, borderless: bool) {
+        self.borderless = borderless;
+        self.update_decorations();
+        // A captured cursor stays hidden until released.
+        if !self.mouse_captured.load(Ordering::SeqCst) {
+            self.window.set_cursor_visible(!borderless);
+        }
+    }
+
+    /// Let areas of the output with alpha below 1 show what is behind a window built transparent.
+    /// Clears to transparent black, so letterboxing is see-through too.
+    pub fn set_window_transparent(&mut self) {
+        self.clear_color = wgpu::Color::TRANSPARENT;
+    }
+
     /// Used to parse messages received from Dashboard and act accordingly.
     fn dashboard_signal_received(&mut self, message: DashboardMessage) {
         match message {
             DashboardMessage::PausePlayChanged => {
                 self.paused = !self.paused;
-                if self.paused {
-                    self.stop_watch.stop();
-                } else {
-                    self.stop_watch.start();
-                }
+                self.update_stop_watch();
             }
             DashboardMessage::Pause => {
                 self.paused = true;
-                self.stop_watch.stop();
+                self.update_stop_watch();
             }
             DashboardMessage::Play => {
                 self.paused = false;
-                self.stop_watch.start();
+                self.update_stop_watch();
+            }
+            DashboardMessage::JitterChanged { enabled, frozen } => {
+                self.jitter_enabled = enabled;
+                self.jitter_frozen = frozen;
+                if !enabled {
+                    self.uniforms.jitter = Vector4::zero();
+                }
+                self.reset_accumulation();
+            }
+            DashboardMessage::HoldTime(held) => {
+                self.time_held = held;
+                self.update_stop_watch();
+            }
+            DashboardMessage::FreezeTimeChanged => {
+                self.time_frozen = !self.time_frozen;
+                self.reset_accumulation();
+                self.update_stop_watch();
             }
             DashboardMessage::TitlebarStatusChanged => {
                 self.show_titlebar = !self.show_titlebar;
-                self.window.set_decorations(self.show_titlebar);
+                self.update_decorations();
             }
-            DashboardMessage::PaintingRenderRequested(resolution) => {
-                self.create_painting(resolution)
+            DashboardMessage::BorderlessMode(borderless) => self.set_borderless(borderless),
+            DashboardMessage::WindowTitleChanged(title) => self.window.set_title(&title),
+            DashboardMessage::PaintingRenderRequested {
+                resolution,
+                all_passes,
+                time,
+            } => {
+                // Only this painting is rendered at the requested time, the canvas carries on from the live one.
+                self.capture_time = time;
+                self.create_painting(resolution, all_passes);
+                self.capture_time = None;
             }
             DashboardMessage::UniformUpdatedViaGUI(modified_uniform) => {
-                self.user_uniforms.insert(modified_uniform);
+                // Only an actual value change restarts accumulation, not e.g. toggling automation.
+                // Uniforms dropped by a reload of the uniforms file are ignored, they are no longer bound.
+                // So are edits sent before the Dashboard heard of a reload that changed the uniform's type.
+                let current = self
+                    .user_uniforms
+                    .get(&modified_uniform)
+                    .filter(|current| current.bytes.len() == modified_uniform.bytes.len());
+                if let Some(current) = current {
+                    if current.bytes != modified_uniform.bytes {
+                        self.reset_accumulation();
+                    }
+                    self.user_uniforms.replace(modified_uniform);
+                }
+            }
+            DashboardMessage::RampUpdatedViaGUI(modified_ramp) => {
+                self.update_ramp(&modified_ramp);
+                self.reset_accumulation();
             }
+            DashboardMessage::AccumulationChanged(enabled) => {
+                self.accumulation_enabled = enabled;
+                self.reset_accumulation();
+            }
+            DashboardMessage::ResetAccumulation => self.reset_accumulation(),
             DashboardMessage::MovieRenderRequested(resolution) => {
                 self.create_movie_frame(resolution);
             }
+            DashboardMessage::MovieWarmUpRequested(resolution) => {
+                self.warm_up_movie_frame(resolution);
+            }
+            DashboardMessage::AntialiasingChanged(aa_samples) => {
+                self.aa_samples = aa_samples;
+                let file = self.shader_file.clone();
+                self.reload_shader(&file);
+            }
+            DashboardMessage::InputImageSelected(file) => self.load_input_image(&file),
+            DashboardMessage::OpenShader(file) => self.open_shader(&file),
+            DashboardMessage::MasterFade {
+                target,
+                color,
+                duration,
+            } => {
+                self.master_fade.set_color(color, &self.queue);
+                self.master_fade.fade_to(target, duration);
+            }
+            DashboardMessage::EnterIdle(settings) => self.enter_idle(settings),
+            DashboardMessage::ExitIdle => self.exit_idle(),
+            DashboardMessage::MirrorWindowCreated(window) => self.add_mirror(window),
+            DashboardMessage::MirrorWindowClosed(id) => self.remove_mirror(id),
+            DashboardMessage::MirrorSettingsChanged(id, settings) => {
+                self.set_mirror_settings(id, settings)
+            }
             DashboardMessage::PaintingResolutionUpdated(resolution) => {
                 self.painting_resolution = resolution
             }
+            DashboardMessage::PaintingWarmUpUpdated(frames) => {
+                self.painting_warm_up_frames = frames;
+            }
+            DashboardMessage::PaintingPassesUpdated(all_passes) => {
+                self.painting_all_passes = all_passes
+            }
+            DashboardMessage::HistogramEnabled(enabled) => self.set_histogram_enabled(enabled),
+            DashboardMessage::ThumbnailSizeChanged(long_edge) => self.set_thumbnail_size(long_edge),
+            DashboardMessage::LockResolution(resolution) => self.lock_resolution(resolution),
+            DashboardMessage::FramingChanged(aspect) => self.set_framing(aspect),
+            DashboardMessage::CopyFrameToClipboard => self.request_clipboard_copy(),
+            DashboardMessage::CaptureReference => self.request_reference_capture(),
+            DashboardMessage::ClearReference => self.clear_reference(),
+            DashboardMessage::CompareChanged(compare) => self.set_compare(compare),
+            DashboardMessage::DebugView(view) => self.set_debug_view(view),
+            DashboardMessage::CheckerboardChanged(enabled) => self.set_checkerboard(enabled),
+            DashboardMessage::HoldFrameOnError(hold) => self.set_hold_frame_on_error(hold),
+            DashboardMessage::MouseSmoothingChanged(amount) => self.set_mouse_smoothing(amount),
+            DashboardMessage::TimeScaleChanged(scale) => self.set_time_scale(scale),
+            DashboardMessage::UniformOffsetsPinned(pins) => self.pin_uniform_offsets(pins),
+            DashboardMessage::FlipOutput {
+                horizontal,
+                vertical,
+                captures,
+            } => self.set_flip(
+                Flip {
+                    horizontal,
+                    vertical,
+                },
+                captures,
+            ),
+            DashboardMessage::StereoChanged(stereo) => self.set_stereo(stereo),
+            DashboardMessage::VariationSheetRequested(request) => {
+                self.create_variation_sheet(request)
+            }
+            DashboardMessage::NoiseChanged { noise_type, seed } => {
+                self.noise_texture.update(noise_type, seed, &self.queue);
+                self.reset_accumulation();
+            }
+        }
+    }
+
+    /// Re-upload the texture of the ramp with the same name, if one is bound.
+    fn update_ramp(&mut self, ramp: &RampUniform) -> bool {
+        match self.ramp_textures.iter().find(|tex| tex.name == ramp.name) {
+            Some(texture) => {
+                texture.update(ramp, &self.queue);
+                true
+            }
+            None => false,
         }
     }
 
@@ -581,24 +987,40 @@ impl Canvas {
         }
         // Referesh user uniforms buffer
         if let Some(buffer) = &self.user_uniforms_buffer {
-            let mut total_size = 0;
-            for a_uniform in &self.user_uniforms {
-                total_size += a_uniform.bytes.len();
-            }
-            let mut bytes = Vec::with_capacity(total_size);
-            for a_uniform in &self.user_uniforms {
-                bytes.extend_from_slice(&a_uniform.bytes);
-            }
-            self.queue.write_buffer(&buffer, 0, &bytes);
+            self.queue
+                .write_buffer(buffer, 0, &self.uniform_layout.pack(&self.user_uniforms));
         }
 
-        // Only actually update uniforms if not paused, but we always update buffer.
-        if !self.paused {
+        self.poll_histogram();
+        self.poll_thumbnail();
+        if self.master_fade.step() {
+            self.transmitter
+                .send(CanvasMessage::MasterFadeLevel(self.master_fade.level))
+                .unwrap();
+        }
+        self.step_mouse_smoothing();
+        // Jitter advances with every rendered frame, even with time frozen, so accumulation gets new samples.
+        if !self.paused && self.jitter_enabled && !self.jitter_frozen {
+            self.advance_jitter();
+        }
+        // Only actually update uniforms if not paused or frozen, but we always update buffer.
+        if !self.paused && !self.time_frozen {
             self.uniforms.frame_num += 1;
-            self.uniforms.time = self.stop_watch.elapsed().as_secs_f32();
             let now = std::time::Instant::now();
-            let delta_duration = now.duration_since(self.last_update);
-            self.uniforms.time_delta = delta_duration.as_secs_f32();
+            match &self.external_clock {
+                _ if self.time_held => self.uniforms.time_delta = 0.0,
+                Some(clock) => {
+                    self.uniforms.time_delta = clock.time() - self.uniforms.time;
+                    self.uniforms.time = clock.time();
+                }
+                None => {
+                    let elapsed = self.stop_watch.elapsed().as_secs_f32();
+                    self.uniforms.time = self.time_scale.time(elapsed);
+                    let delta_duration = now.duration_since(self.last_update);
+                    self.uniforms.time_delta =
+                        delta_duration.as_secs_f32() * self.time_scale.scale();
+                }
+            }
             let today = chrono::Local::now();
             self.uniforms.date =
                 IntVector4::new(today.year(), today.month() as i32, today.day() as i32, 0);
@@ -644,11 +1066,7 @@ impl Canvas {
                 ..
             } => {
                 self.paused = !self.paused;
-                if self.paused {
-                    self.stop_watch.stop();
-                } else {
-                    self.stop_watch.start();
-                }
+                self.update_stop_watch();
                 self.transmitter
                     .send(CanvasMessage::PausePlayChanged)
                     .unwrap();
@@ -658,33 +1076,95 @@ impl Canvas {
                 virtual_keycode: Some(VirtualKeyCode::P),
                 ..
             } => {
-                self.create_painting(self.painting_resolution.clone());
+                self.create_painting(self.painting_resolution, self.painting_all_passes);
+            }
+            KeyboardInput {
+                state: ElementState::Pressed,
+                virtual_keycode: Some(VirtualKeyCode::C),
+                ..
+            } => {
+                let captured = self.mouse_captured.load(Ordering::SeqCst);
+                self.set_mouse_captured(!captured);
             }
             KeyboardInput {
                 state: ElementState::Pressed,
                 virtual_keycode: Some(VirtualKeyCode::Escape),
                 ..
             } => {}
+            KeyboardInput {
+                state: ElementState::Released,
+                virtual_keycode: Some(VirtualKeyCode::Escape),
+                ..
+            } => self.set_mouse_captured(false),
             _ => {}
         }
     }
 
+    /// Flag that is set while the mouse is captured, see [Self::set_mouse_captured].
+    pub fn mouse_capture_flag(&self) -> Arc<AtomicBool> {
+        self.mouse_captured.clone()
+    }
+
+    /// Grab and hide the cursor so mouse movement is reported as deltas in [Uniforms::mouse_delta],
+    /// or release it and go back to reporting absolute positions.
+    fn set_mouse_captured(&mut self, captured: bool) {
+        if self.mouse_captured.load(Ordering::SeqCst) == captured {
+            return;
+        }
+        if let Err(e) = self.window.set_cursor_grab(captured) {
+            warn!("Unable to change cursor grab: {}", e);
+            return;
+        }
+        self.window
+            .set_cursor_visible(!captured && !self.borderless);
+        if captured {
+            let _ = self.window.set_cursor_position(self.window_center());
+        }
+        self.mouse_captured.store(captured, Ordering::SeqCst);
+        self.uniforms.mouse_delta = Vector4::new(0.0, 0.0, captured as i32 as f32, 0.0);
+        info!("Mouse {}.", if captured { "captured" } else { "released" });
+    }
+
+    fn window_center(&self) -> winit::dpi::PhysicalPosition<u32> {
+        winit::dpi::PhysicalPosition::new(self.size.width / 2, self.size.height / 2)
+    }
+
     /// Expected to be called from main thread to handle IO events.
     /// This fn assumes the incoming events are from the Canvas' window.
     pub fn input(&mut self, incoming_event: winit::event::WindowEvent<'_>) {
         match incoming_event {
             WindowEvent::KeyboardInput { input, .. } => self.handle_keyoard_input(&input),
+            WindowEvent::CursorMoved { position, .. }
+                if self.mouse_captured.load(Ordering::SeqCst) =>
+            {
+                // Report movement away from the window center, then warp back so the cursor never hits an edge.
+                let center = self.window_center();
+                let delta = Vector2::new(
+                    position.x as f32 - center.x as f32,
+                    position.y as f32 - center.y as f32,
+                );
+                if delta.x == 0.0 && delta.y == 0.0 {
+                    return;
+                }
+                self.uniforms.mouse_delta.x += delta.x;
+                self.uniforms.mouse_delta.y += delta.y;
+                let _ = self.window.set_cursor_position(center);
+                self.transmitter
+                    .send(CanvasMessage::MouseMoved(delta))
+                    .unwrap();
+            }
             WindowEvent::CursorMoved { position, .. } => {
-                self.uniforms.mouse_position.z = self.uniforms.mouse_position.x;
-                self.uniforms.mouse_position.w = self.uniforms.mouse_position.y;
-                self.uniforms.mouse_position.x = position.x as f32;
-                self.uniforms.mouse_position.y = position.y as f32;
-                // Send message.
+                // Report the position in the rendered region, which is the whole window unless letterboxed.
+                let [x, y, width, height] = self.viewport();
+                let render_size = self.render_size();
+                let position = Vector2::new(
+                    (position.x as f32 - x) * render_size.x as f32 / width,
+                    (position.y as f32 - y) * render_size.y as f32 / height,
+                );
+                self.move_mouse(position);
+                // The Dashboard shows the raw position, even while the bound one is smoothed.
                 self.transmitter
-                    .send(CanvasMessage::MouseMoved(Vector2::new(
-                        self.uniforms.mouse_position.x,
-                        self.uniforms.mouse_position.y,
-                    )))
+                    .send(CanvasMessage::MouseMoved(position))
                     .unwrap();
             }
             WindowEvent::MouseInput { button, state, .. } => match button {
@@ -701,6 +1181,7 @@ impl Canvas {
                     self.uniforms.mouse_button.w = (state == ElementState::Pressed) as i32
                 }
             },
+            WindowEvent::Touch(touch) => self.handle_touch(touch),
             WindowEvent::Resized(physical_size) => {
                 self.resize(physical_size);
             }
@@ -0,0 +1,109 @@
+use super::Canvas;
+use crate::vector::Vector2;
+use std::time::Instant;
+
+/// Cutoff frequency of the derivative filter, unit: Hz.
+const DERIVATIVE_CUTOFF: f32 = 1.0;
+/// How much faster movement raises the cutoff, so quick strokes lag less than slow ones. Unit: Hz per pixel/s.
+const SPEED_COEFFICIENT: f32 = 0.007;
+
+/// One-euro filter on the mouse position: slow movement is smoothed heavily, fast movement barely lags.
+/// See <https://gery.casiez.net/1euro/>.
+#[derive(Debug)]
+pub struct MouseSmoothing {
+    /// 0: off, 1: strongest.
+    amount: f32,
+    /// Raw position in rendered pixels, as last reported by the window.
+    raw: Vector2,
+    /// Filtered position and speed, and when they were computed.
+    state: Option<(Vector2, Vector2, Instant)>,
+}
+
+impl MouseSmoothing {
+    pub fn new() -> MouseSmoothing {
+        MouseSmoothing {
+            amount: 0.0,
+            raw: Vector2::zero(),
+            state: None,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.amount > 0.0
+    }
+
+    /// Filter [Self::raw] up to `now`.
+    fn step(&mut self, now: Instant) -> Vector2 {
+        let (position, speed, then) = match self.state {
+            Some(state) => state,
+            None => (self.raw, Vector2::zero(), now),
+        };
+        let dt = now.duration_since(then).as_secs_f32();
+        if dt <= 0.0 {
+            self.state = Some((position, speed, now));
+            return position;
+        }
+        // 10 Hz when barely smoothing down to 0.1 Hz at full strength.
+        let min_cutoff = 10f32.powf(1.0 - 2.0 * self.amount);
+        let d_alpha = smoothing_factor(DERIVATIVE_CUTOFF, dt);
+        let speed = Vector2::new(
+            lerp(speed.x, (self.raw.x - position.x) / dt, d_alpha),
+            lerp(speed.y, (self.raw.y - position.y) / dt, d_alpha),
+        );
+        let magnitude = (speed.x * speed.x + speed.y * speed.y).sqrt();
+        let alpha = smoothing_factor(min_cutoff + SPEED_COEFFICIENT * magnitude, dt);
+        let position = Vector2::new(
+            lerp(position.x, self.raw.x, alpha),
+            lerp(position.y, self.raw.y, alpha),
+        );
+        self.state = Some((position, speed, now));
+        position
+    }
+}
+
+/// Weight of a new sample in a low-pass filter with the given cutoff, in Hz, after `dt` seconds.
+fn smoothing_factor(cutoff: f32, dt: f32) -> f32 {
+    let tau = 1.0 / (2.0 * std::f32::consts::PI * cutoff);
+    dt / (dt + tau)
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+impl Canvas {
+    /// Smooth the mouse position bound to the shader. 0 binds the raw position, 1 smooths the most.
+    pub fn set_mouse_smoothing(&mut self, amount: f32) {
+        self.mouse_smoothing.amount = amount.clamp(0.0, 1.0);
+        // Start from where the mouse is rather than easing in from a stale position.
+        self.mouse_smoothing.state = None;
+        if !self.mouse_smoothing.enabled() {
+            let raw = self.mouse_smoothing.raw;
+            self.set_mouse_uniform(raw);
+        }
+    }
+
+    /// Record a new mouse position, in rendered pixels. Bound directly unless smoothing is on, see
+    /// [Self::step_mouse_smoothing].
+    pub fn move_mouse(&mut self, position: Vector2) {
+        self.mouse_smoothing.raw = position;
+        if !self.mouse_smoothing.enabled() {
+            self.set_mouse_uniform(position);
+        }
+    }
+
+    /// Move the bound mouse position towards the raw one. Called every frame, so it settles once the mouse stops.
+    pub fn step_mouse_smoothing(&mut self) {
+        if self.mouse_smoothing.enabled() {
+            let position = self.mouse_smoothing.step(Instant::now());
+            self.set_mouse_uniform(position);
+        }
+    }
+
+    fn set_mouse_uniform(&mut self, position: Vector2) {
+        self.uniforms.mouse_position.z = self.uniforms.mouse_position.x;
+        self.uniforms.mouse_position.w = self.uniforms.mouse_position.y;
+        self.uniforms.mouse_position.x = position.x;
+        self.uniforms.mouse_position.y = position.y;
+    }
+}
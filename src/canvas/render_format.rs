@@ -0,0 +1,37 @@
+use super::{PAINTING_TEXTURE_FORMAT, RENDER_TEXTURE_FORMAT};
+
+/// Precision the canvas renders and post-processes in on screen and for movies, independent of the swap chain.
+/// Output is only converted to the display's or the movie's 8-bit format as the last step.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RenderFormat {
+    /// 8 bits per channel, linear. Cheapest, but gradients band once post-processed.
+    Standard,
+    /// 16-bit float per channel, like paintings. Values above 1 survive until the final conversion.
+    HighPrecision,
+}
+
+impl RenderFormat {
+    pub const ALL: [RenderFormat; 2] = [RenderFormat::Standard, RenderFormat::HighPrecision];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RenderFormat::Standard => "rgba8",
+            RenderFormat::HighPrecision => "rgba16f",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<RenderFormat> {
+        RenderFormat::ALL
+            .iter()
+            .copied()
+            .find(|f| f.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Format of the textures the canvas renders through.
+    pub fn texture_format(&self) -> wgpu::TextureFormat {
+        match self {
+            RenderFormat::Standard => RENDER_TEXTURE_FORMAT,
+            RenderFormat::HighPrecision => PAINTING_TEXTURE_FORMAT,
+        }
+    }
+}
@@ -1,22 +1,84 @@
 use std::num::NonZeroU32;
 
+use crate::renderer;
 use crate::texture::default_color_sampler;
+use crate::utils::PaintingLayer;
 use crate::vector::UIntVector2;
 use crate::{postprocessing, recording::MOVIE_TEXTURE_FORMAT};
-use log::info;
-use wgpu::util::{BufferInitDescriptor, DeviceExt};
+use futures::FutureExt;
+use log::{info, warn};
 use wgpu::{
     BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor, BindGroupLayoutEntry,
     BindingResource, Extent3d, LoadOp, Operations, Origin3d,
 };
 
 use super::message::CanvasMessage;
-use super::{Canvas, PAINTING_TEXTURE_FORMAT, RENDER_TEXTURE_FORMAT};
+use super::{Canvas, MapFuture, VariationSheetLayout, PAINTING_TEXTURE_FORMAT};
 use crate::uniforms::Uniforms;
+
+/// A painting whose render has been submitted, mapped for reading once the GPU finishes it.
+pub struct PaintingReadback {
+    /// One per captured pass, the final output last.
+    layers: Vec<PaintingLayer>,
+    resolution: UIntVector2,
+    mapping: MapFuture,
+    /// Set when the only layer holds the cells of a variation sheet rather than a single painting.
+    sheet: Option<VariationSheetLayout>,
+}
+
 impl Canvas {
+    /// Paused canvases don't render, so draw once more to show a change to what the window displays.
+    pub fn request_paused_redraw(&mut self) {
+        if self.paused {
+            self.paused_redraw_requested = true;
+            self.window.request_redraw();
+        }
+    }
+
+    /// Clear the window to the clear color, shown in place of the last good frame while the shader fails to compile.
+    fn clear_window(&mut self) {
+        let frame = match self.swap_chain.get_current_frame() {
+            Ok(frame) => frame,
+            Err(frame_err) => {
+                self.transmitter
+                    .send(CanvasMessage::SwapChainFrameError(frame_err))
+                    .unwrap();
+                return;
+            }
+        };
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Clear Window Encoder"),
+            });
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Clear Window"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: &frame.output.view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(self.clear_color),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        self.queue.submit(Some(encoder.finish()));
+    }
+
     /// Render the shader on the canvas.
     pub fn render_canvas(&mut self) {
-        if self.paused {
+        // While paused, time and jitter stand still, so rendering again reproduces the frame on screen.
+        let redraw_while_paused = self.clipboard_copy_requested
+            || std::mem::replace(&mut self.paused_redraw_requested, false);
+        if self.minimized {
+            return;
+        }
+        if self.shader_error && !self.hold_frame_on_error {
+            self.clear_window();
+            return;
+        }
+        if self.paused && !redraw_while_paused {
             return;
         }
         let frame = match self.swap_chain.get_current_frame() {
@@ -29,20 +91,30 @@ impl Canvas {
             }
         };
         // Create the texture to render to.
+        let render_size = self.render_size();
         let tex_desc = wgpu::TextureDescriptor {
             size: Extent3d {
-                width: self.size.width,
-                height: self.size.height,
+                width: render_size.x,
+                height: render_size.y,
                 depth_or_array_layers: 1,
             },
-            format: RENDER_TEXTURE_FORMAT,
+            format: self.render_format.texture_format(),
             usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
             label: Some("Canvas Render"),
             dimension: wgpu::TextureDimension::D2,
             mip_level_count: 1,
             sample_count: 1,
         };
-        let render_tex = self.device.create_texture(&tex_desc);
+        // While accumulating, render with the high bit-depth painting pipeline so the average keeps its precision.
+        let accumulate = self.accumulation_active();
+        if !accumulate {
+            self.reset_accumulation();
+        }
+        let mut shader_tex_desc = tex_desc.clone();
+        if accumulate {
+            shader_tex_desc.format = PAINTING_TEXTURE_FORMAT;
+        }
+        let render_tex = self.device.create_texture(&shader_tex_desc);
         let render_tex_view = render_tex.create_view(&wgpu::TextureViewDescriptor::default());
 
         let mut encoder = self
@@ -69,7 +141,11 @@ impl Canvas {
             for i in 0..self.bind_groups.len() {
                 render_pass.set_bind_group(i as u32, &self.bind_groups[i], &[]);
             }
-            render_pass.set_pipeline(&self.render_pipeline);
+            render_pass.set_pipeline(if accumulate {
+                &self.painting_pipeline
+            } else {
+                &self.render_pipeline
+            });
             // Set push constants, if any.
             // if let Some(constants) = self.push_constants.as_ref() {
             //     let mut offset: usize = 0;
@@ -86,15 +162,37 @@ impl Canvas {
             render_pass.draw(0..3, 0..1);
         }
 
+        // Blend this frame into the running average and post-process that instead.
+        let accumulated_view;
+        let spare_tex_view;
+        let mut stage_in = &render_tex_view;
+        if accumulate {
+            accumulated_view = self
+                .accumulate_frame(&mut encoder, &render_tex_view)
+                .create_view(&wgpu::TextureViewDescriptor::default());
+            stage_in = &accumulated_view;
+            spare_tex_view = self
+                .device
+                .create_texture(&tex_desc)
+                .create_view(&wgpu::TextureViewDescriptor::default());
+            self.transmitter
+                .send(CanvasMessage::AccumulationSamples(
+                    self.accumulation.as_ref().unwrap().samples,
+                ))
+                .unwrap();
+        } else {
+            spare_tex_view = render_tex.create_view(&wgpu::TextureViewDescriptor::default());
+        }
+
         // We can't create bind groups with swap chain textures, so have to create another temp tex.
         let postprocessing_tex = self.device.create_texture(&tex_desc);
         let postprocessing_tex_view =
             postprocessing_tex.create_view(&wgpu::TextureViewDescriptor::default());
 
-        // Then render any post-processing effects.
-        let mut stage_in = &render_tex_view;
-        let mut stage_out = &postprocessing_tex_view;
+        // Then render any post-processing effects, alternating between two render textures.
+        let stage_targets = [&postprocessing_tex_view, &spare_tex_view];
         for i in 0..self.postprocess_ops.len() {
+            let stage_out = stage_targets[i % 2];
             let postprocess_op = &self.postprocess_ops[i];
             // If user has provided custom uniforms, pass them to the post-processing stage as well.
             let mut custom_data = None;
@@ -114,11 +212,19 @@ impl Canvas {
                 self.clear_color,
                 postprocessing::PipelineType::Render,
             );
-            // Swap input and output textures handles
-            std::mem::swap(&mut stage_in, &mut stage_out);
+            stage_in = stage_out;
+        }
+        if self.master_fade.level > 0.0 {
+            let stage_out = stage_targets[self.postprocess_ops.len() % 2];
+            self.apply_master_fade(&mut encoder, stage_in, stage_out);
+            stage_in = stage_out;
         }
-        // Swap one more time to get final output tex (undo last swap).
-        std::mem::swap(&mut stage_in, &mut stage_out);
+
+        // Debug views and the reference overlay are only drawn to the canvas window, everything else gets the clean output.
+        self.encode_reference_capture(&mut encoder, stage_in);
+        let debug_view = self.encode_debug_view(&mut encoder, stage_in);
+        let window_view = debug_view.as_ref().unwrap_or(stage_in);
+        let compare_view = self.encode_reference_compare(&mut encoder, window_view);
 
         // Render back to swap chain texture.
         // Build new specialized bind groups for this render pass.
@@ -158,7 +264,9 @@ impl Canvas {
                 },
                 BindGroupEntry {
                     binding: 1,
-                    resource: BindingResource::TextureView(stage_out),
+                    resource: BindingResource::TextureView(
+                        compare_view.as_ref().unwrap_or(window_view),
+                    ),
                 },
             ],
         });
@@ -176,33 +284,98 @@ impl Canvas {
                 depth_stencil_attachment: None,
             });
 
-            render_pass.set_bind_group(0, &sc_bind_group, &[]);
+            let [x, y, width, height] = self.viewport();
+            render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
 
-            render_pass.set_pipeline(&self.swap_chain_pipeline);
-            render_pass.draw(0..3, 0..1);
+            // The sampler and texture bindings match, so the output can be drawn by any of these pipelines.
+            let flip = self.flip_pipelines.as_ref();
+            let pipeline = match &self.checkerboard {
+                Some(checkerboard) => {
+                    render_pass.set_pipeline(&checkerboard.checkerboard);
+                    render_pass.draw(0..3, 0..1);
+                    flip.map_or(&checkerboard.over, |f| &f.window_over)
+                }
+                None => flip.map_or(&self.swap_chain_pipeline, |f| &f.window),
+            };
+            render_pass.set_bind_group(0, &sc_bind_group, &[]);
+            render_pass.set_pipeline(pipeline);
+            render_pass.draw(0..3, self.flip.instances());
         }
+        let mirror_frames = self.render_mirrors(
+            &mut encoder,
+            stage_in,
+            compare_view.as_ref().unwrap_or(window_view),
+        );
+        let histogram_copied = self.encode_histogram_copy(&mut encoder, stage_in);
+        let thumbnail_copied = self.encode_thumbnail_copy(&mut encoder, stage_in);
+        let clipboard_copy = self.encode_clipboard_copy(&mut encoder, stage_in);
 
         let command_buffer = encoder.finish();
         self.queue.submit(Some(command_buffer));
+        drop(mirror_frames);
+        if histogram_copied {
+            self.start_histogram_readback();
+        }
+        if thumbnail_copied {
+            self.start_thumbnail_readback();
+        }
+        if let Some(copy) = clipboard_copy {
+            self.finish_clipboard_copy(copy);
+        }
 
         self.transmitter
             .send(CanvasMessage::RenderPassSubmitted)
             .unwrap();
-        self.transmitter.send(CanvasMessage::FrameStep).unwrap();
+        self.transmitter
+            .send(CanvasMessage::FrameStep(std::time::Instant::now()))
+            .unwrap();
     }
 
     /// Similar to [Self::render_canvas()], but renders to a very high bit-depth texture and writes output to file.
-    /// **Note:** File is written to disk asynchronously.
-    pub fn create_painting(&mut self, resolution: UIntVector2) {
+    /// **Note:** Returns once the render is submitted. [Self::poll_painting] hands the result to the Dashboard,
+    /// which writes the file to disk asynchronously.
+    /// With `all_passes`, the output of the shader and of each post-process pass is captured as a layer of its own.
+    pub fn create_painting(&mut self, resolution: UIntVector2, all_passes: bool) {
+        if self.painting_readback.is_some() {
+            warn!("A painting is already being rendered.");
+            return;
+        }
+        // While accumulating, capture the converged average at canvas resolution instead of rendering one noisy frame.
+        // The average is of the live time, so a painting at another time renders its own frame.
+        // It is also of a single view, so stereo paintings render their eyes.
+        let accumulated_size = match &self.accumulation {
+            Some(accumulation)
+                if self.accumulation_active()
+                    && accumulation.samples > 0
+                    && self.capture_time.is_none()
+                    && self.stereo.is_none() =>
+            {
+                info!(
+                    "Capturing {} accumulated samples at canvas resolution {} x {}.",
+                    accumulation.samples, accumulation.size.x, accumulation.size.y
+                );
+                Some(accumulation.size)
+            }
+            _ => None,
+        };
+        let resolution = accumulated_size.unwrap_or(resolution);
+        // Render at a higher resolution and downsample afterwards when supersampling.
+        let supersample = match accumulated_size {
+            Some(_) => 1,
+            None => self.painting_supersample_factor(resolution),
+        };
+        let render_resolution =
+            UIntVector2::new(resolution.x * supersample, resolution.y * supersample);
         let painting_tex_desc = wgpu::TextureDescriptor {
             size: Extent3d {
-                width: resolution.x as u32,
-                height: resolution.y as u32,
+                width: render_resolution.x as u32,
+                height: render_resolution.y as u32,
                 depth_or_array_layers: 1,
             },
             format: PAINTING_TEXTURE_FORMAT,
             usage: wgpu::TextureUsage::RENDER_ATTACHMENT
                 | wgpu::TextureUsage::COPY_SRC
+                | wgpu::TextureUsage::COPY_DST
                 | wgpu::TextureUsage::SAMPLED,
             label: Some("Painting"),
             dimension: wgpu::TextureDimension::D2,
@@ -221,80 +394,30 @@ impl Canvas {
                 label: Some("Painting Encoder"),
             });
 
-        // Modify Uniforms as necessary for painting render
-        {
-            let mut painting_uniforms = self.uniforms.clone();
-            let width_ratio = resolution.x as f32 / self.uniforms.resolution.x as f32;
-            let height_ratio = resolution.y as f32 / self.uniforms.resolution.y as f32;
-            painting_uniforms.mouse_position.x *= width_ratio;
-            painting_uniforms.mouse_position.z *= width_ratio;
-            painting_uniforms.mouse_position.y *= height_ratio;
-            painting_uniforms.mouse_position.w *= height_ratio;
-            painting_uniforms.resolution.x = resolution.x as f32;
-            painting_uniforms.resolution.y = resolution.y as f32;
-
-            // Copy uniforms from CPU to staging buffer, then copy from staging buffer to main buf.
-            let descriptor = BufferInitDescriptor {
-                label: Some("Uniforms Buffer"),
-                contents: bytemuck::bytes_of(&painting_uniforms),
-                usage: wgpu::BufferUsage::COPY_SRC,
-            };
-            let staging_buffer = self.device.create_buffer_init(&descriptor);
-
-            encoder.copy_buffer_to_buffer(
-                &staging_buffer,
-                0,
-                &self.uniforms_device_buffer,
-                0,
-                std::mem::size_of::<Uniforms>() as u64,
-            );
-        }
-
-        // Buffer to copy texture into after all rendering finishes.
-        let buffer_desc = wgpu::BufferDescriptor {
-            label: Some("Painting Staging Buffer"),
-            usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
-            size: ((resolution.x * resolution.y) as usize * std::mem::size_of::<half::f16>() * 4)
-                as u64,
-            mapped_at_creation: false,
-        };
-        let buffer = self.device.create_buffer(&buffer_desc);
-
         let painting_start_time = std::time::Instant::now();
-        // First run the pipeline.
-        {
-            let painting_view = painting.create_view(&wgpu::TextureViewDescriptor::default());
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: None,
-                color_attachments: &[wgpu::RenderPassColorAttachment {
-                    view: &painting_view,
-                    resolve_target: None,
-                    ops: Operations {
-                        load: LoadOp::Clear(self.clear_color),
-                        store: true,
-                    },
-                }],
-                depth_stencil_attachment: None,
-            });
-
-            for i in 0..self.bind_groups.len() {
-                render_pass.set_bind_group(i as u32, &self.bind_groups[i], &[]);
-            }
-            render_pass.set_pipeline(&self.painting_pipeline);
-            // Set push constants, if any.
-            // if let Some(constants) = self.push_constants.as_ref() {
-            //     let mut offset: usize = 0;
-            //     for a_constant in constants {
-            //         let bytes = a_constant.bytes();
-            //         render_pass.set_push_constants(
-            //             wgpu::ShaderStage::FRAGMENT,
-            //             offset as u32,
-            //             &bytes,
-            //         );
-            //         offset += a_constant.size();
-            //     }
-            // }
-            render_pass.draw(0..3, 0..1);
+        // First run the pipeline, once per eye in stereo, or copy in the accumulated average.
+        if accumulated_size.is_some() {
+            self.encode_capture_uniforms(&mut encoder, render_resolution);
+            let accumulation = self.accumulation.as_ref().unwrap();
+            encoder.copy_texture_to_texture(
+                wgpu::ImageCopyTexture {
+                    mip_level: 0,
+                    origin: Origin3d::ZERO,
+                    texture: &accumulation.texture,
+                },
+                wgpu::ImageCopyTexture {
+                    mip_level: 0,
+                    origin: Origin3d::ZERO,
+                    texture: &painting,
+                },
+                Extent3d {
+                    width: resolution.x,
+                    height: resolution.y,
+                    depth_or_array_layers: 1,
+                },
+            );
+        } else {
+            self.encode_warm_up_average(&mut encoder, &painting, render_resolution);
         }
 
         // Then run all post-processing steps, in order.
@@ -304,7 +427,22 @@ impl Canvas {
         if let Some(custom_buffer) = self.user_uniforms_buffer.as_ref() {
             custom_data = Some((custom_buffer, self.user_uniforms_buffer_size.unwrap()));
         }
-        for postprocess_op in &mut self.postprocess_ops {
+        // Single pass shaders have nothing in between to capture.
+        let capture_passes = all_passes && !self.postprocess_ops.is_empty();
+        let mut layers = Vec::new();
+        if capture_passes {
+            layers.push(PaintingLayer {
+                name: String::from("Shader"),
+                buffer: self.encode_painting_layer(
+                    &mut encoder,
+                    stage_in,
+                    render_resolution,
+                    resolution,
+                    supersample,
+                ),
+            });
+        }
+        for (i, postprocess_op) in self.postprocess_ops.iter().enumerate() {
             let input_view = stage_in.create_view(&wgpu::TextureViewDescriptor::default());
             let output_view = stage_out.create_view(&wgpu::TextureViewDescriptor::default());
             postprocess_op.post_process(
@@ -322,6 +460,33 @@ impl Canvas {
             );
             // Swap input and output textures handles
             std::mem::swap(&mut stage_in, &mut stage_out);
+            // The last pass is the final output, captured below.
+            if capture_passes && i + 1 < self.postprocess_ops.len() {
+                layers.push(PaintingLayer {
+                    name: format!("Post-process {}", i + 1),
+                    buffer: self.encode_painting_layer(
+                        &mut encoder,
+                        stage_in,
+                        render_resolution,
+                        resolution,
+                        supersample,
+                    ),
+                });
+            }
+        }
+
+        // Bring supersampled paintings back down to the requested resolution.
+        let downsampled;
+        let srgb_output;
+        if supersample > 1 {
+            downsampled =
+                self.downsample_painting(&mut encoder, stage_in, render_resolution, supersample);
+            let mut srgb_output_desc = painting_tex_desc.clone();
+            srgb_output_desc.size.width = resolution.x;
+            srgb_output_desc.size.height = resolution.y;
+            srgb_output = self.device.create_texture(&srgb_output_desc);
+            stage_in = &downsampled;
+            stage_out = &srgb_output;
         }
 
         // Run one more post-process op, the sRGB conversion.
@@ -344,69 +509,423 @@ impl Canvas {
         }
 
         // Then encode a copy of the texture to the buffer.
-        {
-            let tex_copy_view = wgpu::ImageCopyTexture {
-                mip_level: 0,
-                origin: Origin3d::ZERO,
-                texture: stage_out,
-            };
-            let buf_copy_view = wgpu::ImageCopyBuffer {
-                buffer: &buffer,
-                layout: wgpu::ImageDataLayout {
-                    bytes_per_row: NonZeroU32::new(
-                        ((resolution.x * 4) as usize * std::mem::size_of::<half::f16>()) as u32,
-                    ),
-                    offset: 0,
-                    rows_per_image: NonZeroU32::new(resolution.y),
-                },
-            };
-            encoder.copy_texture_to_buffer(
-                tex_copy_view,
-                buf_copy_view,
-                Extent3d {
-                    width: resolution.x as u32,
-                    height: resolution.y as u32,
-                    depth_or_array_layers: 1,
-                },
-            );
-        }
+        layers.push(PaintingLayer {
+            name: match capture_passes {
+                true => format!("Post-process {}", self.postprocess_ops.len()),
+                false => String::from("Painting"),
+            },
+            buffer: self.encode_painting_copy(&mut encoder, stage_out, resolution),
+        });
 
         let command_buffer = encoder.finish();
         self.queue.submit(Some(command_buffer));
+        self.start_painting_readback(layers, resolution, None, painting_start_time);
+    }
 
-        self.transmitter
-            .send(CanvasMessage::PaintingStarted(
-                buffer,
+    /// Encode the painting's shader pass into `target`, averaged with [Self::painting_warm_up_frames] frames before it
+    /// for shaders that need several frames to settle, e.g. noise seeded by the frame number. Like accumulation, time
+    /// stands still and only `u_frame_num` steps back, so a painting at a given time always comes out the same.
+    fn encode_warm_up_average(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::Texture,
+        resolution: UIntVector2,
+    ) {
+        if self.painting_warm_up_frames == 0 {
+            self.encode_capture_shader_pass(
+                encoder,
+                target,
+                PAINTING_TEXTURE_FORMAT,
+                &self.painting_pipeline,
                 resolution,
-                painting_start_time,
-            ))
+                0,
+            );
+            return;
+        }
+        // Each frame is rendered here, then blended into the target.
+        let frame = self.device.create_texture(&wgpu::TextureDescriptor {
+            size: Extent3d {
+                width: resolution.x,
+                height: resolution.y,
+                depth_or_array_layers: 1,
+            },
+            format: PAINTING_TEXTURE_FORMAT,
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT
+                | wgpu::TextureUsage::SAMPLED
+                | wgpu::TextureUsage::COPY_DST,
+            label: Some("Warm-up Frame"),
+            dimension: wgpu::TextureDimension::D2,
+            mip_level_count: 1,
+            sample_count: 1,
+        });
+        let frame_view = frame.create_view(&wgpu::TextureViewDescriptor::default());
+        let target_view = target.create_view(&wgpu::TextureViewDescriptor::default());
+        for (samples, frames_before) in (0..=self.painting_warm_up_frames).rev().enumerate() {
+            self.encode_capture_shader_pass(
+                encoder,
+                &frame,
+                PAINTING_TEXTURE_FORMAT,
+                &self.painting_pipeline,
+                resolution,
+                frames_before,
+            );
+            renderer::encode_average_blend(
+                &self.device,
+                encoder,
+                &self.accumulation_pipeline,
+                &frame_view,
+                &target_view,
+                samples as u32,
+            );
+        }
+    }
+
+    /// Encode the downsampling and sRGB conversion of an intermediate pass output, like the final one,
+    /// and a copy of the result to a new staging buffer.
+    fn encode_painting_layer(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        render_resolution: UIntVector2,
+        resolution: UIntVector2,
+        supersample: u32,
+    ) -> wgpu::Buffer {
+        let downsampled;
+        let input = match supersample > 1 {
+            true => {
+                downsampled =
+                    self.downsample_painting(encoder, texture, render_resolution, supersample);
+                &downsampled
+            }
+            false => texture,
+        };
+        let output = self.device.create_texture(&wgpu::TextureDescriptor {
+            size: Extent3d {
+                width: resolution.x,
+                height: resolution.y,
+                depth_or_array_layers: 1,
+            },
+            format: PAINTING_TEXTURE_FORMAT,
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT
+                | wgpu::TextureUsage::COPY_SRC
+                | wgpu::TextureUsage::SAMPLED,
+            label: Some("Painting Layer"),
+            dimension: wgpu::TextureDimension::D2,
+            mip_level_count: 1,
+            sample_count: 1,
+        });
+        let input_view = input.create_view(&wgpu::TextureViewDescriptor::default());
+        let output_view = output.create_view(&wgpu::TextureViewDescriptor::default());
+        self.srgb_postprocess.post_process(
+            &input_view,
+            &output_view,
+            (
+                &self.uniforms_device_buffer,
+                std::mem::size_of_val(&self.uniforms),
+            ),
+            self.user_uniforms_buffer
+                .as_ref()
+                .map(|buffer| (buffer, self.user_uniforms_buffer_size.unwrap())),
+            &self.device,
+            encoder,
+            self.clear_color,
+            postprocessing::PipelineType::Painting,
+        );
+        self.encode_painting_copy(encoder, &output, resolution)
+    }
+
+    /// Encode a copy of a finished painting texture to a new staging buffer, flipped if captures are.
+    fn encode_painting_copy(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        resolution: UIntVector2,
+    ) -> wgpu::Buffer {
+        let flipped =
+            self.encode_capture_flip(encoder, texture, PAINTING_TEXTURE_FORMAT, resolution);
+        let texture = flipped.as_ref().unwrap_or(texture);
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Painting Staging Buffer"),
+            usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
+            size: ((resolution.x * resolution.y) as usize * std::mem::size_of::<half::f16>() * 4)
+                as u64,
+            mapped_at_creation: false,
+        });
+        let tex_copy_view = wgpu::ImageCopyTexture {
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+            texture,
+        };
+        let buf_copy_view = wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                bytes_per_row: NonZeroU32::new(
+                    ((resolution.x * 4) as usize * std::mem::size_of::<half::f16>()) as u32,
+                ),
+                offset: 0,
+                rows_per_image: NonZeroU32::new(resolution.y),
+            },
+        };
+        encoder.copy_texture_to_buffer(
+            tex_copy_view,
+            buf_copy_view,
+            Extent3d {
+                width: resolution.x,
+                height: resolution.y,
+                depth_or_array_layers: 1,
+            },
+        );
+        buffer
+    }
+
+    /// Map the `layers` once the submitted painting render has finished, see [Self::poll_painting].
+    /// `resolution` is the size of the finished image, which is reported to the Dashboard.
+    pub fn start_painting_readback(
+        &mut self,
+        layers: Vec<PaintingLayer>,
+        resolution: UIntVector2,
+        sheet: Option<VariationSheetLayout>,
+        start_time: std::time::Instant,
+    ) {
+        // Mapping only completes when this device is polled, which happens here rather than on the writer thread.
+        let mappings = layers
+            .iter()
+            .map(|layer| layer.buffer.slice(..).map_async(wgpu::MapMode::Read));
+        let mapping = futures::future::try_join_all(mappings).map(|result| result.map(|_| ()));
+        self.painting_readback = Some(PaintingReadback {
+            layers,
+            resolution,
+            mapping: Box::pin(mapping),
+            sheet,
+        });
+        self.transmitter
+            .send(CanvasMessage::PaintingStarted(resolution, start_time))
             .unwrap();
     }
 
+    /// Check whether the GPU has finished the painting being rendered, without waiting for it.
+    /// Once it has, the mapped buffer is sent to the Dashboard in [CanvasMessage::PaintingRendered],
+    /// or [CanvasMessage::VariationSheetRendered] for a variation sheet.
+    /// Must be called regularly while a painting is in flight, including while paused,
+    /// since nothing else polls the device when no frames are rendered.
+    pub fn poll_painting(&mut self) {
+        let readback = match self.painting_readback.as_mut() {
+            Some(readback) => readback,
+            None => return,
+        };
+        self.device.poll(wgpu::Maintain::Poll);
+        let result = match readback.mapping.as_mut().now_or_never() {
+            Some(result) => result,
+            None => return,
+        };
+        let PaintingReadback {
+            mut layers,
+            resolution,
+            sheet,
+            ..
+        } = self.painting_readback.take().unwrap();
+        match result {
+            Ok(()) => {
+                let message = match (sheet, layers.pop()) {
+                    (Some(layout), Some(layer)) => {
+                        CanvasMessage::VariationSheetRendered(layer.buffer, layout)
+                    }
+                    (_, last) => {
+                        layers.extend(last);
+                        CanvasMessage::PaintingRendered(layers, resolution)
+                    }
+                };
+                self.transmitter.send(message).unwrap();
+            }
+            Err(e) => {
+                warn!("Error reading back painting: {}", e);
+                self.transmitter
+                    .send(CanvasMessage::PaintingFailed(e.to_string()))
+                    .unwrap();
+            }
+        }
+    }
+
+    /// Supersampling factor per axis for a painting of the given resolution.
+    /// Taken from the AA quality setting, but reduced if the supersampled texture would exceed device limits.
+    pub fn painting_supersample_factor(&self, resolution: UIntVector2) -> u32 {
+        let max_dimension = self.device.limits().max_texture_dimension_2d;
+        let mut factor = self.aa_samples.max(1);
+        while factor > 1
+            && (resolution.x * factor > max_dimension || resolution.y * factor > max_dimension)
+        {
+            factor /= 2;
+        }
+        if factor != self.aa_samples {
+            warn!(
+                "Painting supersampling reduced from {}x to {}x to stay within the {} pixel texture limit.",
+                self.aa_samples, factor, max_dimension
+            );
+        }
+        factor
+    }
+
+    /// Repeatedly halve `input` until it is `factor` times smaller on each axis.
+    /// Sampling the midpoint of each 2x2 block with linear filtering averages it, so `factor` must be a power of two.
+    pub fn downsample_painting(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::Texture,
+        resolution: UIntVector2,
+        factor: u32,
+    ) -> wgpu::Texture {
+        let layout = self.downsample_pipeline.get_bind_group_layout(0);
+        let sampler = default_color_sampler(&self.device);
+        let mut current: Option<wgpu::Texture> = None;
+        let mut size = resolution;
+        let mut remaining = factor;
+        while remaining > 1 {
+            size = UIntVector2::new((size.x / 2).max(1), (size.y / 2).max(1));
+            let output = self.device.create_texture(&wgpu::TextureDescriptor {
+                size: Extent3d {
+                    width: size.x,
+                    height: size.y,
+                    depth_or_array_layers: 1,
+                },
+                format: PAINTING_TEXTURE_FORMAT,
+                usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+                label: Some("Painting Downsample"),
+                dimension: wgpu::TextureDimension::D2,
+                mip_level_count: 1,
+                sample_count: 1,
+            });
+            {
+                let input_view = current
+                    .as_ref()
+                    .unwrap_or(input)
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                let output_view = output.create_view(&wgpu::TextureViewDescriptor::default());
+                let bind_group = self.device.create_bind_group(&BindGroupDescriptor {
+                    label: Some("Painting Downsample Bind Group"),
+                    layout: &layout,
+                    entries: &[
+                        BindGroupEntry {
+                            binding: 0,
+                            resource: BindingResource::Sampler(&sampler),
+                        },
+                        BindGroupEntry {
+                            binding: 1,
+                            resource: BindingResource::TextureView(&input_view),
+                        },
+                    ],
+                });
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: None,
+                    color_attachments: &[wgpu::RenderPassColorAttachment {
+                        view: &output_view,
+                        resolve_target: None,
+                        ops: Operations {
+                            load: LoadOp::Clear(self.clear_color),
+                            store: true,
+                        },
+                    }],
+                    depth_stencil_attachment: None,
+                });
+                render_pass.set_bind_group(0, &bind_group, &[]);
+                render_pass.set_pipeline(&self.downsample_pipeline);
+                render_pass.draw(0..3, 0..1);
+            }
+            current = Some(output);
+            remaining /= 2;
+        }
+        current.unwrap()
+    }
+
+    /// Encode an upload of the uniforms as they should be for a capture at `resolution`, see [Self::capture_uniforms].
+    /// The next [Self::update] restores the canvas' uniforms.
+    pub fn encode_capture_uniforms(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        resolution: UIntVector2,
+    ) {
+        self.encode_uniforms_upload(encoder, &self.capture_uniforms(resolution));
+    }
+
+    /// The uniforms as they should be for a capture at `resolution`.
+    /// The mouse and touch positions are scaled to match and `u_aspect` is the capture's, so the framing matches the preview.
+    /// `u_time` is [Self::capture_time], if set.
+    pub fn capture_uniforms(&self, resolution: UIntVector2) -> Uniforms {
+        let mut capture_uniforms = self.uniforms;
+        let width_ratio = resolution.x as f32 / self.uniforms.resolution.x;
+        let height_ratio = resolution.y as f32 / self.uniforms.resolution.y;
+        capture_uniforms.mouse_position.x *= width_ratio;
+        capture_uniforms.mouse_position.z *= width_ratio;
+        capture_uniforms.mouse_position.y *= height_ratio;
+        capture_uniforms.mouse_position.w *= height_ratio;
+        for touch in capture_uniforms.touches.iter_mut() {
+            touch.x *= width_ratio;
+            touch.y *= height_ratio;
+        }
+        capture_uniforms.set_resolution(resolution.x, resolution.y);
+        if let Some(time) = self.capture_time {
+            capture_uniforms.time = time;
+        }
+        capture_uniforms
+    }
+
+    /// Encode a copy of `uniforms` to the device buffer, see [renderer::encode_uniforms_upload].
+    pub fn encode_uniforms_upload(&self, encoder: &mut wgpu::CommandEncoder, uniforms: &Uniforms) {
+        renderer::encode_uniforms_upload(
+            &self.device,
+            encoder,
+            &self.uniforms_device_buffer,
+            uniforms,
+        );
+    }
+
     /// Expected to be called immediately after the render() function.
     pub fn post_render(&mut self) {
-        // Inform Dashboard of each of our user-provided uniforms.
-        for a_uniform in &self.user_uniforms {
-            let uni = a_uniform.clone();
-            self.transmitter
-                .send(CanvasMessage::UniformForGUI(uni))
-                .unwrap();
-        }
+        // Mouse deltas are per frame.
+        self.uniforms.mouse_delta.x = 0.0;
+        self.uniforms.mouse_delta.y = 0.0;
         // Inform our window we have new contents for it to draw.
         self.window.request_redraw();
     }
 
     /// Called when Dashboard requests a movie render frame.
+    /// With [super::RenderFormat::HighPrecision], the shader and post-processing run at painting precision
+    /// and the frame is only converted to [MOVIE_TEXTURE_FORMAT] at the end.
     pub fn create_movie_frame(&mut self, resolution: UIntVector2) {
-        let painting_tex_desc = wgpu::TextureDescriptor {
+        let frame_start_time = std::time::Instant::now();
+        let buffer = self.render_movie_frame(resolution);
+        self.transmitter
+            .send(CanvasMessage::MovieFrameStarted(
+                buffer,
+                resolution,
+                frame_start_time,
+            ))
+            .unwrap();
+    }
+
+    /// Called when Dashboard requests a warm-up frame before a recording starts.
+    /// Renders a movie frame like [Self::create_movie_frame] but throws it away.
+    pub fn warm_up_movie_frame(&mut self, resolution: UIntVector2) {
+        info!("Rendering a warm-up movie frame.");
+        self.render_movie_frame(resolution);
+    }
+
+    /// Encode and submit a movie frame, returning the staging buffer it is copied to.
+    fn render_movie_frame(&mut self, resolution: UIntVector2) -> wgpu::Buffer {
+        let high_precision = self.render_format == super::RenderFormat::HighPrecision;
+        let frame_format = match high_precision {
+            true => PAINTING_TEXTURE_FORMAT,
+            false => MOVIE_TEXTURE_FORMAT,
+        };
+        let mut painting_tex_desc = wgpu::TextureDescriptor {
             size: Extent3d {
                 width: resolution.x as u32,
                 height: resolution.y as u32,
                 depth_or_array_layers: 1,
             },
-            format: MOVIE_TEXTURE_FORMAT,
+            format: frame_format,
             usage: wgpu::TextureUsage::RENDER_ATTACHMENT
                 | wgpu::TextureUsage::COPY_SRC
+                | wgpu::TextureUsage::COPY_DST
                 | wgpu::TextureUsage::SAMPLED,
             label: Some("Movie Frame"),
             dimension: wgpu::TextureDimension::D2,
@@ -434,42 +953,19 @@ impl Canvas {
                 label: Some("Movie Frame Encoder"),
             });
 
-        let frame_start_time = std::time::Instant::now();
-        // First run the pipeline.
-        {
-            let movie_frame_view = movie_frame.create_view(&wgpu::TextureViewDescriptor::default());
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: None,
-                color_attachments: &[wgpu::RenderPassColorAttachment {
-                    view: &movie_frame_view,
-                    resolve_target: None,
-                    ops: Operations {
-                        load: LoadOp::Clear(self.clear_color),
-                        store: true,
-                    },
-                }],
-                depth_stencil_attachment: None,
-            });
-
-            for i in 0..self.bind_groups.len() {
-                render_pass.set_bind_group(i as u32, &self.bind_groups[i], &[]);
-            }
-            render_pass.set_pipeline(&self.movie_pipeline);
-            // Set push constants, if any.
-            // if let Some(constants) = self.push_constants.as_ref() {
-            //     let mut offset: usize = 0;
-            //     for a_constant in constants {
-            //         let bytes = a_constant.bytes();
-            //         render_pass.set_push_constants(
-            //             wgpu::ShaderStage::FRAGMENT,
-            //             offset as u32,
-            //             &bytes,
-            //         );
-            //         offset += a_constant.size();
-            //     }
-            // }
-            render_pass.draw(0..3, 0..1);
-        }
+        // First run the pipeline, once per eye in stereo.
+        // Movie frames may not have the canvas' resolution, e.g. when it isn't locked while recording.
+        self.encode_capture_shader_pass(
+            &mut encoder,
+            &movie_frame,
+            frame_format,
+            match high_precision {
+                true => &self.painting_pipeline,
+                false => &self.movie_pipeline,
+            },
+            resolution,
+            0,
+        );
 
         // Then run all post-processing steps, in order.
         let mut stage_in = &movie_frame;
@@ -495,13 +991,60 @@ impl Canvas {
                 &self.device,
                 &mut encoder,
                 self.clear_color,
-                postprocessing::PipelineType::Movie,
+                match high_precision {
+                    true => postprocessing::PipelineType::Painting,
+                    false => postprocessing::PipelineType::Movie,
+                },
             );
             // Swap input and output textures handles
             std::mem::swap(&mut stage_in, &mut stage_out);
         }
-        if self.postprocess_ops.len() == 0 {
-            std::mem::swap(&mut stage_in, &mut stage_out);
+
+        // Only now drop to the movie's 8-bit format.
+        let converted_frame;
+        if high_precision {
+            painting_tex_desc.format = MOVIE_TEXTURE_FORMAT;
+            converted_frame = self.device.create_texture(&painting_tex_desc);
+            let input_view = stage_in.create_view(&wgpu::TextureViewDescriptor::default());
+            let output_view = converted_frame.create_view(&wgpu::TextureViewDescriptor::default());
+            let sampler = default_color_sampler(&self.device);
+            let bind_group = self.device.create_bind_group(&BindGroupDescriptor {
+                label: Some("Movie Frame Conversion Bind Group"),
+                layout: &self.movie_convert_pipeline.get_bind_group_layout(0),
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::Sampler(&sampler),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::TextureView(&input_view),
+                    },
+                ],
+            });
+            {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Movie Frame Conversion Render Pass"),
+                    color_attachments: &[wgpu::RenderPassColorAttachment {
+                        view: &output_view,
+                        resolve_target: None,
+                        ops: Operations {
+                            load: LoadOp::Clear(self.clear_color),
+                            store: true,
+                        },
+                    }],
+                    depth_stencil_attachment: None,
+                });
+                render_pass.set_bind_group(0, &bind_group, &[]);
+                render_pass.set_pipeline(&self.movie_convert_pipeline);
+                render_pass.draw(0..3, 0..1);
+            }
+            stage_in = &converted_frame;
+        }
+        let flipped =
+            self.encode_capture_flip(&mut encoder, stage_in, MOVIE_TEXTURE_FORMAT, resolution);
+        if let Some(flipped) = flipped.as_ref() {
+            stage_in = flipped;
         }
 
         // Then encode a copy of the texture to the buffer.
@@ -509,7 +1052,7 @@ impl Canvas {
             let tex_copy_view = wgpu::ImageCopyTexture {
                 mip_level: 0,
                 origin: Origin3d::ZERO,
-                texture: stage_out,
+                texture: stage_in,
             };
             let buf_copy_view = wgpu::ImageCopyBuffer {
                 buffer: &buffer,
@@ -534,13 +1077,6 @@ impl Canvas {
 
         let command_buffer = encoder.finish();
         self.queue.submit(Some(command_buffer));
-
-        self.transmitter
-            .send(CanvasMessage::MovieFrameStarted(
-                buffer,
-                resolution,
-                frame_start_time,
-            ))
-            .unwrap();
+        buffer
     }
 }
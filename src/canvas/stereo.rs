@@ -0,0 +1,154 @@
+use super::Canvas;
+use crate::renderer::encode_shader_pass;
+use crate::vector::{UIntVector2, Vector4};
+use wgpu::{Extent3d, Origin3d};
+
+/// How the two eyes of a stereo capture are arranged in the frame.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StereoLayout {
+    /// Left eye in the left half, right eye in the right half.
+    SideBySide,
+    /// Left eye in the top half, right eye in the bottom half.
+    TopBottom,
+}
+
+impl StereoLayout {
+    pub const ALL: [StereoLayout; 2] = [StereoLayout::SideBySide, StereoLayout::TopBottom];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StereoLayout::SideBySide => "Side by Side",
+            StereoLayout::TopBottom => "Top Bottom",
+        }
+    }
+}
+
+/// Render paintings and movie frames once per eye, packed into one frame of the requested resolution.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StereoSettings {
+    pub layout: StereoLayout,
+    /// Distance between the eyes in the shader's scene units, bound in `u_stereo.w`.
+    pub interaxial: f32,
+}
+
+impl StereoSettings {
+    /// Size of each eye in a frame of `resolution`. An odd split leaves a line between the eyes.
+    pub fn eye_size(&self, resolution: UIntVector2) -> UIntVector2 {
+        match self.layout {
+            StereoLayout::SideBySide => UIntVector2::new((resolution.x / 2).max(1), resolution.y),
+            StereoLayout::TopBottom => UIntVector2::new(resolution.x, (resolution.y / 2).max(1)),
+        }
+    }
+
+    /// Top left corner of the left and right eye in a frame of `resolution`, unit: pixels.
+    pub fn eye_origins(&self, resolution: UIntVector2) -> [UIntVector2; 2] {
+        let eye = self.eye_size(resolution);
+        let right = match self.layout {
+            StereoLayout::SideBySide => UIntVector2::new(resolution.x - eye.x, 0),
+            StereoLayout::TopBottom => UIntVector2::new(0, resolution.y - eye.y),
+        };
+        [UIntVector2::zero(), right]
+    }
+
+    /// `u_stereo` for the left (0) or right (1) eye.
+    pub fn eye_uniform(&self, eye: u32) -> Vector4 {
+        let direction = if eye == 0 { -0.5 } else { 0.5 };
+        Vector4::new(
+            direction * self.interaxial,
+            eye as f32,
+            1.0,
+            self.interaxial,
+        )
+    }
+}
+
+impl Canvas {
+    /// Render paintings and movie frames in stereo, or as a single view with `None`.
+    /// The canvas window always shows a single view.
+    pub fn set_stereo(&mut self, stereo: Option<StereoSettings>) {
+        self.stereo = stereo;
+    }
+
+    /// Encode the shader pass of a capture at `resolution` into `target`, which must have `format`.
+    /// In stereo, each eye renders at its own size with `u_stereo` set and is copied into its part of `target`,
+    /// which must then allow copies to it. Either way the uniforms are left as [Self::encode_capture_uniforms]
+    /// sets them for `resolution`, for the post-processing that follows.
+    /// The shader runs with `u_frame_num` `frames_before` frames back, see [Self::encode_warm_up_average].
+    pub fn encode_capture_shader_pass(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::Texture,
+        format: wgpu::TextureFormat,
+        pipeline: &wgpu::RenderPipeline,
+        resolution: UIntVector2,
+        frames_before: u32,
+    ) {
+        let stereo = match self.stereo {
+            Some(stereo) => stereo,
+            None => {
+                let mut uniforms = self.capture_uniforms(resolution);
+                uniforms.frame_num = uniforms.frame_num.saturating_sub(frames_before);
+                self.encode_uniforms_upload(encoder, &uniforms);
+                let target_view = target.create_view(&wgpu::TextureViewDescriptor::default());
+                self.encode_shader_draw(encoder, &target_view, pipeline);
+                return;
+            }
+        };
+        let eye_size = stereo.eye_size(resolution);
+        let origins = stereo.eye_origins(resolution);
+        for (eye, origin) in origins.iter().enumerate() {
+            let eye_texture = self.device.create_texture(&wgpu::TextureDescriptor {
+                size: Extent3d {
+                    width: eye_size.x,
+                    height: eye_size.y,
+                    depth_or_array_layers: 1,
+                },
+                format,
+                usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+                label: Some("Stereo Eye"),
+                dimension: wgpu::TextureDimension::D2,
+                mip_level_count: 1,
+                sample_count: 1,
+            });
+            // Copies are ordered with the passes, so each eye sees its own uniforms.
+            let mut eye_uniforms = self.capture_uniforms(eye_size);
+            eye_uniforms.stereo = stereo.eye_uniform(eye as u32);
+            eye_uniforms.frame_num = eye_uniforms.frame_num.saturating_sub(frames_before);
+            self.encode_uniforms_upload(encoder, &eye_uniforms);
+            let eye_view = eye_texture.create_view(&wgpu::TextureViewDescriptor::default());
+            self.encode_shader_draw(encoder, &eye_view, pipeline);
+            encoder.copy_texture_to_texture(
+                wgpu::ImageCopyTexture {
+                    mip_level: 0,
+                    origin: Origin3d::ZERO,
+                    texture: &eye_texture,
+                },
+                wgpu::ImageCopyTexture {
+                    mip_level: 0,
+                    origin: Origin3d {
+                        x: origin.x,
+                        y: origin.y,
+                        z: 0,
+                    },
+                    texture: target,
+                },
+                Extent3d {
+                    width: eye_size.x,
+                    height: eye_size.y,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+        self.encode_capture_uniforms(encoder, resolution);
+    }
+
+    /// Encode a pass running the shader with `pipeline` over all of `view`, see [encode_shader_pass].
+    pub fn encode_shader_draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        pipeline: &wgpu::RenderPipeline,
+    ) {
+        encode_shader_pass(encoder, view, pipeline, &self.bind_groups, self.clear_color);
+    }
+}
@@ -0,0 +1,256 @@
+use super::{Canvas, CanvasMessage, MapFuture, VS_MODULE_BYTES};
+use crate::texture::default_color_sampler;
+use crate::utils::compile_builtin_shader;
+use crate::vector::UIntVector2;
+use futures::FutureExt;
+use std::num::NonZeroU32;
+use wgpu::{
+    BindGroupDescriptor, BindGroupEntry, BindingResource, Extent3d, LoadOp, Operations, Origin3d,
+};
+
+static THUMBNAIL_FS_SOURCE: &str = include_str!("../../shaders/thumbnail-downscale.frag");
+/// Stored as displayed, like the canvas window.
+static THUMBNAIL_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Size of a thumbnail of an output of `size` whose longer edge is `long_edge` pixels. Never larger than the output.
+pub fn thumbnail_size(size: UIntVector2, long_edge: u32) -> UIntVector2 {
+    let scale = (long_edge as f32 / size.x.max(size.y).max(1) as f32).min(1.0);
+    UIntVector2::new(
+        ((size.x as f32 * scale).round() as u32).max(1),
+        ((size.y as f32 * scale).round() as u32).max(1),
+    )
+}
+
+/// Downscales the output on the GPU and copies the result to the CPU, one readback at a time, so only a small image
+/// is sent to the Dashboard however large the canvas renders.
+pub struct ThumbnailReadback {
+    /// Longer edge of the thumbnail, unit: pixels.
+    long_edge: u32,
+    /// Size of [Self::texture], for the output size it was created for.
+    size: UIntVector2,
+    texture: wgpu::Texture,
+    buffer: wgpu::Buffer,
+    /// Rows of [Self::buffer] are padded to [wgpu::COPY_BYTES_PER_ROW_ALIGNMENT].
+    padded_bytes_per_row: u32,
+    pipeline: wgpu::RenderPipeline,
+    /// Set while the buffer holds a frame being mapped.
+    mapping: Option<MapFuture>,
+}
+
+/// Pipeline that box filters the output into a thumbnail.
+fn create_thumbnail_pipeline(device: &wgpu::Device) -> wgpu::RenderPipeline {
+    let vs_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+        label: Some("Vertex Shader"),
+        source: wgpu::util::make_spirv(VS_MODULE_BYTES),
+        flags: wgpu::ShaderFlags::VALIDATION,
+    });
+    let fs_data = compile_builtin_shader(
+        THUMBNAIL_FS_SOURCE,
+        shaderc::ShaderKind::Fragment,
+        "thumbnail-downscale.frag",
+    )
+    .expect("Error compiling thumbnail fragment shader.");
+    let fs_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+        label: Some("Thumbnail Fragment Shader"),
+        source: wgpu::util::make_spirv(&fs_data),
+        flags: wgpu::ShaderFlags::VALIDATION,
+    });
+    crate::utils::create_sampling_pipeline(
+        device,
+        &vs_module,
+        &fs_module,
+        THUMBNAIL_TEXTURE_FORMAT,
+        wgpu::BlendState::REPLACE,
+    )
+}
+
+impl ThumbnailReadback {
+    /// * `pipeline` - Reused from a previous readback, since only the sizes change. Created if `None`.
+    fn new(
+        device: &wgpu::Device,
+        output_size: UIntVector2,
+        long_edge: u32,
+        pipeline: Option<wgpu::RenderPipeline>,
+    ) -> ThumbnailReadback {
+        let size = thumbnail_size(output_size, long_edge);
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = (size.x * 4).div_ceil(align) * align;
+        ThumbnailReadback {
+            long_edge,
+            size,
+            texture: device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Thumbnail Readback"),
+                size: Extent3d {
+                    width: size.x,
+                    height: size.y,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: THUMBNAIL_TEXTURE_FORMAT,
+                usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+            }),
+            buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Thumbnail Readback Buffer"),
+                size: (padded_bytes_per_row * size.y) as u64,
+                usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
+                mapped_at_creation: false,
+            }),
+            padded_bytes_per_row,
+            pipeline: pipeline.unwrap_or_else(|| create_thumbnail_pipeline(device)),
+            mapping: None,
+        }
+    }
+
+    /// Whether the readback was created for an output of this size.
+    fn fits(&self, output_size: UIntVector2) -> bool {
+        let size = thumbnail_size(output_size, self.long_edge);
+        size.x == self.size.x && size.y == self.size.y
+    }
+
+    /// GPU memory held while the thumbnail is enabled.
+    pub fn byte_size(&self) -> u64 {
+        crate::utils::texture_bytes(self.size.x, self.size.y, THUMBNAIL_TEXTURE_FORMAT)
+            + (self.padded_bytes_per_row * self.size.y) as u64
+    }
+}
+
+impl Canvas {
+    /// Send the Dashboard thumbnails of the output whose longer edge is this many pixels, or stop with `None`.
+    /// The readback textures are only allocated while thumbnails are on.
+    pub fn set_thumbnail_size(&mut self, long_edge: Option<u32>) {
+        let pipeline = self.thumbnail.take().map(|t| t.pipeline);
+        self.thumbnail = long_edge.map(|edge| {
+            ThumbnailReadback::new(&self.device, self.render_size(), edge.max(1), pipeline)
+        });
+        let bytes = self.thumbnail.as_ref().map_or(0, |t| t.byte_size());
+        self.vram.set("Thumbnail", bytes);
+    }
+
+    /// Encode a downscaled copy of `output` into the readback buffer, if the previous one has been consumed and the
+    /// readback still fits the output. Returns whether a copy was encoded, in which case
+    /// [Self::start_thumbnail_readback] must follow the submit.
+    pub fn encode_thumbnail_copy(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        output: &wgpu::TextureView,
+    ) -> bool {
+        let readback = match &self.thumbnail {
+            Some(readback) if readback.mapping.is_none() && readback.fits(self.render_size()) => {
+                readback
+            }
+            _ => return false,
+        };
+        let view = readback
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = default_color_sampler(&self.device);
+        let bind_group = self.device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Thumbnail Bind Group"),
+            layout: &readback.pipeline.get_bind_group_layout(0),
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Sampler(&sampler),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::TextureView(output),
+                },
+            ],
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Thumbnail Render Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(self.clear_color),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.set_pipeline(&readback.pipeline);
+            render_pass.draw(0..3, 0..1);
+        }
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                texture: &readback.texture,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &readback.buffer,
+                layout: wgpu::ImageDataLayout {
+                    bytes_per_row: NonZeroU32::new(readback.padded_bytes_per_row),
+                    offset: 0,
+                    rows_per_image: NonZeroU32::new(readback.size.y),
+                },
+            },
+            Extent3d {
+                width: readback.size.x,
+                height: readback.size.y,
+                depth_or_array_layers: 1,
+            },
+        );
+        true
+    }
+
+    /// Start mapping the copy encoded by [Self::encode_thumbnail_copy]. Call after submitting it.
+    pub fn start_thumbnail_readback(&mut self) {
+        if let Some(readback) = self.thumbnail.as_mut() {
+            let mapping = readback.buffer.slice(..).map_async(wgpu::MapMode::Read);
+            readback.mapping = Some(Box::pin(mapping));
+        }
+    }
+
+    /// Send the last copied thumbnail to the Dashboard once its readback has finished. Between readbacks, follow
+    /// changes of the output size.
+    pub fn poll_thumbnail(&mut self) {
+        let outgrown = match &self.thumbnail {
+            Some(readback) if readback.mapping.is_none() && !readback.fits(self.render_size()) => {
+                Some(readback.long_edge)
+            }
+            Some(_) => None,
+            None => return,
+        };
+        if let Some(long_edge) = outgrown {
+            self.set_thumbnail_size(Some(long_edge));
+            return;
+        }
+        let readback = match self.thumbnail.as_mut() {
+            Some(readback) => readback,
+            None => return,
+        };
+        let result = match readback.mapping.as_mut() {
+            Some(mapping) => {
+                self.device.poll(wgpu::Maintain::Poll);
+                match mapping.as_mut().now_or_never() {
+                    Some(result) => result,
+                    None => return,
+                }
+            }
+            None => return,
+        };
+        readback.mapping = None;
+        if result.is_err() {
+            return;
+        }
+        let size = readback.size;
+        let pixels = {
+            let padded = readback.buffer.slice(..).get_mapped_range();
+            padded
+                .chunks_exact(readback.padded_bytes_per_row as usize)
+                .flat_map(|row| row[..(size.x * 4) as usize].iter().copied())
+                .collect()
+        };
+        readback.buffer.unmap();
+        self.transmitter
+            .send(CanvasMessage::Thumbnail(pixels, size))
+            .unwrap();
+    }
+}
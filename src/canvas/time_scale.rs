@@ -0,0 +1,41 @@
+use super::Canvas;
+
+/// Multiplier on how fast [crate::uniforms::Uniforms::time] advances, e.g. to review an animation slower or faster.
+/// Applies from the [Canvas::stop_watch] reading at which it last changed, so changing it never makes time jump.
+#[derive(Debug)]
+pub struct TimeScale {
+    scale: f32,
+    /// Time reached when the scale last changed, unit: seconds.
+    base: f32,
+    /// Stopwatch reading when the scale last changed, unit: seconds.
+    since: f32,
+}
+
+impl TimeScale {
+    pub fn new() -> TimeScale {
+        TimeScale {
+            scale: 1.0,
+            base: 0.0,
+            since: 0.0,
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Scaled time for a stopwatch reading of `elapsed` seconds.
+    pub fn time(&self, elapsed: f32) -> f32 {
+        self.base + (elapsed - self.since) * self.scale
+    }
+}
+
+impl Canvas {
+    /// Advance time `scale` times as fast as real time from now on. Frame rate is unaffected.
+    pub fn set_time_scale(&mut self, scale: f32) {
+        let elapsed = self.stop_watch.elapsed().as_secs_f32();
+        self.time_scale.base = self.time_scale.time(elapsed);
+        self.time_scale.since = elapsed;
+        self.time_scale.scale = scale.max(0.0);
+    }
+}
@@ -0,0 +1,88 @@
+use super::{message::CanvasMessage, Canvas};
+use crate::uniforms::MAX_TOUCH_POINTS;
+use crate::vector::Vector4;
+use winit::event::{Touch, TouchPhase};
+
+/// Value of `w` in [crate::uniforms::Uniforms::touches] for a point that just landed.
+const TOUCH_STARTED: f32 = 0.0;
+/// Value of `w` in [crate::uniforms::Uniforms::touches] for a point that has moved since landing.
+const TOUCH_MOVED: f32 = 1.0;
+
+/// Points currently touching the canvas window, oldest first.
+#[derive(Debug)]
+pub struct TouchPoints {
+    /// winit touch id and xy: position in rendered pixels, z: id, w: phase.
+    points: Vec<(u64, Vector4)>,
+    /// Most points tracked at once. Further touches are ignored until one lifts.
+    max: usize,
+}
+
+impl TouchPoints {
+    pub fn new() -> TouchPoints {
+        TouchPoints {
+            points: vec![],
+            max: MAX_TOUCH_POINTS,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+}
+
+impl Canvas {
+    /// Limit the touch points tracked at once, at most [MAX_TOUCH_POINTS].
+    pub fn set_max_touch_points(&mut self, max: usize) {
+        self.touches.max = max.min(MAX_TOUCH_POINTS);
+        self.touches.points.truncate(self.touches.max);
+        self.update_touch_uniforms();
+    }
+
+    /// Track a touch on the canvas window and bind the points to `u_touches`.
+    pub fn handle_touch(&mut self, touch: Touch) {
+        let count = self.touches.len();
+        // Positions are in the rendered region, like the mouse's.
+        let [x, y, width, height] = self.viewport();
+        let render_size = self.render_size();
+        let position = Vector4::new(
+            (touch.location.x as f32 - x) * render_size.x as f32 / width,
+            (touch.location.y as f32 - y) * render_size.y as f32 / height,
+            touch.id as f32,
+            TOUCH_STARTED,
+        );
+        let index = self
+            .touches
+            .points
+            .iter()
+            .position(|(id, _)| *id == touch.id);
+        match (touch.phase, index) {
+            (TouchPhase::Started, None) if count < self.touches.max => {
+                self.touches.points.push((touch.id, position))
+            }
+            (TouchPhase::Moved, Some(i)) => {
+                self.touches.points[i].1 = Vector4 {
+                    w: TOUCH_MOVED,
+                    ..position
+                }
+            }
+            (TouchPhase::Ended, Some(i)) | (TouchPhase::Cancelled, Some(i)) => {
+                self.touches.points.remove(i);
+            }
+            _ => {}
+        }
+        self.update_touch_uniforms();
+        if self.touches.len() != count {
+            self.transmitter
+                .send(CanvasMessage::TouchCount(self.touches.len()))
+                .unwrap();
+        }
+    }
+
+    fn update_touch_uniforms(&mut self) {
+        self.uniforms.touch_count = self.touches.len() as u32;
+        self.uniforms.touches = [Vector4::zero(); MAX_TOUCH_POINTS];
+        for (slot, (_, point)) in self.uniforms.touches.iter_mut().zip(&self.touches.points) {
+            *slot = *point;
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use super::{message::CanvasMessage, Canvas};
+use crate::layout::{builtin_slots, UniformLayout, BUILTIN_BINDING, USER_BINDING};
+use crate::uniforms::Uniforms;
+use log::warn;
+use std::collections::HashMap;
+use std::num::NonZeroU64;
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+
+impl Canvas {
+    /// Place user uniforms at the given byte offsets, by name, and lay the rest out around them.
+    pub fn pin_uniform_offsets(&mut self, pins: HashMap<String, usize>) {
+        self.uniform_pins = pins;
+        self.update_uniform_layout();
+        self.reset_accumulation();
+    }
+
+    /// Lay the user uniforms out again, growing their buffer if needed, check the layout against the shader and
+    /// send it to the Dashboard.
+    pub fn update_uniform_layout(&mut self) {
+        self.uniform_layout = UniformLayout::new(&self.user_uniforms, &self.uniform_pins);
+        let mut warnings = self.uniform_layout.warnings.clone();
+        match self.user_uniforms_buffer_size {
+            // The binding only exists if the shader launched with user uniforms.
+            None if self.uniform_layout.size > 0 => {
+                warnings.push(String::from(
+                    "User uniforms added after launch are not bound until restart",
+                ));
+                warn!("{}", warnings.last().unwrap());
+            }
+            Some(size) if self.uniform_layout.size > size => self.grow_user_uniforms_buffer(),
+            _ => {}
+        }
+        warnings.extend(self.uniform_layout.check_against_shader(&self.fs_spirv));
+
+        let mut slots = builtin_slots();
+        if self.user_uniforms_buffer.is_some() {
+            slots.extend(self.uniform_layout.slots.iter().cloned());
+        }
+        self.transmitter
+            .send(CanvasMessage::UniformLayout { slots, warnings })
+            .unwrap();
+    }
+
+    /// Replace the user uniforms buffer with one that fits [Self::uniform_layout] and bind it in its place.
+    fn grow_user_uniforms_buffer(&mut self) {
+        let size = self.uniform_layout.size;
+        let buffer = self.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Custom Uniforms Buffer"),
+            contents: &self.uniform_layout.pack(&self.user_uniforms),
+            usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+        });
+        let uniforms_size = std::mem::size_of::<Uniforms>();
+        self.bind_groups[0] = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Primary Bind Group"),
+            layout: &self.bind_group_layouts[0],
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: BUILTIN_BINDING,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &self.uniforms_device_buffer,
+                        offset: 0,
+                        size: NonZeroU64::new(uniforms_size as u64),
+                    }),
+                },
+                wgpu::BindGroupEntry {
+                    binding: USER_BINDING,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &buffer,
+                        offset: 0,
+                        size: NonZeroU64::new(size as u64),
+                    }),
+                },
+            ],
+        });
+        self.user_uniforms_buffer = Some(buffer);
+        self.user_uniforms_buffer_size = Some(size);
+        self.vram
+            .set("Uniform buffers", (uniforms_size + size) as u64);
+    }
+}
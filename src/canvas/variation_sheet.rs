@@ -0,0 +1,258 @@
+use super::{Canvas, PAINTING_TEXTURE_FORMAT};
+use crate::postprocessing;
+use crate::uniforms::{UserUniform, UserUniformType};
+use crate::utils::PaintingLayer;
+use crate::vector::UIntVector2;
+use log::{info, warn};
+use std::num::NonZeroU32;
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+use wgpu::{Extent3d, LoadOp, Operations, Origin3d};
+
+/// Bytes per pixel of [PAINTING_TEXTURE_FORMAT].
+const CELL_BYTES_PER_PIXEL: u32 = 8;
+
+/// Values of one uniform to render side by side, see [Canvas::create_variation_sheet].
+#[derive(Clone)]
+pub struct VariationSheetRequest {
+    /// The swept uniform with the value of each cell, in order.
+    pub variations: Vec<UserUniform>,
+    /// Resolution of each cell.
+    pub cell_size: UIntVector2,
+}
+
+/// Where the cells of a variation sheet are in the read back buffer.
+/// Cells are stored one after the other, each with rows padded to [VariationSheetLayout::row_pitch] pixels.
+#[derive(Debug, Clone)]
+pub struct VariationSheetLayout {
+    pub cell_size: UIntVector2,
+    /// Pixels per row of a cell in the buffer, padded to the copy alignment.
+    pub row_pitch: u32,
+    /// Value of the swept uniform in each cell, in order.
+    pub labels: Vec<String>,
+}
+
+impl VariationSheetLayout {
+    /// Cells are laid out in a grid that is as square as possible, filled row by row.
+    pub fn columns(&self) -> u32 {
+        (self.labels.len() as f64).sqrt().ceil().max(1.0) as u32
+    }
+
+    pub fn rows(&self) -> u32 {
+        (self.labels.len() as u32).div_ceil(self.columns())
+    }
+
+    /// Resolution of the stitched sheet.
+    pub fn sheet_size(&self) -> UIntVector2 {
+        UIntVector2::new(
+            self.columns() * self.cell_size.x,
+            self.rows() * self.cell_size.y,
+        )
+    }
+}
+
+/// Value of a swept uniform as printed under its cell.
+pub fn variation_label(uniform: &UserUniform) -> String {
+    match (uniform.inherent_type, uniform.scalar_value()) {
+        (UserUniformType::Float32, Some(value)) | (UserUniformType::Float64, Some(value)) => {
+            format!("{:.3}", value)
+        }
+        (_, Some(value)) => format!("{}", value),
+        (_, None) => String::new(),
+    }
+}
+
+impl Canvas {
+    /// Render each variation like a painting and read them all back in one buffer.
+    /// Reuses the painting readback, so [Self::poll_painting] reports the result
+    /// in [super::CanvasMessage::VariationSheetRendered].
+    pub fn create_variation_sheet(&mut self, request: VariationSheetRequest) {
+        if self.painting_readback.is_some() {
+            warn!("A painting is already being rendered.");
+            return;
+        }
+        if request.variations.is_empty() {
+            return;
+        }
+        if self.user_uniforms_buffer.is_none() {
+            warn!("The shader has no uniforms to vary.");
+            return;
+        }
+        let cell = request.cell_size;
+        let supersample = self.painting_supersample_factor(cell);
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT / CELL_BYTES_PER_PIXEL;
+        let row_pitch = cell.x.div_ceil(alignment) * alignment;
+        let cell_bytes = (row_pitch * cell.y * CELL_BYTES_PER_PIXEL) as u64;
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Variation Sheet Staging Buffer"),
+            usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
+            size: cell_bytes * request.variations.len() as u64,
+            mapped_at_creation: false,
+        });
+        let start_time = std::time::Instant::now();
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Variation Sheet Encoder"),
+            });
+        self.encode_capture_uniforms(
+            &mut encoder,
+            UIntVector2::new(cell.x * supersample, cell.y * supersample),
+        );
+
+        for (i, variation) in request.variations.iter().enumerate() {
+            // Copies are ordered with the passes, so each cell sees its own value.
+            let bytes = self
+                .uniform_layout
+                .pack_iter(self.user_uniforms.iter().map(|u| match u == variation {
+                    true => variation,
+                    false => u,
+                }));
+            let staging_buffer = self.device.create_buffer_init(&BufferInitDescriptor {
+                label: Some("Variation Uniforms Buffer"),
+                contents: &bytes,
+                usage: wgpu::BufferUsage::COPY_SRC,
+            });
+            encoder.copy_buffer_to_buffer(
+                &staging_buffer,
+                0,
+                self.user_uniforms_buffer.as_ref().unwrap(),
+                0,
+                bytes.len() as u64,
+            );
+            let cell_texture = self.encode_variation_cell(&mut encoder, cell, supersample);
+            encoder.copy_texture_to_buffer(
+                wgpu::ImageCopyTexture {
+                    mip_level: 0,
+                    origin: Origin3d::ZERO,
+                    texture: &cell_texture,
+                },
+                wgpu::ImageCopyBuffer {
+                    buffer: &buffer,
+                    layout: wgpu::ImageDataLayout {
+                        bytes_per_row: NonZeroU32::new(row_pitch * CELL_BYTES_PER_PIXEL),
+                        offset: cell_bytes * i as u64,
+                        rows_per_image: NonZeroU32::new(cell.y),
+                    },
+                },
+                Extent3d {
+                    width: cell.x,
+                    height: cell.y,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+        // The next update writes the current uniform values back.
+        self.queue.submit(Some(encoder.finish()));
+
+        let layout = VariationSheetLayout {
+            cell_size: cell,
+            row_pitch,
+            labels: request.variations.iter().map(variation_label).collect(),
+        };
+        let sheet_size = layout.sheet_size();
+        info!(
+            "Rendering {} variations of {} at {} x {}",
+            request.variations.len(),
+            request.variations[0].name,
+            cell.x,
+            cell.y
+        );
+        let layer = PaintingLayer {
+            name: String::from("Variation Sheet"),
+            buffer,
+        };
+        self.start_painting_readback(vec![layer], sheet_size, Some(layout), start_time);
+    }
+
+    /// Encode a painting render of one cell, at the current user uniforms buffer contents.
+    /// Returns the sRGB encoded cell at `resolution`.
+    fn encode_variation_cell(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        resolution: UIntVector2,
+        supersample: u32,
+    ) -> wgpu::Texture {
+        let render_resolution =
+            UIntVector2::new(resolution.x * supersample, resolution.y * supersample);
+        let cell_desc = |size: UIntVector2| wgpu::TextureDescriptor {
+            size: Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            format: PAINTING_TEXTURE_FORMAT,
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT
+                | wgpu::TextureUsage::COPY_SRC
+                | wgpu::TextureUsage::SAMPLED,
+            label: Some("Variation Sheet Cell"),
+            dimension: wgpu::TextureDimension::D2,
+            mip_level_count: 1,
+            sample_count: 1,
+        };
+        let mut stage_in = self.device.create_texture(&cell_desc(render_resolution));
+        let mut stage_out = self.device.create_texture(&cell_desc(render_resolution));
+        {
+            let view = stage_in.create_view(&wgpu::TextureViewDescriptor::default());
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Variation Sheet Render Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(self.clear_color),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+            for i in 0..self.bind_groups.len() {
+                render_pass.set_bind_group(i as u32, &self.bind_groups[i], &[]);
+            }
+            render_pass.set_pipeline(&self.painting_pipeline);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        let custom_data = self
+            .user_uniforms_buffer
+            .as_ref()
+            .map(|buffer| (buffer, self.user_uniforms_buffer_size.unwrap()));
+        let uniforms_data = (
+            &self.uniforms_device_buffer,
+            std::mem::size_of_val(&self.uniforms),
+        );
+        for postprocess_op in &mut self.postprocess_ops {
+            let input_view = stage_in.create_view(&wgpu::TextureViewDescriptor::default());
+            let output_view = stage_out.create_view(&wgpu::TextureViewDescriptor::default());
+            postprocess_op.post_process(
+                &input_view,
+                &output_view,
+                uniforms_data,
+                custom_data,
+                &self.device,
+                encoder,
+                self.clear_color,
+                postprocessing::PipelineType::Painting,
+            );
+            std::mem::swap(&mut stage_in, &mut stage_out);
+        }
+        if supersample > 1 {
+            stage_in = self.downsample_painting(encoder, &stage_in, render_resolution, supersample);
+            stage_out = self.device.create_texture(&cell_desc(resolution));
+        }
+        {
+            let input_view = stage_in.create_view(&wgpu::TextureViewDescriptor::default());
+            let output_view = stage_out.create_view(&wgpu::TextureViewDescriptor::default());
+            self.srgb_postprocess.post_process(
+                &input_view,
+                &output_view,
+                uniforms_data,
+                custom_data,
+                &self.device,
+                encoder,
+                self.clear_color,
+                postprocessing::PipelineType::Painting,
+            );
+        }
+        stage_out
+    }
+}
@@ -0,0 +1,669 @@
+use super::{
+    sidecar_path, Dashboard, DashboardMessage, DashboardState, TimelineReplay, UniformTimeline,
+};
+use crate::recording::{Recorder, RecorderError, MOVIE_TEXTURE_FORMAT};
+use crate::utils::{self, ExportNotifier, VramTracker};
+use crate::vector::UIntVector2;
+use log::{error, info, warn};
+use winit::event::VirtualKeyCode;
+
+/// Named operations the user can trigger from buttons, keyboard shortcuts or the command palette.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Action {
+    TogglePause,
+    ToggleFreezeTime,
+    ToggleTimeHold,
+    ToggleMasterFade,
+    ResetAccumulation,
+    ToggleTitlebar,
+    ToggleBorderless,
+    FitWindowToContent,
+    AddMirrorWindow,
+    CreatePainting,
+    /// Paint the moment at [super::DashboardState::capture_time] rather than the live one.
+    CreatePaintingAtTime,
+    CreateVariationSheet,
+    CopyFrameToClipboard,
+    CaptureReference,
+    ClearReference,
+    ToggleReferenceCompare,
+    InitializeRecorder,
+    ToggleRecording,
+    RerollSeeds,
+    RandomizeUniforms,
+    ToggleUniformSnapshot,
+    ClearUniformSnapshot,
+    SaveShaderSettings,
+    ExportTimelineCsv,
+    ExportTimelineJson,
+    ReplayTimeline,
+    ReplayTimelineFromFile,
+    StopTimelineReplay,
+    ClearTimeline,
+    NewShaderFromTemplate,
+    /// Open [super::DashboardState::open_shader_file] in place of the current shader.
+    OpenShader,
+    /// Reset every uniform to its value in the uniforms JSON file, like double-clicking each control.
+    ResetUniforms,
+    StartBatchRender,
+    CancelBatchRender,
+    StartPlaylist,
+    StopPlaylist,
+    NextPlaylistEntry,
+    PreviousPlaylistEntry,
+    TogglePlaylistPause,
+}
+
+/// Entry in the [ACTIONS] registry.
+pub struct ActionInfo {
+    pub action: Action,
+    /// Name shown in the command palette.
+    pub name: &'static str,
+    /// Key that triggers this action while the Dashboard is focused, if any.
+    pub shortcut: Option<VirtualKeyCode>,
+}
+
+/// Registry of every [Action]. Drives both the Dashboard keybindings and the command palette.
+pub static ACTIONS: &[ActionInfo] = &[
+    ActionInfo {
+        action: Action::TogglePause,
+        name: "Pause / Play",
+        shortcut: Some(VirtualKeyCode::Space),
+    },
+    ActionInfo {
+        action: Action::ToggleFreezeTime,
+        name: "Freeze / Unfreeze Time",
+        shortcut: Some(VirtualKeyCode::F),
+    },
+    ActionInfo {
+        action: Action::ToggleTimeHold,
+        name: "Hold / Release Time",
+        shortcut: Some(VirtualKeyCode::H),
+    },
+    ActionInfo {
+        action: Action::ToggleMasterFade,
+        name: "Fade Out / Fade In",
+        shortcut: Some(VirtualKeyCode::B),
+    },
+    ActionInfo {
+        action: Action::ResetAccumulation,
+        name: "Reset Accumulation",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::ToggleTitlebar,
+        name: "Show / Hide Titlebar",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::ToggleBorderless,
+        name: "Toggle Borderless Canvas",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::FitWindowToContent,
+        name: "Fit Window to Content",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::AddMirrorWindow,
+        name: "Add Mirror Window",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::CreatePainting,
+        name: "Create Painting",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::CreatePaintingAtTime,
+        name: "Create Painting at Time",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::CreateVariationSheet,
+        name: "Create Variation Sheet",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::CopyFrameToClipboard,
+        name: "Copy Frame to Clipboard",
+        shortcut: Some(VirtualKeyCode::K),
+    },
+    ActionInfo {
+        action: Action::CaptureReference,
+        name: "Capture Reference Frame",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::ClearReference,
+        name: "Clear Reference Frame",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::ToggleReferenceCompare,
+        name: "Show / Hide Reference Compare",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::InitializeRecorder,
+        name: "Initialize Recorder",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::ToggleRecording,
+        name: "Start / Stop Recording",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::RerollSeeds,
+        name: "Reroll Seeds",
+        shortcut: Some(VirtualKeyCode::R),
+    },
+    ActionInfo {
+        action: Action::RandomizeUniforms,
+        name: "Randomize Uniforms",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::ToggleUniformSnapshot,
+        name: "Snapshot / Swap Uniforms (A/B)",
+        shortcut: Some(VirtualKeyCode::A),
+    },
+    ActionInfo {
+        action: Action::ClearUniformSnapshot,
+        name: "Clear Uniform Snapshot",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::SaveShaderSettings,
+        name: "Save Shader Settings",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::ExportTimelineCsv,
+        name: "Export Uniform Timeline (CSV)",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::ExportTimelineJson,
+        name: "Export Uniform Timeline (JSON)",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::ReplayTimeline,
+        name: "Replay Uniform Timeline",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::ReplayTimelineFromFile,
+        name: "Replay Uniform Timeline From File",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::StopTimelineReplay,
+        name: "Stop Timeline Replay",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::ClearTimeline,
+        name: "Clear Uniform Timeline",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::NewShaderFromTemplate,
+        name: "New Shader From Template",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::OpenShader,
+        name: "Open Shader",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::ResetUniforms,
+        name: "Reset All Uniforms",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::StartBatchRender,
+        name: "Start Batch Render",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::CancelBatchRender,
+        name: "Cancel Batch Render",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::StartPlaylist,
+        name: "Start Playlist",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::StopPlaylist,
+        name: "Stop Playlist",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::NextPlaylistEntry,
+        name: "Next Shader in Playlist",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::PreviousPlaylistEntry,
+        name: "Previous Shader in Playlist",
+        shortcut: None,
+    },
+    ActionInfo {
+        action: Action::TogglePlaylistPause,
+        name: "Pause / Resume Playlist",
+        shortcut: None,
+    },
+];
+
+/// Find the action bound to the given key, if any.
+pub fn action_for_key(key: VirtualKeyCode) -> Option<Action> {
+    ACTIONS
+        .iter()
+        .find(|info| info.shortcut == Some(key))
+        .map(|info| info.action)
+}
+
+/// Score how well `query` fuzzy-matches `candidate`.
+/// Every character of the query must appear in the candidate in order, ignoring case.
+/// Consecutive matches and matches at the start of a word score higher. Returns `None` if there is no match.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut last_match: Option<usize> = None;
+    for query_char in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = candidate[position..]
+            .iter()
+            .position(|c| *c == query_char)?
+            + position;
+        score += 1;
+        if last_match.is_some_and(|last| last + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !candidate[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        last_match = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+/// Actions matching the query, best match first. An empty query lists every action in registry order.
+pub fn filter_actions(query: &str) -> Vec<&'static ActionInfo> {
+    let mut matches: Vec<(i32, &'static ActionInfo)> = ACTIONS
+        .iter()
+        .filter_map(|info| fuzzy_score(query, info.name).map(|score| (score, info)))
+        .collect();
+    matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    matches.into_iter().map(|(_, info)| info).collect()
+}
+
+impl Dashboard {
+    /// Perform the given action.
+    pub fn run_action(&mut self, action: Action) {
+        match action {
+            Action::TogglePause => {
+                self.state.paused = !self.state.paused;
+                self.transmitter.send(DashboardMessage::PausePlayChanged);
+            }
+            Action::ToggleFreezeTime => {
+                self.state.time_frozen = !self.state.time_frozen;
+                self.transmitter.send(DashboardMessage::FreezeTimeChanged);
+            }
+            Action::ToggleTimeHold => {
+                self.state.time_held = !self.state.time_held;
+                self.transmitter
+                    .send(DashboardMessage::HoldTime(self.state.time_held));
+            }
+            Action::ResetAccumulation => {
+                self.state.accumulation_samples = 0;
+                self.transmitter.send(DashboardMessage::ResetAccumulation);
+            }
+            Action::ToggleTitlebar => {
+                self.state.show_titlebar = !self.state.show_titlebar;
+                self.transmitter
+                    .send(DashboardMessage::TitlebarStatusChanged);
+            }
+            Action::ToggleBorderless => {
+                self.state.borderless = !self.state.borderless;
+                self.transmitter
+                    .send(DashboardMessage::BorderlessMode(self.state.borderless));
+            }
+            Action::FitWindowToContent => {
+                if self.state.controls_content_height <= 0.0 {
+                    return;
+                }
+                let height = (self.state.controls_content_height * self.hidpi_factor).ceil();
+                self.window.set_inner_size(winit::dpi::PhysicalSize::new(
+                    self.window.inner_size().width,
+                    height as u32,
+                ));
+            }
+            Action::ToggleMasterFade => {
+                self.state.master_faded_out = !self.state.master_faded_out;
+                self.transmitter.send(DashboardMessage::MasterFade {
+                    target: self.state.master_faded_out as u32 as f32,
+                    color: self.state.master_fade_color,
+                    duration: self.state.master_fade_duration,
+                });
+            }
+            Action::AddMirrorWindow => self.state.mirror_windows_requested += 1,
+            Action::CopyFrameToClipboard => {
+                self.transmitter
+                    .send(DashboardMessage::CopyFrameToClipboard);
+            }
+            Action::CaptureReference => {
+                // Comparing is what a reference is for, so show it straight away.
+                self.state.compare_enabled = true;
+                self.transmitter.send(DashboardMessage::CaptureReference);
+            }
+            Action::ClearReference => {
+                self.state.reference_size = None;
+                self.transmitter.send(DashboardMessage::ClearReference);
+            }
+            Action::ToggleReferenceCompare => {
+                self.state.compare_enabled = !self.state.compare_enabled;
+                self.transmitter.send(DashboardMessage::CompareChanged(
+                    self.state.compare_settings(),
+                ));
+            }
+            Action::CreatePainting | Action::CreatePaintingAtTime => {
+                if self.state.painting_busy() {
+                    warn!("A painting is already in progress.");
+                    return;
+                }
+                let path = utils::PaintingFile::new(&self.state.painting_filename).path;
+                if !self.check_painting_writable(&path) {
+                    return;
+                }
+                if self.state.pause_while_painting {
+                    self.transmitter.send(DashboardMessage::Pause);
+                }
+                self.transmitter
+                    .send(DashboardMessage::PaintingRenderRequested {
+                        resolution: UIntVector2::new(
+                            self.state.painting_resolution.x as u32,
+                            self.state.painting_resolution.y as u32,
+                        ),
+                        all_passes: self.state.export_painting_passes,
+                        time: match action {
+                            Action::CreatePaintingAtTime => Some(self.state.capture_time),
+                            _ => None,
+                        },
+                    });
+                self.state.painting_requested = true;
+            }
+            Action::CreateVariationSheet => {
+                if self.state.painting_busy() {
+                    warn!("A painting is already in progress.");
+                    return;
+                }
+                let path = utils::PaintingFile::new(&self.state.painting_filename).stem
+                    + "_variations.tiff";
+                if !self.check_painting_writable(&path) {
+                    return;
+                }
+                if let Some(request) = self.variation_sheet_request() {
+                    if self.state.pause_while_painting {
+                        self.transmitter.send(DashboardMessage::Pause);
+                    }
+                    self.transmitter
+                        .send(DashboardMessage::VariationSheetRequested(request));
+                    self.state.painting_requested = true;
+                }
+            }
+            Action::InitializeRecorder => {
+                if let Err(err) = initialize_recorder(
+                    &mut self.state,
+                    &mut self.recorder,
+                    &self.export_notifier,
+                    &self.vram,
+                ) {
+                    self.recording_failed(err);
+                }
+            }
+            Action::ToggleRecording => match self.recorder.as_mut() {
+                None => warn!("Initialize the recorder before starting a recording."),
+                Some(recorder) => {
+                    if self.state.recording_in_progress {
+                        self.state.recording_in_progress = false;
+                        if let Err(err) = recorder.stop() {
+                            self.recording_failed(err);
+                        }
+                    } else {
+                        self.state.recording_in_progress = true;
+                        self.movie_frames_requested = 0;
+                        if self.state.recording_warm_up {
+                            // Rendered ahead of the first frame requested, and never sent to the recorder.
+                            self.transmitter
+                                .send(DashboardMessage::MovieWarmUpRequested(UIntVector2::new(
+                                    self.state.recording_resolution.x as u32,
+                                    self.state.recording_resolution.y as u32,
+                                )));
+                        }
+                        self.state.recording_start_time = Some(std::time::Instant::now());
+                    }
+                }
+            },
+            Action::RerollSeeds => {
+                // The new values are sent to the canvas with the other modified uniforms in post_render.
+                for (name, uniform) in self.state.gui_uniforms.iter_mut() {
+                    if uniform.is_seed() {
+                        uniform.reroll();
+                        self.state.modified_uniforms.insert(name.clone());
+                    }
+                }
+            }
+            Action::RandomizeUniforms => {
+                self.state.randomize_seed = utils::random_u64() as u32;
+                self.randomize_uniforms();
+            }
+            Action::ToggleUniformSnapshot => match self.state.uniform_snapshot.as_mut() {
+                None => {
+                    self.state.uniform_snapshot = Some(self.state.gui_uniforms.clone());
+                    self.state.showing_uniform_snapshot = false;
+                    info!("Stored uniform snapshot.");
+                }
+                Some(snapshot) => {
+                    // Swapped values are sent to the canvas with the other modified uniforms in post_render.
+                    for (name, uniform) in self.state.gui_uniforms.iter_mut() {
+                        match snapshot.get_mut(name) {
+                            Some(stored) if stored.bytes.len() == uniform.bytes.len() => {
+                                std::mem::swap(&mut uniform.bytes, &mut stored.bytes);
+                                self.state.modified_uniforms.insert(name.clone());
+                            }
+                            _ => {}
+                        }
+                    }
+                    self.state.showing_uniform_snapshot = !self.state.showing_uniform_snapshot;
+                }
+            },
+            Action::ClearUniformSnapshot => {
+                self.state.uniform_snapshot = None;
+                self.state.showing_uniform_snapshot = false;
+            }
+            Action::SaveShaderSettings => match &self.state.shader_file {
+                None => warn!("No shader is open to save settings for."),
+                Some(shader_file) => {
+                    let path = sidecar_path(shader_file);
+                    let contents = self.state.shader_settings_json().pretty(2);
+                    match std::fs::write(&path, contents) {
+                        Ok(_) => info!("Saved shader settings to {}", path),
+                        Err(e) => error!("Error saving shader settings {}: {}", path, e),
+                    }
+                }
+            },
+            Action::ExportTimelineCsv => {
+                let filename = format!("{}.csv", self.state.timeline_filename);
+                write_timeline(&filename, &self.state.uniform_timeline.to_csv());
+            }
+            Action::ExportTimelineJson => {
+                let filename = format!("{}.json", self.state.timeline_filename);
+                write_timeline(&filename, &self.state.uniform_timeline.to_json().pretty(2));
+            }
+            Action::ReplayTimeline => {
+                let changes = self
+                    .state
+                    .uniform_timeline
+                    .changes
+                    .iter()
+                    .cloned()
+                    .collect();
+                self.state.timeline_replay = Some(TimelineReplay::new(changes));
+            }
+            Action::ReplayTimelineFromFile => {
+                let filename = format!("{}.json", self.state.timeline_filename);
+                let text = match std::fs::read_to_string(&filename) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        error!("Error reading timeline {}: {}", filename, e);
+                        return;
+                    }
+                };
+                match json::parse(&text) {
+                    Ok(data) => {
+                        let changes = UniformTimeline::from_json(&data);
+                        self.state.timeline_replay = Some(TimelineReplay::new(changes));
+                    }
+                    Err(e) => error!("Error parsing timeline {}: {}", filename, e),
+                }
+            }
+            Action::StopTimelineReplay => self.state.timeline_replay = None,
+            Action::ClearTimeline => self.state.uniform_timeline.clear(),
+            Action::NewShaderFromTemplate => {
+                let file = self.state.new_shader_file.clone();
+                let template = self.state.new_shader_template;
+                match template.write(&file) {
+                    Ok(_) => {
+                        info!("Wrote {} template to {}", template.name(), file);
+                        self.state.new_shader_error_msg = None;
+                        self.transmitter.send(DashboardMessage::OpenShader(file));
+                    }
+                    Err(e) => {
+                        error!("Error creating shader {}: {}", file, e);
+                        self.state.new_shader_error_msg = Some(e);
+                    }
+                }
+            }
+            Action::OpenShader => {
+                let file = self.state.open_shader_file.clone();
+                if file.is_empty() {
+                    warn!("Type the path of a shader to open.");
+                    return;
+                }
+                // The Canvas reports back with ShaderOpened, which loads the shader's settings.
+                self.transmitter.send(DashboardMessage::OpenShader(file));
+            }
+            Action::ResetUniforms => self.state.reset_uniforms(),
+            Action::StartBatchRender => self.start_batch_render(),
+            Action::CancelBatchRender => self.cancel_batch_render(),
+            Action::StartPlaylist => self.start_playlist(),
+            Action::StopPlaylist => self.stop_playlist(),
+            Action::NextPlaylistEntry => self.next_playlist_entry(),
+            Action::PreviousPlaylistEntry => self.previous_playlist_entry(),
+            Action::TogglePlaylistPause => {
+                if let Some(playlist) = self.state.playlist.as_mut() {
+                    playlist.paused = !playlist.paused;
+                }
+            }
+        }
+    }
+}
+
+/// Create the recorder for [Action::InitializeRecorder] unless there is one, without needing a Dashboard.
+/// Settings that can't be recorded leave `recorder` empty and are explained in
+/// [DashboardState::recording_error_msg]. Errors creating the recorder are returned.
+fn initialize_recorder(
+    state: &mut DashboardState,
+    recorder: &mut Option<Recorder>,
+    export_notifier: &ExportNotifier,
+    vram: &VramTracker,
+) -> Result<(), RecorderError> {
+    if recorder.is_none() && !state.movie_framerate_valid() {
+        let msg = format!(
+            "Invalid framerate {}, it must be at least 1 fps.",
+            state.movie_framerate
+        );
+        error!("{}", msg);
+        state.recording_error_msg = Some(msg);
+    } else if let Some(msg) = state.sequence_numbering_error() {
+        error!("{}", msg);
+        state.recording_error_msg = Some(msg);
+    } else if recorder.is_none() && state.recording_dry_run {
+        info!("Dry run recording, nothing will be written.");
+        *recorder = Some(Recorder::dry_run(
+            state.recording_resolution.x as u32,
+            state.recording_resolution.y as u32,
+            state.movie_framerate as u32,
+        ));
+        state.recording_error_msg = None;
+    } else if recorder.is_none() {
+        let new_recorder = Recorder::new(
+            state.recording_resolution.x as u32,
+            state.recording_resolution.y as u32,
+            MOVIE_TEXTURE_FORMAT,
+            state.movie_framerate as u32,
+            state.recording_filename.clone(),
+            export_notifier.clone(),
+            state.movie_codec,
+            state.movie_container,
+            match state.record_changes_only {
+                true => Some(state.change_threshold),
+                false => None,
+            },
+            state.export_title(),
+            &state.sequence_numbering,
+        )?;
+        // A movie frame texture and its staging buffer.
+        vram.set(
+            "Recording",
+            2 * utils::texture_bytes(
+                state.recording_resolution.x as u32,
+                state.recording_resolution.y as u32,
+                MOVIE_TEXTURE_FORMAT,
+            ),
+        );
+        *recorder = Some(new_recorder);
+        state.recording_error_msg = None;
+    }
+    Ok(())
+}
+
+/// Write an exported timeline to disk, logging the outcome.
+fn write_timeline(filename: &str, contents: &str) {
+    match std::fs::write(filename, contents) {
+        Ok(_) => info!("Wrote uniform timeline {}", filename),
+        Err(e) => error!("Error writing uniform timeline {}: {}", filename, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn initialize_recorder_rejects_non_positive_framerates() {
+        for framerate in [0, -5].iter().copied() {
+            let mut state = DashboardState::new();
+            state.movie_framerate = framerate;
+            let mut recorder = None;
+            let result = initialize_recorder(
+                &mut state,
+                &mut recorder,
+                &ExportNotifier::default(),
+                &VramTracker::default(),
+            );
+            assert!(result.is_ok(), "{} fps", framerate);
+            assert!(recorder.is_none(), "{} fps", framerate);
+            assert!(state.recording_error_msg.is_some(), "{} fps", framerate);
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use super::{Dashboard, DashboardMessage};
+use crate::utils::{ExportEvent, PaintingFile};
+use crate::vector::UIntVector2;
+use log::{error, info, warn};
+use std::path::Path;
+use std::sync::mpsc::Receiver;
+
+/// What a [BatchRender] is waiting for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BatchPhase {
+    /// The Canvas to compile the current shader.
+    Compiling,
+    /// The Canvas to render the current shader's painting.
+    Painting,
+    /// The painting to be written to disk.
+    Writing,
+}
+
+/// Renders every shader in a directory to a still, one after the other, e.g. for a contact sheet.
+pub struct BatchRender {
+    /// Shader files in the directory, sorted by name.
+    pub shaders: Vec<String>,
+    /// Index of the shader being rendered in [Self::shaders].
+    pub current: usize,
+    pub phase: BatchPhase,
+    output_dir: String,
+    /// Stills are written in the format of the painting filename.
+    extension: String,
+    resolution: UIntVector2,
+    /// Shader to reopen once the batch is done.
+    previous_shader: Option<String>,
+    exports: Receiver<ExportEvent>,
+    /// Shaders that failed so far and why. A failure moves on to the next shader rather than ending the batch.
+    pub failures: Vec<(String, String)>,
+}
+
+impl BatchRender {
+    /// Path of the still of the current shader, e.g. `stills/003_tunnel.tiff`. The index keeps the directory order.
+    pub fn output_path(&self) -> String {
+        let stem = Path::new(&self.shaders[self.current])
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Path::new(&self.output_dir)
+            .join(format!("{:03}_{}{}", self.current, stem, self.extension))
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// `.frag` and `.spv` files in `dir`, sorted by name.
+fn shaders_in_directory(dir: &str) -> Result<Vec<String>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| e.to_string())?;
+    let mut shaders: Vec<String> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && matches!(
+                    path.extension().and_then(|e| e.to_str()),
+                    Some("frag") | Some("spv")
+                )
+        })
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    shaders.sort();
+    match shaders.is_empty() {
+        true => Err(format!("No .frag or .spv shaders in {}", dir)),
+        false => Ok(shaders),
+    }
+}
+
+impl Dashboard {
+    /// Start rendering every shader in the batch directory at the painting resolution, with the uniform values of
+    /// the uniforms file.
+    pub fn start_batch_render(&mut self) {
+        if self.state.batch_render.is_some() {
+            warn!("A batch render is already running.");
+            return;
+        }
+        if self.state.painting_busy() {
+            warn!("A painting is already in progress.");
+            return;
+        }
+        let shaders = match shaders_in_directory(&self.state.batch_directory) {
+            Ok(shaders) => shaders,
+            Err(e) => {
+                error!("Batch render: {}", e);
+                self.state.batch_error_msg = Some(e);
+                return;
+            }
+        };
+        let output_dir = self.state.batch_output_directory.clone();
+        if let Err(e) = std::fs::create_dir_all(&output_dir) {
+            let msg = format!("Could not create {}: {}", output_dir, e);
+            error!("Batch render: {}", msg);
+            self.state.batch_error_msg = Some(msg);
+            return;
+        }
+        let painting = PaintingFile::new(&self.state.painting_filename);
+        info!(
+            "Batch rendering {} shaders from {} to {}",
+            shaders.len(),
+            self.state.batch_directory,
+            output_dir
+        );
+        // Every shader starts from the values in the uniforms file, not from the tweaks of the current one.
+        self.state.reset_uniforms();
+        self.state.batch_error_msg = None;
+        self.state.batch_render = Some(BatchRender {
+            shaders,
+            current: 0,
+            phase: BatchPhase::Compiling,
+            output_dir,
+            extension: String::from(&painting.path[painting.stem.len()..]),
+            resolution: UIntVector2::new(
+                self.state.painting_resolution.x as u32,
+                self.state.painting_resolution.y as u32,
+            ),
+            previous_shader: self.state.shader_file.clone(),
+            exports: self.export_notifier.subscribe(),
+            failures: vec![],
+        });
+        self.open_batch_shader();
+    }
+
+    /// Stop after the current shader's painting, if one is being rendered, and reopen the shader from before.
+    pub fn cancel_batch_render(&mut self) {
+        let batch = match self.state.batch_render.as_mut() {
+            Some(batch) => batch,
+            None => return,
+        };
+        info!("Batch render cancelled.");
+        if batch.phase == BatchPhase::Compiling {
+            batch.shaders.truncate(batch.current);
+            self.finish_batch_render();
+        } else {
+            batch.shaders.truncate(batch.current + 1);
+        }
+    }
+
+    /// Open the current shader of the batch, or finish it if all have been rendered.
+    fn open_batch_shader(&mut self) {
+        let shader = match self.state.batch_render.as_mut() {
+            Some(batch) if batch.current < batch.shaders.len() => {
+                batch.phase = BatchPhase::Compiling;
+                batch.shaders[batch.current].clone()
+            }
+            Some(_) => return self.finish_batch_render(),
+            None => return,
+        };
+        self.transmitter.send(DashboardMessage::OpenShader(shader));
+    }
+
+    fn finish_batch_render(&mut self) {
+        let batch = match self.state.batch_render.take() {
+            Some(batch) => batch,
+            None => return,
+        };
+        info!(
+            "Batch render finished: {} of {} shaders rendered.",
+            batch.shaders.len() - batch.failures.len(),
+            batch.shaders.len()
+        );
+        for (shader, reason) in &batch.failures {
+            warn!("Batch render failed for {}: {}", shader, reason);
+        }
+        self.state.batch_failures = batch.failures;
+        if let Some(shader) = batch.previous_shader {
+            self.transmitter.send(DashboardMessage::OpenShader(shader));
+        }
+    }
+
+    /// Note why the current shader failed and move on to the next.
+    fn batch_shader_failed(&mut self, reason: String) {
+        if let Some(batch) = self.state.batch_render.as_mut() {
+            let shader = batch.shaders[batch.current].clone();
+            error!("Batch render: {} failed: {}", shader, reason);
+            batch.failures.push((shader, reason));
+            batch.current += 1;
+        }
+        self.open_batch_shader();
+    }
+
+    /// The Canvas finished compiling a shader. Paint the current shader of the batch if it compiled.
+    pub fn batch_shader_compiled(&mut self, result: Result<(), String>) {
+        let batch = match self.state.batch_render.as_mut() {
+            Some(batch) if batch.phase == BatchPhase::Compiling => batch,
+            _ => return,
+        };
+        match result {
+            Ok(_) => {
+                batch.phase = BatchPhase::Painting;
+                let resolution = batch.resolution;
+                self.transmitter
+                    .send(DashboardMessage::PaintingRenderRequested {
+                        resolution,
+                        all_passes: false,
+                        time: None,
+                    });
+                self.state.painting_requested = true;
+            }
+            Err(e) => self.batch_shader_failed(e),
+        }
+    }
+
+    /// Where to write a painting the Canvas has rendered, if it belongs to the batch rather than the painting filename.
+    pub fn batch_painting_rendered(&mut self) -> Option<String> {
+        match self.state.batch_render.as_mut() {
+            Some(batch) if batch.phase == BatchPhase::Painting => {
+                batch.phase = BatchPhase::Writing;
+                Some(batch.output_path())
+            }
+            _ => None,
+        }
+    }
+
+    pub fn batch_painting_failed(&mut self, reason: String) {
+        if matches!(&self.state.batch_render, Some(batch) if batch.phase == BatchPhase::Painting) {
+            self.batch_shader_failed(reason);
+        }
+    }
+
+    /// Move on to the next shader once the current still has been written. Called every frame.
+    pub fn update_batch_render(&mut self) {
+        let written = match self.state.batch_render.as_mut() {
+            Some(batch) if batch.phase == BatchPhase::Writing => {
+                let path = batch.output_path();
+                batch
+                    .exports
+                    .try_iter()
+                    .find(|event| event.path == Path::new(&path))
+            }
+            _ => return,
+        };
+        match written {
+            Some(ExportEvent { error: Some(e), .. }) => self.batch_shader_failed(e),
+            Some(_) => {
+                if let Some(batch) = self.state.batch_render.as_mut() {
+                    batch.current += 1;
+                }
+                self.open_batch_shader();
+            }
+            None => {}
+        }
+    }
+}
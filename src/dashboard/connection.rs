@@ -0,0 +1,110 @@
+use super::{Action, Dashboard, DashboardMessage};
+use crate::canvas::CanvasMessage;
+use log::{info, warn};
+use std::sync::mpsc::{Receiver, Sender};
+
+/// What happens when the canvas window is closed while the Dashboard is open.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CanvasCloseBehavior {
+    /// Quit Easel, closing the Dashboard too.
+    Quit,
+    /// Keep the Dashboard open, showing the Canvas as disconnected with the option to relaunch it.
+    KeepDashboard,
+}
+
+impl CanvasCloseBehavior {
+    pub fn from_name(name: &str) -> Option<CanvasCloseBehavior> {
+        match name {
+            "quit" => Some(CanvasCloseBehavior::Quit),
+            "keep-dashboard" => Some(CanvasCloseBehavior::KeepDashboard),
+            _ => None,
+        }
+    }
+}
+
+/// Sends [DashboardMessage]s to the Canvas. Once the Canvas is gone, messages are dropped instead of panicking,
+/// and the Dashboard finds out from its receiving end, see [Dashboard::canvas_connected].
+pub struct CanvasSender {
+    sender: Sender<DashboardMessage>,
+}
+
+impl CanvasSender {
+    pub fn new(sender: Sender<DashboardMessage>) -> CanvasSender {
+        CanvasSender { sender }
+    }
+
+    /// Returns whether the Canvas was there to receive the message.
+    pub fn send(&self, message: DashboardMessage) -> bool {
+        self.sender.send(message).is_ok()
+    }
+}
+
+impl Dashboard {
+    /// Whether a Canvas is running. False once the canvas window was closed or the Canvas stopped.
+    pub fn canvas_connected(&self) -> bool {
+        self.state.canvas_connected
+    }
+
+    /// The Canvas hung up. Stop everything that needs it and autosave the uniforms.
+    pub fn canvas_disconnected(&mut self) {
+        if !self.state.canvas_connected {
+            return;
+        }
+        warn!("Canvas disconnected.");
+        self.state.canvas_connected = false;
+        if self.state.recording_in_progress {
+            self.run_action(Action::ToggleRecording);
+        }
+        self.state.playlist = None;
+        // A relaunched Canvas starts with time running.
+        self.state.time_held = false;
+        if self.state.batch_render.is_some() {
+            self.cancel_batch_render();
+        }
+        self.save_uniform_autosave();
+    }
+
+    /// Ask for a new canvas window and Canvas, see [Self::take_canvas_relaunch_request].
+    pub fn request_canvas_relaunch(&mut self) {
+        self.state.canvas_relaunch_requested = true;
+    }
+
+    /// Whether a relaunch was asked for since the last call.
+    /// Windows can only be built on the main thread, which then passes the new Canvas' channels to
+    /// [Self::reconnect_canvas].
+    pub fn take_canvas_relaunch_request(&mut self) -> bool {
+        std::mem::take(&mut self.state.canvas_relaunch_requested)
+    }
+
+    /// Talk to a relaunched Canvas, which opened `launched_shader` with the launch arguments.
+    /// The shader open when the last Canvas went away is opened again if it was another one. Either way the Canvas
+    /// restores the uniform values autosaved here, if autosaving is on.
+    pub fn reconnect_canvas(
+        &mut self,
+        transmitter: Sender<DashboardMessage>,
+        receiver: Receiver<CanvasMessage>,
+        launched_shader: &str,
+    ) {
+        info!("Canvas relaunched.");
+        self.save_uniform_autosave();
+        self.transmitter = CanvasSender::new(transmitter);
+        self.receiver = receiver;
+        self.state.canvas_connected = true;
+        self.movie_frame_in_flight = false;
+        self.canvas_resolution_lock = None;
+        self.last_sent_painting_resolution = None;
+        self.last_sent_painting_passes = None;
+        self.last_sent_painting_warm_up = None;
+        self.last_sent_time_scale = None;
+        if self.state.borderless {
+            self.transmitter
+                .send(DashboardMessage::BorderlessMode(true));
+        }
+        if let Some(shader_file) = self.state.shader_file.clone() {
+            if shader_file != launched_shader {
+                self.transmitter
+                    .send(DashboardMessage::OpenShader(shader_file));
+            }
+        }
+    }
+}
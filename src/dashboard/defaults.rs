@@ -0,0 +1,189 @@
+use super::{resolution_from_json, DashboardState, DashboardTheme, MaintainStrategy};
+use crate::vector::IntVector2;
+use log::{error, info, warn};
+
+/// Environment variable naming a JSON file of defaults, in the same format as the shader settings file.
+pub const DEFAULTS_FILE_VAR: &str = "EASEL_DEFAULTS";
+
+/// Initial painting and recording options for a fresh session, e.g. a studio's house standards.
+/// Saved shader settings still take precedence once a shader is opened.
+#[derive(Debug, Default, Clone)]
+pub struct SessionDefaults {
+    pub painting_resolution: Option<IntVector2>,
+    pub painting_filename: Option<String>,
+    pub recording_resolution: Option<IntVector2>,
+    pub movie_framerate: Option<i32>,
+    pub recording_filename: Option<String>,
+    pub autosave_uniforms: Option<bool>,
+    pub maintain_strategy: Option<MaintainStrategy>,
+    pub theme: Option<DashboardTheme>,
+    pub log_dropped_frames: Option<bool>,
+    /// Unit: minutes
+    pub idle_timeout: Option<f32>,
+    pub idle_framerate: Option<f32>,
+    pub idle_dim: Option<f32>,
+}
+
+impl SessionDefaults {
+    /// Read the file named by [DEFAULTS_FILE_VAR], if set, then apply the individual variables
+    /// `EASEL_PAINTING_RESOLUTION`, `EASEL_PAINTING_FILENAME`, `EASEL_RECORDING_RESOLUTION`, `EASEL_MOVIE_FRAMERATE`,
+    /// `EASEL_RECORDING_FILENAME`, `EASEL_AUTOSAVE_UNIFORMS`, `EASEL_MAINTAIN`, `EASEL_THEME`, `EASEL_LOG_DROPPED_FRAMES`,
+    /// `EASEL_IDLE_TIMEOUT`, `EASEL_IDLE_FRAMERATE` and `EASEL_IDLE_DIM` over it.
+    /// Resolutions are written as `WIDTHxHEIGHT`.
+    pub fn load() -> SessionDefaults {
+        let mut defaults = match std::env::var(DEFAULTS_FILE_VAR) {
+            Ok(path) => SessionDefaults::from_file(&path),
+            Err(_) => SessionDefaults::default(),
+        };
+        if let Some(resolution) = env_var("EASEL_PAINTING_RESOLUTION", parse_resolution) {
+            defaults.painting_resolution = Some(resolution);
+        }
+        if let Some(filename) = env_var("EASEL_PAINTING_FILENAME", |v| Some(String::from(v))) {
+            defaults.painting_filename = Some(filename);
+        }
+        if let Some(resolution) = env_var("EASEL_RECORDING_RESOLUTION", parse_resolution) {
+            defaults.recording_resolution = Some(resolution);
+        }
+        if let Some(framerate) = env_var("EASEL_MOVIE_FRAMERATE", |v| {
+            v.parse().ok().filter(|fps: &i32| *fps > 0)
+        }) {
+            defaults.movie_framerate = Some(framerate);
+        }
+        if let Some(filename) = env_var("EASEL_RECORDING_FILENAME", |v| Some(String::from(v))) {
+            defaults.recording_filename = Some(filename);
+        }
+        if let Some(autosave) = env_var("EASEL_AUTOSAVE_UNIFORMS", parse_bool) {
+            defaults.autosave_uniforms = Some(autosave);
+        }
+        if let Some(strategy) = env_var("EASEL_MAINTAIN", MaintainStrategy::from_name) {
+            defaults.maintain_strategy = Some(strategy);
+        }
+        if let Some(theme) = env_var("EASEL_THEME", DashboardTheme::from_name) {
+            defaults.theme = Some(theme);
+        }
+        if let Some(log) = env_var("EASEL_LOG_DROPPED_FRAMES", parse_bool) {
+            defaults.log_dropped_frames = Some(log);
+        }
+        if let Some(minutes) = env_var("EASEL_IDLE_TIMEOUT", parse_non_negative) {
+            defaults.idle_timeout = Some(minutes);
+        }
+        if let Some(fps) = env_var("EASEL_IDLE_FRAMERATE", |v| {
+            v.parse().ok().filter(|fps: &f32| *fps > 0.0)
+        }) {
+            defaults.idle_framerate = Some(fps);
+        }
+        if let Some(dim) = env_var("EASEL_IDLE_DIM", parse_non_negative) {
+            defaults.idle_dim = Some(dim.min(1.0));
+        }
+        defaults
+    }
+
+    fn from_file(path: &str) -> SessionDefaults {
+        let data = match std::fs::read_to_string(path).map(|text| json::parse(&text)) {
+            Ok(Ok(data)) => data,
+            Ok(Err(e)) => {
+                error!("Error parsing defaults {}: {}", path, e);
+                return SessionDefaults::default();
+            }
+            Err(e) => {
+                error!("Error reading defaults {}: {}", path, e);
+                return SessionDefaults::default();
+            }
+        };
+        info!("Loaded defaults from {}", path);
+        SessionDefaults {
+            painting_resolution: resolution_from_json(&data["painting_resolution"]),
+            painting_filename: data["painting_filename"].as_str().map(String::from),
+            recording_resolution: resolution_from_json(&data["recording_resolution"]),
+            movie_framerate: data["movie_framerate"].as_i32().filter(|fps| *fps > 0),
+            recording_filename: data["recording_filename"].as_str().map(String::from),
+            autosave_uniforms: data["autosave_uniforms"].as_bool(),
+            maintain_strategy: data["maintain_strategy"]
+                .as_str()
+                .and_then(MaintainStrategy::from_name),
+            theme: data["theme"].as_str().and_then(DashboardTheme::from_name),
+            log_dropped_frames: data["log_dropped_frames"].as_bool(),
+            idle_timeout: data["idle_timeout"].as_f32().filter(|m| *m >= 0.0),
+            idle_framerate: data["idle_framerate"].as_f32().filter(|fps| *fps > 0.0),
+            idle_dim: data["idle_dim"].as_f32().map(|d| d.clamp(0.0, 1.0)),
+        }
+    }
+}
+
+impl DashboardState {
+    /// Replace the built-in defaults with the configured ones.
+    pub fn apply_session_defaults(&mut self) {
+        let defaults = self.session_defaults.clone();
+        if let Some(resolution) = defaults.painting_resolution {
+            self.painting_resolution = resolution;
+        }
+        if let Some(filename) = defaults.painting_filename {
+            self.painting_filename = filename;
+        }
+        if let Some(resolution) = defaults.recording_resolution {
+            self.recording_resolution = resolution;
+        }
+        if let Some(framerate) = defaults.movie_framerate {
+            self.movie_framerate = framerate;
+        }
+        if let Some(filename) = defaults.recording_filename {
+            self.recording_filename = filename;
+        }
+        if let Some(autosave) = defaults.autosave_uniforms {
+            self.autosave_uniforms = autosave;
+        }
+        if let Some(strategy) = defaults.maintain_strategy {
+            self.maintain_strategy = strategy;
+        }
+        if let Some(theme) = defaults.theme {
+            self.theme = theme;
+        }
+        if let Some(log) = defaults.log_dropped_frames {
+            self.log_dropped_frames = log;
+        }
+        if let Some(minutes) = defaults.idle_timeout {
+            self.idle_timeout = minutes;
+        }
+        if let Some(fps) = defaults.idle_framerate {
+            self.idle_framerate = fps;
+        }
+        if let Some(dim) = defaults.idle_dim {
+            self.idle_dim = dim;
+        }
+    }
+}
+
+/// Parsed value of an environment variable, warning if it is set but invalid.
+fn env_var<T>(name: &str, parse: impl Fn(&str) -> Option<T>) -> Option<T> {
+    let value = std::env::var(name).ok()?;
+    let parsed = parse(value.trim());
+    if parsed.is_none() {
+        warn!("Ignoring invalid value {:?} for {}.", value, name);
+    }
+    parsed
+}
+
+/// Parse `WIDTHxHEIGHT`, e.g. `3840x2160`.
+fn parse_resolution(value: &str) -> Option<IntVector2> {
+    let mut parts = value.split(['x', 'X']);
+    let width = parts.next()?.trim().parse().ok().filter(|w: &i32| *w > 0)?;
+    let height = parts.next()?.trim().parse().ok().filter(|h: &i32| *h > 0)?;
+    match parts.next() {
+        Some(_) => None,
+        None => Some(IntVector2::new(width, height)),
+    }
+}
+
+/// Parse a number that is zero or more, e.g. `2.5`.
+fn parse_non_negative(value: &str) -> Option<f32> {
+    value.parse().ok().filter(|v: &f32| *v >= 0.0)
+}
+
+/// Parse `1`/`0`, `true`/`false` or `on`/`off`.
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "1" | "true" | "on" => Some(true),
+        "0" | "false" | "off" => Some(false),
+        _ => None,
+    }
+}
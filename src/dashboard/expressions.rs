@@ -0,0 +1,173 @@
+use super::Dashboard;
+use crate::uniforms::{UserUniform, UserUniformType};
+use crate::utils::convert_value_to_bytes;
+use evalexpr::{
+    ContextWithMutableFunctions, ContextWithMutableVariables, Function, HashMapContext, Node, Value,
+};
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// A one-argument math function expressions can call, with its name.
+type MathFunction = (&'static str, fn(f64) -> f64);
+
+/// Expression driving a uniform, e.g. `0.5 + 0.5 * sin(t)`.
+pub struct UniformExpression {
+    pub source: String,
+    node: Option<Node>,
+    /// Parse error, or the error of the last evaluation.
+    pub error: Option<String>,
+}
+
+impl UniformExpression {
+    pub fn new(source: &str) -> UniformExpression {
+        let (node, error) = match evalexpr::build_operator_tree(source) {
+            Ok(node) => (Some(node), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        UniformExpression {
+            source: String::from(source),
+            node,
+            error,
+        }
+    }
+}
+
+/// Expressions evaluated every frame to animate scalar and bool uniforms, keyed by uniform name.
+/// `t` is seconds since the Dashboard started and `frame` the canvas frame count.
+pub struct UniformExpressions {
+    pub expressions: HashMap<String, UniformExpression>,
+    start: Instant,
+    context: HashMapContext,
+}
+
+impl UniformExpressions {
+    pub fn new() -> UniformExpressions {
+        let mut context = HashMapContext::new();
+        context
+            .set_value("pi".into(), Value::Float(std::f64::consts::PI))
+            .unwrap();
+        let math: [MathFunction; 7] = [
+            ("sin", f64::sin),
+            ("cos", f64::cos),
+            ("tan", f64::tan),
+            ("abs", f64::abs),
+            ("sqrt", f64::sqrt),
+            ("exp", f64::exp),
+            ("fract", f64::fract),
+        ];
+        for (name, f) in math.iter().copied() {
+            context
+                .set_function(
+                    name.into(),
+                    Function::new(move |argument| Ok(Value::Float(f(argument.as_number()?)))),
+                )
+                .unwrap();
+        }
+        UniformExpressions {
+            expressions: HashMap::new(),
+            start: Instant::now(),
+            context,
+        }
+    }
+
+    /// Attach `source` to the uniform, or detach its expression if `source` is blank.
+    pub fn set(&mut self, name: &str, source: &str) {
+        match source.trim().is_empty() {
+            true => self.expressions.remove(name),
+            false => self
+                .expressions
+                .insert(String::from(name), UniformExpression::new(source)),
+        };
+    }
+
+    /// Evaluate every expression and write the results into the matching uniforms.
+    /// Returns the names of the uniforms whose value changed.
+    pub fn apply(
+        &mut self,
+        uniforms: &mut HashMap<String, UserUniform>,
+        frame: usize,
+    ) -> Vec<String> {
+        let t = self.start.elapsed().as_secs_f64();
+        self.context.set_value("t".into(), Value::Float(t)).unwrap();
+        self.context
+            .set_value("frame".into(), Value::Int(frame as i64))
+            .unwrap();
+        let mut modified = vec![];
+        for (name, expression) in self.expressions.iter_mut() {
+            let (node, uniform) = match (&expression.node, uniforms.get_mut(name)) {
+                (Some(node), Some(uniform)) => (node, uniform),
+                _ => continue,
+            };
+            if !uniform.automatable {
+                expression.error = Some(String::from(
+                    "Paused, turn on the uniform's automatable toggle to follow the expression",
+                ));
+                continue;
+            }
+            let value = node
+                .eval_with_context(&self.context)
+                .and_then(|value| match value {
+                    Value::Boolean(b) => Ok(b as u32 as f64),
+                    value => value.as_number(),
+                });
+            let value = match value {
+                Ok(value) => value,
+                Err(err) => {
+                    expression.error = Some(err.to_string());
+                    continue;
+                }
+            };
+            expression.error = None;
+            let previous = uniform.bytes.clone();
+            match uniform.inherent_type {
+                UserUniformType::Bool => {
+                    uniform.bytes = convert_value_to_bytes((value != 0.0) as u32)
+                }
+                _ => {
+                    if !uniform.set_scalar_value(value) {
+                        expression.error = Some(String::from(
+                            "Only numbers and bools can be driven by an expression",
+                        ));
+                    }
+                }
+            }
+            if uniform.bytes != previous {
+                modified.push(name.clone());
+            }
+        }
+        modified
+    }
+
+    /// Expression sources, as saved in the shader settings.
+    pub fn to_json(&self) -> json::JsonValue {
+        let mut data = json::JsonValue::new_object();
+        for (name, expression) in &self.expressions {
+            data[name.as_str()] = expression.source.as_str().into();
+        }
+        data
+    }
+
+    /// Replace all expressions with those saved by [Self::to_json].
+    pub fn load_json(&mut self, data: &json::JsonValue) {
+        self.expressions.clear();
+        for (name, source) in data.entries() {
+            if let Some(source) = source.as_str() {
+                self.set(name, source);
+            }
+        }
+    }
+}
+
+impl Dashboard {
+    /// Drive uniforms from their expressions, see [UniformExpressions].
+    pub fn update_uniform_expressions(&mut self) {
+        let modified = self
+            .state
+            .uniform_expressions
+            .apply(&mut self.state.gui_uniforms, self.state.frame_num);
+        self.state
+            .unrecorded_uniforms
+            .extend(modified.iter().cloned());
+        self.state.modified_uniforms.extend(modified);
+    }
+}
@@ -0,0 +1,84 @@
+use super::{Dashboard, DashboardMessage};
+use crate::vector::{IntVector2, UIntVector2};
+
+/// Aspect ratio presets for producing the same piece for different feeds.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Framing {
+    Landscape,
+    Square,
+    Portrait,
+    Vertical,
+}
+
+impl Framing {
+    pub const ALL: [Framing; 4] = [
+        Framing::Landscape,
+        Framing::Square,
+        Framing::Vertical,
+        Framing::Portrait,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Framing::Landscape => "16:9",
+            Framing::Square => "1:1",
+            Framing::Vertical => "9:16",
+            Framing::Portrait => "4:5",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Framing> {
+        Framing::ALL.iter().copied().find(|f| f.name() == name)
+    }
+
+    /// Width to height ratio.
+    pub fn aspect(&self) -> UIntVector2 {
+        match self {
+            Framing::Landscape => UIntVector2::new(16, 9),
+            Framing::Square => UIntVector2::new(1, 1),
+            Framing::Vertical => UIntVector2::new(9, 16),
+            Framing::Portrait => UIntVector2::new(4, 5),
+        }
+    }
+
+    /// Resolution of this aspect with the same long edge as `resolution`, see [fit_aspect].
+    pub fn fit(&self, resolution: IntVector2, max: i32) -> IntVector2 {
+        fit_aspect(self.aspect(), resolution, max)
+    }
+
+    /// Fraction of a `window` sized area this framing covers once letterboxed in it, as `[width, height]`.
+    pub fn coverage(&self, window: IntVector2) -> [f32; 2] {
+        let aspect = self.aspect();
+        let (width, height) = (window.x.max(1) as f32, window.y.max(1) as f32);
+        let scale = (width / aspect.x as f32).min(height / aspect.y as f32);
+        [
+            aspect.x as f32 * scale / width,
+            aspect.y as f32 * scale / height,
+        ]
+    }
+}
+
+/// Resolution with the `aspect` width to height ratio and the same long edge as `resolution`, at most `max` on either side.
+/// Both sides are rounded to even numbers, which the movie encoders require.
+pub fn fit_aspect(aspect: UIntVector2, resolution: IntVector2, max: i32) -> IntVector2 {
+    let long_edge = resolution.x.max(resolution.y).max(2).min(max) as f32;
+    let scale = long_edge / aspect.x.max(aspect.y).max(1) as f32;
+    let even = |side: f32| ((side / 2.0).round() as i32 * 2).max(2).min(max);
+    IntVector2::new(even(aspect.x as f32 * scale), even(aspect.y as f32 * scale))
+}
+
+impl Dashboard {
+    /// Frame the canvas to the preset's aspect, letterboxed in its window, and conform the painting and recording
+    /// resolutions to it. `None` returns to filling the window and leaves the resolutions as they are.
+    pub fn set_framing(&mut self, framing: Option<Framing>) {
+        self.state.framing = framing;
+        if let Some(framing) = framing {
+            let max = self.state.max_texture_dimension as i32;
+            self.state.painting_resolution = framing.fit(self.state.painting_resolution, max);
+            self.state.recording_resolution = framing.fit(self.state.recording_resolution, max);
+        }
+        self.transmitter.send(DashboardMessage::FramingChanged(
+            framing.map(|f| f.aspect()),
+        ));
+    }
+}
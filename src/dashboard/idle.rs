@@ -0,0 +1,48 @@
+use super::{Dashboard, DashboardMessage};
+use crate::canvas::IdleSettings;
+use std::time::Instant;
+use winit::event::{Event, WindowEvent};
+
+/// Whether `event` is the user interacting with any of the windows, as opposed to e.g. a redraw.
+pub fn is_user_input(event: &Event<()>) -> bool {
+    match event {
+        Event::WindowEvent { event, .. } => matches!(
+            event,
+            WindowEvent::KeyboardInput { .. }
+                | WindowEvent::ReceivedCharacter(_)
+                | WindowEvent::MouseInput { .. }
+                | WindowEvent::MouseWheel { .. }
+                | WindowEvent::CursorMoved { .. }
+                | WindowEvent::Touch(_)
+        ),
+        _ => false,
+    }
+}
+
+impl Dashboard {
+    /// Restart the idle timeout, waking the canvas if it is idle.
+    pub fn note_user_input(&mut self) {
+        self.state.last_user_input = Instant::now();
+        if self.state.idle {
+            self.state.idle = false;
+            self.transmitter.send(DashboardMessage::ExitIdle);
+        }
+    }
+
+    /// Send the canvas idle once [super::DashboardState::idle_timeout] has passed without input.
+    /// Never while recording, which needs every frame.
+    pub fn update_idle(&mut self) {
+        let timeout = self.state.idle_timeout;
+        if self.state.idle || timeout <= 0.0 || self.state.recording_in_progress {
+            return;
+        }
+        if self.state.last_user_input.elapsed().as_secs_f32() >= timeout * 60.0 {
+            self.state.idle = true;
+            let settings = IdleSettings {
+                framerate: self.state.idle_framerate,
+                dim: self.state.idle_dim,
+            };
+            self.transmitter.send(DashboardMessage::EnterIdle(settings));
+        }
+    }
+}
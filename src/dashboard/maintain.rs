@@ -0,0 +1,48 @@
+use super::Dashboard;
+
+/// How [Dashboard::update] maintains its device, trading latency for power use.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MaintainStrategy {
+    /// Check for finished GPU work without blocking. Lowest latency, but keeps the CPU busy.
+    Poll,
+    /// Block until the GPU has finished the previous frame. Saves power, e.g. on battery.
+    Wait,
+    /// [Self::Wait] while the canvas is paused or idle, or the Dashboard is unfocused, [Self::Poll] otherwise.
+    Auto,
+}
+
+impl MaintainStrategy {
+    pub const ALL: [MaintainStrategy; 3] = [
+        MaintainStrategy::Auto,
+        MaintainStrategy::Poll,
+        MaintainStrategy::Wait,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MaintainStrategy::Poll => "Poll",
+            MaintainStrategy::Wait => "Wait",
+            MaintainStrategy::Auto => "Auto",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<MaintainStrategy> {
+        MaintainStrategy::ALL
+            .iter()
+            .copied()
+            .find(|s| s.name().eq_ignore_ascii_case(name))
+    }
+}
+
+impl Dashboard {
+    /// The strategy in effect this frame, resolving [MaintainStrategy::Auto].
+    pub fn effective_maintain(&self) -> wgpu::Maintain {
+        let idle = self.state.paused || !self.state.dashboard_focused || self.state.idle;
+        match self.state.maintain_strategy {
+            MaintainStrategy::Poll => wgpu::Maintain::Poll,
+            MaintainStrategy::Wait => wgpu::Maintain::Wait,
+            MaintainStrategy::Auto if idle => wgpu::Maintain::Wait,
+            MaintainStrategy::Auto => wgpu::Maintain::Poll,
+        }
+    }
+}
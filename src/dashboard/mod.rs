@@ -1,35 +1,189 @@
 use crate::vector::{IntVector2, UIntVector2};
-use crate::{canvas::CanvasMessage, uniforms::UserUniform};
-use crate::{recording::Recorder, utils::AsyncTiffWriter};
-use core::panic;
+use crate::{
+    canvas::{
+        CanvasMessage, CompareSettings, DebugView, IdleSettings, MirrorSettings, StereoSettings,
+        VariationSheetRequest,
+    },
+    texture::NoiseType,
+    uniforms::{load_uniforms_from_json, RampUniform, UserUniform},
+};
+use crate::{
+    recording::{Recorder, RecorderError},
+    utils,
+    utils::{AsyncTiffWriter, ExportNotifier, VramTracker},
+};
 
 use imgui::FontSource;
-use imgui_wgpu::RendererConfig;
+use imgui_wgpu::{RendererConfig, Texture, TextureConfig};
 use imgui_winit_support;
-use log::info;
+use log::{error, info, warn};
 use std::{
-    sync::mpsc::{Receiver, Sender},
+    collections::HashMap,
+    sync::mpsc::{Receiver, Sender, TryRecvError},
     time::Instant,
 };
-use wgpu::{PowerPreference, RequestAdapterOptions};
-use winit::window::Window;
+use wgpu::PowerPreference;
+use winit::window::{Window, WindowId};
 
+mod actions;
+pub use self::actions::*;
+mod batch;
+pub use self::batch::*;
+mod connection;
+pub use self::connection::*;
+mod defaults;
+pub use self::defaults::*;
+mod expressions;
+pub use self::expressions::*;
+mod framing;
+pub use self::framing::*;
+mod maintain;
+pub use self::maintain::*;
+mod playlist;
+pub use self::playlist::*;
+mod idle;
+pub use self::idle::*;
+mod theme;
+pub use self::theme::*;
+mod timeline;
+pub use self::timeline::*;
+mod reproduce;
+pub use self::reproduce::*;
+mod scenes;
+pub use self::scenes::*;
+mod sidecar;
+pub use self::sidecar::*;
+mod variation_sheet;
+pub use self::variation_sheet::*;
 mod ui;
 pub use self::ui::*;
 
 mod state;
 pub use self::state::*;
 
+/// Whether the imgui renderer expects textures with premultiplied alpha.
+/// [imgui_wgpu] blends with `SrcAlpha, OneMinusSrcAlpha`, i.e. straight alpha,
+/// so premultiplied images must be converted before they are shown in the GUI.
+pub static IMGUI_PREMULTIPLIED_ALPHA: bool = false;
+
+/// Smallest size the Dashboard window can be resized to, in logical pixels.
+/// The controls scroll below this height, but any narrower and the widget labels get cut off.
+pub const MIN_WINDOW_SIZE: [f64; 2] = [360.0, 240.0];
+
+/// Convert tightly packed RGBA8 pixels to the alpha mode of the imgui renderer, see [IMGUI_PREMULTIPLIED_ALPHA].
+fn convert_to_imgui_alpha(pixels: &mut [u8], premultiplied: bool) {
+    if premultiplied && !IMGUI_PREMULTIPLIED_ALPHA {
+        utils::unpremultiply_alpha(pixels);
+    } else if !premultiplied && IMGUI_PREMULTIPLIED_ALPHA {
+        utils::premultiply_alpha(pixels);
+    }
+}
+
 /// Message Enums used by [Dashboard] to send messages to interested parties.
 pub enum DashboardMessage {
     PausePlayChanged,
+    /// Toggle freezing time and frame count while the Canvas keeps rendering.
+    FreezeTimeChanged,
+    /// Hold the time uniform still, or let it carry on from where it was held. Unlike freezing, frames keep counting
+    /// and nothing accumulates, so a recording gets a held segment.
+    HoldTime(bool),
+    /// Enable or disable progressive accumulation, see [crate::canvas::Canvas::accumulation_active].
+    AccumulationChanged(bool),
+    /// Restart the accumulated average.
+    ResetAccumulation,
+    /// Sub-pixel jitter settings, see [crate::uniforms::Uniforms::jitter].
+    JitterChanged {
+        enabled: bool,
+        frozen: bool,
+    },
     Play,
     Pause,
     TitlebarStatusChanged,
-    PaintingRenderRequested(UIntVector2),
+    /// Hide the canvas window's border, titlebar and cursor, regardless of the titlebar setting, or bring them back.
+    BorderlessMode(bool),
+    /// Title the canvas window with this, see [DashboardState::canvas_window_title].
+    WindowTitleChanged(String),
+    /// Render a painting at this resolution, with a TIFF page per pass if `all_passes` is set.
+    /// With a `time`, the painting shows that moment instead of the live one, see [DashboardState::capture_time].
+    PaintingRenderRequested {
+        resolution: UIntVector2,
+        all_passes: bool,
+        time: Option<f32>,
+    },
     PaintingResolutionUpdated(UIntVector2),
+    /// Whether paintings from the canvas window shortcut capture every pass, see [DashboardState::export_painting_passes].
+    PaintingPassesUpdated(bool),
+    /// Frames to render and average into each painting, see [DashboardState::painting_warm_up_frames].
+    PaintingWarmUpUpdated(u32),
+    /// Capture a movie frame at the given resolution. Only one is requested at a time, the next once the Canvas
+    /// answers with [CanvasMessage::MovieFrameStarted].
     MovieRenderRequested(UIntVector2),
+    /// Render a movie frame at the given resolution and discard it, so the recording doesn't start with a frame of
+    /// resources that haven't been used yet. Nothing is sent back.
+    MovieWarmUpRequested(UIntVector2),
     UniformUpdatedViaGUI(UserUniform),
+    RampUpdatedViaGUI(RampUniform),
+    /// Samples per axis, see [crate::utils::AA_SAMPLES_DEFINE].
+    AntialiasingChanged(u32),
+    /// Path of an image to bind as the shader's input texture.
+    InputImageSelected(String),
+    /// Path of a fragment shader to render instead of the current one.
+    OpenShader(String),
+    /// Fade the on-screen output towards `color` until `target` is reached, over `duration` seconds.
+    MasterFade {
+        target: f32,
+        color: [f32; 3],
+        duration: f32,
+    },
+    /// No input for the idle timeout: render slower and dim the output until [Self::ExitIdle].
+    EnterIdle(IdleSettings),
+    ExitIdle,
+    /// New window the Canvas should mirror its output to. Windows are created on the main thread.
+    MirrorWindowCreated(Window),
+    MirrorWindowClosed(WindowId),
+    MirrorSettingsChanged(WindowId, MirrorSettings),
+    /// Start or stop reading back frames for [CanvasMessage::Histogram].
+    HistogramEnabled(bool),
+    /// Start sending [CanvasMessage::Thumbnail]s with this longer edge, in pixels, or stop with `None`.
+    ThumbnailSizeChanged(Option<u32>),
+    /// Render the canvas at a fixed resolution, letterboxed in its window, or follow the window size with `None`.
+    LockResolution(Option<UIntVector2>),
+    /// Render only a region of the canvas window with this width to height ratio, letterboxed, or the whole window with `None`.
+    FramingChanged(Option<UIntVector2>),
+    /// Put the next frame shown on the canvas on the system clipboard.
+    CopyFrameToClipboard,
+    /// Store the next frame shown on the canvas to compare later tweaks against.
+    CaptureReference,
+    ClearReference,
+    /// Overlay the reference frame on the canvas window, or hide it with `None`.
+    CompareChanged(Option<CompareSettings>),
+    /// Show a debug visualization in the canvas window instead of the output.
+    DebugView(DebugView),
+    /// Show a checkerboard behind transparent areas of the canvas window. Never affects captures.
+    CheckerboardChanged(bool),
+    /// Keep the last good frame on the canvas window while the shader fails to compile, rather than clearing it.
+    HoldFrameOnError(bool),
+    /// Smoothing of the mouse position bound to the shader, from 0 (raw) to 1 (strongest).
+    MouseSmoothingChanged(f32),
+    /// How many times as fast as real time the time uniform advances, 1 for real time.
+    TimeScaleChanged(f32),
+    /// Byte offsets in the user uniforms buffer to place uniforms at, by name, from the shader settings.
+    UniformOffsetsPinned(HashMap<String, usize>),
+    /// Mirror the canvas window output, e.g. for rear projection. Paintings and movies are only mirrored with `captures`.
+    FlipOutput {
+        horizontal: bool,
+        vertical: bool,
+        captures: bool,
+    },
+    /// Render paintings and movie frames as a stereo pair packed into one frame, or as a single view with `None`.
+    StereoChanged(Option<StereoSettings>),
+    /// Render a painting of each variation at a small size, to be stitched into a contact sheet.
+    VariationSheetRequested(VariationSheetRequest),
+    /// Regenerate the noise texture bound after the ramps.
+    NoiseChanged {
+        noise_type: NoiseType,
+        seed: u32,
+    },
 }
 
 /// Centralized controller and GUI class.
@@ -55,10 +209,28 @@ pub struct Dashboard {
 
     state: DashboardState,
 
-    transmitter: Sender<DashboardMessage>,
+    transmitter: CanvasSender,
     receiver: Receiver<CanvasMessage>,
     recorder: Option<Recorder>,
     last_movie_frame_time: Option<Instant>,
+    /// Frames requested from the Canvas for the current recording, including warm-up frames.
+    movie_frames_requested: u64,
+    /// A movie frame was requested and the Canvas has not reported capturing it yet, so no other is requested.
+    movie_frame_in_flight: bool,
+    /// Resolution the Canvas was last told to lock to, as width and height.
+    canvas_resolution_lock: Option<(u32, u32)>,
+    /// Painting resolution the Canvas was last told about, so it is only sent again when it changes.
+    last_sent_painting_resolution: Option<UIntVector2>,
+    last_sent_painting_passes: Option<bool>,
+    last_sent_painting_warm_up: Option<u32>,
+    /// Time scale the Canvas was last told about, see [DashboardState::time_scale].
+    last_sent_time_scale: Option<f32>,
+    /// Informs subscribers when paintings and movies finish exporting.
+    export_notifier: ExportNotifier,
+    /// Replaced by the Canvas' tracker once it is received, so both report to the same record.
+    vram: VramTracker,
+    /// Keyboard modifiers currently held while the Dashboard window is focused.
+    modifiers: winit::event::ModifiersState,
 }
 
 impl Dashboard {
@@ -79,20 +251,22 @@ impl Dashboard {
             surface = instance.create_surface(&window);
         }
 
-        let adapter = instance
-            .request_adapter(&RequestAdapterOptions {
-                compatible_surface: Some(&surface),
-                power_preference: PowerPreference::LowPower,
-            })
-            .await
-            .unwrap();
-        let device_desc = wgpu::DeviceDescriptor {
-            label: None,
-            features: adapter.features(),
-            limits: Default::default(),
+        let adapter = match utils::request_adapter(
+            &instance,
+            &surface,
+            PowerPreference::LowPower,
+            "Dashboard",
+        )
+        .await
+        {
+            Some(adapter) => adapter,
+            None => {
+                error!("No GPU adapter is available to draw the Dashboard. Check that a Vulkan, Metal or DirectX 12 driver is installed.");
+                std::process::exit(1);
+            }
         };
-
-        let (device, mut queue) = adapter.request_device(&device_desc, None).await.unwrap();
+        let (device, mut queue) =
+            utils::request_device(&adapter, Default::default(), "Dashboard").await;
 
         //------------------------------------------------------------------------------------------
         // Setup swap chain
@@ -141,9 +315,11 @@ impl Dashboard {
         renderer_config.texture_format = sc_desc.format;
         let renderer = imgui_wgpu::Renderer::new(&mut imgui, &device, &mut queue, renderer_config);
         let mut state = DashboardState::new();
+        state.session_defaults = SessionDefaults::load();
+        state.apply_session_defaults();
         state.render_window_size = IntVector2::new(size.width as i32, size.height as i32);
 
-        Self {
+        let dashboard = Self {
             window,
             instance,
             surface,
@@ -160,139 +336,698 @@ impl Dashboard {
             last_frame: std::time::Instant::now(),
             hidpi_factor,
             state,
-            transmitter,
+            transmitter: CanvasSender::new(transmitter),
             receiver,
             recorder: None,
             last_movie_frame_time: None,
+            movie_frames_requested: 0,
+            movie_frame_in_flight: false,
+            canvas_resolution_lock: None,
+            last_sent_painting_resolution: None,
+            last_sent_painting_passes: None,
+            last_sent_painting_warm_up: None,
+            last_sent_time_scale: None,
+            export_notifier: ExportNotifier::default(),
+            vram: VramTracker::default(),
+            modifiers: Default::default(),
+        };
+        dashboard.update_swap_chain_vram();
+        dashboard
+    }
+
+    /// Load the settings sidecar and autosaved uniform values for the given shader, if there are any.
+    fn open_shader_settings(&mut self, shader_file: String) {
+        self.state.open_shader_file = shader_file.clone();
+        // Batch renders use the uniforms file's values and the batch's resolution, not each shader's settings.
+        if self.state.batch_render.is_some() {
+            self.state.shader_file = Some(shader_file);
+            return;
+        }
+        // The Canvas reports its window size as the painting resolution on startup, before the shader is opened.
+        if let Some(resolution) = self.state.session_defaults.painting_resolution {
+            self.state.painting_resolution = resolution;
+        }
+        self.load_shader_sidecar(&shader_file);
+        if self.state.autosave_uniforms {
+            self.load_uniform_autosave(&shader_file);
+        }
+        // Exports reproduce the shader being rendered, which may have been opened after launch.
+        if let Some(shader_arg) = self.state.launch_args.first_mut() {
+            *shader_arg = shader_file.clone();
+        }
+        self.state.shader_file = Some(shader_file);
+    }
+
+    /// Load the settings saved with `Save Shader Settings`. Without a sidecar, the defaults are kept.
+    fn load_shader_sidecar(&mut self, shader_file: &str) {
+        let path = sidecar_path(shader_file);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => return,
+        };
+        match json::parse(&text) {
+            Ok(data) => {
+                let previous_aa_samples = self.state.aa_samples;
+                let previous_framing = self.state.framing;
+                let previous_noise = (self.state.noise_type, self.state.noise_seed);
+                let previous_pins = self.state.uniform_pins.clone();
+                let previous_title = self.state.canvas_window_title();
+                self.state.pending_shader_uniforms = self.state.load_shader_settings(&data);
+                if self.state.aa_samples != previous_aa_samples {
+                    self.transmitter
+                        .send(DashboardMessage::AntialiasingChanged(self.state.aa_samples));
+                }
+                if self.state.framing != previous_framing {
+                    // The saved resolutions already match the saved framing.
+                    self.transmitter.send(DashboardMessage::FramingChanged(
+                        self.state.framing.map(|f| f.aspect()),
+                    ));
+                }
+                if (self.state.noise_type, self.state.noise_seed) != previous_noise {
+                    self.transmitter.send(DashboardMessage::NoiseChanged {
+                        noise_type: self.state.noise_type,
+                        seed: self.state.noise_seed,
+                    });
+                }
+                if self.state.uniform_pins != previous_pins {
+                    self.transmitter
+                        .send(DashboardMessage::UniformOffsetsPinned(
+                            self.state.uniform_pins.clone(),
+                        ));
+                }
+                let title = self.state.canvas_window_title();
+                if title != previous_title {
+                    self.transmitter
+                        .send(DashboardMessage::WindowTitleChanged(title));
+                }
+                if let Some(name) = self.state.export_title() {
+                    info!("Piece: {}", name);
+                }
+                info!("Loaded shader settings from {}", path);
+            }
+            Err(e) => error!("Error parsing shader settings {}: {}", path, e),
+        }
+    }
+
+    /// Restore the uniform values the shader had when Otium last exited.
+    /// They are more recent than the sidecar's, so replace those with the same name.
+    fn load_uniform_autosave(&mut self, shader_file: &str) {
+        let path = autosave_path(shader_file);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => return,
+        };
+        match json::parse(&text) {
+            Ok(data) => {
+                let autosaved = load_uniforms_from_json(&data);
+                self.state
+                    .pending_shader_uniforms
+                    .retain(|saved| !autosaved.contains(saved));
+                self.state.pending_shader_uniforms.extend(autosaved);
+                info!("Restored autosaved uniforms from {}", path);
+            }
+            Err(e) => error!("Error parsing autosaved uniforms {}: {}", path, e),
+        }
+    }
+
+    /// Save the current uniform values for the open shader, to be restored the next time it is opened.
+    /// With autosave turned off, any previous autosave is removed instead, so the shader starts from its defaults.
+    pub fn save_uniform_autosave(&self) {
+        let shader_file = match &self.state.shader_file {
+            Some(shader_file) => shader_file,
+            None => return,
+        };
+        let path = autosave_path(shader_file);
+        if !self.state.autosave_uniforms {
+            if std::path::Path::new(&path).exists() {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    error!("Error removing autosaved uniforms {}: {}", path, e);
+                }
+            }
+            return;
+        }
+        if self.state.gui_uniforms.is_empty() {
+            return;
+        }
+        let mut data = json::JsonValue::new_object();
+        data["uniforms"] = self.state.uniforms_json();
+        match std::fs::write(&path, data.pretty(2)) {
+            Ok(_) => info!("Autosaved uniforms to {}", path),
+            Err(e) => error!("Error autosaving uniforms {}: {}", path, e),
+        }
+    }
+
+    /// Draw a new value for every automatable, unlocked uniform that has a range, from [DashboardState::randomize_seed].
+    /// The same seed gives the same values, so a look found by randomizing can be found again.
+    /// Uniforms following an expression are left to it.
+    pub fn randomize_uniforms(&mut self) {
+        let mut random = utils::SeededRandom::new(self.state.randomize_seed as u64);
+        let expressions = &self.state.uniform_expressions.expressions;
+        // Draw in file order so a seed doesn't depend on the order of the map.
+        let mut uniforms: Vec<&mut UserUniform> = self.state.gui_uniforms.values_mut().collect();
+        uniforms.sort_by_key(|uniform| uniform.order);
+        let mut count = 0;
+        for uniform in uniforms {
+            if !uniform.automatable || uniform.locked || expressions.contains_key(&uniform.name) {
+                continue;
+            }
+            // The new values are sent to the canvas with the other modified uniforms in post_render.
+            if uniform.randomize(&mut random) {
+                self.state.modified_uniforms.insert(uniform.name.clone());
+                count += 1;
+            }
+        }
+        info!(
+            "Randomized {} uniforms with seed {}",
+            count, self.state.randomize_seed
+        );
+    }
+
+    /// Offer the imgui demo and metrics windows in the Controls window. Off by default so end users never see them.
+    pub fn enable_developer_mode(&mut self) {
+        self.state.developer_mode = true;
+    }
+
+    /// Number of mirror windows asked for since the last call.
+    /// Windows can only be built on the main thread, which then passes each to [Self::add_mirror_window].
+    pub fn take_mirror_window_requests(&mut self) -> usize {
+        std::mem::take(&mut self.state.mirror_windows_requested)
+    }
+
+    /// Hand a newly built mirror window over to the Canvas, along with any saved settings for it.
+    pub fn add_mirror_window(&mut self, window: Window) {
+        let id = window.id();
+        let settings = match self.state.pending_mirror_settings.is_empty() {
+            true => MirrorSettings::default(),
+            false => self.state.pending_mirror_settings.remove(0),
+        };
+        self.state.mirror_outputs.push((id, settings.clone()));
+        self.transmitter
+            .send(DashboardMessage::MirrorWindowCreated(window));
+        self.transmitter
+            .send(DashboardMessage::MirrorSettingsChanged(id, settings));
+    }
+
+    /// Stop mirroring to the given window. The Canvas owns the window, so this also closes it.
+    pub fn mirror_window_closed(&mut self, id: WindowId) {
+        self.state
+            .mirror_outputs
+            .retain(|(mirror_id, _)| *mirror_id != id);
+        self.transmitter
+            .send(DashboardMessage::MirrorWindowClosed(id));
+    }
+
+    /// Record the size of the Dashboard's double-buffered swap chain.
+    fn update_swap_chain_vram(&self) {
+        self.vram.set(
+            "Dashboard swap chain",
+            2 * utils::texture_bytes(self.sc_desc.width, self.sc_desc.height, self.sc_desc.format),
+        );
+    }
+
+    /// Handle for subscribing to painting and movie export events.
+    /// The same notifier can be passed directly to [AsyncTiffWriter] and [Recorder] to use them without a Dashboard.
+    #[allow(dead_code)]
+    pub fn export_notifier(&self) -> ExportNotifier {
+        self.export_notifier.clone()
+    }
+
+    /// Upload an RGBA8 image so it can be displayed in the GUI with [imgui::Image].
+    /// The pixel data is converted to the alpha mode of the renderer, see [IMGUI_PREMULTIPLIED_ALPHA].
+    /// * `pixels` - Tightly packed RGBA8 pixel data.
+    /// * `premultiplied` - Whether `pixels` has premultiplied alpha.
+    pub fn register_preview_texture(
+        &mut self,
+        mut pixels: Vec<u8>,
+        resolution: UIntVector2,
+        premultiplied: bool,
+    ) -> imgui::TextureId {
+        convert_to_imgui_alpha(&mut pixels, premultiplied);
+        let texture_config = TextureConfig {
+            size: wgpu::Extent3d {
+                width: resolution.x,
+                height: resolution.y,
+                depth_or_array_layers: 1,
+            },
+            label: Some("GUI Preview"),
+            format: Some(wgpu::TextureFormat::Rgba8UnormSrgb),
+            ..Default::default()
+        };
+        let texture = Texture::new(&self.device, &self.imgui_renderer, texture_config);
+        texture.write(&self.queue, &pixels, resolution.x, resolution.y);
+        self.imgui_renderer.textures.insert(texture)
+    }
+
+    /// Show a thumbnail from the Canvas in the preview, reusing its texture while the size stays the same.
+    fn show_thumbnail(&mut self, mut pixels: Vec<u8>, size: UIntVector2) {
+        if let Some((id, current)) = self.state.thumbnail {
+            if current.x == size.x && current.y == size.y {
+                if let Some(texture) = self.imgui_renderer.textures.get(id) {
+                    convert_to_imgui_alpha(&mut pixels, false);
+                    texture.write(&self.queue, &pixels, size.x, size.y);
+                    return;
+                }
+            }
+            self.imgui_renderer.textures.remove(id);
+        }
+        let id = self.register_preview_texture(pixels, size, false);
+        self.state.thumbnail = Some((id, size));
+    }
+
+    /// Stop showing the preview and free its texture.
+    fn clear_thumbnail(&mut self) {
+        if let Some((id, _)) = self.state.thumbnail.take() {
+            self.imgui_renderer.textures.remove(id);
         }
     }
 
     /// Used to parse and respond to messages received from [crate::canvas::Canvas]
     fn handle_message(&mut self, message: CanvasMessage) {
         match message {
-            CanvasMessage::FrameStep => self.state.frame_num += 1,
+            CanvasMessage::FrameStep(at) => {
+                self.state.frame_num += 1;
+                if let Some(last) = self.state.last_frame_step {
+                    self.state.canvas_frame_time =
+                        at.saturating_duration_since(last).as_secs_f64() * 1000.0;
+                }
+                self.state.last_frame_step = Some(at);
+                self.state.canvas_frame_rate.frame(at);
+            }
             CanvasMessage::MouseMoved(pos) => self.state.mouse_pos = pos,
             CanvasMessage::RenderPassSubmitted => {}
             CanvasMessage::WindowResized(new_size) => self.state.render_window_size = new_size,
-            CanvasMessage::SwapChainFrameError(frame_error) => match frame_error {
-                wgpu::SwapChainError::Timeout => self.state.frame_timeout_count += 1,
-                _ => {}
-            },
-            CanvasMessage::PaintingStarted(buf, resolution, start_time) => {
-                let filename = self.state.painting_filename.clone() + ".tiff";
+            CanvasMessage::SwapChainFrameError(frame_error) => {
+                self.state.canvas_frame_rate.dropped(Instant::now());
+                if frame_error == wgpu::SwapChainError::Timeout {
+                    self.state.frame_timeout_count += 1;
+                }
+            }
+            CanvasMessage::PaintingStarted(resolution, start_time) => {
+                self.state.painting_requested = false;
+                self.state.painting_rendering = true;
                 self.state.painting_start_time = Some(start_time);
+                // Count supersampled pixels, as that is what the render time scales with.
+                self.state.painting_pixels = resolution.x as u64
+                    * resolution.y as u64
+                    * (self.state.aa_samples * self.state.aa_samples) as u64;
+            }
+            CanvasMessage::PaintingRendered(layers, resolution) => {
+                self.state.painting_rendering = false;
+                self.note_export(ExportKind::Painting);
+                let file = match self.batch_painting_rendered() {
+                    Some(path) => utils::PaintingFile::new(&path),
+                    None => utils::PaintingFile::new(&self.state.painting_filename),
+                };
                 let open_externally = match cfg!(target_os = "macos") {
                     true => self.state.open_painting_externally,
                     false => false,
                 };
+                self.vram.set(
+                    "Painting staging buffer",
+                    layers.len() as u64
+                        * utils::texture_bytes(
+                            resolution.x,
+                            resolution.y,
+                            crate::canvas::PAINTING_TEXTURE_FORMAT,
+                        ),
+                );
                 self.state.painting_progress_receiver = Some(AsyncTiffWriter::write(
-                    buf,
+                    layers,
                     UIntVector2::new(resolution.x as u32, resolution.y as u32),
-                    filename,
+                    file,
+                    self.state.uniform_overlay(),
+                    self.state.export_title(),
                     open_externally,
+                    self.export_notifier.clone(),
+                ));
+            }
+            CanvasMessage::VariationSheetRendered(buf, layout) => {
+                self.state.painting_rendering = false;
+                let size = layout.sheet_size();
+                self.vram.set(
+                    "Painting staging buffer",
+                    utils::texture_bytes(
+                        layout.row_pitch,
+                        layout.cell_size.y * layout.labels.len() as u32,
+                        crate::canvas::PAINTING_TEXTURE_FORMAT,
+                    ),
+                );
+                info!(
+                    "Stitching {} variations into a {} x {} sheet",
+                    layout.labels.len(),
+                    size.x,
+                    size.y
+                );
+                self.state.painting_progress_receiver = Some(write_variation_sheet(
+                    buf,
+                    layout,
+                    utils::PaintingFile::new(&self.state.painting_filename).stem
+                        + "_variations.tiff",
+                    self.export_notifier.clone(),
                 ));
             }
+            CanvasMessage::PaintingFailed(err_msg) => {
+                error!("Painting failed: {}", err_msg);
+                self.state.painting_requested = false;
+                self.batch_painting_failed(err_msg.clone());
+                self.state.painting_rendering = false;
+                self.state.painting_start_time = None;
+                if self.state.pause_while_painting {
+                    self.transmitter.send(DashboardMessage::Play);
+                }
+            }
             CanvasMessage::ShaderCompilationFailed(err_msg) => {
+                self.batch_shader_compiled(Err(err_msg.clone()));
+                self.playlist_shader_compiled(Err(err_msg.clone()));
                 self.state.shader_compilation_error_msg = Some(err_msg);
                 // Pause rendering
-                self.transmitter.send(DashboardMessage::Pause).unwrap();
+                self.transmitter.send(DashboardMessage::Pause);
             }
             CanvasMessage::ShaderCompilationSucceeded => {
                 self.state.shader_compilation_error_msg = None;
-                self.transmitter.send(DashboardMessage::Play).unwrap();
+                self.transmitter.send(DashboardMessage::Play);
                 self.state.paused = false;
+                self.batch_shader_compiled(Ok(()));
+                self.playlist_shader_compiled(Ok(()));
             }
             CanvasMessage::PausePlayChanged => {
                 self.state.paused = !self.state.paused;
             }
-            CanvasMessage::UniformForGUI(uniform) => {
+            CanvasMessage::ClearUniforms => {
+                // Edits still waiting for post_render would otherwise be sent for uniforms that no longer exist,
+                // or with the old type's bytes for one that was redeclared.
+                self.state.gui_uniforms.clear();
+                self.state.modified_uniforms.clear();
+            }
+            CanvasMessage::UniformsForGUI(uniforms) => {
+                // Replaces the whole set, so uniforms removed from the uniforms file disappear.
+                self.state.gui_uniforms = uniforms
+                    .into_iter()
+                    .map(|uniform| (uniform.name.clone(), uniform))
+                    .collect();
+                let gui_uniforms = &self.state.gui_uniforms;
                 self.state
-                    .gui_uniforms
-                    .insert(uniform.name.clone(), uniform);
+                    .modified_uniforms
+                    .retain(|name| gui_uniforms.contains_key(name));
+            }
+            CanvasMessage::RampForGUI(ramp) => {
+                match self
+                    .state
+                    .gui_ramps
+                    .iter_mut()
+                    .find(|r| r.name == ramp.name)
+                {
+                    Some(existing) => *existing = ramp,
+                    None => self.state.gui_ramps.push(ramp),
+                }
+            }
+            CanvasMessage::InputImageLoaded(file) => {
+                self.state.input_image_loaded = Some(file);
+                self.state.input_image_error_msg = None;
+            }
+            CanvasMessage::InputImageFailed(msg) => {
+                self.state.input_image_error_msg = Some(msg);
+            }
+            CanvasMessage::MasterFadeLevel(level) => self.state.master_fade_level = level,
+            CanvasMessage::Histogram(histogram) => self.state.histogram = Some(histogram),
+            // Thumbnails may still arrive just after the preview was hidden.
+            CanvasMessage::Thumbnail(pixels, size) if self.state.preview_enabled => {
+                self.show_thumbnail(pixels, size)
+            }
+            CanvasMessage::Thumbnail(..) => {}
+            CanvasMessage::AccumulationSamples(samples) => {
+                self.state.accumulation_samples = samples;
+            }
+            CanvasMessage::ShaderOpened(shader_file) => self.open_shader_settings(shader_file),
+            CanvasMessage::VramTrackerCreated(tracker) => {
+                for (site, bytes) in self.vram.allocations() {
+                    tracker.set(&site, bytes);
+                }
+                self.vram = tracker;
+            }
+            CanvasMessage::MaxTextureDimension(max) => {
+                self.state.max_texture_dimension = max;
             }
             CanvasMessage::UpdatePaintingResolutioninGUI(res) => {
                 self.state.painting_resolution = res;
             }
+            CanvasMessage::WireframeSupported(supported) => {
+                self.state.wireframe_supported = supported;
+            }
+            CanvasMessage::MissingCapabilities(capabilities) => {
+                self.state.missing_capabilities = capabilities;
+            }
+            CanvasMessage::TouchCount(count) => {
+                self.state.touch_count = count;
+            }
+            CanvasMessage::UniformLayout { slots, warnings } => {
+                for a_warning in &warnings {
+                    warn!("Uniform layout: {}", a_warning);
+                }
+                self.state.uniform_layout_slots = slots;
+                self.state.uniform_layout_warnings = warnings;
+            }
+            CanvasMessage::ReferenceCaptured(size) => {
+                self.state.reference_size = Some(size);
+                // The overlay may have been waiting for a reference to show.
+                self.transmitter.send(DashboardMessage::CompareChanged(
+                    self.state.compare_settings(),
+                ));
+            }
             CanvasMessage::MovieFrameStarted(buf, resolution, start_time) => {
-                if let Some(ref mut recorder) = self.recorder {
-                    recorder.add_frame(buf, resolution, start_time);
-                } else {
-                    panic!("Frame received for movie at timestamp {:?}, but no recorder is instantiated.", start_time);
+                self.movie_frame_in_flight = false;
+                let result = match self.recorder.as_mut() {
+                    Some(recorder) => recorder.add_frame(buf, resolution, start_time),
+                    // Frames still in flight when a recording fails are dropped.
+                    None => {
+                        warn!("Frame received for movie at timestamp {:?}, but no recorder is instantiated.", start_time);
+                        Ok(())
+                    }
+                };
+                if let Err(err) = result {
+                    self.recording_failed(err);
                 }
             }
         }
     }
 
+    /// Abandon the current recording and show why in the GUI.
+    /// Whether a painting can be written to `path`, showing why not in the painting options otherwise.
+    fn check_painting_writable(&mut self, path: &str) -> bool {
+        match utils::check_writable(std::path::Path::new(path)) {
+            Ok(()) => {
+                self.state.painting_error_msg = None;
+                true
+            }
+            Err(msg) => {
+                error!("Painting: {}", msg);
+                self.state.painting_error_msg = Some(msg);
+                false
+            }
+        }
+    }
+
+    fn recording_failed(&mut self, err: RecorderError) {
+        error!("Recording failed: {}", err);
+        self.state.recording_error_msg = Some(err.to_string());
+        self.state.recording_in_progress = false;
+        self.recorder = None;
+        self.movie_frame_in_flight = false;
+        self.vram.set("Recording", 0);
+    }
+
     /// Expected to be called every frame tick **before** [Self::render_dashboard()]
     /// Checks the receiver queue for any incoming messages, among other things.
     pub fn update(&mut self) {
-        self.device.poll(wgpu::Maintain::Poll);
+        self.device.poll(self.effective_maintain());
+        self.update_idle();
+        self.state.refresh_available_memory();
         let update_time = std::time::Instant::now();
         // First, check if we have received any messages and act accordingly
         loop {
             let msg_result = self.receiver.try_recv();
             match msg_result {
                 Ok(msg) => self.handle_message(msg),
-                Err(_) => break,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.canvas_disconnected();
+                    break;
+                }
             }
         }
-        for (_, uniform) in &self.state.gui_uniforms {
-            let err = self
-                .transmitter
-                .send(DashboardMessage::UniformUpdatedViaGUI(uniform.clone()));
-            match err {
-                Ok(_) => {}
-                Err(msg) => info!("Dashboard hung up: {}", msg),
+        // Apply saved uniform values once the Canvas has told us about a uniform with a matching name and type.
+        let gui_uniforms = &mut self.state.gui_uniforms;
+        let modified_uniforms = &mut self.state.modified_uniforms;
+        self.state.pending_shader_uniforms.retain(|saved| {
+            match gui_uniforms.get_mut(&saved.name) {
+                Some(current) if current.bytes.len() == saved.bytes.len() => {
+                    current.bytes = saved.bytes.clone();
+                    modified_uniforms.insert(saved.name.clone());
+                    false
+                }
+                Some(_) => {
+                    warn!(
+                        "Saved uniform {} has a different type, ignoring.",
+                        saved.name
+                    );
+                    false
+                }
+                None => true,
+            }
+        });
+        self.update_uniform_expressions();
+        self.update_batch_render();
+        self.update_playlist();
+        // Apply any uniform changes a timeline replay has due.
+        if let Some(replay) = self.state.timeline_replay.as_mut() {
+            let due = replay.poll();
+            if replay.finished() {
+                info!("Timeline replay finished.");
+                self.state.timeline_replay = None;
+            }
+            for uniform in due {
+                if !self.state.is_automatable(&uniform.name) {
+                    continue;
+                }
+                self.state.modified_uniforms.insert(uniform.name.clone());
+                self.state.unrecorded_uniforms.insert(uniform.name.clone());
+                self.state
+                    .gui_uniforms
+                    .insert(uniform.name.clone(), uniform);
             }
         }
+        self.update_uniform_scenes();
+        let mut recording_failure = None;
+        let mut recording_finished = false;
         if let Some(ref mut recorder) = self.recorder {
-            if self.state.movie_framerate < 1 {
-                panic!("Invalid framerate {} provided!", self.state.movie_framerate);
-            }
             // If we have not stopped, keep requesting frames on the selected FPS interval
             let mut frame_needed = self.state.recording_in_progress;
             if let Some(last_frame_time) = self.last_movie_frame_time.as_mut() {
-                let seconds_per_frame = 1.0 / (self.state.movie_framerate as f64);
+                // The recorder's own framerate, since the field may be edited, even to 0, while recording.
+                let seconds_per_frame = 1.0 / (recorder.framerate().max(1) as f64);
                 let delta = (update_time - *last_frame_time).as_secs_f64();
                 frame_needed = frame_needed && delta >= seconds_per_frame;
             }
-            if frame_needed && recorder.ready {
+            // A fixed-duration recording requests exactly its frame count, then stops once they're all encoded.
+            if let Some(frame_count) = self.state.movie_frame_count() {
+                frame_needed = frame_needed && self.movie_frames_requested < frame_count;
+                if self.state.recording_in_progress && recorder.frames_added >= frame_count {
+                    self.state.recording_in_progress = false;
+                    recording_failure = recorder.stop().err();
+                }
+            }
+            // Wait for the Canvas to capture the last frame requested, so requests can't pile up when it can't keep up.
+            if frame_needed && recorder.ready && !self.movie_frame_in_flight {
+                // Always the size the recorder was created with, even if the GUI resolution has been edited since.
                 self.transmitter
-                    .send(DashboardMessage::MovieRenderRequested(UIntVector2::new(
-                        self.state.recording_resolution.x as u32,
-                        self.state.recording_resolution.y as u32,
-                    )))
-                    .unwrap();
+                    .send(DashboardMessage::MovieRenderRequested(
+                        recorder.resolution(),
+                    ));
                 self.last_movie_frame_time = Some(update_time);
+                self.movie_frames_requested += 1;
+                self.movie_frame_in_flight = true;
             }
             // If finished, cleanup.
-            if recorder.poll() {
-                self.recorder.take().unwrap().finish();
+            match recorder.poll() {
+                Ok(true) => {
+                    let recorder = self.recorder.take().unwrap();
+                    // A dry run leaves nothing to reproduce.
+                    let dry_run = recorder.is_dry_run();
+                    recording_failure = recorder.finish().err();
+                    recording_finished = recording_failure.is_none() && !dry_run;
+                    self.vram.set("Recording", 0);
+                }
+                Ok(false) => {}
+                Err(err) => recording_failure = Some(err),
             }
         }
+        if let Some(err) = recording_failure {
+            self.recording_failed(err);
+        }
+        if recording_finished {
+            self.note_export(ExportKind::Movie);
+        }
+        // Lock the canvas to the recording size while recording, so window resizes can't change the composition.
+        // Otherwise to the exact resolution, if one is set.
+        let lock = match &self.recorder {
+            Some(recorder)
+                if self.state.recording_in_progress
+                    && self.state.lock_resolution_while_recording =>
+            {
+                Some(recorder.resolution())
+            }
+            _ => self.state.exact_canvas_resolution(),
+        };
+        let lock_size = lock.map(|resolution| (resolution.x, resolution.y));
+        if lock_size != self.canvas_resolution_lock
+            && self
+                .transmitter
+                .send(DashboardMessage::LockResolution(lock))
+        {
+            self.canvas_resolution_lock = lock_size;
+        }
 
-        // Ping Canvas with the currently set painting res
-        let err = self
-            .transmitter
-            .send(DashboardMessage::PaintingResolutionUpdated(
-                UIntVector2::new(
-                    self.state.painting_resolution.x as u32,
-                    self.state.painting_resolution.y as u32,
-                ),
-            ));
-        match err {
-            Err(msg) => info!("Canvas hung up: {}", msg),
-            Ok(_) => {}
+        // Tell the Canvas about the painting res, only when it has changed since last sent.
+        let painting_resolution = UIntVector2::new(
+            self.state.painting_resolution.x as u32,
+            self.state.painting_resolution.y as u32,
+        );
+        let painting_resolution_changed = match self.last_sent_painting_resolution {
+            Some(sent) => sent.x != painting_resolution.x || sent.y != painting_resolution.y,
+            None => true,
+        };
+        if painting_resolution_changed
+            && self
+                .transmitter
+                .send(DashboardMessage::PaintingResolutionUpdated(
+                    painting_resolution,
+                ))
+        {
+            self.last_sent_painting_resolution = Some(painting_resolution);
+        }
+        let painting_passes = self.state.export_painting_passes;
+        if self.last_sent_painting_passes != Some(painting_passes)
+            && self
+                .transmitter
+                .send(DashboardMessage::PaintingPassesUpdated(painting_passes))
+        {
+            self.last_sent_painting_passes = Some(painting_passes);
+        }
+        let painting_warm_up = self.state.painting_warm_up_frames;
+        if self.last_sent_painting_warm_up != Some(painting_warm_up)
+            && self
+                .transmitter
+                .send(DashboardMessage::PaintingWarmUpUpdated(painting_warm_up))
+        {
+            self.last_sent_painting_warm_up = Some(painting_warm_up);
+        }
+        // Recordings run at real time unless the time scale is explicitly applied to them, so captures aren't sped up by accident.
+        let time_scale =
+            match self.state.recording_in_progress && !self.state.time_scale_in_recordings {
+                true => 1.0,
+                false => self.state.time_scale,
+            };
+        if self.last_sent_time_scale != Some(time_scale)
+            && self
+                .transmitter
+                .send(DashboardMessage::TimeScaleChanged(time_scale))
+        {
+            self.last_sent_time_scale = Some(time_scale);
         }
     }
 
     pub fn post_render(&mut self) {
-        for (_name, uniform) in &self.state.gui_uniforms {
-            self.transmitter
-                .send(DashboardMessage::UniformUpdatedViaGUI(uniform.clone()))
-                .unwrap();
+        // Only changed uniforms are sent, the Canvas keeps the values of the rest.
+        for name in self.state.modified_uniforms.drain() {
+            if let Some(uniform) = self.state.gui_uniforms.get(&name) {
+                if !self.state.unrecorded_uniforms.contains(&name) {
+                    self.state.uniform_timeline.record(uniform);
+                }
+                self.transmitter
+                    .send(DashboardMessage::UniformUpdatedViaGUI(uniform.clone()));
+            }
         }
-        self.state.gui_uniforms.clear();
+        self.state.unrecorded_uniforms.clear();
         let now = std::time::Instant::now();
         self.state.last_render_time = (now - self.last_frame).as_secs_f64() * 1000.0;
         self.window.request_redraw();
@@ -0,0 +1,299 @@
+use super::{Dashboard, DashboardMessage};
+use crate::utils::random_u64;
+use log::{error, info, warn};
+use std::path::Path;
+use std::time::Instant;
+
+/// How long each shader of a playlist is shown unless its entry says otherwise, unit: seconds.
+const DEFAULT_ENTRY_DURATION: f32 = 60.0;
+
+/// Shader shown by a [Playlist] and for how long.
+#[derive(Debug, Clone)]
+pub struct PlaylistEntry {
+    pub shader: String,
+    /// Unit: seconds.
+    pub duration: f32,
+}
+
+/// What a [Playlist] is waiting for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlaylistPhase {
+    /// The Canvas to compile the current shader.
+    Compiling,
+    /// The current shader's duration to run out.
+    Showing,
+    /// The output to fade to the fade color before the `next` entry is opened, see [Playlist::move_to].
+    FadingOut {
+        next: usize,
+        back: bool,
+        started: Instant,
+    },
+}
+
+/// Shaders shown one after the other on a timer, e.g. for an unattended installation.
+pub struct Playlist {
+    pub entries: Vec<PlaylistEntry>,
+    /// Index of the shader being shown in [Self::entries].
+    pub current: usize,
+    pub phase: PlaylistPhase,
+    /// Pick the next entry at random instead of in order.
+    pub shuffle: bool,
+    /// Hold the current shader until resumed.
+    pub paused: bool,
+    /// Time to fade out to the master fade color and back in between shaders, unit: seconds. Cuts with 0.
+    pub fade: f32,
+    /// Time the current shader has been shown, not counting pauses, unit: seconds.
+    pub elapsed: f32,
+    last_tick: Instant,
+    /// Entries shown before the current one, most recent last, for [Dashboard::previous_playlist_entry].
+    history: Vec<usize>,
+    /// Failures in a row, so a playlist of shaders that all fail stops instead of cycling forever.
+    consecutive_failures: usize,
+    /// Shaders that failed to compile so far and why.
+    pub failures: Vec<(String, String)>,
+}
+
+impl Playlist {
+    /// Read a playlist file, e.g. `{"fade": 2, "shuffle": false, "shaders": [{"path": "a.frag", "duration": 120}, "b.frag"]}`.
+    /// Entries are a path, or a path with a duration in seconds. Relative paths are relative to the playlist file.
+    pub fn load(file: &str) -> Result<Playlist, String> {
+        let text = std::fs::read_to_string(file).map_err(|e| e.to_string())?;
+        let data = json::parse(&text).map_err(|e| e.to_string())?;
+        let directory = Path::new(file).parent().unwrap_or_else(|| Path::new(""));
+        let default_duration = data["duration"].as_f32().unwrap_or(DEFAULT_ENTRY_DURATION);
+        let mut entries = vec![];
+        for entry in data["shaders"].members() {
+            let (path, duration) = match entry.as_str() {
+                Some(path) => (path, default_duration),
+                None => match entry["path"].as_str() {
+                    Some(path) => (path, entry["duration"].as_f32().unwrap_or(default_duration)),
+                    None => return Err(format!("Playlist entry without a path: {}", entry.dump())),
+                },
+            };
+            entries.push(PlaylistEntry {
+                shader: directory.join(path).to_string_lossy().into_owned(),
+                duration: duration.max(1.0),
+            });
+        }
+        if entries.is_empty() {
+            return Err(format!("No shaders in playlist {}", file));
+        }
+        Ok(Playlist {
+            entries,
+            current: 0,
+            phase: PlaylistPhase::Compiling,
+            shuffle: data["shuffle"].as_bool().unwrap_or(false),
+            paused: false,
+            fade: data["fade"].as_f32().unwrap_or(0.0).max(0.0),
+            elapsed: 0.0,
+            last_tick: Instant::now(),
+            history: vec![],
+            consecutive_failures: 0,
+            failures: vec![],
+        })
+    }
+
+    /// Entry to show after the current one. Shuffling never picks the current entry again.
+    fn next_index(&self) -> usize {
+        let count = self.entries.len();
+        match self.shuffle && count > 1 {
+            true => (self.current + 1 + (random_u64() % (count as u64 - 1)) as usize) % count,
+            false => (self.current + 1) % count,
+        }
+    }
+
+    /// Make `next` the current entry. The one left is remembered for going back, unless this is going back.
+    fn move_to(&mut self, next: usize, back: bool) {
+        if !back {
+            self.history.push(self.current);
+        }
+        self.current = next;
+    }
+
+    /// Seconds left of the current shader's duration.
+    pub fn remaining(&self) -> f32 {
+        (self.entries[self.current].duration - self.elapsed).max(0.0)
+    }
+}
+
+impl Dashboard {
+    /// Start the playlist in `file`, e.g. one given at launch.
+    pub fn play_playlist_file(&mut self, file: &str) {
+        self.state.playlist_file = String::from(file);
+        self.start_playlist();
+    }
+
+    /// Load the playlist file and start showing its first shader. Replaces the running playlist, if any.
+    pub fn start_playlist(&mut self) {
+        let file = self.state.playlist_file.clone();
+        match Playlist::load(&file) {
+            Ok(playlist) => {
+                info!(
+                    "Playing {} shaders from playlist {}",
+                    playlist.entries.len(),
+                    file
+                );
+                self.state.playlist_error_msg = None;
+                self.state.playlist = Some(playlist);
+                self.open_playlist_entry();
+            }
+            Err(e) => {
+                error!("Playlist: {}", e);
+                self.state.playlist_error_msg = Some(e);
+            }
+        }
+    }
+
+    /// Stop cycling and keep showing the current shader.
+    pub fn stop_playlist(&mut self) {
+        if let Some(playlist) = self.state.playlist.take() {
+            info!("Playlist stopped.");
+            // Don't leave the output faded out mid-transition.
+            if matches!(
+                playlist.phase,
+                PlaylistPhase::FadingOut { .. } | PlaylistPhase::Compiling
+            ) {
+                self.fade_in_after_playlist_entry(playlist.fade / 2.0);
+            }
+        }
+    }
+
+    /// Show the next shader now, fading if the playlist does.
+    pub fn next_playlist_entry(&mut self) {
+        if let Some(playlist) = self.state.playlist.as_ref() {
+            let next = playlist.next_index();
+            self.switch_playlist_entry(next, false);
+        }
+    }
+
+    /// Show the shader shown before the current one now, or the one before it in the list.
+    pub fn previous_playlist_entry(&mut self) {
+        if let Some(playlist) = self.state.playlist.as_mut() {
+            let count = playlist.entries.len();
+            let previous = playlist
+                .history
+                .pop()
+                .unwrap_or((playlist.current + count - 1) % count);
+            self.switch_playlist_entry(previous, true);
+        }
+    }
+
+    /// Move to `next`, fading out first if the playlist fades. See [Playlist::move_to] for `back`.
+    fn switch_playlist_entry(&mut self, next: usize, back: bool) {
+        let fade = match self.state.playlist.as_mut() {
+            Some(playlist) if playlist.fade > 0.0 => {
+                playlist.phase = PlaylistPhase::FadingOut {
+                    next,
+                    back,
+                    started: Instant::now(),
+                };
+                playlist.fade
+            }
+            Some(playlist) => {
+                playlist.move_to(next, back);
+                return self.open_playlist_entry();
+            }
+            None => return,
+        };
+        self.transmitter.send(DashboardMessage::MasterFade {
+            target: 1.0,
+            color: self.state.master_fade_color,
+            duration: fade / 2.0,
+        });
+    }
+
+    /// Ask the Canvas to open the current entry's shader.
+    fn open_playlist_entry(&mut self) {
+        let shader = match self.state.playlist.as_mut() {
+            Some(playlist) => {
+                playlist.phase = PlaylistPhase::Compiling;
+                playlist.elapsed = 0.0;
+                playlist.entries[playlist.current].shader.clone()
+            }
+            None => return,
+        };
+        info!("Playlist: showing {}", shader);
+        self.transmitter.send(DashboardMessage::OpenShader(shader));
+    }
+
+    /// Fade back in over `duration` seconds after a playlist fade out, unless the output was faded out by hand.
+    fn fade_in_after_playlist_entry(&mut self, duration: f32) {
+        if self.state.master_fade_level <= 0.0 || self.state.master_faded_out {
+            return;
+        }
+        self.transmitter.send(DashboardMessage::MasterFade {
+            target: 0.0,
+            color: self.state.master_fade_color,
+            duration,
+        });
+    }
+
+    /// The Canvas finished compiling a shader. Start the current entry's timer if it compiled, skip it otherwise.
+    pub fn playlist_shader_compiled(&mut self, result: Result<(), String>) {
+        let playlist = match self.state.playlist.as_mut() {
+            Some(playlist) if playlist.phase == PlaylistPhase::Compiling => playlist,
+            _ => return,
+        };
+        match result {
+            Ok(_) => {
+                playlist.phase = PlaylistPhase::Showing;
+                playlist.last_tick = Instant::now();
+                playlist.consecutive_failures = 0;
+                if playlist.fade > 0.0 {
+                    let duration = playlist.fade / 2.0;
+                    self.fade_in_after_playlist_entry(duration);
+                }
+            }
+            Err(e) => {
+                let shader = playlist.entries[playlist.current].shader.clone();
+                warn!("Playlist: skipping {}, it failed to compile: {}", shader, e);
+                playlist.failures.push((shader, e));
+                playlist.consecutive_failures += 1;
+                if playlist.consecutive_failures >= playlist.entries.len() {
+                    error!("Playlist: every shader failed to compile, stopping.");
+                    self.state.playlist_error_msg = Some(String::from(
+                        "Every shader in the playlist failed to compile.",
+                    ));
+                    self.stop_playlist();
+                    return;
+                }
+                let next = playlist.next_index();
+                playlist.move_to(next, false);
+                self.open_playlist_entry();
+            }
+        }
+    }
+
+    /// Advance the current shader's timer and move on once it runs out. Called every frame.
+    pub fn update_playlist(&mut self) {
+        // A batch render opens shaders of its own.
+        if self.state.batch_render.is_some() {
+            return;
+        }
+        let playlist = match self.state.playlist.as_mut() {
+            Some(playlist) => playlist,
+            None => return,
+        };
+        let now = Instant::now();
+        let delta = now.duration_since(playlist.last_tick).as_secs_f32();
+        playlist.last_tick = now;
+        let phase = playlist.phase;
+        match phase {
+            PlaylistPhase::Showing if !playlist.paused => {
+                playlist.elapsed += delta;
+                if playlist.remaining() <= 0.0 {
+                    self.next_playlist_entry();
+                }
+            }
+            PlaylistPhase::FadingOut {
+                next,
+                back,
+                started,
+            } if now.duration_since(started).as_secs_f32() >= playlist.fade / 2.0 => {
+                playlist.move_to(next, back);
+                self.open_playlist_entry();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use super::{Dashboard, DashboardState};
+use log::info;
+
+/// Kind of export a [DashboardState::reproduce_command] is written for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportKind {
+    Painting,
+    Movie,
+}
+
+impl DashboardState {
+    /// One-line shell command relaunching Easel with the shader, files and export settings used for the last export.
+    /// Settings without a command line flag or environment variable, like seeds and the movie codec, follow as a comment.
+    pub fn reproduce_command(&self, kind: ExportKind) -> String {
+        let mut words = vec![];
+        let mut notes = vec![];
+        match kind {
+            ExportKind::Painting => {
+                words.push(format!(
+                    "EASEL_PAINTING_RESOLUTION={}x{}",
+                    self.painting_resolution.x, self.painting_resolution.y
+                ));
+                words.push(format!(
+                    "EASEL_PAINTING_FILENAME={}",
+                    shell_quote(&self.painting_filename)
+                ));
+                notes.push(format!("aa {}", self.aa_samples));
+            }
+            ExportKind::Movie => {
+                words.push(format!(
+                    "EASEL_RECORDING_RESOLUTION={}x{}",
+                    self.recording_resolution.x, self.recording_resolution.y
+                ));
+                words.push(format!("EASEL_MOVIE_FRAMERATE={}", self.movie_framerate));
+                words.push(format!(
+                    "EASEL_RECORDING_FILENAME={}",
+                    shell_quote(&self.recording_filename)
+                ));
+                notes.push(format!(
+                    "{} {}",
+                    self.movie_codec.name(),
+                    self.movie_container.extension()
+                ));
+            }
+        }
+        words.push(String::from("easel"));
+        words.extend(self.launch_args.iter().map(|arg| shell_quote(arg)));
+        notes.push(format!(
+            "noise {} seed {}",
+            self.noise_type.name(),
+            self.noise_seed
+        ));
+        let mut seeds: Vec<_> = self
+            .gui_uniforms
+            .values()
+            .filter(|u| u.is_seed())
+            .map(|u| format!("{} {}", u.name, u.value_json().dump()))
+            .collect();
+        seeds.sort();
+        notes.extend(seeds);
+        format!("{} # {}", words.join(" "), notes.join(", "))
+    }
+}
+
+impl Dashboard {
+    /// See [DashboardState::launch_args].
+    pub fn set_launch_args(&mut self, args: Vec<String>) {
+        self.state.launch_args = args;
+    }
+
+    /// Log and show the command reproducing the export that just finished.
+    pub fn note_export(&mut self, kind: ExportKind) {
+        let command = self.state.reproduce_command(kind);
+        info!("Reproduce with: {}", command);
+        self.state.last_export_command = Some(command);
+    }
+}
+
+/// Quote `word` for a POSIX shell, if it needs it.
+fn shell_quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+    match plain {
+        true => String::from(word),
+        false => format!("'{}'", word.replace('\'', "'\\''")),
+    }
+}
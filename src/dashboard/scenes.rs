@@ -0,0 +1,169 @@
+use super::{uniforms_to_json, Dashboard};
+use crate::uniforms::{load_uniforms_from_json, UserUniform, UserUniformType};
+use log::info;
+use std::collections::{BTreeMap, HashMap};
+
+/// Uniform values stored for one value of the scene selector.
+pub struct UniformScene {
+    pub name: String,
+    /// Values of every uniform but the selector, by name.
+    pub uniforms: HashMap<String, UserUniform>,
+}
+
+impl UniformScene {
+    /// A scene without stored values yet, named after its selector value until renamed.
+    pub fn new(value: i64) -> UniformScene {
+        UniformScene {
+            name: format!("Scene {}", value),
+            uniforms: HashMap::new(),
+        }
+    }
+}
+
+/// Uniform values kept per scene of a shader that switches between scenes with an integer or bool uniform, the
+/// selector. Switching the selector stores the values of the scene left and recalls those of the scene switched to.
+pub struct UniformScenes {
+    /// Name of the selector uniform, `None` to keep one set of values for all scenes.
+    pub selector: Option<String>,
+    /// Scenes visited so far, by selector value. The current scene's stored values are replaced when it is left.
+    pub scenes: BTreeMap<i64, UniformScene>,
+    /// Selector value the current uniform values belong to, `None` until the selector uniform is seen.
+    current: Option<i64>,
+}
+
+impl UniformScenes {
+    pub fn new() -> UniformScenes {
+        UniformScenes {
+            selector: None,
+            scenes: BTreeMap::new(),
+            current: None,
+        }
+    }
+
+    /// Select scenes with the named uniform, or stop with `None`. Scenes stored for another selector are forgotten.
+    pub fn set_selector(&mut self, selector: Option<String>) {
+        if selector != self.selector {
+            self.selector = selector;
+            self.scenes.clear();
+            self.current = None;
+        }
+    }
+
+    /// Selector value of the scene being shown.
+    pub fn current(&self) -> Option<i64> {
+        self.current
+    }
+
+    /// Selector, scene names and stored values, as saved in the shader settings.
+    /// The current scene is saved with the `uniforms` being shown rather than the values stored when it was last left.
+    pub fn to_json(&self, uniforms: &HashMap<String, UserUniform>) -> json::JsonValue {
+        let mut data = json::JsonValue::new_object();
+        data["selector"] = self.selector.as_deref().into();
+        let mut scenes = json::JsonValue::new_array();
+        for (value, scene) in &self.scenes {
+            let mut saved = json::JsonValue::new_object();
+            saved["value"] = (*value).into();
+            saved["name"] = scene.name.as_str().into();
+            saved["uniforms"] = match self.current == Some(*value) {
+                true => uniforms_to_json(uniforms.values().filter(|u| !self.is_selector(u))),
+                false => uniforms_to_json(scene.uniforms.values()),
+            };
+            scenes.push(saved).unwrap();
+        }
+        data["scenes"] = scenes;
+        data
+    }
+
+    /// Replace the selector and scenes with those saved by [Self::to_json].
+    pub fn load_json(&mut self, data: &json::JsonValue) {
+        self.selector = data["selector"].as_str().map(String::from);
+        self.scenes = data["scenes"]
+            .members()
+            .filter_map(|saved| {
+                let value = saved["value"].as_i64()?;
+                let mut scene = UniformScene::new(value);
+                if let Some(name) = saved["name"].as_str() {
+                    scene.name = String::from(name);
+                }
+                scene.uniforms = load_uniforms_from_json(saved)
+                    .into_iter()
+                    .map(|uniform| (uniform.name.clone(), uniform))
+                    .collect();
+                Some((value, scene))
+            })
+            .collect();
+        // The saved uniform values are the current scene's, whichever value the selector is restored to.
+        self.current = None;
+    }
+
+    fn is_selector(&self, uniform: &UserUniform) -> bool {
+        self.selector.as_ref() == Some(&uniform.name)
+    }
+}
+
+/// Value of a uniform that can select a scene, `None` for floats and vectors.
+pub fn scene_selector_value(uniform: &UserUniform) -> Option<i64> {
+    match uniform.inherent_type {
+        UserUniformType::Bool => uniform.get_value::<u32>().ok().map(|v| v as i64),
+        UserUniformType::UInt32
+        | UserUniformType::UInt64
+        | UserUniformType::Int32
+        | UserUniformType::Int64 => uniform.scalar_value().map(|v| v as i64),
+        _ => None,
+    }
+}
+
+impl Dashboard {
+    /// Store and recall uniform values when the scene selector changes, see [UniformScenes].
+    /// Called every frame after everything else that may change uniforms, so the selector's final value is used.
+    pub fn update_uniform_scenes(&mut self) {
+        let scenes = &mut self.state.uniform_scenes;
+        let uniforms = &mut self.state.gui_uniforms;
+        // The selector is missing while the shader reloads, the scene stays the same.
+        let value = match scenes.selector.as_ref().and_then(|name| uniforms.get(name)) {
+            Some(selector) => match scene_selector_value(selector) {
+                Some(value) => value,
+                None => return,
+            },
+            None => return,
+        };
+        let previous = scenes.current.replace(value);
+        // Listed from the start, so the scene can be named before it is left.
+        scenes
+            .scenes
+            .entry(value)
+            .or_insert_with(|| UniformScene::new(value));
+        let previous = match previous {
+            Some(previous) if previous != value => previous,
+            _ => return,
+        };
+        let stored = uniforms
+            .values()
+            .filter(|uniform| !scenes.is_selector(uniform))
+            .map(|uniform| (uniform.name.clone(), uniform.clone()))
+            .collect();
+        scenes
+            .scenes
+            .entry(previous)
+            .or_insert_with(|| UniformScene::new(previous))
+            .uniforms = stored;
+        // A scene not visited before keeps the values it is switched to with.
+        let scene = &scenes.scenes[&value];
+        if scene.uniforms.is_empty() {
+            return;
+        }
+        // Recalled values are sent to the canvas with the other modified uniforms in post_render.
+        for (name, saved) in &scene.uniforms {
+            match uniforms.get_mut(name) {
+                Some(current)
+                    if current.bytes.len() == saved.bytes.len() && current.bytes != saved.bytes =>
+                {
+                    current.bytes = saved.bytes.clone();
+                    self.state.modified_uniforms.insert(name.clone());
+                }
+                _ => {}
+            }
+        }
+        info!("Recalled the uniforms of {}.", scene.name);
+    }
+}
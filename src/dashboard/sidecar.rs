@@ -0,0 +1,184 @@
+use super::{DashboardState, Framing, AA_SAMPLE_COUNTS, MAX_PAINTING_WARM_UP_FRAMES};
+use crate::canvas::MirrorSettings;
+use crate::recording::FrameNumbering;
+use crate::texture::NoiseType;
+use crate::uniforms::{load_uniforms_from_json, UserUniform};
+use crate::vector::IntVector2;
+
+/// Path of the settings file kept next to a shader, e.g. `shader.frag.otium.json`.
+pub fn sidecar_path(shader_file: &str) -> String {
+    format!("{}.otium.json", shader_file)
+}
+
+/// Path of the uniform values saved automatically on exit, e.g. `shader.frag.otium.autosave.json`.
+pub fn autosave_path(shader_file: &str) -> String {
+    format!("{}.otium.autosave.json", shader_file)
+}
+
+impl DashboardState {
+    /// Apply settings saved by [Self::shader_settings_json]. Missing keys keep their current values.
+    /// Returns the saved uniform values, which the caller should apply once the Canvas has reported its uniforms.
+    pub fn load_shader_settings(&mut self, data: &json::JsonValue) -> Vec<UserUniform> {
+        if let Some(resolution) = resolution_from_json(&data["painting_resolution"]) {
+            self.painting_resolution = resolution;
+        }
+        if let Some(filename) = data["painting_filename"].as_str() {
+            self.painting_filename = String::from(filename);
+        }
+        if let Some(resolution) = resolution_from_json(&data["recording_resolution"]) {
+            self.recording_resolution = resolution;
+        }
+        if let Some(framerate) = data["movie_framerate"].as_i32().filter(|fps| *fps > 0) {
+            self.movie_framerate = framerate;
+        }
+        if let Some(fixed_duration) = data["movie_fixed_duration"].as_bool() {
+            self.movie_fixed_duration = fixed_duration;
+        }
+        if let Some(duration) = data["movie_duration"].as_f64() {
+            self.movie_duration = duration;
+        }
+        if let Some(filename) = data["recording_filename"].as_str() {
+            self.recording_filename = String::from(filename);
+        }
+        if data.has_key("sequence_numbering") {
+            self.sequence_numbering = FrameNumbering::from_json(&data["sequence_numbering"]);
+        }
+        // Set directly, the saved filenames already follow the saved name.
+        if let Some(name) = data["piece_name"].as_str() {
+            self.piece_name = String::from(name);
+        }
+        if let Some(in_title) = data["piece_name_in_title"].as_bool() {
+            self.piece_name_in_title = in_title;
+        }
+        if let Some(all_passes) = data["painting_passes"].as_bool() {
+            self.export_painting_passes = all_passes;
+        }
+        if let Some(frames) = data["painting_warm_up_frames"].as_u32() {
+            self.painting_warm_up_frames = frames.min(MAX_PAINTING_WARM_UP_FRAMES);
+        }
+        if let Some(samples) = data["aa_samples"].as_u32() {
+            // Only the factors the GUI offers, the shader is compiled for one of them.
+            self.aa_samples = match AA_SAMPLE_COUNTS.contains(&samples) {
+                true => samples,
+                false => AA_SAMPLE_COUNTS[0],
+            };
+        }
+        if let Some(noise_type) = data["noise_type"].as_str().and_then(NoiseType::from_name) {
+            self.noise_type = noise_type;
+        }
+        if let Some(seed) = data["noise_seed"].as_u32() {
+            self.noise_seed = seed;
+        }
+        if data.has_key("framing") {
+            self.framing = data["framing"].as_str().and_then(Framing::from_name);
+        }
+        if data.has_key("exact_resolution") {
+            let exact_resolution = resolution_from_json(&data["exact_resolution"]);
+            self.exact_resolution_enabled = exact_resolution.is_some();
+            if let Some(resolution) = exact_resolution {
+                self.exact_resolution = resolution;
+            }
+        }
+        // Reopen the saved mirror windows, unless some are open already.
+        if self.mirror_outputs.is_empty() {
+            self.pending_mirror_settings = data["mirrors"]
+                .members()
+                .map(MirrorSettings::from_json)
+                .collect();
+            self.mirror_windows_requested = self.pending_mirror_settings.len();
+        }
+        if data.has_key("uniform_offsets") {
+            self.uniform_pins = data["uniform_offsets"]
+                .entries()
+                .filter_map(|(name, offset)| Some((String::from(name), offset.as_usize()?)))
+                .collect();
+        }
+        if data.has_key("expressions") {
+            self.uniform_expressions.load_json(&data["expressions"]);
+        }
+        if data.has_key("uniform_order") {
+            self.uniform_display_order = data["uniform_order"]
+                .members()
+                .filter_map(|name| name.as_str().map(String::from))
+                .collect();
+        }
+        if data.has_key("scenes") {
+            self.uniform_scenes.load_json(&data["scenes"]);
+        }
+        load_uniforms_from_json(data).into_iter().collect()
+    }
+
+    /// Current per-shader settings: the piece name, painting and recording options, uniform values, expressions, scenes,
+    /// control order and pinned offsets, noise and mirror outputs.
+    /// Uniforms are stored in the same format as the uniforms JSON file.
+    pub fn shader_settings_json(&self) -> json::JsonValue {
+        let mut data = json::JsonValue::new_object();
+        data["painting_resolution"] = resolution_to_json(&self.painting_resolution);
+        data["painting_filename"] = self.painting_filename.as_str().into();
+        data["recording_resolution"] = resolution_to_json(&self.recording_resolution);
+        data["movie_framerate"] = self.movie_framerate.into();
+        data["movie_fixed_duration"] = self.movie_fixed_duration.into();
+        data["movie_duration"] = self.movie_duration.into();
+        data["recording_filename"] = self.recording_filename.as_str().into();
+        data["sequence_numbering"] = self.sequence_numbering.to_json();
+        data["piece_name"] = self.piece_name.as_str().into();
+        data["piece_name_in_title"] = self.piece_name_in_title.into();
+        data["painting_passes"] = self.export_painting_passes.into();
+        data["painting_warm_up_frames"] = self.painting_warm_up_frames.into();
+        data["aa_samples"] = self.aa_samples.into();
+        data["framing"] = self.framing.map(|f| f.name()).into();
+        data["exact_resolution"] = match self.exact_resolution_enabled {
+            true => resolution_to_json(&self.exact_resolution),
+            false => json::JsonValue::Null,
+        };
+        data["noise_type"] = self.noise_type.name().into();
+        data["noise_seed"] = self.noise_seed.into();
+        data["uniforms"] = self.uniforms_json();
+        data["expressions"] = self.uniform_expressions.to_json();
+        data["scenes"] = self.uniform_scenes.to_json(&self.gui_uniforms);
+        data["uniform_order"] = self
+            .uniform_display_order
+            .iter()
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>()
+            .into();
+        let mut offsets = json::JsonValue::new_object();
+        for (name, offset) in &self.uniform_pins {
+            offsets[name.as_str()] = (*offset).into();
+        }
+        data["uniform_offsets"] = offsets;
+        data["mirrors"] = self
+            .mirror_outputs
+            .iter()
+            .map(|(_, settings)| settings.to_json())
+            .collect::<Vec<_>>()
+            .into();
+        data
+    }
+
+    /// Current uniform values, in the same format as the uniforms JSON file.
+    pub fn uniforms_json(&self) -> json::JsonValue {
+        uniforms_to_json(self.gui_uniforms.values())
+    }
+}
+
+/// Uniform values in the same format as the uniforms JSON file.
+pub fn uniforms_to_json<'a>(uniforms: impl Iterator<Item = &'a UserUniform>) -> json::JsonValue {
+    let mut data = json::JsonValue::new_object();
+    for uniform in uniforms {
+        let mut pair = json::JsonValue::new_array();
+        pair.push(uniform.type_name()).unwrap();
+        pair.push(uniform.value_json()).unwrap();
+        data[uniform.name.as_str()] = pair;
+    }
+    data
+}
+
+fn resolution_to_json(resolution: &IntVector2) -> json::JsonValue {
+    json::array![resolution.x, resolution.y]
+}
+
+/// Read a `[width, height]` array, as written to the shader settings.
+pub fn resolution_from_json(data: &json::JsonValue) -> Option<IntVector2> {
+    Some(IntVector2::new(data[0].as_i32()?, data[1].as_i32()?))
+}
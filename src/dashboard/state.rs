@@ -1,33 +1,430 @@
+use super::{
+    fit_aspect, BatchRender, DashboardTheme, Framing, MaintainStrategy, Playlist, SessionDefaults,
+    TimelineReplay, UniformExpressions, UniformScenes, UniformTimeline,
+};
 use crate::{
-    uniforms::UserUniform,
-    utils::WriteFinished,
-    vector::{IntVector2, Vector2},
+    canvas::{
+        CompareMode, CompareSettings, DebugView, Histogram, MirrorSettings, StereoLayout,
+        StereoSettings,
+    },
+    layout::UniformSlot,
+    overlay::{OverlayCorner, TextOverlay},
+    recording::{FrameNumbering, MovieCodec, MovieContainer},
+    skeletons::ShaderTemplate,
+    texture::NoiseType,
+    uniforms::{RampUniform, UserUniform},
+    utils::{self, WriteFinished},
+    vector::{IntVector2, UIntVector2, Vector2},
+};
+use log::warn;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::{
+    sync::mpsc::Receiver,
+    time::{Duration, Instant},
 };
-use std::collections::HashMap;
-use std::{sync::mpsc::Receiver, usize};
+use winit::window::WindowId;
+
+/// Basenames of the painting and recording filenames until a piece is named.
+const DEFAULT_PAINTING_BASENAME: &str = "Painting";
+const DEFAULT_RECORDING_BASENAME: &str = "Muybridge";
+
+/// Relative difference between the recording's and the canvas' aspect ratios beyond which recordings are framed visibly
+/// differently from the canvas window.
+const ASPECT_TOLERANCE: f32 = 0.01;
+
+/// Most warm-up frames a painting renders, which all go into a single submission.
+pub const MAX_PAINTING_WARM_UP_FRAMES: u32 = 1000;
+
+/// Supersampling factors offered for paintings, the first one is the default.
+pub const AA_SAMPLE_COUNTS: [u32; 3] = [1, 2, 4];
+
+/// Bytes per pixel of [crate::canvas::PAINTING_TEXTURE_FORMAT].
+const PAINTING_BYTES_PER_PIXEL: u64 = 8;
+
+/// How often [DashboardState::available_memory] is looked up again.
+const AVAILABLE_MEMORY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Rough cost of rendering a painting, shown before the user commits to it.
+pub struct PaintingEstimate {
+    /// Painting + post-process textures and the staging buffer.
+    pub gpu_bytes: u64,
+    /// Transcoded 16-bit pixel data held before the TIFF is written.
+    pub cpu_bytes: u64,
+    /// Unit: seconds
+    pub duration: f64,
+}
+
+/// Dropped Dashboard frames are logged at most once per [DroppedFrameLog::INTERVAL], as a count.
+pub struct DroppedFrameLog {
+    /// Frames dropped since the last message.
+    pub pending: usize,
+    pub last_error: Option<wgpu::SwapChainError>,
+    pub last_logged: Option<Instant>,
+}
+
+impl DroppedFrameLog {
+    pub const INTERVAL: Duration = Duration::from_secs(1);
+
+    pub fn new() -> DroppedFrameLog {
+        DroppedFrameLog {
+            pending: 0,
+            last_error: None,
+            last_logged: None,
+        }
+    }
+
+    /// Count a dropped frame, logging the count if a message is due. Nothing is logged when `enabled` is false.
+    pub fn record(&mut self, error: wgpu::SwapChainError, enabled: bool) {
+        self.pending += 1;
+        self.last_error = Some(error);
+        self.flush(enabled);
+    }
+
+    /// Log the frames dropped since the last message, if any and a message is due.
+    pub fn flush(&mut self, enabled: bool) {
+        if self.pending == 0 {
+            return;
+        }
+        let now = Instant::now();
+        if let Some(last) = self.last_logged {
+            if now - last < Self::INTERVAL {
+                return;
+            }
+        }
+        let error = self.last_error.take();
+        if let (true, Some(error)) = (enabled, error) {
+            match self.pending {
+                1 => warn!("GUI Dropped frame: {:?}", error),
+                count => warn!(
+                    "GUI Dropped {} frames in the last second, last error: {:?}",
+                    count, error
+                ),
+            }
+        }
+        self.pending = 0;
+        self.last_logged = Some(now);
+    }
+}
+
+/// Rolling rates of the frames the Canvas presented and of the ones it dropped, over the last
+/// [CanvasFrameRate::WINDOW]. Separate from the Dashboard's own frame timing, so a slow shader can be told apart from
+/// presentation hitches.
+pub struct CanvasFrameRate {
+    frames: VecDeque<Instant>,
+    dropped: VecDeque<Instant>,
+}
+
+impl CanvasFrameRate {
+    pub const WINDOW: Duration = Duration::from_secs(1);
+
+    pub fn new() -> CanvasFrameRate {
+        CanvasFrameRate {
+            frames: VecDeque::new(),
+            dropped: VecDeque::new(),
+        }
+    }
+
+    /// Count a frame the Canvas finished at `at`.
+    pub fn frame(&mut self, at: Instant) {
+        self.frames.push_back(at);
+        Self::prune(&mut self.frames, at);
+    }
+
+    /// Count a frame the Canvas dropped at `at` because it got no swap chain frame.
+    pub fn dropped(&mut self, at: Instant) {
+        self.dropped.push_back(at);
+        Self::prune(&mut self.dropped, at);
+    }
+
+    /// Frames per second over the last [Self::WINDOW].
+    pub fn fps(&self, now: Instant) -> f64 {
+        Self::rate(&self.frames, now)
+    }
+
+    /// Dropped frames per second over the last [Self::WINDOW].
+    pub fn dropped_per_second(&self, now: Instant) -> f64 {
+        Self::rate(&self.dropped, now)
+    }
+
+    fn prune(times: &mut VecDeque<Instant>, now: Instant) {
+        while matches!(times.front(), Some(t) if now.saturating_duration_since(*t) > Self::WINDOW) {
+            times.pop_front();
+        }
+    }
+
+    fn rate(times: &VecDeque<Instant>, now: Instant) -> f64 {
+        let recent = times
+            .iter()
+            .filter(|t| now.saturating_duration_since(**t) <= Self::WINDOW)
+            .count();
+        recent as f64 / Self::WINDOW.as_secs_f64()
+    }
+}
+
 /// Struct containing information the GUI is displaying and interacting with.
 pub struct DashboardState {
     pub last_render_time: f64,
     pub frame_num: usize,
+    /// Frames of either window that timed out waiting for the swap chain.
     pub frame_timeout_count: usize,
+    pub dropped_frames: DroppedFrameLog,
+    /// Log dropped Dashboard frames. They are still counted when muted.
+    pub log_dropped_frames: bool,
+    /// Minutes without input before the canvas goes idle, 0 disables it. See [super::Dashboard::update_idle].
+    pub idle_timeout: f32,
+    /// See [crate::canvas::IdleSettings].
+    pub idle_framerate: f32,
+    pub idle_dim: f32,
+    /// Whether the canvas was last sent idle.
+    pub idle: bool,
+    pub last_user_input: Instant,
     pub mouse_pos: Vector2,
+    /// See [super::DashboardMessage::MouseSmoothingChanged].
+    pub mouse_smoothing: f32,
+    /// Number of points touching the canvas window.
+    pub touch_count: usize,
     pub render_window_size: IntVector2,
     pub paused: bool,
+    /// Whether the Dashboard window has keyboard focus, see [MaintainStrategy::Auto].
+    pub dashboard_focused: bool,
+    pub maintain_strategy: MaintainStrategy,
+    pub theme: DashboardTheme,
+    /// Time and frame count are frozen, but the canvas still renders.
+    pub time_frozen: bool,
+    /// See [super::DashboardMessage::HoldTime].
+    pub time_held: bool,
+    /// How many times as fast as real time the time uniform advances, from 0.1 to 10.
+    pub time_scale: f32,
+    /// Whether recordings use [Self::time_scale] too, rather than real time.
+    pub time_scale_in_recordings: bool,
+    pub jitter_enabled: bool,
+    pub jitter_frozen: bool,
+    pub accumulation_enabled: bool,
+    pub histogram_enabled: bool,
+    /// Most recent histogram received from the Canvas.
+    pub histogram: Option<Histogram>,
+    /// Show a live thumbnail of the canvas output in the Dashboard.
+    pub preview_enabled: bool,
+    /// Longer edge of the thumbnail the Canvas downscales its output to for the preview, unit: pixels.
+    pub preview_size: u32,
+    /// Texture of the most recent thumbnail and its size.
+    pub thumbnail: Option<(imgui::TextureId, UIntVector2)>,
+    /// Frames averaged so far, as last reported by the canvas.
+    pub accumulation_samples: u32,
+    pub debug_view: DebugView,
+    /// Whether the canvas window shows a checkerboard behind transparent areas.
+    pub checkerboard_enabled: bool,
+    /// See [super::DashboardMessage::HoldFrameOnError].
+    pub hold_frame_on_error: bool,
+    /// Offsets of user uniforms pinned in the shader settings, see [super::DashboardMessage::UniformOffsetsPinned].
+    pub uniform_pins: HashMap<String, usize>,
+    /// Uniform buffer layout last reported by the Canvas.
+    pub uniform_layout_slots: Vec<UniformSlot>,
+    pub uniform_layout_warnings: Vec<String>,
+    /// Mirroring of the canvas window, see [super::DashboardMessage::FlipOutput].
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+    pub flip_captures: bool,
+    /// Whether paintings and movies are captured in stereo, see [super::DashboardMessage::StereoChanged].
+    pub stereo_enabled: bool,
+    pub stereo_layout: StereoLayout,
+    /// See [StereoSettings::interaxial].
+    pub stereo_interaxial: f32,
+    /// Whether the canvas device can show [DebugView::Wireframe].
+    pub wireframe_supported: bool,
+    /// Functionality unavailable on the canvas device, see [crate::utils::OPTIONAL_FEATURES].
+    pub missing_capabilities: Vec<&'static str>,
+    /// Scalar uniform swept by [super::Action::CreateVariationSheet].
+    pub variation_uniform: Option<String>,
+    /// Number of cells in the variation sheet, including the minimum and maximum.
+    pub variation_count: i32,
+    pub variation_min: f32,
+    pub variation_max: f32,
+    /// Width of each cell. The height follows the painting aspect ratio.
+    pub variation_cell_width: i32,
+    /// Noise texture bound after the ramps. The same type and seed always give the same noise.
+    pub noise_type: NoiseType,
+    pub noise_seed: u32,
+    /// Size of the reference frame the canvas holds for comparison, if any.
+    pub reference_size: Option<UIntVector2>,
+    /// Whether the reference frame is overlaid on the canvas window.
+    pub compare_enabled: bool,
+    pub compare_mode: CompareMode,
+    /// See [CompareSettings::amount].
+    pub compare_amount: f32,
     pub show_titlebar: bool,
+    /// See [super::DashboardMessage::BorderlessMode].
+    pub borderless: bool,
+    /// Whether the last master fade request was towards the fade color.
+    pub master_faded_out: bool,
+    /// As last reported by the canvas.
+    pub master_fade_level: f32,
+    pub master_fade_color: [f32; 3],
+    /// unit: seconds.
+    pub master_fade_duration: f32,
+    /// Height the Controls window needs to show everything currently expanded, unit: logical pixels.
+    pub controls_content_height: f32,
+    /// Windows the canvas is mirrored to, see [crate::canvas::MirrorOutput].
+    pub mirror_outputs: Vec<(WindowId, MirrorSettings)>,
+    /// Mirror windows asked for that the main thread has not built yet.
+    pub mirror_windows_requested: usize,
+    /// Settings loaded from the shader settings, given to mirror windows as they are built.
+    pub pending_mirror_settings: Vec<MirrorSettings>,
     pub painting_resolution: IntVector2,
+    /// Aspect preset the canvas is letterboxed to, `None` fills the window.
+    pub framing: Option<Framing>,
+    /// Render the canvas at [Self::exact_resolution] whatever the window size and scale factor, see [Self::canvas_lock].
+    pub exact_resolution_enabled: bool,
+    /// Unit: physical pixels.
+    pub exact_resolution: IntVector2,
+    /// Configured through the environment, applied before any shader settings.
+    pub session_defaults: SessionDefaults,
+    /// Largest painting or recording dimension the canvas device supports, unit: pixels.
+    pub max_texture_dimension: u32,
     pub recording_resolution: IntVector2,
     pub painting_filename: String,
     pub recording_filename: String,
+    /// Human name of the piece, e.g. for installations, empty for none. Written into the metadata of exports and
+    /// used as the basename of the export filenames, see [Self::set_piece_name].
+    pub piece_name: String,
+    /// Title the canvas window with [Self::piece_name].
+    pub piece_name_in_title: bool,
     pub recording_in_progress: bool,
+    /// Render and discard one frame before recording starts, to avoid a black first frame.
+    pub recording_warm_up: bool,
+    /// Repeat the last frame written in place of frames that changed less than [Self::change_threshold].
+    pub record_changes_only: bool,
+    /// Initialize a recorder that renders and counts frames but writes nothing, see [crate::recording::Recorder::dry_run].
+    pub recording_dry_run: bool,
+    /// Unit: 8-bit levels
+    pub change_threshold: f32,
+    pub movie_codec: MovieCodec,
+    pub movie_container: MovieContainer,
+    /// Names of the files of a [MovieContainer::PngSequence].
+    pub sequence_numbering: FrameNumbering,
     /// Unit: seconds
     pub movie_framerate: i32,
+    /// Render the canvas at the recording resolution while recording, letterboxed in its window.
+    pub lock_resolution_while_recording: bool,
+    /// Stop recording automatically after [Self::movie_duration].
+    pub movie_fixed_duration: bool,
+    /// Requested length of a fixed-duration recording, unit: seconds.
+    /// Recorded as [Self::movie_frame_count] frames, so the movie may be slightly shorter or longer.
+    pub movie_duration: f64,
     /// Only available on macOS.
     pub open_painting_externally: bool,
     pub pause_while_painting: bool,
+    /// Write the output of the shader and each post-process pass as pages of the painting TIFF.
+    pub export_painting_passes: bool,
+    /// Frames rendered and averaged into each painting so shaders can settle, at most [MAX_PAINTING_WARM_UP_FRAMES].
+    pub painting_warm_up_frames: u32,
+    /// Samples per axis for shader anti-aliasing and painting supersampling.
+    pub aa_samples: u32,
+    /// Value of `u_time` for [super::Action::CreatePaintingAtTime], unit: seconds.
+    pub capture_time: f32,
+    /// Burn the uniform values into paintings. Off on every launch, so clean exports can't pick it up by accident.
+    pub burn_in_uniforms: bool,
+    /// Size and placement of the burned in values, see [crate::overlay::TextOverlay].
+    pub burn_in_scale: u32,
+    pub burn_in_corner: OverlayCorner,
+    /// Set from requesting a painting or variation sheet until the Canvas reports it started rendering.
+    pub painting_requested: bool,
+    /// Why the last painting or variation sheet could not be written, if it couldn't.
+    pub painting_error_msg: Option<String>,
+    /// Set while the Canvas renders a painting, until it hands the result over for writing.
+    pub painting_rendering: bool,
     pub painting_progress_receiver: Option<Receiver<WriteFinished>>,
     pub shader_compilation_error_msg: Option<String>,
+    /// Why the last recording failed, shown until the recorder is initialized again.
+    pub recording_error_msg: Option<String>,
+    /// Path typed into the input image field.
+    pub input_image_file: String,
+    /// Path of the input image currently bound, if any.
+    pub input_image_loaded: Option<String>,
+    pub input_image_error_msg: Option<String>,
+    /// Path typed into the open shader field, see [super::Action::OpenShader]. Follows the shader that's open.
+    pub open_shader_file: String,
+    /// Path typed into the new shader field, see [super::Action::NewShaderFromTemplate].
+    pub new_shader_file: String,
+    pub new_shader_template: ShaderTemplate,
+    /// Why the last shader could not be created, shown until one is.
+    pub new_shader_error_msg: Option<String>,
+    /// Directory of shaders typed into the batch field, see [super::Action::StartBatchRender].
+    pub batch_directory: String,
+    /// Directory the batch's stills are written to.
+    pub batch_output_directory: String,
+    pub batch_render: Option<BatchRender>,
+    /// Why the last batch could not start, shown until one does.
+    pub batch_error_msg: Option<String>,
+    /// Shaders that failed in the last finished batch and why.
+    pub batch_failures: Vec<(String, String)>,
+    /// Playlist file typed into the playlist field, see [super::Action::StartPlaylist].
+    pub playlist_file: String,
+    pub playlist: Option<Playlist>,
+    /// Why the last playlist could not start or was stopped, shown until one starts.
+    pub playlist_error_msg: Option<String>,
     pub painting_start_time: Option<std::time::Instant>,
+    /// When the current recording was started, to estimate how long it has left.
+    pub recording_start_time: Option<Instant>,
+    /// Number of pixels in the painting currently being rendered.
+    pub painting_pixels: u64,
+    /// Measured from the last finished painting, used to estimate the next one.
+    pub painting_seconds_per_pixel: Option<f64>,
+    /// Memory a painting can use, see [crate::utils::available_memory]. `None` where it can't be told.
+    pub available_memory: Option<u64>,
+    available_memory_checked: Option<Instant>,
+    /// Time between the two most recent canvas frames, unit: milliseconds.
+    pub canvas_frame_time: f64,
+    pub last_frame_step: Option<Instant>,
+    pub canvas_frame_rate: CanvasFrameRate,
     pub gui_uniforms: HashMap<String, UserUniform>,
+    /// Names of uniforms in the order their controls are shown, see [Self::ordered_uniform_names].
+    /// Only the GUI follows it, the Canvas lays uniforms out in the order of the uniforms JSON file.
+    pub uniform_display_order: Vec<String>,
+    /// Names of the [Self::gui_uniforms] changed on the Dashboard side since they were last sent to the Canvas.
+    pub modified_uniforms: HashSet<String>,
+    /// Those of [Self::modified_uniforms] written by expressions or a timeline replay rather than by the user,
+    /// which the [Self::uniform_timeline] leaves out.
+    pub unrecorded_uniforms: HashSet<String>,
+    /// Uniform values stored for quick A/B comparison. Swapped by name with [Self::gui_uniforms].
+    pub uniform_snapshot: Option<HashMap<String, UserUniform>>,
+    /// Whether [Self::gui_uniforms] currently holds the snapshot values rather than the ones being tweaked.
+    pub showing_uniform_snapshot: bool,
+    /// Whether a Canvas is running, see [super::Dashboard::canvas_disconnected].
+    pub canvas_connected: bool,
+    pub canvas_relaunch_requested: bool,
+    /// Seed the last randomization of the uniforms was drawn from, see [super::Dashboard::randomize_uniforms].
+    pub randomize_seed: u32,
+    /// Expressions animating uniforms, applied every frame before a timeline replay.
+    pub uniform_expressions: UniformExpressions,
+    /// Uniform values kept per scene of the shader, recalled when its scene selector uniform changes.
+    pub uniform_scenes: UniformScenes,
+    /// Log of uniform edits made this session.
+    pub uniform_timeline: UniformTimeline,
+    /// Replay of a timeline in progress, if any.
+    pub timeline_replay: Option<TimelineReplay>,
+    /// Exported timelines are written to this name with a .csv or .json extension.
+    pub timeline_filename: String,
+    pub command_palette_open: bool,
+    pub command_palette_query: String,
+    /// Focus the palette's text field on the next frame.
+    pub command_palette_grab_focus: bool,
+    /// Arguments Easel was launched with, repeated in [Self::reproduce_command].
+    pub launch_args: Vec<String>,
+    /// Command reproducing the last painting or movie exported, see [Self::reproduce_command].
+    pub last_export_command: Option<String>,
+    /// Set by `--dev`. Offers the imgui demo and metrics windows, for developing new Dashboard widgets.
+    pub developer_mode: bool,
+    pub show_imgui_demo: bool,
+    pub show_imgui_metrics: bool,
+    /// Shader being rendered, as reported by the Canvas. Its settings sidecar is saved next to it.
+    pub shader_file: Option<String>,
+    /// Uniform values from the settings sidecar that haven't been matched to a Canvas uniform yet.
+    pub pending_shader_uniforms: Vec<UserUniform>,
+    /// Save uniform values on exit and restore them when the same shader is opened again.
+    pub autosave_uniforms: bool,
+    /// Color ramps in binding order.
+    pub gui_ramps: Vec<RampUniform>,
 }
 
 impl DashboardState {
@@ -36,22 +433,453 @@ impl DashboardState {
             last_render_time: 0.0,
             frame_num: 0,
             frame_timeout_count: 0,
+            dropped_frames: DroppedFrameLog::new(),
+            log_dropped_frames: true,
+            idle_timeout: 0.0,
+            idle_framerate: 5.0,
+            idle_dim: 0.5,
+            idle: false,
+            last_user_input: Instant::now(),
             mouse_pos: Vector2::zero(),
+            mouse_smoothing: 0.0,
+            touch_count: 0,
             render_window_size: IntVector2::zero(),
             paused: false,
+            dashboard_focused: true,
+            maintain_strategy: MaintainStrategy::Auto,
+            theme: DashboardTheme::Pastel,
+            time_frozen: false,
+            time_held: false,
+            time_scale: 1.0,
+            time_scale_in_recordings: false,
+            jitter_enabled: false,
+            jitter_frozen: false,
+            accumulation_enabled: false,
+            histogram_enabled: false,
+            checkerboard_enabled: false,
+            hold_frame_on_error: true,
+            uniform_pins: HashMap::new(),
+            uniform_layout_slots: vec![],
+            uniform_layout_warnings: vec![],
+            flip_horizontal: false,
+            flip_vertical: false,
+            flip_captures: false,
+            stereo_enabled: false,
+            stereo_layout: StereoLayout::SideBySide,
+            stereo_interaxial: 0.064,
+            debug_view: DebugView::Off,
+            wireframe_supported: false,
+            missing_capabilities: vec![],
+            variation_uniform: None,
+            variation_count: 9,
+            variation_min: 0.0,
+            variation_max: 1.0,
+            variation_cell_width: 256,
+            noise_type: NoiseType::White,
+            noise_seed: 0,
+            reference_size: None,
+            compare_enabled: false,
+            compare_mode: CompareMode::Wipe,
+            compare_amount: 0.5,
+            histogram: None,
+            preview_enabled: false,
+            preview_size: 256,
+            thumbnail: None,
+            accumulation_samples: 0,
             show_titlebar: true,
+            borderless: false,
+            master_faded_out: false,
+            master_fade_level: 0.0,
+            master_fade_color: [0.0, 0.0, 0.0],
+            master_fade_duration: 3.0,
+            controls_content_height: 0.0,
+            mirror_outputs: Vec::new(),
+            mirror_windows_requested: 0,
+            pending_mirror_settings: Vec::new(),
             painting_resolution: IntVector2::zero(),
+            framing: None,
+            exact_resolution_enabled: false,
+            exact_resolution: IntVector2::new(1920, 1080),
+            session_defaults: SessionDefaults::default(),
+            max_texture_dimension: wgpu::Limits::default().max_texture_dimension_2d,
             recording_resolution: IntVector2::new(1024, 1024),
-            painting_filename: String::from("Painting"),
-            recording_filename: String::from("Muybridge"),
+            painting_filename: String::from(DEFAULT_PAINTING_BASENAME),
+            recording_filename: String::from(DEFAULT_RECORDING_BASENAME),
+            piece_name: String::new(),
+            piece_name_in_title: false,
             recording_in_progress: false,
+            recording_warm_up: true,
+            record_changes_only: false,
+            recording_dry_run: false,
+            change_threshold: 0.5,
+            movie_codec: MovieCodec::Hevc,
+            movie_container: MovieContainer::Mp4,
+            sequence_numbering: FrameNumbering::default(),
             movie_framerate: 60,
+            lock_resolution_while_recording: true,
+            movie_fixed_duration: false,
+            movie_duration: 10.0,
             open_painting_externally: true,
             pause_while_painting: true,
+            export_painting_passes: false,
+            painting_warm_up_frames: 0,
+            aa_samples: AA_SAMPLE_COUNTS[0],
+            capture_time: 0.0,
+            burn_in_uniforms: false,
+            burn_in_scale: 2,
+            burn_in_corner: OverlayCorner::BottomLeft,
+            painting_requested: false,
+            painting_error_msg: None,
+            painting_rendering: false,
             painting_progress_receiver: None,
             shader_compilation_error_msg: None,
+            recording_error_msg: None,
+            input_image_file: String::new(),
+            input_image_loaded: None,
+            input_image_error_msg: None,
+            open_shader_file: String::new(),
+            new_shader_file: String::from("new-shader.frag"),
+            new_shader_template: ShaderTemplate::Plain,
+            new_shader_error_msg: None,
+            batch_directory: String::from("shaders"),
+            batch_output_directory: String::from("batch"),
+            batch_render: None,
+            batch_error_msg: None,
+            batch_failures: vec![],
+            playlist_file: String::from("playlist.json"),
+            playlist: None,
+            playlist_error_msg: None,
             painting_start_time: None,
+            recording_start_time: None,
+            painting_pixels: 0,
+            painting_seconds_per_pixel: None,
+            available_memory: None,
+            available_memory_checked: None,
+            canvas_frame_time: 0.0,
+            last_frame_step: None,
+            canvas_frame_rate: CanvasFrameRate::new(),
             gui_uniforms: HashMap::new(),
+            modified_uniforms: HashSet::new(),
+            unrecorded_uniforms: HashSet::new(),
+            uniform_display_order: vec![],
+            uniform_snapshot: None,
+            showing_uniform_snapshot: false,
+            canvas_connected: true,
+            canvas_relaunch_requested: false,
+            randomize_seed: 0,
+            gui_ramps: Vec::new(),
+            shader_file: None,
+            pending_shader_uniforms: Vec::new(),
+            autosave_uniforms: true,
+            uniform_expressions: UniformExpressions::new(),
+            uniform_scenes: UniformScenes::new(),
+            uniform_timeline: UniformTimeline::new(),
+            timeline_replay: None,
+            timeline_filename: String::from("Timeline"),
+            command_palette_open: false,
+            command_palette_query: String::new(),
+            command_palette_grab_focus: false,
+            launch_args: Vec::new(),
+            last_export_command: None,
+            developer_mode: false,
+            show_imgui_demo: false,
+            show_imgui_metrics: false,
+        }
+    }
+
+    /// Uniform values to burn into a painting, one `name: value` line each in name order, if enabled.
+    pub fn uniform_overlay(&self) -> Option<TextOverlay> {
+        if !self.burn_in_uniforms || self.gui_uniforms.is_empty() {
+            return None;
+        }
+        let mut names: Vec<&String> = self.gui_uniforms.keys().collect();
+        names.sort();
+        Some(TextOverlay {
+            lines: names
+                .into_iter()
+                .map(|name| format!("{}: {}", name, self.gui_uniforms[name].display_value()))
+                .collect(),
+            scale: self.burn_in_scale,
+            corner: self.burn_in_corner,
+        })
+    }
+
+    /// Rename the piece. Export filenames still named after the previous piece, or the defaults if there was none,
+    /// follow the new name, keeping their directory and extension. Filenames the user chose are left alone.
+    pub fn set_piece_name(&mut self, name: &str) {
+        let previous = piece_basename(&self.piece_name);
+        let next = piece_basename(name);
+        let rename = |filename: &mut String, default: &str| {
+            let (dir, stem, extension) = split_filename(filename);
+            if stem == previous.as_deref().unwrap_or(default) {
+                *filename = format!("{}{}{}", dir, next.as_deref().unwrap_or(default), extension);
+            }
+        };
+        rename(&mut self.painting_filename, DEFAULT_PAINTING_BASENAME);
+        rename(&mut self.recording_filename, DEFAULT_RECORDING_BASENAME);
+        self.piece_name = String::from(name);
+    }
+
+    /// Piece name to write into export metadata, if the piece is named.
+    pub fn export_title(&self) -> Option<String> {
+        match self.piece_name.trim() {
+            "" => None,
+            name => Some(String::from(name)),
+        }
+    }
+
+    /// Title of the canvas window.
+    pub fn canvas_window_title(&self) -> String {
+        match self.export_title() {
+            Some(name) if self.piece_name_in_title => name,
+            _ => String::from("Canvas"),
+        }
+    }
+
+    /// Names of the current uniforms in the order their controls are shown: those reordered in the GUI first, then
+    /// the rest in the order of the uniforms JSON file.
+    pub fn ordered_uniform_names(&self) -> Vec<String> {
+        let mut rest: Vec<&UserUniform> = self
+            .gui_uniforms
+            .values()
+            .filter(|uniform| !self.uniform_display_order.contains(&uniform.name))
+            .collect();
+        rest.sort_by_key(|uniform| uniform.order);
+        self.uniform_display_order
+            .iter()
+            .filter(|name| self.gui_uniforms.contains_key(*name))
+            .cloned()
+            .chain(rest.into_iter().map(|uniform| uniform.name.clone()))
+            .collect()
+    }
+
+    /// Show the controls of the uniform at `from` in [Self::ordered_uniform_names] at `to` instead.
+    /// Uniforms the shader doesn't declare right now keep their place at the end, for when they are declared again.
+    pub fn move_uniform(&mut self, from: usize, to: usize) {
+        let mut order = self.ordered_uniform_names();
+        if from >= order.len() || to >= order.len() {
+            return;
+        }
+        let name = order.remove(from);
+        order.insert(to, name);
+        let gui_uniforms = &self.gui_uniforms;
+        order.extend(
+            self.uniform_display_order
+                .drain(..)
+                .filter(|name| !gui_uniforms.contains_key(name)),
+        );
+        self.uniform_display_order = order;
+    }
+
+    /// Reset every uniform to its value in the uniforms JSON file. The values are sent to the canvas with the other
+    /// modified uniforms in post_render.
+    pub fn reset_uniforms(&mut self) {
+        for (name, uniform) in self.gui_uniforms.iter_mut() {
+            uniform.reset();
+            self.modified_uniforms.insert(name.clone());
+        }
+    }
+
+    /// Whether a painting has been requested, is rendering or is being written, so another can't be started yet.
+    pub fn painting_busy(&self) -> bool {
+        self.painting_requested
+            || self.painting_rendering
+            || self.painting_progress_receiver.is_some()
+    }
+
+    /// Estimate memory use and render duration of a painting at the current painting resolution.
+    /// Duration is extrapolated from the last painting if there was one, otherwise from the canvas frame time.
+    pub fn painting_estimate(&self) -> PaintingEstimate {
+        let pixels =
+            self.painting_resolution.x.max(0) as u64 * self.painting_resolution.y.max(0) as u64;
+        let supersampled_pixels = pixels * (self.aa_samples * self.aa_samples) as u64;
+        let canvas_pixels = (self.render_window_size.x.max(1) as u64
+            * self.render_window_size.y.max(1) as u64) as f64;
+        let duration = match self.painting_seconds_per_pixel {
+            Some(seconds_per_pixel) => seconds_per_pixel * supersampled_pixels as f64,
+            None => self.canvas_frame_time / 1000.0 * supersampled_pixels as f64 / canvas_pixels,
+        };
+        PaintingEstimate {
+            gpu_bytes: (2 * supersampled_pixels + pixels) * PAINTING_BYTES_PER_PIXEL,
+            cpu_bytes: pixels * PAINTING_BYTES_PER_PIXEL,
+            duration,
+        }
+    }
+
+    /// Look up [Self::available_memory] again, at most every [AVAILABLE_MEMORY_INTERVAL].
+    pub fn refresh_available_memory(&mut self) {
+        if let Some(checked) = self.available_memory_checked {
+            if checked.elapsed() < AVAILABLE_MEMORY_INTERVAL {
+                return;
+            }
+        }
+        self.available_memory = utils::available_memory();
+        self.available_memory_checked = Some(Instant::now());
+    }
+
+    /// Overlay the Canvas should currently show, `None` when disabled or there is no reference to show.
+    pub fn compare_settings(&self) -> Option<CompareSettings> {
+        match (self.compare_enabled, self.reference_size) {
+            (true, Some(_)) => Some(CompareSettings {
+                mode: self.compare_mode,
+                amount: self.compare_amount,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Stereo settings the Canvas should capture with, `None` when disabled.
+    pub fn stereo_settings(&self) -> Option<StereoSettings> {
+        match self.stereo_enabled {
+            true => Some(StereoSettings {
+                layout: self.stereo_layout,
+                interaxial: self.stereo_interaxial,
+            }),
+            false => None,
+        }
+    }
+
+    /// Whether a recorder can be initialized with [Self::movie_framerate]. The field may hold 0 or less while
+    /// being typed in.
+    pub fn movie_framerate_valid(&self) -> bool {
+        framerate_valid(self.movie_framerate)
+    }
+
+    /// Number of frames to record for a fixed-duration recording, if one is configured.
+    pub fn movie_frame_count(&self) -> Option<u64> {
+        if self.movie_fixed_duration {
+            Some(whole_frame_count(self.movie_duration, self.movie_framerate))
+        } else {
+            None
+        }
+    }
+
+    /// Exact resolution the canvas should render at, if one is set.
+    pub fn exact_canvas_resolution(&self) -> Option<UIntVector2> {
+        match self.exact_resolution_enabled {
+            true => Some(UIntVector2::new(
+                self.exact_resolution.x.max(1) as u32,
+                self.exact_resolution.y.max(1) as u32,
+            )),
+            false => None,
+        }
+    }
+
+    /// Width to height ratio of what the canvas window shows: the exact resolution or framing if one is set,
+    /// the window otherwise. `None` until the canvas has reported a size.
+    pub fn canvas_aspect(&self) -> Option<UIntVector2> {
+        if let Some(resolution) = self.exact_canvas_resolution() {
+            return Some(resolution);
+        }
+        match self.framing {
+            Some(framing) => Some(framing.aspect()),
+            None if self.render_window_size.x > 0 && self.render_window_size.y > 0 => {
+                Some(UIntVector2::new(
+                    self.render_window_size.x as u32,
+                    self.render_window_size.y as u32,
+                ))
+            }
+            None => None,
+        }
+    }
+
+    /// Recording resolution with the canvas' aspect and the same long edge, if [Self::recording_resolution] is framed
+    /// differently from what the canvas window shows.
+    /// Not while the canvas is locked to the recording resolution, since it then shows the recording's framing.
+    pub fn recording_aspect_mismatch(&self) -> Option<IntVector2> {
+        if self.recording_in_progress && self.lock_resolution_while_recording {
+            return None;
+        }
+        let canvas = self.canvas_aspect()?;
+        let recording = self.recording_resolution;
+        if recording.x <= 0 || recording.y <= 0 {
+            return None;
+        }
+        let canvas_ratio = canvas.x as f32 / canvas.y as f32;
+        let recording_ratio = recording.x as f32 / recording.y as f32;
+        if (recording_ratio / canvas_ratio - 1.0).abs() <= ASPECT_TOLERANCE {
+            return None;
+        }
+        Some(fit_aspect(
+            canvas,
+            recording,
+            self.max_texture_dimension as i32,
+        ))
+    }
+
+    /// Why a fixed-duration PNG sequence would outgrow its [Self::sequence_numbering], if it would.
+    /// Open-ended sequences can't be checked up front.
+    pub fn sequence_numbering_error(&self) -> Option<String> {
+        if self.movie_container != MovieContainer::PngSequence {
+            return None;
+        }
+        self.sequence_numbering
+            .check(self.movie_frame_count()?)
+            .err()
+    }
+
+    /// Whether anything but its own control may drive the uniform or ramp `name`, e.g. a timeline replay.
+    /// Names the shader doesn't declare are not automatable.
+    pub fn is_automatable(&self, name: &str) -> bool {
+        match self.gui_uniforms.get(name) {
+            Some(uniform) => uniform.automatable,
+            None => self
+                .gui_ramps
+                .iter()
+                .any(|ramp| ramp.name == name && ramp.automatable),
+        }
+    }
+}
+
+/// `name` as a filename, with characters that are awkward in paths replaced by `_`. `None` for an empty name.
+fn piece_basename(name: &str) -> Option<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    Some(
+        name.chars()
+            .map(|c| match c.is_alphanumeric() || c == '-' || c == '_' {
+                true => c,
+                false => '_',
+            })
+            .collect(),
+    )
+}
+
+/// Directory including its trailing separator, stem and extension including its dot, any of which may be empty.
+fn split_filename(filename: &str) -> (&str, &str, &str) {
+    let name_start = filename.rfind(['/', '\\']).map_or(0, |i| i + 1);
+    let (dir, name) = filename.split_at(name_start);
+    let stem_end = match name.rfind('.') {
+        Some(dot) if dot > 0 => dot,
+        _ => name.len(),
+    };
+    let (stem, extension) = name.split_at(stem_end);
+    (dir, stem, extension)
+}
+
+/// `duration` seconds at `framerate` rounded to a whole number of frames, at least one.
+/// The actual duration of the movie is then `frames / framerate`.
+pub fn whole_frame_count(duration: f64, framerate: i32) -> u64 {
+    ((duration * framerate.max(1) as f64).round() as u64).max(1)
+}
+
+/// Whether movies can be recorded at `framerate` frames per second, see [DashboardState::movie_framerate_valid].
+pub fn framerate_valid(framerate: i32) -> bool {
+    framerate >= 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn movie_framerate_must_be_positive() {
+        let mut state = DashboardState::new();
+        for (framerate, valid) in [(0, false), (-5, false), (1, true)].iter().copied() {
+            state.movie_framerate = framerate;
+            assert_eq!(state.movie_framerate_valid(), valid, "{} fps", framerate);
         }
     }
 }
@@ -0,0 +1,45 @@
+use imgui::StyleColor;
+
+/// Colors of the Dashboard GUI.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DashboardTheme {
+    /// Otium's own light palette.
+    Pastel,
+    /// imgui's styling, left untouched, e.g. for more contrast or a dark desktop.
+    Default,
+}
+
+impl DashboardTheme {
+    pub const ALL: [DashboardTheme; 2] = [DashboardTheme::Pastel, DashboardTheme::Default];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DashboardTheme::Pastel => "Pastel",
+            DashboardTheme::Default => "Default",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<DashboardTheme> {
+        DashboardTheme::ALL
+            .iter()
+            .copied()
+            .find(|t| t.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Style colors pushed over imgui's for every Dashboard frame. None for [Self::Default].
+    pub fn colors(&self) -> &'static [(StyleColor, [f32; 4])] {
+        match self {
+            DashboardTheme::Pastel => &[
+                (StyleColor::Text, [0.0, 0.0, 0.0, 1.0]),
+                (StyleColor::Header, [0.949, 0.949, 0.953, 1.0]),
+                (StyleColor::HeaderHovered, [1.0, 1.0, 1.0, 1.0]),
+                (StyleColor::Button, [0.741, 0.933, 0.984, 1.0]),
+                (StyleColor::ButtonActive, [0.741, 0.933, 0.984, 1.0]),
+                (StyleColor::ButtonHovered, [0.533, 0.851, 0.816, 1.0]),
+                (StyleColor::FrameBg, [0.741, 0.933, 0.984, 1.0]),
+                (StyleColor::WindowBg, [0.906, 0.784, 0.573, 1.0]),
+            ],
+            DashboardTheme::Default => &[],
+        }
+    }
+}
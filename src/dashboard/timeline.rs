@@ -0,0 +1,142 @@
+use crate::uniforms::{load_uniforms_from_json, UserUniform};
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+/// Most changes a [UniformTimeline] keeps, the oldest are dropped first.
+pub const MAX_TIMELINE_CHANGES: usize = 100_000;
+
+/// A single uniform edit, timestamped relative to the start of the [UniformTimeline].
+#[derive(Clone)]
+pub struct UniformChange {
+    /// Unit: seconds
+    pub time: f64,
+    pub uniform: UserUniform,
+}
+
+/// In-memory log of the uniform changes made through the GUI during a session, up to [MAX_TIMELINE_CHANGES].
+/// Only changes are appended, so re-sending an unchanged uniform is free.
+pub struct UniformTimeline {
+    start: Instant,
+    pub changes: VecDeque<UniformChange>,
+    last_values: HashMap<String, Vec<u8>>,
+}
+
+impl UniformTimeline {
+    pub fn new() -> UniformTimeline {
+        UniformTimeline {
+            start: Instant::now(),
+            changes: VecDeque::new(),
+            last_values: HashMap::new(),
+        }
+    }
+
+    /// Append the uniform if its value differs from the last one recorded for it.
+    pub fn record(&mut self, uniform: &UserUniform) {
+        if self.last_values.get(&uniform.name) == Some(&uniform.bytes) {
+            return;
+        }
+        self.last_values
+            .insert(uniform.name.clone(), uniform.bytes.clone());
+        if self.changes.len() == MAX_TIMELINE_CHANGES {
+            self.changes.pop_front();
+        }
+        self.changes.push_back(UniformChange {
+            time: self.start.elapsed().as_secs_f64(),
+            uniform: uniform.clone(),
+        });
+    }
+
+    /// Forget all changes and restart the clock.
+    pub fn clear(&mut self) {
+        *self = UniformTimeline::new();
+    }
+
+    /// One row per change: `time,name,type,value`. Vector values are quoted JSON arrays.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("time,name,type,value\n");
+        for change in &self.changes {
+            let mut value = change.uniform.value_json().dump();
+            if value.contains(',') {
+                value = format!("\"{}\"", value);
+            }
+            csv.push_str(&format!(
+                "{:.4},\"{}\",{},{}\n",
+                change.time,
+                change.uniform.name.replace('"', "\"\""),
+                change.uniform.type_name(),
+                value
+            ));
+        }
+        csv
+    }
+
+    /// An array of `{"time", "name", "type", "value"}` objects, readable by [Self::from_json].
+    pub fn to_json(&self) -> json::JsonValue {
+        let mut array = json::JsonValue::new_array();
+        for change in &self.changes {
+            let mut entry = json::JsonValue::new_object();
+            entry["time"] = change.time.into();
+            entry["name"] = change.uniform.name.as_str().into();
+            entry["type"] = change.uniform.type_name().into();
+            entry["value"] = change.uniform.value_json();
+            array.push(entry).unwrap();
+        }
+        array
+    }
+
+    /// Parse changes previously written by [Self::to_json]. Malformed entries are skipped.
+    pub fn from_json(data: &json::JsonValue) -> Vec<UniformChange> {
+        let mut changes = Vec::new();
+        for entry in data.members() {
+            let (time, name) = match (entry["time"].as_f64(), entry["name"].as_str()) {
+                (Some(time), Some(name)) => (time, name),
+                _ => continue,
+            };
+            // Reuse the uniforms file loader by wrapping the entry in the same format.
+            let mut pair = json::JsonValue::new_array();
+            pair.push(entry["type"].clone()).unwrap();
+            pair.push(entry["value"].clone()).unwrap();
+            let mut uniforms = json::JsonValue::new_object();
+            uniforms[name] = pair;
+            let mut wrapper = json::JsonValue::new_object();
+            wrapper["uniforms"] = uniforms;
+            if let Some(uniform) = load_uniforms_from_json(&wrapper).into_iter().next() {
+                changes.push(UniformChange { time, uniform });
+            }
+        }
+        changes
+    }
+}
+
+/// Re-applies recorded [UniformChange]s at the same times they were originally made.
+pub struct TimelineReplay {
+    start: Instant,
+    changes: Vec<UniformChange>,
+    next: usize,
+}
+
+impl TimelineReplay {
+    pub fn new(mut changes: Vec<UniformChange>) -> TimelineReplay {
+        changes.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+        TimelineReplay {
+            start: Instant::now(),
+            changes,
+            next: 0,
+        }
+    }
+
+    /// Returns the changes that have come due since the last poll.
+    pub fn poll(&mut self) -> Vec<UserUniform> {
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let mut due = Vec::new();
+        while self.next < self.changes.len() && self.changes[self.next].time <= elapsed {
+            due.push(self.changes[self.next].uniform.clone());
+            self.next += 1;
+        }
+        due
+    }
+
+    pub fn finished(&self) -> bool {
+        self.next >= self.changes.len()
+    }
+}
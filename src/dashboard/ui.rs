@@ -1,13 +1,36 @@
-use super::{Dashboard, DashboardMessage};
-use crate::{recording::Recorder, recording::MOVIE_TEXTURE_FORMAT, uniforms, vector::UIntVector2};
+use super::actions::{self, Action};
+use super::{
+    framerate_valid, scene_selector_value, whole_frame_count, Dashboard, DashboardMessage,
+    DashboardTheme, Framing, MaintainStrategy, PlaylistPhase, UniformScenes, AA_SAMPLE_COUNTS,
+    MAX_PAINTING_WARM_UP_FRAMES,
+};
+use crate::{
+    canvas::{
+        present_mode_name, CompareMode, DebugView, Histogram, MirrorSettings, StereoLayout,
+        MIRROR_PRESENT_MODES,
+    },
+    layout::{UniformSlot, USER_BINDING},
+    overlay::OverlayCorner,
+    recording::{FrameNumbering, MovieCodec, MovieContainer, Recorder},
+    skeletons::ShaderTemplate,
+    texture::NoiseType,
+    uniforms::{self, UserUniformType},
+    utils,
+    vector::{IntVector2, Vector2},
+};
 use imgui::Condition;
-use imgui::{im_str, ImString, StyleColor};
+use imgui::{im_str, FocusedWidget, ImStr, ImString, StyleColor};
 use log::{info, warn};
+use std::collections::HashMap;
+use std::time::Instant;
 use winit::event::*;
 
 impl Dashboard {
     /// Renders the UI and responds to UI events.
     pub fn render_dashboard(&mut self) {
+        if self.size.width == 0 || self.size.height == 0 {
+            return;
+        }
         let now = std::time::Instant::now();
         self.imgui_context
             .io_mut()
@@ -17,55 +40,266 @@ impl Dashboard {
         let frame = match self.swap_chain.get_current_frame() {
             Ok(frame) => frame,
             Err(e) => {
-                warn!("GUI Dropped frame: {:?}", e);
+                if e == wgpu::SwapChainError::Timeout {
+                    self.state.frame_timeout_count += 1;
+                }
+                self.state
+                    .dropped_frames
+                    .record(e, self.state.log_dropped_frames);
                 return;
             }
         };
+        self.state
+            .dropped_frames
+            .flush(self.state.log_dropped_frames);
         self.imgui_platform
             .prepare_frame(self.imgui_context.io_mut(), &self.window)
             .expect("Failed to prepare frame");
 
         let ui = self.imgui_context.frame();
+        // Actions are run once the frame has been rendered, as `ui` borrows the imgui context until then.
+        // So are the edits below, whose handling needs the whole Dashboard.
+        let mut actions = vec![];
+        let mut preview_changed = false;
+        let mut framing_pressed = None;
+        let mut relaunch_canvas_pressed = false;
+        let mut apply_randomize_seed_pressed = false;
+        let mut closed_mirror = None;
         let mut color_tokens = vec![];
-        color_tokens.push(ui.push_style_color(StyleColor::Text, [0.0, 0.0, 0.0, 1.0]));
-        color_tokens.push(ui.push_style_color(StyleColor::Header, [0.949, 0.949, 0.953, 1.0]));
-        color_tokens.push(ui.push_style_color(StyleColor::HeaderHovered, [1.0, 1.0, 1.0, 1.0]));
-        color_tokens.push(ui.push_style_color(StyleColor::Button, [0.741, 0.933, 0.984, 1.0]));
-        color_tokens
-            .push(ui.push_style_color(StyleColor::ButtonActive, [0.741, 0.933, 0.984, 1.0]));
-        color_tokens
-            .push(ui.push_style_color(StyleColor::ButtonHovered, [0.533, 0.851, 0.816, 1.0]));
-        color_tokens.push(ui.push_style_color(StyleColor::FrameBg, [0.741, 0.933, 0.984, 1.0]));
-        color_tokens.push(ui.push_style_color(StyleColor::WindowBg, [0.906, 0.784, 0.573, 1.0]));
+        for (style_color, color) in self.state.theme.colors() {
+            color_tokens.push(ui.push_style_color(*style_color, *color));
+        }
 
         {
             let render_time = self.state.last_render_time;
+            let painting_estimate = self.state.painting_estimate();
+            let available_memory = self.state.available_memory;
+            let recording_aspect_suggestion = self.state.recording_aspect_mismatch();
+            let movie_frame_count = self.state.movie_frame_count();
+            let uniform_display_order = self.state.ordered_uniform_names();
+            let max_texture_dimension = self.state.max_texture_dimension as i32;
             let frame_num = self.state.frame_num;
             let frame_timeouts = self.state.frame_timeout_count;
+            let canvas_fps = self.state.canvas_frame_rate.fps(now);
+            let canvas_dropped_rate = self.state.canvas_frame_rate.dropped_per_second(now);
             let mouse_pos = self.state.mouse_pos;
+            let mouse_smoothing = &mut self.state.mouse_smoothing;
+            let mut mouse_smoothing_changed = false;
+            let touch_count = self.state.touch_count;
             let render_canvas_size = self.state.render_window_size;
             let paused_state = self.state.paused;
+            let time_scale = &mut self.state.time_scale;
+            let time_held = self.state.time_held;
+            let mut hold_time_button_pressed = false;
+            let time_scale_in_recordings = &mut self.state.time_scale_in_recordings;
             let mut pause_button_pressed = false;
+            let time_frozen_state = self.state.time_frozen;
+            let mut freeze_button_pressed = false;
+            let mut save_settings_button_pressed = false;
+            let master_faded_out = self.state.master_faded_out;
+            let mut master_fade_level = self.state.master_fade_level;
+            let mut master_fade_button_pressed = false;
+            let mut master_fade_level_changed = false;
+            let mut master_fade_color_changed = false;
+            let master_fade_color = &mut self.state.master_fade_color;
+            let master_fade_duration = &mut self.state.master_fade_duration;
+            let jitter_enabled = &mut self.state.jitter_enabled;
+            let jitter_frozen = &mut self.state.jitter_frozen;
+            let mut jitter_changed = false;
+            let accumulation_enabled = &mut self.state.accumulation_enabled;
+            let histogram_enabled = &mut self.state.histogram_enabled;
+            let histogram = self.state.histogram.as_ref();
+            let mut histogram_changed = false;
+            let preview_enabled = &mut self.state.preview_enabled;
+            let preview_size = &mut self.state.preview_size;
+            let thumbnail = self.state.thumbnail;
+            let accumulation_samples = self.state.accumulation_samples;
+            let mut accumulation_changed = false;
+            let mut reset_accumulation_button_pressed = false;
             let titlebars_state = self.state.show_titlebar;
             let mut titlebar_button_pressed = false;
-            let gui_width = self.size.width as f32 / self.hidpi_factor - 10.0;
+            let borderless_state = self.state.borderless;
+            let mut borderless_button_pressed = false;
+            let mut fit_window_button_pressed = false;
+            let mut controls_content_height = self.state.controls_content_height;
+            // Leave room for the scrollbar when the controls were taller than the window last frame.
+            let window_height = self.size.height as f32 / self.hidpi_factor;
+            let scrollbar_width = match controls_content_height > window_height {
+                true => ui.clone_style().scrollbar_size,
+                false => 0.0,
+            };
+            let gui_width = self.size.width as f32 / self.hidpi_factor - 10.0 - scrollbar_width;
             let mut create_painting_button_pressed = false;
+            let mut copy_frame_button_pressed = false;
+            let framing = self.state.framing;
+            let exact_resolution_enabled = &mut self.state.exact_resolution_enabled;
+            let exact_width = &mut self.state.exact_resolution.x;
+            let exact_height = &mut self.state.exact_resolution.y;
+            let maintain_strategy = &mut self.state.maintain_strategy;
+            let theme = &mut self.state.theme;
+            let log_dropped_frames = &mut self.state.log_dropped_frames;
+            let idle_timeout = &mut self.state.idle_timeout;
+            let idle_framerate = &mut self.state.idle_framerate;
+            let idle_dim = &mut self.state.idle_dim;
+            let debug_view = self.state.debug_view;
+            let wireframe_supported = self.state.wireframe_supported;
+            let missing_capabilities = &self.state.missing_capabilities;
+            let uniform_layout_slots = &self.state.uniform_layout_slots;
+            let uniform_layout_warnings = &self.state.uniform_layout_warnings;
+            let mut debug_view_selected = None;
+            let checkerboard_enabled = &mut self.state.checkerboard_enabled;
+            let mut checkerboard_changed = false;
+            let hold_frame_on_error = &mut self.state.hold_frame_on_error;
+            let mut hold_frame_on_error_changed = false;
+            let last_export_command = self.state.last_export_command.as_ref();
+            let mut copy_export_command_pressed = false;
+            let flip_horizontal = &mut self.state.flip_horizontal;
+            let flip_vertical = &mut self.state.flip_vertical;
+            let flip_captures = &mut self.state.flip_captures;
+            let mut flip_changed = false;
+            let stereo_enabled = &mut self.state.stereo_enabled;
+            let stereo_layout = &mut self.state.stereo_layout;
+            let stereo_interaxial = &mut self.state.stereo_interaxial;
+            let mut stereo_changed = false;
+            let reference_size = self.state.reference_size;
+            let compare_enabled = &mut self.state.compare_enabled;
+            let compare_mode = &mut self.state.compare_mode;
+            let compare_amount = &mut self.state.compare_amount;
+            let mut compare_changed = false;
+            let mut capture_reference_button_pressed = false;
+            let mut clear_reference_button_pressed = false;
             let painting_width = &mut self.state.painting_resolution.x;
             let painting_height = &mut self.state.painting_resolution.y;
+            let mut match_canvas_aspect_pressed = false;
             let _recording_width = &mut self.state.recording_resolution.x;
             let _recording_height = &mut self.state.recording_resolution.y;
             let movie_framerate = &mut self.state.movie_framerate;
+            let movie_fixed_duration = &mut self.state.movie_fixed_duration;
+            let lock_resolution_while_recording = &mut self.state.lock_resolution_while_recording;
+            let movie_duration = &mut self.state.movie_duration;
+            let recording_warm_up = &mut self.state.recording_warm_up;
+            let record_changes_only = &mut self.state.record_changes_only;
+            let recording_dry_run = &mut self.state.recording_dry_run;
+            let change_threshold = &mut self.state.change_threshold;
+            let movie_codec = &mut self.state.movie_codec;
+            let movie_container = &mut self.state.movie_container;
+            let sequence_numbering = &mut self.state.sequence_numbering;
+            let recording_stem = String::from(MovieContainer::stem(&self.state.recording_filename));
             let mut painting_filename = ImString::with_capacity(256);
             let mut recording_filename = ImString::with_capacity(256);
             let open_painting_externally = &mut self.state.open_painting_externally;
             let pause_while_painting = &mut self.state.pause_while_painting;
+            let export_painting_passes = &mut self.state.export_painting_passes;
+            let painting_warm_up_frames = &mut self.state.painting_warm_up_frames;
+            let capture_time = &mut self.state.capture_time;
+            let mut create_painting_at_time_pressed = false;
+            let burn_in_uniforms = &mut self.state.burn_in_uniforms;
+            let burn_in_scale = &mut self.state.burn_in_scale;
+            let burn_in_corner = &mut self.state.burn_in_corner;
+            let aa_samples = &mut self.state.aa_samples;
+            let mut aa_samples_changed = false;
             let shader_compilation_error_msg = self.state.shader_compilation_error_msg.as_ref();
+            let mut variation_candidates: Vec<String> = self
+                .state
+                .gui_uniforms
+                .values()
+                .filter(|uniform| uniform.scalar_value().is_some())
+                .map(|uniform| uniform.name.clone())
+                .collect();
+            variation_candidates.sort();
+            let mut scene_selector_candidates: Vec<String> = self
+                .state
+                .gui_uniforms
+                .values()
+                .filter(|uniform| scene_selector_value(uniform).is_some())
+                .map(|uniform| uniform.name.clone())
+                .collect();
+            scene_selector_candidates.sort();
+            let uniforms_reordered = !self.state.uniform_display_order.is_empty();
+            let mut moved_uniform = None;
+            let mut reset_uniform_order_pressed = false;
+            let uniform_scenes = &mut self.state.uniform_scenes;
+            let variation_uniform = &mut self.state.variation_uniform;
+            let variation_count = &mut self.state.variation_count;
+            let variation_min = &mut self.state.variation_min;
+            let variation_max = &mut self.state.variation_max;
+            let variation_cell_width = &mut self.state.variation_cell_width;
+            let mut create_variation_sheet_button_pressed = false;
             let user_uniforms = &mut self.state.gui_uniforms;
+            let autosave_uniforms = &mut self.state.autosave_uniforms;
+            let user_ramps = &mut self.state.gui_ramps;
+            let mut modified_ramps = vec![];
+            let mut modified_uniforms = vec![];
+            let uniform_expressions = &self.state.uniform_expressions.expressions;
+            let mut expression_edits = vec![];
+            let mut reroll_seeds_button_pressed = false;
+            let canvas_connected = self.state.canvas_connected;
+            let mut randomize_seed = self.state.randomize_seed as i32;
+            let mut randomize_button_pressed = false;
+            let has_uniform_snapshot = self.state.uniform_snapshot.is_some();
+            let showing_uniform_snapshot = self.state.showing_uniform_snapshot;
+            let mut uniform_snapshot_button_pressed = false;
+            let mut clear_snapshot_button_pressed = false;
+            let mut open_shader_file = ImString::with_capacity(256);
+            open_shader_file.push_str(&self.state.open_shader_file);
+            let mut open_shader_file_changed = false;
+            let mut open_shader_button_pressed = false;
+            let mut new_shader_file = ImString::with_capacity(256);
+            new_shader_file.push_str(&self.state.new_shader_file);
+            let mut new_shader_file_changed = false;
+            let new_shader_template = &mut self.state.new_shader_template;
+            let new_shader_error_msg = self.state.new_shader_error_msg.as_ref();
+            let mut new_shader_button_pressed = false;
+            let mut batch_directory = ImString::with_capacity(256);
+            batch_directory.push_str(&self.state.batch_directory);
+            let mut batch_output_directory = ImString::with_capacity(256);
+            batch_output_directory.push_str(&self.state.batch_output_directory);
+            let mut batch_directories_changed = false;
+            let batch_progress = self
+                .state
+                .batch_render
+                .as_ref()
+                .map(|batch| (batch.current, batch.shaders.len(), batch.failures.len()));
+            let batch_error_msg = self.state.batch_error_msg.as_ref();
+            let batch_failures = &self.state.batch_failures;
+            let mut batch_action = None;
+            let mut playlist_file = ImString::with_capacity(256);
+            playlist_file.push_str(&self.state.playlist_file);
+            let mut playlist_file_changed = false;
+            let playlist = self.state.playlist.as_mut();
+            let playlist_error_msg = self.state.playlist_error_msg.as_ref();
+            let mut playlist_action = None;
+            let mut input_image_file = ImString::with_capacity(256);
+            input_image_file.push_str(&self.state.input_image_file);
+            let mut input_image_file_changed = false;
+            let mut load_input_image_button_pressed = false;
+            let noise_type = &mut self.state.noise_type;
+            // imgui only edits signed integers, the bits are kept as they are.
+            let mut noise_seed = self.state.noise_seed as i32;
+            let mut noise_changed = false;
+            let mut reseed_noise_button_pressed = false;
+            let input_image_loaded = self.state.input_image_loaded.as_ref();
+            let input_image_error_msg = self.state.input_image_error_msg.as_ref();
+            let recording_error_msg = self.state.recording_error_msg.as_ref();
+            let mirror_outputs = &mut self.state.mirror_outputs;
+            let mut modified_mirrors = vec![];
+            let mut add_mirror_button_pressed = false;
+            let vram_total = self.vram.total();
+            let vram_allocations = self.vram.allocations();
+            let timeline_changes = self.state.uniform_timeline.changes.len();
+            let replaying_timeline = self.state.timeline_replay.is_some();
+            let mut timeline_filename = ImString::with_capacity(256);
+            let mut timeline_filename_changed = false;
+            let mut timeline_action = None;
+            let developer_mode = self.state.developer_mode;
+            let show_imgui_demo = &mut self.state.show_imgui_demo;
+            let show_imgui_metrics = &mut self.state.show_imgui_metrics;
             let mut start_record_button_pressed = false;
             let mut stop_record_button_pressed = false;
             let recording_in_progress = &mut self.state.recording_in_progress;
             let mut init_recorder_button_pressed = false;
             let recorder = self.recorder.as_ref();
+            let recording_start_time = self.state.recording_start_time;
             let recorder_ready = match recorder {
                 Some(rec) => rec.ready,
                 None => false,
@@ -73,27 +307,42 @@ impl Dashboard {
 
             painting_filename.push_str(&self.state.painting_filename);
             recording_filename.push_str(&self.state.recording_filename);
+            timeline_filename.push_str(&self.state.timeline_filename);
             let mut painting_filename_changed = false;
             let mut recording_filename_changed = false;
+            let mut piece_name = ImString::with_capacity(256);
+            piece_name.push_str(&self.state.piece_name);
+            let mut piece_name_changed = false;
+            let piece_name_in_title = &mut self.state.piece_name_in_title;
+            let mut piece_name_in_title_changed = false;
             let painting_in_progress = match &mut self.state.painting_progress_receiver {
-                None => false,
+                None => self.state.painting_requested || self.state.painting_rendering,
                 Some(rx) => {
                     let msg_result = rx.try_recv();
                     match msg_result {
-                        Ok(_) => {
+                        Ok(utils::WriteFinished::Finished(event)) => {
                             self.state.painting_progress_receiver = None;
+                            // Also shows failures to write in the middle, e.g. once the disk fills up.
+                            self.state.painting_error_msg = event.error.as_ref().map(|e| {
+                                format!("Could not write {}: {}", event.path.display(), e)
+                            });
+                            self.vram.set("Painting staging buffer", 0);
 
                             // Log the amount of time render + write took.
                             if let Some(start) = self.state.painting_start_time {
                                 let now = std::time::Instant::now();
                                 let elapsed = now.duration_since(start).as_secs_f64();
                                 info!("Painting render + write took {} seconds", elapsed);
+                                if self.state.painting_pixels > 0 {
+                                    self.state.painting_seconds_per_pixel =
+                                        Some(elapsed / self.state.painting_pixels as f64);
+                                }
                                 self.state.painting_start_time = None;
                             }
 
                             // Send message to unpause the rendering.
                             if *pause_while_painting {
-                                self.transmitter.send(DashboardMessage::Play).unwrap();
+                                self.transmitter.send(DashboardMessage::Play);
                             }
                             false
                         } // Finished.
@@ -101,6 +350,10 @@ impl Dashboard {
                     }
                 }
             };
+            let painting_requested = self.state.painting_requested;
+            let painting_error_msg = self.state.painting_error_msg.as_ref();
+            let painting_rendering = self.state.painting_rendering;
+            let painting_start_time = self.state.painting_start_time;
             let controls = imgui::Window::new(im_str!("Controls"));
 
             controls
@@ -113,9 +366,20 @@ impl Dashboard {
                 )
                 .position([0.0, 0.0], Condition::Always)
                 .collapsible(false)
-                .no_decoration()
+                .title_bar(false)
+                .resizable(false)
                 .movable(false)
                 .build(&ui, || {
+                    if !canvas_connected {
+                        ui.text_colored([1.0, 0.325, 0.286, 1.0], "Canvas disconnected");
+                        ui.text_wrapped(im_str!("The canvas window was closed. A relaunched Canvas starts from the launch arguments and the shader's saved settings, and autosaved uniform values. Close the Dashboard to quit."));
+                        relaunch_canvas_pressed =
+                            ui.button(im_str!("Relaunch Canvas"), [gui_width, 25.0]);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Opens the canvas window again with the launch arguments, then the shader that was open.");
+                        }
+                        ui.separator();
+                    }
                     if imgui::CollapsingHeader::new(im_str!("Stats & Controls"))
                         .default_open(true)
                         .open_on_arrow(true)
@@ -123,6 +387,21 @@ impl Dashboard {
                         .build(&ui)
                     {
                         ui.text(format!("Render Time: {:.3} ms", render_time));
+                        ui.text(format!("Canvas FPS: {:.0}", canvas_fps));
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Frames the Canvas rendered in the last second. Render Time above is the Dashboard's own.");
+                        }
+                        if canvas_dropped_rate > 0.0 {
+                            ui.text_colored(
+                                [1.0, 0.8, 0.0, 1.0],
+                                format!("Canvas Dropped Frames: {:.0}/s", canvas_dropped_rate),
+                            );
+                        } else {
+                            ui.text("Canvas Dropped Frames: 0/s");
+                        }
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Frames the Canvas skipped in the last second because the swap chain had none to give it. A low FPS with drops points at presentation hitches, a low FPS without them at a slow shader.");
+                        }
                         ui.text(format!("Frames Rendered: {}", frame_num));
                         ui.text(format!("Frame Timeouts: {}", frame_timeouts));
                         ui.text(im_str!(
@@ -130,6 +409,13 @@ impl Dashboard {
                             mouse_pos.x,
                             mouse_pos.y
                         ));
+                        mouse_smoothing_changed = imgui::Slider::new(im_str!("Mouse Smoothing"))
+                            .range(0.0..=1.0)
+                            .build(&ui, mouse_smoothing);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Smooths the mouse position bound to the shader, most for slow movement. The position above stays raw. 0 is off.");
+                        }
+                        ui.text(format!("Touch Points: {}", touch_count));
                         ui.text(im_str!(
                             "Canvas Size: {} x {}",
                             render_canvas_size.x,
@@ -141,6 +427,120 @@ impl Dashboard {
                         } else {
                             pause_button_pressed = ui.button(im_str!("Pause"), [gui_width, 25.0]);
                         }
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Stops time and rendering to save power.");
+                        }
+                        if time_frozen_state {
+                            freeze_button_pressed =
+                                ui.button(im_str!("Unfreeze Time"), [gui_width, 25.0]);
+                        } else {
+                            freeze_button_pressed =
+                                ui.button(im_str!("Freeze Time"), [gui_width, 25.0]);
+                        }
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text(
+                                "Stops time and frame count but keeps rendering, so uniform edits show immediately.",
+                            );
+                        }
+                        if time_held {
+                            ui.text_colored([1.0, 0.8, 0.0, 1.0], "Time is held, press H to release.");
+                        }
+                        imgui::Slider::new(im_str!("Time Scale"))
+                            .range(0.1..=10.0)
+                            .flags(imgui::SliderFlags::LOGARITHMIC)
+                            .display_format(im_str!("%.2fx"))
+                            .build(&ui, time_scale);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("How fast time advances in the shader. The frame rate is unchanged.");
+                        }
+                        ui.same_line(0.0);
+                        if ui.small_button(im_str!("1x##TimeScale")) {
+                            *time_scale = 1.0;
+                        }
+                        ui.checkbox(im_str!("Time Scale in Recordings"), time_scale_in_recordings);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Recordings run at real time unless checked.");
+                        }
+                        ui.separator();
+                        let fade_label = match master_faded_out {
+                            true => im_str!("Fade In"),
+                            false => im_str!("Fade Out"),
+                        };
+                        master_fade_button_pressed = ui.button(fade_label, [gui_width, 40.0]);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Fades the canvas and mirrors to the fade color and back. Shortcut: B");
+                        }
+                        master_fade_level_changed = imgui::Slider::new(im_str!("Master Fade"))
+                            .range(0.0..=1.0)
+                            .build(&ui, &mut master_fade_level);
+                        master_fade_color_changed =
+                            imgui::ColorEdit::new(im_str!("Fade Color"), master_fade_color)
+                                .build(&ui);
+                        imgui::Drag::new(im_str!("Fade Duration (s)"))
+                            .range(0.0..=60.0)
+                            .speed(0.05)
+                            .build(&ui, master_fade_duration);
+                        ui.separator();
+                        jitter_changed |= ui.checkbox(im_str!("Sub-pixel Jitter"), jitter_enabled);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Offsets u_jitter by an R2 sequence each frame, for TAA or accumulation.");
+                        }
+                        if *jitter_enabled {
+                            ui.same_line(0.0);
+                            jitter_changed |= ui.checkbox(im_str!("Freeze##Jitter"), jitter_frozen);
+                        }
+                        accumulation_changed =
+                            ui.checkbox(im_str!("Progressive Accumulation"), accumulation_enabled);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text(
+                                "Averages successive frames while time is frozen and jitter is running.",
+                            );
+                        }
+                        if *accumulation_enabled {
+                            if time_frozen_state && *jitter_enabled && !*jitter_frozen {
+                                ui.text(format!("Samples: {}", accumulation_samples));
+                                ui.same_line(0.0);
+                                reset_accumulation_button_pressed =
+                                    ui.small_button(im_str!("Reset##Accumulation"));
+                            } else {
+                                ui.text_wrapped(im_str!(
+                                    "Freeze time and enable unfrozen jitter to accumulate."
+                                ));
+                            }
+                        }
+                        imgui::ComboBox::new(im_str!("Debug View"))
+                            .preview_value(&im_str!("{}", debug_view.name()))
+                            .build(&ui, || {
+                                for view in DebugView::ALL.iter() {
+                                    let supported = *view != DebugView::Wireframe || wireframe_supported;
+                                    if imgui::Selectable::new(&im_str!("{}", view.name()))
+                                        .selected(*view == debug_view)
+                                        .disabled(!supported)
+                                        .build(&ui)
+                                    {
+                                        debug_view_selected = Some(*view);
+                                    }
+                                }
+                            });
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text(match wireframe_supported {
+                                true => "Shown in the canvas window only, never exported.",
+                                false => "Shown in the canvas window only, never exported. Wireframe is not supported by this GPU.",
+                            });
+                        }
+                        checkerboard_changed =
+                            ui.checkbox(im_str!("Transparency Checkerboard"), checkerboard_enabled);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Show a checkerboard behind transparent areas in the canvas window. Exports keep their true alpha.");
+                        }
+                        flip_changed |= ui.checkbox(im_str!("Flip Horizontally"), flip_horizontal);
+                        flip_changed |= ui.checkbox(im_str!("Flip Vertically"), flip_vertical);
+                        if *flip_horizontal || *flip_vertical {
+                            flip_changed |= ui.checkbox(im_str!("Flip Captures"), flip_captures);
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text("Flip paintings and movies too. Uncheck when the flip is only for the physical display, e.g. rear projection.");
+                            }
+                        }
                         if titlebars_state {
                             titlebar_button_pressed =
                                 ui.button(im_str!("Hide Titlebar"), [gui_width, 25.0]);
@@ -148,6 +548,276 @@ impl Dashboard {
                             titlebar_button_pressed =
                                 ui.button(im_str!("Show Titlebar"), [gui_width, 25.0]);
                         }
+                        if borderless_state {
+                            borderless_button_pressed =
+                                ui.button(im_str!("Restore Canvas Border"), [gui_width, 25.0]);
+                        } else {
+                            borderless_button_pressed =
+                                ui.button(im_str!("Borderless Canvas"), [gui_width, 25.0]);
+                        }
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Hides the canvas window's border, titlebar and cursor, for screen recording the window.");
+                        }
+                        fit_window_button_pressed =
+                            ui.button(im_str!("Fit Window to Content"), [gui_width, 25.0]);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Resize this window to the height of the expanded controls.");
+                        }
+                        piece_name_changed =
+                            ui.input_text(im_str!("Piece Name"), &mut piece_name).build();
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Names paintings and recordings that still have the default filename, and is written into their metadata.");
+                        }
+                        piece_name_in_title_changed =
+                            ui.checkbox(im_str!("Show in Window Title"), piece_name_in_title);
+                        save_settings_button_pressed =
+                            ui.button(im_str!("Save Shader Settings"), [gui_width, 25.0]);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text(
+                                "Remember painting, recording and uniform settings for this shader.",
+                            );
+                        }
+                    }
+
+                    if imgui::CollapsingHeader::new(im_str!("Display"))
+                        .default_open(false)
+                        .open_on_arrow(true)
+                        .open_on_double_click(true)
+                        .build(&ui)
+                    {
+                        ui.text("Device Maintenance");
+                        for strategy in MaintainStrategy::ALL.iter() {
+                            ui.same_line(0.0);
+                            ui.radio_button(&im_str!("{}##Maintain", strategy.name()), maintain_strategy, *strategy);
+                        }
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Poll keeps the Dashboard most responsive, Wait saves power. Auto waits while paused or unfocused.");
+                        }
+                        ui.text("Theme");
+                        for option in DashboardTheme::ALL.iter() {
+                            ui.same_line(0.0);
+                            ui.radio_button(&im_str!("{}##Theme", option.name()), theme, *option);
+                        }
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Default leaves imgui's own colors alone, with more contrast than Pastel.");
+                        }
+                        hold_frame_on_error_changed =
+                            ui.checkbox(im_str!("Hold Last Frame on Error"), hold_frame_on_error);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("While the shader fails to compile, keep showing the last frame it rendered. Off clears the canvas window instead.");
+                        }
+                        ui.checkbox(im_str!("Log Dropped Frames"), log_dropped_frames);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Warn about Dashboard frames dropped by the swap chain, at most once a second. They are still counted in Frame Timeouts.");
+                        }
+                        imgui::Drag::new(im_str!("Idle Timeout (min)"))
+                            .range(0.0..=1440.0)
+                            .speed(0.1)
+                            .build(&ui, idle_timeout);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Without input for this long, the canvas renders slower and dims until any key or mouse input. 0 never goes idle.");
+                        }
+                        imgui::Slider::new(im_str!("Idle Framerate"))
+                            .range(1.0..=60.0)
+                            .build(&ui, idle_framerate);
+                        imgui::Slider::new(im_str!("Idle Dim"))
+                            .range(0.0..=1.0)
+                            .build(&ui, idle_dim);
+                    }
+
+                    if imgui::CollapsingHeader::new(im_str!("GPU Info"))
+                        .default_open(false)
+                        .open_on_arrow(true)
+                        .open_on_double_click(true)
+                        .build(&ui)
+                    {
+                        ui.text(format!(
+                            "Estimated VRAM: {}",
+                            utils::format_bytes(vram_total)
+                        ));
+                        if ui.is_item_hovered() {
+                            ui.tooltip(|| {
+                                for (site, bytes) in &vram_allocations {
+                                    ui.text(format!("{}: {}", site, utils::format_bytes(*bytes)));
+                                }
+                            });
+                        }
+                        ui.text(format!(
+                            "Max Texture Size: {0} x {0}",
+                            max_texture_dimension
+                        ));
+                        if !missing_capabilities.is_empty() {
+                            ui.text_colored(
+                                [1.0, 0.325, 0.286, 1.0],
+                                format!("Unavailable: {}", missing_capabilities.join(", ")),
+                            );
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text("The GPU lacks optional features these need");
+                            }
+                        }
+                    }
+
+                    if imgui::CollapsingHeader::new(im_str!("Uniform Layout"))
+                        .default_open(false)
+                        .open_on_arrow(true)
+                        .open_on_double_click(true)
+                        .build(&ui)
+                    {
+                        ui.text("Binding  Offset  Size  Name");
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Offsets and sizes are in bytes. Pin user uniforms to an offset with \"uniform_offsets\" in the shader settings.");
+                        }
+                        for slot in uniform_layout_slots {
+                            ui.text(format!(
+                                "{:>7}  {:>6}  {:>4}  {}{}",
+                                slot.binding,
+                                slot.offset,
+                                slot.size,
+                                slot.name,
+                                if slot.pinned { " (pinned)" } else { "" }
+                            ));
+                        }
+                        for warning in uniform_layout_warnings {
+                            ui.text_colored([1.0, 0.8, 0.0, 1.0], warning);
+                        }
+                        imgui::TreeNode::new(im_str!("User Uniform Bytes")).build(&ui, || {
+                            uniform_bytes_ui(&ui, uniform_layout_slots, user_uniforms);
+                        });
+                    }
+
+                    if imgui::CollapsingHeader::new(im_str!("Histogram"))
+                        .default_open(false)
+                        .open_on_arrow(true)
+                        .open_on_double_click(true)
+                        .build(&ui)
+                    {
+                        histogram_changed =
+                            ui.checkbox(im_str!("Show Histogram"), histogram_enabled);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Reads back a downsampled copy of the canvas output, which has a small performance cost.");
+                        }
+                        if let (true, Some(histogram)) = (*histogram_enabled, histogram) {
+                            histogram_ui(&ui, histogram, gui_width);
+                        }
+                    }
+
+                    if imgui::CollapsingHeader::new(im_str!("Preview"))
+                        .default_open(false)
+                        .open_on_arrow(true)
+                        .open_on_double_click(true)
+                        .build(&ui)
+                    {
+                        preview_changed = ui.checkbox(im_str!("Show Preview"), preview_enabled);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Shows a live thumbnail of the canvas output. The canvas downscales it on the GPU, so only the thumbnail is read back.");
+                        }
+                        let mut size = *preview_size as i32;
+                        if imgui::Slider::new(im_str!("Thumbnail Size"))
+                            .range(64..=1024)
+                            .display_format(im_str!("%d px"))
+                            .build(&ui, &mut size)
+                        {
+                            *preview_size = size as u32;
+                            preview_changed = true;
+                        }
+                        if let (true, Some((id, size))) = (*preview_enabled, thumbnail) {
+                            let height = gui_width * size.y as f32 / size.x as f32;
+                            imgui::Image::new(id, [gui_width, height]).build(&ui);
+                        }
+                    }
+
+                    if imgui::CollapsingHeader::new(im_str!("Framing"))
+                        .default_open(false)
+                        .open_on_arrow(true)
+                        .open_on_double_click(true)
+                        .build(&ui)
+                    {
+                        let preview_width = (gui_width - 4.0 * ui.clone_style().item_spacing[0]) / 5.0;
+                        if framing_preset_ui(&ui, None, framing, render_canvas_size, preview_width) {
+                            framing_pressed = Some(None);
+                        }
+                        for preset in Framing::ALL.iter() {
+                            ui.same_line(0.0);
+                            if framing_preset_ui(&ui, Some(*preset), framing, render_canvas_size, preview_width) {
+                                framing_pressed = Some(Some(*preset));
+                            }
+                        }
+                        ui.checkbox(im_str!("Exact Resolution"), exact_resolution_enabled);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Render the canvas at exactly this many pixels, scaled to fit its window, whatever the window size and display scaling. Overrides the framing.");
+                        }
+                        if *exact_resolution_enabled {
+                            dimension_input_ui(
+                                &ui,
+                                im_str!("Width##Exact"),
+                                exact_width,
+                                max_texture_dimension,
+                            );
+                            dimension_input_ui(
+                                &ui,
+                                im_str!("Height##Exact"),
+                                exact_height,
+                                max_texture_dimension,
+                            );
+                        }
+                    }
+
+                    if imgui::CollapsingHeader::new(im_str!("Reference Compare"))
+                        .default_open(false)
+                        .open_on_arrow(true)
+                        .open_on_double_click(true)
+                        .build(&ui)
+                    {
+                        capture_reference_button_pressed =
+                            ui.button(im_str!("Capture Reference"), [gui_width, 25.0]);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Stores the frame on the canvas to compare further tweaks against. Only shown in the canvas window, never exported.");
+                        }
+                        if let Some(size) = reference_size {
+                            ui.text(format!("Reference: {} x {}", size.x, size.y));
+                            compare_changed |= ui.checkbox(im_str!("Show Reference"), compare_enabled);
+                            for mode in CompareMode::ALL.iter() {
+                                ui.same_line(0.0);
+                                compare_changed |= ui.radio_button(&im_str!("{}", mode.name()), compare_mode, *mode);
+                            }
+                            let amount_label = match compare_mode {
+                                CompareMode::Wipe => im_str!("Wipe Position"),
+                                CompareMode::OnionSkin => im_str!("Reference Opacity"),
+                            };
+                            compare_changed |= imgui::Slider::new(amount_label)
+                                .range(0.0..=1.0)
+                                .build(&ui, compare_amount);
+                            clear_reference_button_pressed =
+                                ui.button(im_str!("Clear Reference"), [gui_width, 25.0]);
+                        }
+                    }
+
+                    if imgui::CollapsingHeader::new(im_str!("Stereo Capture"))
+                        .default_open(false)
+                        .open_on_arrow(true)
+                        .open_on_double_click(true)
+                        .build(&ui)
+                    {
+                        stereo_changed |= ui.checkbox(im_str!("Capture in Stereo"), stereo_enabled);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Renders paintings and movie frames once per eye, packed into the painting or recording resolution. The canvas window keeps a single view.");
+                        }
+                        if *stereo_enabled {
+                            for (i, layout) in StereoLayout::ALL.iter().enumerate() {
+                                if i > 0 {
+                                    ui.same_line(0.0);
+                                }
+                                stereo_changed |= ui.radio_button(&im_str!("{}##Stereo", layout.name()), stereo_layout, *layout);
+                            }
+                            stereo_changed |= imgui::Drag::new(im_str!("Interaxial Distance"))
+                                .range(0.0..=f32::MAX)
+                                .speed(0.001)
+                                .display_format(im_str!("%.4f"))
+                                .build(&ui, stereo_interaxial);
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text("Distance between the eyes in scene units, bound as u_stereo.w. Each eye is offset by half of it in u_stereo.x.");
+                            }
+                        }
                     }
 
                     if imgui::CollapsingHeader::new(im_str!("Painting Options"))
@@ -156,36 +826,542 @@ impl Dashboard {
                         .open_on_double_click(true)
                         .build(&ui)
                     {
-                        ui.input_int(im_str!("Width##Painting"), painting_width)
-                            .build();
-                        ui.input_int(im_str!("Height##Painting"), painting_height)
-                            .build();
+                        dimension_input_ui(
+                            &ui,
+                            im_str!("Width##Painting"),
+                            painting_width,
+                            max_texture_dimension,
+                        );
+                        dimension_input_ui(
+                            &ui,
+                            im_str!("Height##Painting"),
+                            painting_height,
+                            max_texture_dimension,
+                        );
 
                         let file_input =
                             ui.input_text(im_str!("Filename##Painting"), &mut painting_filename);
                         painting_filename_changed = file_input.build();
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("End with .png or .jpg to choose the format. Written as TIFF otherwise.");
+                        }
                         if cfg!(target_os = "macos") {
                             ui.checkbox(im_str!("Open in External App"), open_painting_externally);
                         }
                         ui.checkbox(im_str!("Pause While Painting"), pause_while_painting);
+                        ui.checkbox(im_str!("Export Each Pass"), export_painting_passes);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Writes the shader output and each post-processing pass as named pages of the TIFF, for compositing. Single pass shaders are unaffected.");
+                        }
+                        let mut warm_up_frames = *painting_warm_up_frames as i32;
+                        if ui
+                            .input_int(im_str!("Warm-up Frames##Painting"), &mut warm_up_frames)
+                            .build()
+                        {
+                            *painting_warm_up_frames =
+                                (warm_up_frames.max(0) as u32).min(MAX_PAINTING_WARM_UP_FRAMES);
+                        }
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Frames rendered before the painting and averaged into it, each a frame number earlier at the same time, for shaders that need to settle.");
+                        }
+                        ui.checkbox(im_str!("Burn In Uniform Values"), burn_in_uniforms);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Draws each uniform's name and value into a corner of the painting, e.g. for tutorials and parameter studies. Off on every launch.");
+                        }
+                        if *burn_in_uniforms {
+                            ui.text_colored([1.0, 0.8, 0.0, 1.0], im_str!("Paintings will show the uniform values"));
+                            imgui::Slider::new(im_str!("Text Size##BurnIn"))
+                                .range(1..=16)
+                                .build(&ui, burn_in_scale);
+                            for corner in OverlayCorner::ALL.iter() {
+                                ui.radio_button(&im_str!("{}##BurnIn", corner.name()), burn_in_corner, *corner);
+                            }
+                        }
+                        ui.text("AA Quality");
+                        for samples in AA_SAMPLE_COUNTS.iter() {
+                            ui.same_line(0.0);
+                            aa_samples_changed |=
+                                ui.radio_button(&im_str!("{}x##AA", samples), aa_samples, *samples);
+                        }
                         if !painting_in_progress {
                             create_painting_button_pressed =
                                 ui.button(im_str!("Create"), [gui_width, 50.0]);
+                            imgui::Drag::new(im_str!("##CaptureTime"))
+                                .range(0.0..=f32::MAX)
+                                .speed(0.01)
+                                .display_format(im_str!("t = %.3f s"))
+                                .build(&ui, capture_time);
+                            ui.same_line(0.0);
+                            create_painting_at_time_pressed =
+                                ui.button(im_str!("Create at Time"), [0.0, 0.0]);
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text("Paints the frame at this u_time instead of the live one, so a moment of an animation can be reproduced exactly. The canvas keeps running.");
+                            }
+                        } else if painting_requested {
+                            // Nothing to estimate from until the Canvas starts, which may take a while for huge frames.
+                            let spinner = ["|", "/", "-", "\\"][(ui.time() * 8.0) as usize % 4];
+                            imgui::ProgressBar::new(0.0)
+                                .size([gui_width, 50.0])
+                                .overlay_text(&im_str!("Waiting for Canvas {}", spinner))
+                                .build(&ui);
+                        } else {
+                            // Progress is estimated, the render and write report no intermediate steps.
+                            let elapsed = painting_start_time
+                                .map_or(0.0, |start| start.elapsed().as_secs_f64());
+                            let fraction = match painting_estimate.duration > 0.0 {
+                                true => (elapsed / painting_estimate.duration).min(0.99),
+                                false => 0.0,
+                            };
+                            let stage = match painting_rendering {
+                                true => "Rendering",
+                                false => "Writing",
+                            };
+                            imgui::ProgressBar::new(fraction as f32)
+                                .size([gui_width, 50.0])
+                                .overlay_text(&im_str!("{} ({:.1} s)", stage, elapsed))
+                                .build(&ui);
+                        }
+                        if let Some(msg) = painting_error_msg {
+                            ui.text_colored([1.0, 0.325, 0.286, 1.0], msg);
+                        }
+                        copy_frame_button_pressed =
+                            ui.button(im_str!("Copy Frame to Clipboard"), [gui_width, 25.0]);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Copy the frame shown on the canvas, at its on-screen resolution, without writing a file. Shortcut: K");
+                        }
+                        ui.text(format!(
+                            "Estimated: {} GPU, {} CPU, {:.1} s",
+                            utils::format_bytes(painting_estimate.gpu_bytes),
+                            utils::format_bytes(painting_estimate.cpu_bytes),
+                            painting_estimate.duration
+                        ));
+                        if let Some(available) = available_memory {
+                            if painting_estimate.gpu_bytes.max(painting_estimate.cpu_bytes)
+                                > available
+                            {
+                                ui.text_colored(
+                                    [1.0, 0.325, 0.286, 1.0],
+                                    format!(
+                                        "Exceeds the {} of memory available.",
+                                        utils::format_bytes(available)
+                                    ),
+                                );
+                            }
                         }
                     }
                     //---------------------------------
-                    if imgui::CollapsingHeader::new(im_str!("Recording Options"))
-                        .default_open(true)
+                    if !variation_candidates.is_empty()
+                        && imgui::CollapsingHeader::new(im_str!("Variation Sheet"))
+                            .default_open(false)
+                            .open_on_arrow(true)
+                            .open_on_double_click(true)
+                            .build(&ui)
+                    {
+                        let preview = variation_uniform.as_deref().unwrap_or("None");
+                        imgui::ComboBox::new(im_str!("Uniform##Variation"))
+                            .preview_value(&im_str!("{}", preview))
+                            .build(&ui, || {
+                                for name in &variation_candidates {
+                                    if imgui::Selectable::new(&im_str!("{}", name))
+                                        .selected(variation_uniform.as_ref() == Some(name))
+                                        .build(&ui)
+                                    {
+                                        *variation_uniform = Some(name.clone());
+                                    }
+                                }
+                            });
+                        if ui.input_int(im_str!("Count##Variation"), variation_count).build() {
+                            *variation_count = (*variation_count).clamp(2, 64);
+                        }
+                        ui.input_float(im_str!("Min##Variation"), variation_min).build();
+                        ui.input_float(im_str!("Max##Variation"), variation_max).build();
+                        if ui.input_int(im_str!("Cell Width##Variation"), variation_cell_width).build() {
+                            *variation_cell_width = (*variation_cell_width).clamp(16, max_texture_dimension);
+                        }
+                        create_variation_sheet_button_pressed = ui.button(
+                            im_str!("Create Variation Sheet"),
+                            [gui_width, 25.0],
+                        ) && !painting_in_progress;
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Renders the uniform at evenly spaced values from Min to Max, like a painting, into one labeled TIFF next to the painting.");
+                        }
+                    }
+                    //---------------------------------
+                    if imgui::CollapsingHeader::new(im_str!("Open Shader"))
+                        .default_open(false)
+                        .open_on_arrow(true)
+                        .open_on_double_click(true)
+                        .build(&ui)
+                    {
+                        open_shader_file_changed = ui
+                            .input_text(im_str!("File##OpenShader"), &mut open_shader_file)
+                            .build();
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Loaded in place of the current shader, with its uniforms file and shader settings.");
+                        }
+                        open_shader_button_pressed =
+                            ui.button(im_str!("Load##OpenShader"), [gui_width, 25.0]);
+                    }
+                    //---------------------------------
+                    if imgui::CollapsingHeader::new(im_str!("New Shader"))
+                        .default_open(false)
+                        .open_on_arrow(true)
+                        .open_on_double_click(true)
+                        .build(&ui)
+                    {
+                        for template in ShaderTemplate::ALL.iter() {
+                            ui.radio_button(&im_str!("{}##NewShader", template.name()), new_shader_template, *template);
+                        }
+                        new_shader_file_changed = ui
+                            .input_text(im_str!("File##NewShader"), &mut new_shader_file)
+                            .build();
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Written only if no file exists there yet, then loaded in place of the current shader.");
+                        }
+                        new_shader_button_pressed =
+                            ui.button(im_str!("Create & Load##NewShader"), [gui_width, 25.0]);
+                        if let Some(msg) = new_shader_error_msg {
+                            ui.text_colored([1.0, 0.325, 0.286, 1.0], msg);
+                        }
+                    }
+                    //---------------------------------
+                    if imgui::CollapsingHeader::new(im_str!("Batch Render"))
+                        .default_open(false)
                         .open_on_arrow(true)
                         .open_on_double_click(true)
                         .build(&ui)
                     {
-                        ui.input_int(im_str!("Width##Movie"), _recording_width)
+                        batch_directories_changed |= ui
+                            .input_text(im_str!("Shaders##Batch"), &mut batch_directory)
                             .build();
-                        ui.input_int(im_str!("Height##Movie"), _recording_height)
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Directory of .frag and .spv shaders, rendered in name order.");
+                        }
+                        batch_directories_changed |= ui
+                            .input_text(im_str!("Output##Batch"), &mut batch_output_directory)
                             .build();
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Stills are written here as 000_name, 001_name... in the format of the painting filename, at the painting resolution.");
+                        }
+                        match batch_progress {
+                            Some((current, total, failed)) => {
+                                imgui::ProgressBar::new(current as f32 / total as f32)
+                                    .size([gui_width, 0.0])
+                                    .overlay_text(&im_str!("{} / {}", current, total))
+                                    .build(&ui);
+                                if failed > 0 {
+                                    ui.text_colored([1.0, 0.8, 0.0, 1.0], format!("Failed: {}", failed));
+                                }
+                                if ui.button(im_str!("Cancel##Batch"), [gui_width, 25.0]) {
+                                    batch_action = Some(Action::CancelBatchRender);
+                                }
+                            }
+                            None => {
+                                if ui.button(im_str!("Render All##Batch"), [gui_width, 25.0])
+                                    && !painting_in_progress
+                                {
+                                    batch_action = Some(Action::StartBatchRender);
+                                }
+                                if let Some(msg) = batch_error_msg {
+                                    ui.text_colored([1.0, 0.325, 0.286, 1.0], msg);
+                                }
+                                for (shader, reason) in batch_failures {
+                                    ui.text_colored([1.0, 0.8, 0.0, 1.0], format!("Failed: {}", shader));
+                                    if ui.is_item_hovered() {
+                                        ui.tooltip_text(reason);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    //---------------------------------
+                    if imgui::CollapsingHeader::new(im_str!("Playlist"))
+                        .default_open(false)
+                        .open_on_arrow(true)
+                        .open_on_double_click(true)
+                        .build(&ui)
+                    {
+                        playlist_file_changed = ui
+                            .input_text(im_str!("File##Playlist"), &mut playlist_file)
+                            .build();
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("JSON file listing the shaders to cycle through and how long to show each, see the docs.");
+                        }
+                        match playlist {
+                            Some(playlist) => {
+                                let entry = &playlist.entries[playlist.current];
+                                ui.text_wrapped(&im_str!(
+                                    "{} / {}: {}",
+                                    playlist.current + 1,
+                                    playlist.entries.len(),
+                                    entry.shader
+                                ));
+                                let status = match playlist.phase {
+                                    PlaylistPhase::Compiling => String::from("Compiling"),
+                                    PlaylistPhase::FadingOut { .. } => String::from("Fading"),
+                                    PlaylistPhase::Showing if playlist.paused => String::from("Paused"),
+                                    PlaylistPhase::Showing => format!("{:.0} s left", playlist.remaining()),
+                                };
+                                imgui::ProgressBar::new((playlist.elapsed / entry.duration).min(1.0))
+                                    .size([gui_width, 0.0])
+                                    .overlay_text(&im_str!("{}", status))
+                                    .build(&ui);
+                                let button_width = (gui_width - 2.0 * ui.clone_style().item_spacing[0]) / 3.0;
+                                if ui.button(im_str!("Previous##Playlist"), [button_width, 25.0]) {
+                                    playlist_action = Some(Action::PreviousPlaylistEntry);
+                                }
+                                ui.same_line(0.0);
+                                let pause_label = match playlist.paused {
+                                    true => im_str!("Resume##Playlist"),
+                                    false => im_str!("Pause##Playlist"),
+                                };
+                                if ui.button(pause_label, [button_width, 25.0]) {
+                                    playlist_action = Some(Action::TogglePlaylistPause);
+                                }
+                                ui.same_line(0.0);
+                                if ui.button(im_str!("Next##Playlist"), [button_width, 25.0]) {
+                                    playlist_action = Some(Action::NextPlaylistEntry);
+                                }
+                                ui.checkbox(im_str!("Shuffle##Playlist"), &mut playlist.shuffle);
+                                imgui::Drag::new(im_str!("Fade (s)##Playlist"))
+                                    .range(0.0..=60.0)
+                                    .speed(0.05)
+                                    .build(&ui, &mut playlist.fade);
+                                if ui.is_item_hovered() {
+                                    ui.tooltip_text("Fades out to the master fade color and back in between shaders. Cuts straight to the next shader with 0.");
+                                }
+                                for (shader, reason) in &playlist.failures {
+                                    ui.text_colored([1.0, 0.8, 0.0, 1.0], format!("Skipped: {}", shader));
+                                    if ui.is_item_hovered() {
+                                        ui.tooltip_text(reason);
+                                    }
+                                }
+                                if ui.button(im_str!("Stop##Playlist"), [gui_width, 25.0]) {
+                                    playlist_action = Some(Action::StopPlaylist);
+                                }
+                            }
+                            None => {
+                                if ui.button(im_str!("Start##Playlist"), [gui_width, 25.0]) {
+                                    playlist_action = Some(Action::StartPlaylist);
+                                }
+                                if let Some(msg) = playlist_error_msg {
+                                    ui.text_colored([1.0, 0.325, 0.286, 1.0], msg);
+                                }
+                            }
+                        }
+                    }
+                    //---------------------------------
+                    if imgui::CollapsingHeader::new(im_str!("Input Image"))
+                        .default_open(false)
+                        .open_on_arrow(true)
+                        .open_on_double_click(true)
+                        .build(&ui)
+                    {
+                        input_image_file_changed = ui
+                            .input_text(im_str!("File##InputImage"), &mut input_image_file)
+                            .build();
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Bound as texture_0 in set 1.");
+                        }
+                        load_input_image_button_pressed =
+                            ui.button(im_str!("Load##InputImage"), [gui_width, 25.0]);
+                        if let Some(msg) = input_image_error_msg {
+                            ui.text_colored([1.0, 0.325, 0.286, 1.0], msg);
+                        } else if let Some(file) = input_image_loaded {
+                            ui.text_wrapped(&im_str!("Loaded: {}", file));
+                        }
+                    }
+                    //---------------------------------
+                    if imgui::CollapsingHeader::new(im_str!("Noise Texture"))
+                        .default_open(false)
+                        .open_on_arrow(true)
+                        .open_on_double_click(true)
+                        .build(&ui)
+                    {
+                        for a_type in NoiseType::ALL.iter() {
+                            noise_changed |=
+                                ui.radio_button(&im_str!("{}##Noise", a_type.name()), noise_type, *a_type);
+                            ui.same_line(0.0);
+                        }
+                        ui.new_line();
+                        noise_changed |= ui.input_int(im_str!("Seed##Noise"), &mut noise_seed).build();
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Bound after the ramps in set 1. The same type and seed always give the same noise.");
+                        }
+                        reseed_noise_button_pressed = ui.button(im_str!("Reseed##Noise"), [gui_width, 25.0]);
+                    }
+                    //---------------------------------
+                    if imgui::CollapsingHeader::new(im_str!("Mirror Outputs"))
+                        .default_open(false)
+                        .open_on_arrow(true)
+                        .open_on_double_click(true)
+                        .build(&ui)
+                    {
+                        add_mirror_button_pressed =
+                            ui.button(im_str!("Add Mirror Window"), [gui_width, 25.0]);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Open another window showing the canvas, e.g. one per projector.");
+                        }
+                        for (i, (id, settings)) in mirror_outputs.iter_mut().enumerate() {
+                            let mirror_token = ui.push_id(i as i32);
+                            ui.separator();
+                            ui.text(format!("Mirror {}", i + 1));
+                            if mirror_settings_ui(&ui, settings) {
+                                modified_mirrors.push((*id, settings.clone()));
+                            }
+                            if ui.small_button(im_str!("Close")) {
+                                closed_mirror = Some(*id);
+                            }
+                            mirror_token.pop(&ui);
+                        }
+                    }
+                    //---------------------------------
+                    if imgui::CollapsingHeader::new(im_str!("Recording Options"))
+                        .default_open(true)
+                        .open_on_arrow(true)
+                        .open_on_double_click(true)
+                        .build(&ui)
+                    {
+                        dimension_input_ui(
+                            &ui,
+                            im_str!("Width##Movie"),
+                            _recording_width,
+                            max_texture_dimension,
+                        );
+                        dimension_input_ui(
+                            &ui,
+                            im_str!("Height##Movie"),
+                            _recording_height,
+                            max_texture_dimension,
+                        );
+                        if let Some(suggestion) = recording_aspect_suggestion {
+                            ui.text_colored(
+                                [1.0, 0.8, 0.0, 1.0],
+                                "The recording's aspect differs from the canvas.",
+                            );
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text("Movies are framed as the recording resolution says, so they will show more or less than the canvas window does.");
+                            }
+                            match_canvas_aspect_pressed = ui.button(
+                                &ImString::new(format!(
+                                    "Use {} x {}##MatchAspect",
+                                    suggestion.x, suggestion.y
+                                )),
+                                [gui_width, 25.0],
+                            );
+                        }
                         ui.input_int(im_str!("Framerate##Movie"), movie_framerate)
                             .build();
+                        let movie_framerate_valid = framerate_valid(*movie_framerate);
+                        if !movie_framerate_valid {
+                            ui.text_colored(
+                                [1.0, 0.8, 0.0, 1.0],
+                                "The framerate must be at least 1 fps.",
+                            );
+                        }
+                        ui.checkbox(
+                            im_str!("Lock Canvas Resolution"),
+                            lock_resolution_while_recording,
+                        );
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("While recording, render the canvas at the recording resolution, letterboxed in its window, so resizing it can't change the composition.");
+                        }
+                        ui.checkbox(im_str!("Fixed Duration##Movie"), movie_fixed_duration);
+                        if *movie_fixed_duration {
+                            let mut duration = *movie_duration as f32;
+                            if ui
+                                .input_float(im_str!("Duration (s)##Movie"), &mut duration)
+                                .build()
+                            {
+                                *movie_duration = duration.max(0.0) as f64;
+                            }
+                            // The movie can only be a whole number of frames long.
+                            let frames = whole_frame_count(*movie_duration, *movie_framerate);
+                            let actual_duration = frames as f64 / (*movie_framerate).max(1) as f64;
+                            if (actual_duration - *movie_duration).abs() > 1e-6 {
+                                ui.text_colored(
+                                    [1.0, 0.8, 0.0, 1.0],
+                                    format!(
+                                        "Adjusted to {:.3} s, {} frames",
+                                        actual_duration, frames
+                                    ),
+                                );
+                            } else {
+                                ui.text(format!("{} frames", frames));
+                            }
+                        }
+                        if recorder.is_none() {
+                            ui.text("Codec:");
+                            let mut codec_changed = false;
+                            for codec in MovieCodec::ALL.iter() {
+                                codec_changed |= ui.radio_button(
+                                    &im_str!("{}##Codec", codec.name()),
+                                    movie_codec,
+                                    *codec,
+                                );
+                            }
+                            // Follow the codec to its usual container, unless the chosen one works too.
+                            if codec_changed && !movie_codec.supports(*movie_container) {
+                                *movie_container = movie_codec.containers()[0];
+                            }
+                            ui.text("Container:");
+                            for container in MovieContainer::ALL.iter() {
+                                ui.same_line(0.0);
+                                ui.radio_button(
+                                    &im_str!("{}##Container", container.extension()),
+                                    movie_container,
+                                    *container,
+                                );
+                            }
+                            if !movie_codec.supports(*movie_container) {
+                                let supported: Vec<_> = movie_codec
+                                    .containers()
+                                    .iter()
+                                    .map(|c| c.extension())
+                                    .collect();
+                                ui.text_colored(
+                                    [1.0, 0.8, 0.0, 1.0],
+                                    format!(
+                                        "{} can't be written to {}, use {}.",
+                                        movie_codec.name(),
+                                        movie_container.extension(),
+                                        supported.join(" or ")
+                                    ),
+                                );
+                            }
+                            if *movie_container == MovieContainer::PngSequence {
+                                sequence_numbering_ui(
+                                    &ui,
+                                    sequence_numbering,
+                                    &recording_stem,
+                                    movie_frame_count,
+                                    *movie_framerate,
+                                );
+                            }
+                            ui.checkbox(im_str!("Discard Warm-up Frame"), recording_warm_up);
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text(
+                                    "Render one frame before recording starts so the movie doesn't begin with a black frame.",
+                                );
+                            }
+                            ui.checkbox(im_str!("Only Record Changes"), record_changes_only);
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text("Repeat the last frame instead of frames that barely changed, so slow pieces take far less space with HEVC or VP9. Timing is unaffected.");
+                            }
+                            if *record_changes_only {
+                                imgui::Slider::new(im_str!("Change Threshold"))
+                                    .range(0.0..=10.0)
+                                    .display_format(im_str!("%.2f levels"))
+                                    .build(&ui, change_threshold);
+                                if ui.is_item_hovered() {
+                                    ui.tooltip_text("Average difference from the last frame written, in 8-bit levels, below which a frame counts as unchanged.");
+                                }
+                            }
+                            ui.checkbox(im_str!("Dry Run"), recording_dry_run);
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text("Render and count frames as usual, but skip FFmpeg and write nothing.");
+                            }
+                        }
 
                         let file_input =
                             ui.input_text(im_str!("Filename##Movie"), &mut recording_filename);
@@ -198,25 +1374,207 @@ impl Dashboard {
                                 start_record_button_pressed =
                                     ui.button(im_str!("Start##Recording"), [gui_width, 25.0]);
                             }
-                        } else {
+                            let hold_label = match time_held {
+                                true => im_str!("Release Time"),
+                                false => im_str!("Hold Time"),
+                            };
+                            hold_time_button_pressed = ui.button(hold_label, [gui_width, 25.0]);
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text("Holds u_time still while frames keep being recorded, for a freeze frame, then carries on from the held moment. Shortcut: H");
+                            }
+                        } else if movie_framerate_valid {
                             init_recorder_button_pressed =
                                 ui.button(im_str!("Initialize##Recording"), [gui_width, 25.0]);
                         }
+                        if let Some(msg) = recording_error_msg {
+                            ui.text_colored([1.0, 0.325, 0.286, 1.0], msg);
+                        }
+                        if let (Some(rec), Some(start), true) =
+                            (recorder, recording_start_time, *recording_in_progress)
+                        {
+                            recording_progress_ui(&ui, rec, start, movie_frame_count, gui_width);
+                        }
+                        if recorder.is_some_and(|rec| rec.is_dry_run()) {
+                            ui.text_colored([1.0, 0.8, 0.0, 1.0], "Dry run, nothing is written.");
+                        }
+                        if let Some(dropped) = recorder.map(|rec| rec.frames_dropped) {
+                            if dropped > 0 {
+                                ui.text_colored(
+                                    [1.0, 0.8, 0.0, 1.0],
+                                    format!("{} frames of the wrong size dropped", dropped),
+                                );
+                            }
+                        }
+                    }
+                    //---------------------------------
+                    if let Some(command) = last_export_command {
+                        if imgui::CollapsingHeader::new(im_str!("Reproduce Last Export"))
+                            .default_open(false)
+                            .open_on_arrow(true)
+                            .open_on_double_click(true)
+                            .build(&ui)
+                        {
+                            ui.text_wrapped(&ImString::new(command));
+                            copy_export_command_pressed =
+                                ui.button(im_str!("Copy Command"), [gui_width, 25.0]);
+                        }
                     }
                     //---------------------------------
-                    if !user_uniforms.is_empty() {
-                        if imgui::CollapsingHeader::new(im_str!("Uniforms"))
+                    if (!user_uniforms.is_empty() || !user_ramps.is_empty())
+                        && imgui::CollapsingHeader::new(im_str!("Uniforms"))
                             .default_open(true)
                             .open_on_arrow(true)
                             .open_on_double_click(true)
                             .build(&ui)
+                    {
+                        ui.checkbox(im_str!("Autosave Uniforms"), autosave_uniforms);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Save the uniform values on exit and restore them the next time this shader is opened. Uncheck to start from the shader's defaults next time.");
+                        }
+                        if user_uniforms.values().any(|u| u.is_seed())
+                            && ui.button(im_str!("Reroll Seeds (R)"), [gui_width, 25.0])
                         {
-                            for (_name, uniform) in user_uniforms {
-                                uniforms::update_user_uniform_ui(&ui, uniform);
+                            reroll_seeds_button_pressed = true;
+                        }
+                        if user_uniforms.values().any(|u| u.is_randomizable()) {
+                            randomize_button_pressed =
+                                ui.button(im_str!("Randomize"), [gui_width, 25.0]);
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text("Pick new values for the automatable, unlocked uniforms within their min and max, from a new seed.");
+                            }
+                            ui.input_int(im_str!("Seed##Randomize"), &mut randomize_seed)
+                                .build();
+                            ui.same_line(0.0);
+                            apply_randomize_seed_pressed = ui.small_button(im_str!("Apply"));
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text("Randomize from this seed again, e.g. to get back a look found by randomizing.");
+                            }
+                        }
+                        if !user_uniforms.is_empty() {
+                            let snapshot_label =
+                                match (has_uniform_snapshot, showing_uniform_snapshot) {
+                                    (false, _) => im_str!("Snapshot Uniforms (A)"),
+                                    (true, false) => im_str!("Show Snapshot (A)"),
+                                    (true, true) => im_str!("Show Tweaked Values (A)"),
+                                };
+                            uniform_snapshot_button_pressed =
+                                ui.button(snapshot_label, [gui_width, 25.0]);
+                            if has_uniform_snapshot {
+                                clear_snapshot_button_pressed = ui.button(
+                                    im_str!("Clear Snapshot"),
+                                    [gui_width, 25.0],
+                                );
+                                if ui.is_item_hovered() {
+                                    ui.tooltip_text("Keeps the values currently shown.");
+                                }
+                            }
+                        }
+                        if !scene_selector_candidates.is_empty() {
+                            uniform_scenes_ui(&ui, uniform_scenes, &scene_selector_candidates);
+                        }
+                        if uniforms_reordered {
+                            reset_uniform_order_pressed =
+                                ui.button(im_str!("Reset Order##Uniforms"), [gui_width, 25.0]);
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text("Show the uniforms in the order of the uniforms JSON file again.");
+                            }
+                        }
+                        for (index, name) in uniform_display_order.iter().enumerate() {
+                            let uniform = match user_uniforms.get_mut(name) {
+                                Some(uniform) => uniform,
+                                None => continue,
+                            };
+                            if let Some(from) = uniform_drag_handle_ui(&ui, name, index) {
+                                moved_uniform = Some((from, index));
+                            }
+                            ui.same_line(0.0);
+                            if uniforms::update_user_uniform_ui(&ui, uniform) {
+                                modified_uniforms.push(name.clone());
                             }
+                            // Only numbers and bools can follow an expression.
+                            if uniform.scalar_value().is_none()
+                                && !matches!(uniform.inherent_type, UserUniformType::Bool)
+                            {
+                                continue;
+                            }
+                            let expression = uniform_expressions.get(name);
+                            let mut source = ImString::with_capacity(256);
+                            if let Some(expression) = expression {
+                                source.push_str(&expression.source);
+                            }
+                            if ui
+                                .input_text(&ImString::new(format!("= ##{}", name)), &mut source)
+                                .build()
+                            {
+                                expression_edits.push((name.clone(), String::from(source.to_str())));
+                            }
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text("Expression of t, seconds, and frame driving this uniform. Leave empty to set it by hand.");
+                            }
+                            if let Some(error) = expression.and_then(|e| e.error.as_ref()) {
+                                ui.text_colored([1.0, 0.325, 0.286, 1.0], error);
+                            }
+                        }
+                        for ramp in user_ramps.iter_mut() {
+                            if uniforms::update_ramp_uniform_ui(&ui, ramp) {
+                                modified_ramps.push(ramp.clone());
+                            }
+                        }
+                    }
+                    //---------------------------------
+                    if imgui::CollapsingHeader::new(im_str!("Session Timeline"))
+                        .default_open(false)
+                        .open_on_arrow(true)
+                        .open_on_double_click(true)
+                        .build(&ui)
+                    {
+                        ui.text(format!("Recorded changes: {}", timeline_changes));
+                        timeline_filename_changed = ui
+                            .input_text(im_str!("Filename##Timeline"), &mut timeline_filename)
+                            .build();
+                        let half_width = (gui_width - 8.0) / 2.0;
+                        if ui.button(im_str!("Export CSV##Timeline"), [half_width, 25.0]) {
+                            timeline_action = Some(Action::ExportTimelineCsv);
+                        }
+                        ui.same_line(0.0);
+                        if ui.button(im_str!("Export JSON##Timeline"), [half_width, 25.0]) {
+                            timeline_action = Some(Action::ExportTimelineJson);
+                        }
+                        if replaying_timeline {
+                            ui.text_colored([1.0, 0.8, 0.0, 1.0], im_str!("Replaying..."));
+                            if ui.button(im_str!("Stop Replay##Timeline"), [gui_width, 25.0]) {
+                                timeline_action = Some(Action::StopTimelineReplay);
+                            }
+                        } else {
+                            if ui.button(im_str!("Replay##Timeline"), [half_width, 25.0]) {
+                                timeline_action = Some(Action::ReplayTimeline);
+                            }
+                            ui.same_line(0.0);
+                            if ui.button(im_str!("Replay From File##Timeline"), [half_width, 25.0])
+                            {
+                                timeline_action = Some(Action::ReplayTimelineFromFile);
+                            }
+                        }
+                        if ui.button(im_str!("Clear##Timeline"), [gui_width, 25.0]) {
+                            timeline_action = Some(Action::ClearTimeline);
                         }
                     }
                     //---------------------------------
+                    if developer_mode
+                        && imgui::CollapsingHeader::new(im_str!("Developer"))
+                            .default_open(false)
+                            .open_on_arrow(true)
+                            .open_on_double_click(true)
+                            .build(&ui)
+                    {
+                        ui.checkbox(im_str!("imgui Demo Window"), show_imgui_demo);
+                        ui.checkbox(im_str!("imgui Metrics Window"), show_imgui_metrics);
+                    }
+                    // The cursor now sits one item spacing below the last widget.
+                    let style = ui.clone_style();
+                    controls_content_height =
+                        ui.cursor_pos()[1] - style.item_spacing[1] + style.window_padding[1];
+                    //---------------------------------
                     ui.popup_modal(im_str!("Shader Recompilation")).build(|| {
                         if shader_compilation_error_msg.is_none() {
                             ui.close_current_popup();
@@ -233,7 +1591,7 @@ impl Dashboard {
 
                     // Popup modal to display while recorder is initializing.
                     ui.popup_modal(im_str!("Recorder Processing")).build(|| {
-                        if recorder_ready {
+                        if recorder_ready || recording_error_msg.is_some() {
                             ui.close_current_popup();
                         }
                         ui.text_colored([1.0, 0.325, 0.286, 1.0], im_str!("Recorder###Modal"));
@@ -248,49 +1606,298 @@ impl Dashboard {
                     }
                 });
 
+            if *show_imgui_demo {
+                ui.show_demo_window(show_imgui_demo);
+            }
+            if *show_imgui_metrics {
+                ui.show_metrics_window(show_imgui_metrics);
+            }
+
+            if self.state.command_palette_open {
+                let mut query = ImString::with_capacity(256);
+                query.push_str(&self.state.command_palette_query);
+                let grab_focus = self.state.command_palette_grab_focus;
+                let mut palette_open = true;
+                let mut chosen_action = None;
+                imgui::Window::new(im_str!("Command Palette"))
+                    .size([gui_width, 0.0], Condition::Always)
+                    .position([5.0, 30.0], Condition::Always)
+                    .opened(&mut palette_open)
+                    .collapsible(false)
+                    .always_auto_resize(true)
+                    .build(&ui, || {
+                        if grab_focus {
+                            ui.set_keyboard_focus_here(FocusedWidget::Next);
+                        }
+                        let submitted = ui
+                            .input_text(im_str!("##CommandPaletteQuery"), &mut query)
+                            .enter_returns_true(true)
+                            .build();
+                        let matches = actions::filter_actions(query.to_str());
+                        for (i, info) in matches.iter().enumerate() {
+                            let label = match info.shortcut {
+                                Some(key) => im_str!("{} ({:?})", info.name, key),
+                                None => im_str!("{}", info.name),
+                            };
+                            if imgui::Selectable::new(&label).selected(i == 0).build(&ui) {
+                                chosen_action = Some(info.action);
+                            }
+                        }
+                        if submitted && chosen_action.is_none() {
+                            chosen_action = matches.first().map(|info| info.action);
+                        }
+                    });
+                self.state.command_palette_grab_focus = false;
+                self.state.command_palette_query = String::from(query.to_str());
+                if let Some(action) = chosen_action {
+                    actions.push(action);
+                }
+                if chosen_action.is_some() || !palette_open {
+                    self.state.command_palette_open = false;
+                    self.state.command_palette_query.clear();
+                }
+            }
+
             if pause_button_pressed {
-                self.state.paused = !self.state.paused;
-                self.transmitter
-                    .send(DashboardMessage::PausePlayChanged)
-                    .unwrap();
+                actions.push(Action::TogglePause);
+            }
+            if accumulation_changed {
+                self.state.accumulation_samples = 0;
+                self.transmitter.send(DashboardMessage::AccumulationChanged(
+                    self.state.accumulation_enabled,
+                ));
+            }
+            if reset_accumulation_button_pressed {
+                actions.push(Action::ResetAccumulation);
+            }
+            if histogram_changed {
+                if !self.state.histogram_enabled {
+                    self.state.histogram = None;
+                }
+                self.transmitter.send(DashboardMessage::HistogramEnabled(
+                    self.state.histogram_enabled,
+                ));
+            }
+            if jitter_changed {
+                self.transmitter.send(DashboardMessage::JitterChanged {
+                    enabled: self.state.jitter_enabled,
+                    frozen: self.state.jitter_frozen,
+                });
+            }
+            if save_settings_button_pressed {
+                actions.push(Action::SaveShaderSettings);
+            }
+            if freeze_button_pressed {
+                actions.push(Action::ToggleFreezeTime);
+            }
+            if master_fade_button_pressed {
+                actions.push(Action::ToggleMasterFade);
+            }
+            if master_fade_level_changed || master_fade_color_changed {
+                // Dragging the slider jumps to that level, a color change keeps any fade in progress going.
+                let (target, duration) = match master_fade_level_changed {
+                    true => (master_fade_level, 0.0),
+                    false => (
+                        self.state.master_faded_out as u32 as f32,
+                        self.state.master_fade_duration,
+                    ),
+                };
+                self.state.master_faded_out = target > 0.0;
+                self.transmitter.send(DashboardMessage::MasterFade {
+                    target,
+                    color: self.state.master_fade_color,
+                    duration,
+                });
             }
             if titlebar_button_pressed {
-                self.state.show_titlebar = !self.state.show_titlebar;
+                actions.push(Action::ToggleTitlebar);
+            }
+            if hold_time_button_pressed {
+                actions.push(Action::ToggleTimeHold);
+            }
+            if let (true, Some(suggestion)) =
+                (match_canvas_aspect_pressed, recording_aspect_suggestion)
+            {
+                self.state.recording_resolution = suggestion;
+            }
+            if borderless_button_pressed {
+                actions.push(Action::ToggleBorderless);
+            }
+            self.state.controls_content_height = controls_content_height;
+            if fit_window_button_pressed {
+                actions.push(Action::FitWindowToContent);
+            }
+            if mouse_smoothing_changed {
                 self.transmitter
-                    .send(DashboardMessage::TitlebarStatusChanged)
-                    .unwrap();
+                    .send(DashboardMessage::MouseSmoothingChanged(
+                        self.state.mouse_smoothing,
+                    ));
+            }
+            if hold_frame_on_error_changed {
+                self.transmitter.send(DashboardMessage::HoldFrameOnError(
+                    self.state.hold_frame_on_error,
+                ));
+            }
+            if checkerboard_changed {
+                self.transmitter.send(DashboardMessage::CheckerboardChanged(
+                    self.state.checkerboard_enabled,
+                ));
+            }
+            if copy_export_command_pressed {
+                let command = self.state.last_export_command.clone().unwrap_or_default();
+                match arboard::Clipboard::new()
+                    .and_then(|mut clipboard| clipboard.set_text(command))
+                {
+                    Ok(_) => info!("Copied the export command to the clipboard."),
+                    Err(err) => warn!("Could not copy the export command: {}", err),
+                }
+            }
+            if flip_changed {
+                self.transmitter.send(DashboardMessage::FlipOutput {
+                    horizontal: self.state.flip_horizontal,
+                    vertical: self.state.flip_vertical,
+                    captures: self.state.flip_captures,
+                });
+            }
+            if stereo_changed {
+                self.transmitter.send(DashboardMessage::StereoChanged(
+                    self.state.stereo_settings(),
+                ));
+            }
+            if let Some(view) = debug_view_selected {
+                self.state.debug_view = view;
+                self.transmitter.send(DashboardMessage::DebugView(view));
+            }
+            if capture_reference_button_pressed {
+                actions.push(Action::CaptureReference);
+            }
+            if clear_reference_button_pressed {
+                actions.push(Action::ClearReference);
+            }
+            if compare_changed {
+                self.transmitter.send(DashboardMessage::CompareChanged(
+                    self.state.compare_settings(),
+                ));
+            }
+            if aa_samples_changed {
+                self.transmitter
+                    .send(DashboardMessage::AntialiasingChanged(self.state.aa_samples));
             }
             if painting_filename_changed {
                 self.state.painting_filename = String::from(painting_filename.to_str());
             }
+            if copy_frame_button_pressed {
+                actions.push(Action::CopyFrameToClipboard);
+            }
+            if create_painting_at_time_pressed {
+                actions.push(Action::CreatePaintingAtTime);
+            }
             if create_painting_button_pressed {
-                if *pause_while_painting {
-                    self.transmitter.send(DashboardMessage::Pause).unwrap();
-                }
+                actions.push(Action::CreatePainting);
+            }
+            if create_variation_sheet_button_pressed {
+                actions.push(Action::CreateVariationSheet);
+            }
+            for ramp in modified_ramps {
                 self.transmitter
-                    .send(DashboardMessage::PaintingRenderRequested(UIntVector2::new(
-                        self.state.painting_resolution.x as u32,
-                        self.state.painting_resolution.y as u32,
-                    )))
-                    .unwrap();
+                    .send(DashboardMessage::RampUpdatedViaGUI(ramp));
+            }
+            self.state.modified_uniforms.extend(modified_uniforms);
+            if let Some((from, to)) = moved_uniform {
+                self.state.move_uniform(from, to);
+            }
+            if reset_uniform_order_pressed {
+                self.state.uniform_display_order.clear();
+            }
+            for (name, source) in expression_edits {
+                self.state.uniform_expressions.set(&name, &source);
             }
             if recording_filename_changed {
                 self.state.recording_filename = String::from(recording_filename.to_str());
             }
-            if init_recorder_button_pressed && self.recorder.is_none() {
-                self.recorder = Some(Recorder::new(
-                    self.state.recording_resolution.x as u32,
-                    self.state.recording_resolution.y as u32,
-                    MOVIE_TEXTURE_FORMAT,
-                    *movie_framerate as u32,
-                    format!("{}.mp4", self.state.recording_filename),
+            if piece_name_changed {
+                self.state.set_piece_name(piece_name.to_str());
+            }
+            if piece_name_changed || piece_name_in_title_changed {
+                self.transmitter.send(DashboardMessage::WindowTitleChanged(
+                    self.state.canvas_window_title(),
+                ));
+            }
+            if reroll_seeds_button_pressed {
+                actions.push(Action::RerollSeeds);
+            }
+            self.state.randomize_seed = randomize_seed as u32;
+            if randomize_button_pressed {
+                actions.push(Action::RandomizeUniforms);
+            }
+            if uniform_snapshot_button_pressed {
+                actions.push(Action::ToggleUniformSnapshot);
+            }
+            if clear_snapshot_button_pressed {
+                actions.push(Action::ClearUniformSnapshot);
+            }
+            if open_shader_file_changed {
+                self.state.open_shader_file = String::from(open_shader_file.to_str());
+            }
+            if open_shader_button_pressed {
+                actions.push(Action::OpenShader);
+            }
+            if new_shader_file_changed {
+                self.state.new_shader_file = String::from(new_shader_file.to_str());
+            }
+            if new_shader_button_pressed {
+                actions.push(Action::NewShaderFromTemplate);
+            }
+            if batch_directories_changed {
+                self.state.batch_directory = String::from(batch_directory.to_str());
+                self.state.batch_output_directory = String::from(batch_output_directory.to_str());
+            }
+            if let Some(action) = batch_action {
+                actions.push(action);
+            }
+            if playlist_file_changed {
+                self.state.playlist_file = String::from(playlist_file.to_str());
+            }
+            if let Some(action) = playlist_action {
+                actions.push(action);
+            }
+            if input_image_file_changed {
+                self.state.input_image_file = String::from(input_image_file.to_str());
+            }
+            if reseed_noise_button_pressed {
+                noise_seed = utils::random_u64() as i32;
+                noise_changed = true;
+            }
+            if noise_changed {
+                self.state.noise_seed = noise_seed as u32;
+                self.transmitter.send(DashboardMessage::NoiseChanged {
+                    noise_type: self.state.noise_type,
+                    seed: self.state.noise_seed,
+                });
+            }
+            if load_input_image_button_pressed {
+                self.transmitter.send(DashboardMessage::InputImageSelected(
+                    self.state.input_image_file.clone(),
                 ));
             }
-            if start_record_button_pressed {
-                self.state.recording_in_progress = true;
-            } else if stop_record_button_pressed {
-                self.recorder.as_mut().unwrap().stop();
-                self.state.recording_in_progress = false;
+            for (id, settings) in modified_mirrors {
+                self.transmitter
+                    .send(DashboardMessage::MirrorSettingsChanged(id, settings));
+            }
+            if add_mirror_button_pressed {
+                actions.push(Action::AddMirrorWindow);
+            }
+            if timeline_filename_changed {
+                self.state.timeline_filename = String::from(timeline_filename.to_str());
+            }
+            if let Some(action) = timeline_action {
+                actions.push(action);
+            }
+            if init_recorder_button_pressed {
+                actions.push(Action::InitializeRecorder);
+            }
+            if start_record_button_pressed || stop_record_button_pressed {
+                actions.push(Action::ToggleRecording);
             }
         }
 
@@ -326,6 +1933,34 @@ impl Dashboard {
         }
 
         self.queue.submit(Some(encoder.finish()));
+
+        for action in actions {
+            self.run_action(action);
+        }
+        if preview_changed {
+            if !self.state.preview_enabled {
+                self.clear_thumbnail();
+            }
+            self.transmitter
+                .send(DashboardMessage::ThumbnailSizeChanged(
+                    match self.state.preview_enabled {
+                        true => Some(self.state.preview_size),
+                        false => None,
+                    },
+                ));
+        }
+        if let Some(framing) = framing_pressed {
+            self.set_framing(framing);
+        }
+        if relaunch_canvas_pressed {
+            self.request_canvas_relaunch();
+        }
+        if apply_randomize_seed_pressed {
+            self.randomize_uniforms();
+        }
+        if let Some(id) = closed_mirror {
+            self.mirror_window_closed(id);
+        }
     }
 
     /// Receives events from the winit event queue and responds appropriately.
@@ -336,25 +1971,39 @@ impl Dashboard {
             }
             WindowEvent::Resized(physical_size) => {
                 self.size = physical_size;
-                self.sc_desc = wgpu::SwapChainDescriptor {
-                    usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
-                    format: wgpu::TextureFormat::Bgra8UnormSrgb,
-                    width: physical_size.width as u32,
-                    height: physical_size.height as u32,
-                    present_mode: wgpu::PresentMode::Mailbox,
-                };
+                // Skip swap chain recreation while minimized, a zero-sized swap chain is invalid.
+                // Rendering is skipped until the window has a nonzero size again.
+                if physical_size.width == 0 || physical_size.height == 0 {
+                    return;
+                }
+                // Only the size changes, the swap chain keeps the format and present mode it was created with.
+                self.sc_desc.width = physical_size.width;
+                self.sc_desc.height = physical_size.height;
                 self.swap_chain = self.device.create_swap_chain(&self.surface, &self.sc_desc);
+                self.update_swap_chain_vram();
             }
+            WindowEvent::ModifiersChanged(modifiers) => self.modifiers = modifiers,
             WindowEvent::KeyboardInput { input, .. } => match input {
                 KeyboardInput {
                     state: ElementState::Pressed,
-                    virtual_keycode: Some(VirtualKeyCode::Space),
+                    virtual_keycode: Some(VirtualKeyCode::P),
+                    ..
+                } if self.modifiers.ctrl() || self.modifiers.logo() => {
+                    self.state.command_palette_open = !self.state.command_palette_open;
+                    self.state.command_palette_grab_focus = self.state.command_palette_open;
+                }
+                KeyboardInput {
+                    state: ElementState::Pressed,
+                    virtual_keycode: Some(key),
                     ..
                 } => {
-                    self.state.paused = !self.state.paused;
-                    self.transmitter
-                        .send(DashboardMessage::PausePlayChanged)
-                        .unwrap();
+                    // Don't steal keys from text fields.
+                    if self.imgui_context.io().want_text_input {
+                        return;
+                    }
+                    if let Some(action) = actions::action_for_key(key) {
+                        self.run_action(action);
+                    }
                 }
                 _ => (),
             },
@@ -363,7 +2012,468 @@ impl Dashboard {
     }
 
     pub fn imgui_input(&mut self, event: &winit::event::Event<()>) {
+        if super::is_user_input(event) {
+            self.note_user_input();
+        }
+        if let Event::WindowEvent {
+            window_id,
+            event: WindowEvent::Focused(focused),
+        } = event
+        {
+            if *window_id == self.window.id() {
+                self.state.dashboard_focused = *focused;
+            }
+        }
         self.imgui_platform
             .handle_event(self.imgui_context.io_mut(), &self.window, event);
     }
 }
+
+/// Drag widget for a [Vector2], returns whether it changed.
+fn drag_vector2_ui(ui: &imgui::Ui, label: &ImStr, value: &mut Vector2, min: f32, max: f32) -> bool {
+    let mut components = [value.x, value.y];
+    let changed = imgui::Drag::new(label)
+        .range(min..=max)
+        .speed(0.001)
+        .build_array(ui, &mut components);
+    *value = Vector2::new(components[0], components[1]);
+    changed
+}
+
+/// Content, timing, crop, warp and edge blend controls of a mirror output, returns whether anything changed.
+fn mirror_settings_ui(ui: &imgui::Ui, settings: &mut MirrorSettings) -> bool {
+    let mut changed = ui.checkbox(im_str!("Show Overlays"), &mut settings.show_overlays);
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Show the debug view and reference overlay like the canvas window, e.g. for an operator's preview. Unchecked shows the clean output.");
+    }
+    ui.text("Present Mode");
+    for mode in MIRROR_PRESENT_MODES.iter() {
+        ui.same_line(0.0);
+        changed |= ui.radio_button(
+            &im_str!("{}##PresentMode", present_mode_name(*mode)),
+            &mut settings.present_mode,
+            *mode,
+        );
+    }
+    changed |= imgui::Drag::new(im_str!("Max Framerate"))
+        .range(0.0..=240.0)
+        .speed(0.5)
+        .build(ui, &mut settings.max_framerate);
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Most frames shown per second in this window. 0 shows every canvas frame.");
+    }
+    changed |= drag_vector2_ui(ui, im_str!("Crop Offset"), &mut settings.offset, 0.0, 1.0);
+    changed |= drag_vector2_ui(ui, im_str!("Crop Size"), &mut settings.size, 0.01, 1.0);
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Region of the canvas shown, in normalized coordinates. Overlap neighbours for edge blending.");
+    }
+    changed |= ui.checkbox(im_str!("Fullscreen"), &mut settings.fullscreen);
+
+    imgui::TreeNode::new(im_str!("Warp")).build(ui, || {
+        let corner_labels = [
+            im_str!("Top Left"),
+            im_str!("Top Right"),
+            im_str!("Bottom Right"),
+            im_str!("Bottom Left"),
+        ];
+        for (corner, label) in settings.corners.iter_mut().zip(corner_labels.iter()) {
+            changed |= drag_vector2_ui(ui, label, corner, -1.0, 2.0);
+        }
+        let mut grid_size = settings.warp_grid_size as i32;
+        if ui.input_int(im_str!("Grid Points"), &mut grid_size).build() {
+            settings.set_warp_grid_size(grid_size.max(2) as usize);
+            changed = true;
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Control points per side. Changing this clears the grid offsets.");
+        }
+        let n = settings.warp_grid_size;
+        for (i, offset) in settings.warp_offsets.iter_mut().enumerate() {
+            changed |= drag_vector2_ui(
+                ui,
+                &im_str!("Point {}, {}", i % n, i / n),
+                offset,
+                -1.0,
+                1.0,
+            );
+        }
+        if ui.small_button(im_str!("Reset Warp")) {
+            settings.reset_warp();
+            changed = true;
+        }
+    });
+    imgui::TreeNode::new(im_str!("Edge Blend")).build(ui, || {
+        let labels = [
+            im_str!("Left"),
+            im_str!("Right"),
+            im_str!("Top"),
+            im_str!("Bottom"),
+        ];
+        for (width, label) in settings.blend_widths.iter_mut().zip(labels.iter()) {
+            changed |= imgui::Drag::new(label)
+                .range(0.0..=0.5)
+                .speed(0.001)
+                .build(ui, width);
+        }
+        changed |= imgui::Drag::new(im_str!("Exponent"))
+            .range(0.1..=4.0)
+            .speed(0.01)
+            .build(ui, &mut settings.blend_exponent);
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Shapes the falloff so overlapping projectors sum to even brightness.");
+        }
+    });
+    changed
+}
+
+/// The user uniforms buffer as the Canvas packs it, one row per uniform or run of padding, with each row's bytes in
+/// upload order (little endian) and the value they hold.
+fn uniform_bytes_ui(
+    ui: &imgui::Ui,
+    slots: &[UniformSlot],
+    uniforms: &HashMap<String, uniforms::UserUniform>,
+) {
+    let user_slots: Vec<&UniformSlot> =
+        slots.iter().filter(|s| s.binding == USER_BINDING).collect();
+    if user_slots.is_empty() {
+        ui.text_disabled("No user uniforms are bound.");
+        return;
+    }
+    ui.text("Offset  Size  Bytes");
+    if ui.is_item_hovered() {
+        ui.tooltip_text("std140 layout, offsets and sizes in bytes. Bytes are in the order they are uploaded, grouped by 4. Padding is uploaded as zeros.");
+    }
+    let hex = |bytes: &[u8]| {
+        bytes
+            .chunks(4)
+            .map(|word| {
+                word.iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    };
+    let padding_row = |offset: usize, size: usize| {
+        ui.text_disabled(format!(
+            "{:>6}  {:>4}  {}  (padding)",
+            offset,
+            size,
+            hex(&vec![0; size])
+        ));
+    };
+    let mut end = 0;
+    for slot in user_slots {
+        if slot.offset > end {
+            padding_row(end, slot.offset - end);
+        }
+        let mut bytes = vec![0; slot.size];
+        let value = match uniforms.get(&slot.name) {
+            Some(uniform) => {
+                let len = slot.size.min(uniform.bytes.len());
+                bytes[..len].copy_from_slice(&uniform.bytes[..len]);
+                format!(
+                    "{} {} = {}",
+                    uniform.type_name(),
+                    slot.name,
+                    uniform.display_value()
+                )
+            }
+            None => format!("{} (no value in the Dashboard)", slot.name),
+        };
+        ui.text(format!(
+            "{:>6}  {:>4}  {}  {}",
+            slot.offset,
+            slot.size,
+            hex(&bytes),
+            value
+        ));
+        end = end.max(slot.offset + slot.size);
+    }
+    // Blocks are padded to a multiple of 16 bytes.
+    let size = end.div_ceil(16) * 16;
+    if size > end {
+        padding_row(end, size - end);
+    }
+    ui.text(format!("{} bytes in total", size));
+}
+
+/// Start, padding and separator of the files of a PNG sequence named `stem`, with the names they give.
+/// Warns if `frame_count` frames, or frames at `framerate` for long, outgrow the padding.
+fn sequence_numbering_ui(
+    ui: &imgui::Ui,
+    numbering: &mut FrameNumbering,
+    stem: &str,
+    frame_count: Option<u64>,
+    framerate: i32,
+) {
+    let mut start = numbering.start as i32;
+    if ui
+        .input_int(im_str!("Start Frame##Sequence"), &mut start)
+        .build()
+    {
+        numbering.start = start.max(0) as u32;
+    }
+    let mut padding = numbering.padding as i32;
+    if ui
+        .input_int(im_str!("Padding##Sequence"), &mut padding)
+        .build()
+    {
+        numbering.padding = (padding.max(1) as u32).min(FrameNumbering::MAX_PADDING);
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Digits frame numbers are zero-padded to.");
+    }
+    let mut separator = ImString::with_capacity(16);
+    separator.push_str(&numbering.separator);
+    if ui
+        .input_text(im_str!("Separator##Sequence"), &mut separator)
+        .build()
+    {
+        numbering.separator = String::from(separator.to_str());
+    }
+    ui.text(numbering.template(stem));
+    if ui.is_item_hovered() {
+        ui.tooltip_text(format!("First file: {}", numbering.frame_filename(stem, 0)));
+    }
+    match frame_count {
+        Some(frames) => {
+            if let Err(msg) = numbering.check(frames) {
+                ui.text_colored([1.0, 0.8, 0.0, 1.0], msg);
+            }
+        }
+        None => {
+            let capacity = numbering.capacity();
+            ui.text(format!(
+                "Room for {} frames, {:.1} min at {} fps",
+                capacity,
+                capacity as f64 / framerate.max(1) as f64 / 60.0,
+                framerate
+            ));
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Later frames get longer names, which sort out of order.");
+            }
+        }
+    }
+}
+
+/// One plot per channel on a shared scale, plus the share of pixels in each channel's top bin.
+fn histogram_ui(ui: &imgui::Ui, histogram: &Histogram, width: f32) {
+    let channels = [
+        (im_str!("##HistogramRed"), im_str!("R"), &histogram.red),
+        (im_str!("##HistogramGreen"), im_str!("G"), &histogram.green),
+        (im_str!("##HistogramBlue"), im_str!("B"), &histogram.blue),
+    ];
+    let scale_max = channels
+        .iter()
+        .flat_map(|(_, _, bins)| bins.iter())
+        .fold(0.0f32, |max, bin| max.max(*bin));
+    for (label, overlay, bins) in channels.iter() {
+        ui.plot_histogram(label, bins)
+            .overlay_text(overlay)
+            .scale_min(0.0)
+            .scale_max(scale_max)
+            .graph_size([width, 50.0])
+            .build();
+    }
+    let clipped = |bins: &Vec<f32>| bins.last().copied().unwrap_or(0.0) * 100.0;
+    ui.text(format!(
+        "Top bin: R {:.1}%  G {:.1}%  B {:.1}%",
+        clipped(&histogram.red),
+        clipped(&histogram.green),
+        clipped(&histogram.blue)
+    ));
+    if ui.is_item_hovered() {
+        ui.tooltip_text(
+            "Share of pixels at or near full intensity. High values suggest clipped highlights.",
+        );
+    }
+}
+
+/// Preview of the region of the canvas window a framing preset keeps, above a button selecting it.
+/// `None` stands for filling the whole window. Returns whether the button was pressed.
+fn framing_preset_ui(
+    ui: &imgui::Ui,
+    preset: Option<Framing>,
+    current: Option<Framing>,
+    window_size: IntVector2,
+    width: f32,
+) -> bool {
+    let id_token = ui.push_id(preset.map_or("Window", |f| f.name()));
+    let pressed = ui.group(|| {
+        // The window's shape scaled into a square preview, with the framed region centered in it.
+        let [x, y] = ui.cursor_screen_pos();
+        let window_aspect = window_size.x.max(1) as f32 / window_size.y.max(1) as f32;
+        let window_extent = match window_aspect >= 1.0 {
+            true => [width, width / window_aspect],
+            false => [width * window_aspect, width],
+        };
+        let [coverage_x, coverage_y] = preset.map_or([1.0, 1.0], |f| f.coverage(window_size));
+        let framed_extent = [window_extent[0] * coverage_x, window_extent[1] * coverage_y];
+        let centered = |extent: [f32; 2]| {
+            let min = [x + (width - extent[0]) / 2.0, y + (width - extent[1]) / 2.0];
+            (min, [min[0] + extent[0], min[1] + extent[1]])
+        };
+        let draw_list = ui.get_window_draw_list();
+        let (min, max) = centered(window_extent);
+        draw_list
+            .add_rect(min, max, [0.0, 0.0, 0.0, 1.0])
+            .filled(true)
+            .build();
+        let (min, max) = centered(framed_extent);
+        draw_list
+            .add_rect(min, max, [0.741, 0.933, 0.984, 1.0])
+            .filled(true)
+            .build();
+        drop(draw_list);
+        ui.dummy([width, width]);
+
+        let label = match preset {
+            Some(framing) => im_str!("{}", framing.name()),
+            None => ImString::new("Window"),
+        };
+        let selected_token = match preset == current {
+            // The hover color of the theme, so the highlight matches it.
+            true => Some(ui.push_style_color(
+                StyleColor::Button,
+                ui.style_color(StyleColor::ButtonHovered),
+            )),
+            false => None,
+        };
+        let pressed = ui.button(&label, [width, 25.0]);
+        if let Some(token) = selected_token {
+            token.pop(ui);
+        }
+        pressed
+    });
+    if ui.is_item_hovered() {
+        ui.tooltip_text(match preset {
+            Some(_) => "Letterbox the canvas to this aspect and conform the painting and recording resolutions to it. The colored area is the part of the canvas window it fills.",
+            None => "Fill the whole canvas window again. The painting and recording resolutions are kept.",
+        });
+    }
+    id_token.pop(ui);
+    pressed
+}
+
+/// Integer input for a texture dimension, clamped to `1..=max` so renders can't exceed the device limit.
+fn dimension_input_ui(ui: &imgui::Ui, label: &ImStr, value: &mut i32, max: i32) {
+    ui.input_int(label, value).build();
+    *value = (*value).max(1).min(max);
+    if ui.is_item_hovered() {
+        ui.tooltip_text(format!(
+            "Limited to {} pixels, the largest texture the GPU supports.",
+            max
+        ));
+    }
+}
+
+/// Recorded movie time, plus a progress bar and the estimated time left for a fixed-duration recording.
+/// The estimate assumes frames keep rendering and encoding as fast as they have since the recording started.
+fn recording_progress_ui(
+    ui: &imgui::Ui,
+    recorder: &Recorder,
+    start: Instant,
+    frame_count: Option<u64>,
+    width: f32,
+) {
+    let elapsed = start.elapsed().as_secs_f64();
+    ui.text(format!(
+        "Recorded {:.1} s ({} frames) in {:.1} s",
+        recorder.recorded_duration(),
+        recorder.frames_added,
+        elapsed
+    ));
+    let target = match frame_count {
+        Some(target) if target > 0 => target,
+        _ => return,
+    };
+    let done = recorder.frames_added.min(target);
+    imgui::ProgressBar::new(done as f32 / target as f32)
+        .size([width, 25.0])
+        .overlay_text(&im_str!("{} / {} frames", done, target))
+        .build(ui);
+    if done == 0 {
+        ui.text("Estimating time remaining...");
+        return;
+    }
+    let seconds_per_frame = elapsed / done as f64;
+    ui.text(format!(
+        "About {:.0} s remaining",
+        (target - done) as f64 * seconds_per_frame
+    ));
+}
+
+/// Pick the scene selector uniform and name or forget the scenes stored for it, see [UniformScenes].
+fn uniform_scenes_ui(ui: &imgui::Ui, scenes: &mut UniformScenes, candidates: &[String]) {
+    imgui::TreeNode::new(im_str!("Scenes")).build(ui, || {
+        let preview = scenes.selector.as_deref().unwrap_or("None");
+        let mut selector = None;
+        imgui::ComboBox::new(im_str!("Selector##Scenes"))
+            .preview_value(&im_str!("{}", preview))
+            .build(ui, || {
+                if imgui::Selectable::new(im_str!("None"))
+                    .selected(scenes.selector.is_none())
+                    .build(ui)
+                {
+                    selector = Some(None);
+                }
+                for name in candidates {
+                    if imgui::Selectable::new(&im_str!("{}", name))
+                        .selected(scenes.selector.as_ref() == Some(name))
+                        .build(ui)
+                    {
+                        selector = Some(Some(name.clone()));
+                    }
+                }
+            });
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Integer or bool uniform choosing the shader's scene. Changing it stores the other uniforms' values for the scene left and recalls those of the scene switched to.");
+        }
+        if let Some(selector) = selector {
+            scenes.set_selector(selector);
+        }
+        let current = scenes.current();
+        let mut forget = None;
+        for (value, scene) in scenes.scenes.iter_mut() {
+            let mut name = ImString::with_capacity(64);
+            name.push_str(&scene.name);
+            let label = match current == Some(*value) {
+                true => ImString::new(format!("= {} (current)##Scene{}", value, value)),
+                false => ImString::new(format!("= {}##Scene{}", value, value)),
+            };
+            if ui.input_text(&label, &mut name).build() {
+                scene.name = String::from(name.to_str());
+            }
+            ui.same_line(0.0);
+            if ui.small_button(&ImString::new(format!("Forget##Scene{}", value))) {
+                forget = Some(*value);
+            }
+        }
+        if let Some(value) = forget {
+            scenes.scenes.remove(&value);
+        }
+    });
+}
+
+/// Handle to drag a uniform's controls onto another uniform's handle, which then shows them in its place.
+/// Returns the position of the uniform dropped onto the handle at `index`, if one was.
+fn uniform_drag_handle_ui(ui: &imgui::Ui, name: &str, index: usize) -> Option<usize> {
+    let payload_name = im_str!("UniformOrder");
+    ui.small_button(&ImString::new(format!("=##Move{}", name)));
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Drag onto another uniform's handle to move the controls there.");
+    }
+    if let Some(tooltip) = imgui::DragDropSource::new(payload_name).begin_payload(ui, index) {
+        ui.text(name);
+        tooltip.end();
+    }
+    let target = imgui::DragDropTarget::new(ui)?;
+    let dropped = match target.accept_payload::<usize>(payload_name, imgui::DragDropFlags::empty())
+    {
+        Some(Ok(payload)) if payload.data != index => Some(payload.data),
+        _ => None,
+    };
+    target.pop();
+    dropped
+}
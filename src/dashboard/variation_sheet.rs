@@ -0,0 +1,184 @@
+use super::Dashboard;
+use crate::canvas::{VariationSheetLayout, VariationSheetRequest};
+use crate::overlay::glyph;
+use crate::utils::{
+    transcode_painting_data, ExportEvent, ExportFormat, ExportNotifier, WriteFinished,
+};
+use crate::vector::UIntVector2;
+use image::tiff::TiffEncoder;
+use image::ImageEncoder;
+use log::{error, info, warn};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver};
+
+/// 16-bit RGBA image the cells are stitched into.
+struct Sheet {
+    pixels: Vec<u16>,
+    width: u32,
+}
+
+impl Sheet {
+    fn fill(&mut self, x: u32, y: u32, width: u32, height: u32, color: [u16; 4]) {
+        for row in y..y + height {
+            for column in x..(x + width).min(self.width) {
+                let i = ((row * self.width + column) * 4) as usize;
+                if i + 4 <= self.pixels.len() {
+                    self.pixels[i..i + 4].copy_from_slice(&color);
+                }
+            }
+        }
+    }
+
+    /// Print `text` on a dark box in the bottom left corner of the cell at `origin`.
+    fn label(&mut self, text: &str, origin: UIntVector2, cell_size: UIntVector2) {
+        let scale = (cell_size.y / 64).max(1);
+        let box_width = ((text.chars().count() as u32 * 4 + 1) * scale).min(cell_size.x);
+        let box_height = (7 * scale).min(cell_size.y);
+        let top = origin.y + cell_size.y - box_height;
+        self.fill(origin.x, top, box_width, box_height, [0, 0, 0, u16::MAX]);
+        for (i, c) in text.chars().enumerate() {
+            let rows = match glyph(c) {
+                Some(rows) => rows,
+                None => continue,
+            };
+            let left = origin.x + (i as u32 * 4 + 1) * scale;
+            for (row, bits) in rows.iter().enumerate() {
+                for column in 0..3 {
+                    if bits & (0b100 >> column) != 0
+                        && left + (column + 1) * scale <= origin.x + box_width
+                    {
+                        self.fill(
+                            left + column * scale,
+                            top + (row as u32 + 1) * scale,
+                            scale,
+                            scale,
+                            [u16::MAX; 4],
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Stitch the cells read back from the GPU into one labeled image and write it as a 16-bit TIFF.
+fn write_variation_sheet_to_disk(
+    buffer: wgpu::Buffer,
+    layout: &VariationSheetLayout,
+    filename: &str,
+) -> Result<(), String> {
+    let cell = layout.cell_size;
+    let count = layout.labels.len() as u32;
+    // The cells are stacked in the buffer, so it reads as one tall image of padded rows.
+    let mut cell_data = Vec::<u8>::new();
+    transcode_painting_data(
+        buffer,
+        UIntVector2::new(layout.row_pitch, cell.y * count),
+        &mut cell_data,
+    );
+    let cell_pixels: Vec<u16> = cell_data
+        .chunks_exact(2)
+        .map(|bytes| u16::from_ne_bytes([bytes[0], bytes[1]]))
+        .collect();
+
+    let size = layout.sheet_size();
+    let mut sheet = Sheet {
+        pixels: vec![0; (size.x * size.y * 4) as usize],
+        width: size.x,
+    };
+    let columns = layout.columns();
+    for (i, label) in layout.labels.iter().enumerate() {
+        let i = i as u32;
+        let origin = UIntVector2::new((i % columns) * cell.x, (i / columns) * cell.y);
+        for row in 0..cell.y {
+            let src = (((i * cell.y + row) * layout.row_pitch) * 4) as usize;
+            let dst = (((origin.y + row) * size.x + origin.x) * 4) as usize;
+            let len = (cell.x * 4) as usize;
+            sheet.pixels[dst..dst + len].copy_from_slice(&cell_pixels[src..src + len]);
+        }
+        sheet.label(label, origin, cell);
+    }
+
+    let bytes: Vec<u8> = sheet
+        .pixels
+        .iter()
+        .flat_map(|component| component.to_ne_bytes().to_vec())
+        .collect();
+    let file = File::create(filename).map_err(|e| e.to_string())?;
+    let mut writer = BufWriter::new(file);
+    TiffEncoder::new(&mut writer)
+        .write_image(&bytes, size.x, size.y, image::ColorType::Rgba16)
+        .map_err(|e| e.to_string())?;
+    writer.flush().map_err(|e| e.to_string())
+}
+
+/// Like [crate::utils::AsyncTiffWriter::write], but for a variation sheet.
+/// **Note:** This function launches an async task and returns immediately.
+pub fn write_variation_sheet(
+    buffer: wgpu::Buffer,
+    layout: VariationSheetLayout,
+    filename: String,
+    notifier: ExportNotifier,
+) -> Receiver<WriteFinished> {
+    let (tx, rx) = channel();
+    std::thread::spawn(move || {
+        let result = write_variation_sheet_to_disk(buffer, &layout, &filename);
+        match &result {
+            Ok(_) => info!("Wrote variation sheet {} to disk", filename),
+            Err(e) => error!("Error writing variation sheet {}: {}", filename, e),
+        }
+        let event = ExportEvent {
+            path: PathBuf::from(&filename),
+            resolution: layout.sheet_size(),
+            format: ExportFormat::Tiff,
+            error: result.err(),
+        };
+        notifier.notify(&event);
+        tx.send(WriteFinished::Finished(event)).ok();
+    });
+    rx
+}
+
+impl Dashboard {
+    /// Variations of the selected uniform, evenly spaced from the minimum to the maximum, inclusive.
+    /// Cells have the aspect ratio of the painting resolution.
+    pub fn variation_sheet_request(&self) -> Option<VariationSheetRequest> {
+        let name = match &self.state.variation_uniform {
+            Some(name) => name,
+            None => {
+                warn!("Select a uniform to vary first.");
+                return None;
+            }
+        };
+        let uniform = match self.state.gui_uniforms.get(name) {
+            Some(uniform) if uniform.scalar_value().is_some() => uniform,
+            _ => {
+                warn!("Uniform {} can't be varied.", name);
+                return None;
+            }
+        };
+        let count = self.state.variation_count.max(2) as usize;
+        let (min, max) = (
+            self.state.variation_min as f64,
+            self.state.variation_max as f64,
+        );
+        let variations = (0..count)
+            .map(|i| {
+                let mut variation = uniform.clone();
+                variation.set_scalar_value(min + (max - min) * i as f64 / (count - 1) as f64);
+                variation
+            })
+            .collect();
+        let width = self.state.variation_cell_width.max(1) as u32;
+        let painting = self.state.painting_resolution;
+        let height = (width as f64 * painting.y.max(1) as f64 / painting.x.max(1) as f64)
+            .round()
+            .max(1.0) as u32;
+        Some(VariationSheetRequest {
+            variations,
+            cell_size: UIntVector2::new(width, height),
+        })
+    }
+}
@@ -0,0 +1,212 @@
+//! Where each uniform lives in the uniform buffers, and whether the shader agrees.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::uniforms::{UserUniform, MAX_TOUCH_POINTS};
+use crate::utils::spirv_uniform_block_members;
+
+/// Binding of Easel's uniforms in set 0.
+pub const BUILTIN_BINDING: u32 = 0;
+/// Binding of the user uniforms in set 0.
+pub const USER_BINDING: u32 = 1;
+
+/// A uniform's place in one of the uniform buffers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UniformSlot {
+    pub name: String,
+    /// Binding in set 0, [BUILTIN_BINDING] or [USER_BINDING].
+    pub binding: u32,
+    /// Unit: bytes from the start of the buffer.
+    pub offset: usize,
+    /// Unit: bytes.
+    pub size: usize,
+    /// Whether the offset was pinned in the shader settings instead of following the uniforms JSON file.
+    pub pinned: bool,
+}
+
+/// Easel's uniforms, in the order and with the names of the shader skeleton.
+pub fn builtin_slots() -> Vec<UniformSlot> {
+    let members = [
+        ("u_resolution", 16),
+        ("u_mouse_info", 16),
+        ("u_mouse_button_pressed", 16),
+        ("u_date", 16),
+        ("u_time", 4),
+        ("u_time_delta", 4),
+        ("u_frame_num", 4),
+        ("u_num_textures", 4),
+        ("u_mouse_delta", 16),
+        ("u_jitter", 16),
+        ("u_aspect", 4),
+        ("u_touch_count", 4),
+        ("u_touches", 16 * MAX_TOUCH_POINTS),
+        ("u_stereo", 16),
+    ];
+    let mut offset = 0;
+    members
+        .iter()
+        .map(|(name, size)| {
+            let alignment = if *size >= 16 { 16 } else { 4 };
+            offset = align_to(offset, alignment);
+            let slot = UniformSlot {
+                name: name.to_string(),
+                binding: BUILTIN_BINDING,
+                offset,
+                size: *size,
+                pinned: false,
+            };
+            offset += size;
+            slot
+        })
+        .collect()
+}
+
+/// Layout of the user uniforms buffer. Uniforms follow the order of the uniforms JSON file with std140 alignment,
+/// except those pinned to an offset, which the others are packed around.
+#[derive(Debug, Clone, Default)]
+pub struct UniformLayout {
+    /// Sorted by offset.
+    pub slots: Vec<UniformSlot>,
+    /// Size of the buffer, a multiple of 16 bytes. Zero without user uniforms.
+    pub size: usize,
+    /// Pins that could not be honored.
+    pub warnings: Vec<String>,
+}
+
+impl UniformLayout {
+    pub fn new(uniforms: &HashSet<UserUniform>, pins: &HashMap<String, usize>) -> UniformLayout {
+        let mut ordered: Vec<&UserUniform> = uniforms.iter().collect();
+        ordered.sort_by_key(|u| u.order);
+        let mut layout = UniformLayout::default();
+        let mut names: Vec<&String> = pins.keys().collect();
+        names.sort();
+        for name in names {
+            if !uniforms.iter().any(|u| &u.name == name) {
+                layout.warnings.push(format!(
+                    "Pinned uniform {} is not in the uniforms file",
+                    name
+                ));
+            }
+        }
+
+        // Pinned uniforms first, so the rest flow around them.
+        for uniform in ordered.iter().filter(|u| pins.contains_key(&u.name)) {
+            let offset = pins[&uniform.name];
+            let alignment = uniform.inherent_type.std140_alignment();
+            if !offset.is_multiple_of(alignment) {
+                layout.warnings.push(format!(
+                    "{} is pinned to offset {}, which is not a multiple of its {} byte alignment",
+                    uniform.name, offset, alignment
+                ));
+                continue;
+            }
+            if let Some(other) = layout.overlapping(offset, uniform.bytes.len()) {
+                layout.warnings.push(format!(
+                    "{} is pinned to offset {}, which overlaps {}",
+                    uniform.name, offset, other
+                ));
+                continue;
+            }
+            layout.insert(uniform, offset, true);
+        }
+        let placed: HashSet<String> = layout.slots.iter().map(|s| s.name.clone()).collect();
+        let mut offset = 0;
+        for uniform in ordered.iter().filter(|u| !placed.contains(&u.name)) {
+            let alignment = uniform.inherent_type.std140_alignment();
+            offset = align_to(offset, alignment);
+            while let Some(end) = layout.overlapping_end(offset, uniform.bytes.len()) {
+                offset = align_to(end, alignment);
+            }
+            layout.insert(uniform, offset, false);
+            offset += uniform.bytes.len();
+        }
+        let end = layout
+            .slots
+            .iter()
+            .map(|s| s.offset + s.size)
+            .max()
+            .unwrap_or(0);
+        layout.size = align_to(end, 16);
+        layout
+    }
+
+    fn insert(&mut self, uniform: &UserUniform, offset: usize, pinned: bool) {
+        self.slots.push(UniformSlot {
+            name: uniform.name.clone(),
+            binding: USER_BINDING,
+            offset,
+            size: uniform.bytes.len(),
+            pinned,
+        });
+        self.slots.sort_by_key(|s| s.offset);
+    }
+
+    fn overlapping_slot(&self, offset: usize, size: usize) -> Option<&UniformSlot> {
+        self.slots
+            .iter()
+            .find(|s| offset < s.offset + s.size && s.offset < offset + size)
+    }
+
+    fn overlapping(&self, offset: usize, size: usize) -> Option<String> {
+        self.overlapping_slot(offset, size).map(|s| s.name.clone())
+    }
+
+    fn overlapping_end(&self, offset: usize, size: usize) -> Option<usize> {
+        self.overlapping_slot(offset, size)
+            .map(|s| s.offset + s.size)
+    }
+
+    /// Contents of the user uniforms buffer, [Self::size] bytes long.
+    pub fn pack(&self, uniforms: &HashSet<UserUniform>) -> Vec<u8> {
+        self.pack_iter(uniforms.iter())
+    }
+
+    /// Like [Self::pack], for uniforms that are not in a set, e.g. with one replaced by a variation.
+    pub fn pack_iter<'a>(&self, uniforms: impl IntoIterator<Item = &'a UserUniform>) -> Vec<u8> {
+        let mut bytes = vec![0; self.size];
+        for uniform in uniforms {
+            if let Some(slot) = self.slots.iter().find(|s| s.name == uniform.name) {
+                let len = slot.size.min(uniform.bytes.len());
+                bytes[slot.offset..slot.offset + len].copy_from_slice(&uniform.bytes[..len]);
+            }
+        }
+        bytes
+    }
+
+    /// Compare Easel's and this layout with the uniform blocks the shader declares. Easel's uniforms are matched by
+    /// position since shaders may rename them, user uniforms by name. Returns one message per mismatch.
+    pub fn check_against_shader(&self, fs_spirv: &[u8]) -> Vec<String> {
+        let mut mismatches = vec![];
+        if let Some(members) = spirv_uniform_block_members(fs_spirv, 0, BUILTIN_BINDING) {
+            for ((name, offset), slot) in members.iter().zip(builtin_slots()) {
+                if *offset as usize != slot.offset {
+                    mismatches.push(format!(
+                        "Binding 0: {} is at offset {} in the shader, Easel writes {} at {}",
+                        name, offset, slot.name, slot.offset
+                    ));
+                }
+            }
+        }
+        if let Some(members) = spirv_uniform_block_members(fs_spirv, 0, USER_BINDING) {
+            for (name, offset) in members.iter().filter(|(name, _)| !name.is_empty()) {
+                match self.slots.iter().find(|s| &s.name == name) {
+                    Some(slot) if slot.offset != *offset as usize => mismatches.push(format!(
+                        "Binding 1: {} is at offset {} in the shader but {} in the buffer, \
+                         pin it in \"uniform_offsets\" or reorder the uniforms file",
+                        name, offset, slot.offset
+                    )),
+                    None => mismatches.push(format!(
+                        "Binding 1: {} is declared in the shader but not in the uniforms file",
+                        name
+                    )),
+                    _ => {}
+                }
+            }
+        }
+        mismatches
+    }
+}
+
+fn align_to(offset: usize, alignment: usize) -> usize {
+    offset.div_ceil(alignment) * alignment
+}
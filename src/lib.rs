@@ -0,0 +1,24 @@
+//! The shader rendering core of Easel, for embedding in other wgpu applications.
+//!
+//! [ShaderRenderer] renders an Easel shader using a [wgpu::Device] and [wgpu::Queue] owned by the caller:
+//! ```ignore
+//! let fs_spirv = easel_rs::utils::compile_builtin_shader(&source, shaderc::ShaderKind::Fragment, "painting.frag")?;
+//! let mut renderer = easel_rs::ShaderRenderer::new(&device, &queue, &fs_spirv, Vec::new(), &[], &[]);
+//! renderer.uniforms.time = 1.5;
+//! let image = renderer.render_image(&device, &queue, easel_rs::vector::UIntVector2::new(1920, 1080), 0);
+//! ```
+//! The Easel app's window, Dashboard, post-processing, recording and mirror outputs are not part of the library.
+//! Its Canvas keeps its own buffers, textures and pipelines, which it rebuilds as files change and shares with those
+//! outputs, but lays out, uploads, binds and draws the shader with the same functions as [ShaderRenderer]:
+//! [layout::UniformLayout], [renderer::encode_uniforms_upload], [renderer::create_uniform_bindings],
+//! [renderer::create_texture_bindings], [utils::create_shader_pipeline] and [renderer::encode_shader_pass].
+
+pub mod layout;
+pub mod overlay;
+pub mod renderer;
+pub mod texture;
+pub mod uniforms;
+pub mod utils;
+pub mod vector;
+
+pub use renderer::ShaderRenderer;
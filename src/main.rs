@@ -3,18 +3,27 @@
 //! To this end, Easel intentionally uses high bitrate textures during the render process even though they are less memory and compute efficient.
 //! Easel is designed to be part of a workflow where you may want to further edit your digital paintings in an image editing program for later printing and display.
 //! Paintings are rendered using 16-bits-per-component textures and written to disk as uncompressed high-res 16-bit TIFF files.
+//! End the painting filename with `.png` or `.jpg` to write a 16-bit PNG or an 8-bit JPEG instead. Without one of these
+//! or `.tiff`, `.tiff` is appended. Extensions of formats that can't be written, such as `.exr`, are replaced with `.tiff`
+//! and warned about.
 //!
 //! While rendering to screen, lower bitrate textures are used for efficiency.
 //! However, when the `Create Painting` button is pressed, a separte render pipeline utilising 16-bit textures is run to create the digital painting.
 //! Please note that using high bitrate texture such as these consumes large amounts of memory.
 //! The maximum painting resolution is determined by the amount of memory in your GPU.
 //! Attempting to use more than this will cause the program to crash.
+//! Paintings are rendered on the canvas thread and written to disk on a thread of their own,
+//! so the Dashboard stays responsive and shows their progress, even with `Pause While Painting` on.
 //!
 //! Easel is designed to be cross-platform and run on Windows, macOS, and Linux.
 //! It uses [wgpu] as the render backend and [imgui] for the GUI.
 //!
 //! # Getting Started
 //! Easel expects the shaders and bindings to follow a certain format. To get started, use the `--generate` option to create a basic shader.
+//! Once running, the Dashboard's `New Shader` section writes a plain fragment, Shadertoy-style or raymarching starter
+//! shader to the given path and switches to it. Templates only use the uniforms Easel always binds, so they run as is,
+//! and an existing file is never overwritten. With `--auto-update`, the new file is watched in place of the old one.
+//! The `Open Shader` section, also in the command palette, switches to any other shader by path the same way.
 //!
 //! # Usage
 //! Easel supports rendering either text source fragment shaders or compiled SPIR-V modules. If providing a text shader, the extension must be ".frag".
@@ -27,10 +36,15 @@
 //! Easel automatically provides the following uniform data to all shaders:
 //!
 //!   - Viewport resolution in pixels.
+//!   - Viewport aspect ratio (width / height). During paintings and movie frames, that of the frame being captured.
 //!   - Time in seconds since program start.
 //!   - Time in seconds since last frame.
 //!   - Current render frame count (starts at 0)
 //!   - Current mouse position + mouse position in the previous frame.
+//!   - Mouse movement since the previous frame while the mouse is captured.
+//!   - Optional sub-pixel jitter offset.
+//!   - Points touching the canvas window, see [Touch Input](#touch-input).
+//!   - The eye being rendered in a stereo capture, see [Stereo Capture](#stereo-capture).
 //!
 //! Use the skeleton shader as a reference for the order and bindings for these uniforms.
 //!
@@ -50,8 +64,23 @@
 //! }
 //! ````
 //!
-//! At this time, the only the following data types are supported for push constants and uniforms: `i32`, `i64`, `f32`, `f64`, `u32`, `u64`, and `bool`.
+//! At this time, the only the following data types are supported for push constants and uniforms: `i32`, `i64`, `f32`, `f64`, `u32`, `u64`, and `bool`, plus `ivec2` and `ivec3` uniforms.
 //! **Note:** `bool` uniforms are bound as `u32` in shaders to respect alignment constraints.
+//! Integer vectors take an array of components, optionally followed by an inclusive `[min, max]` range the GUI clamps to,
+//! e.g. `"tiles": ["ivec2", [8, 8], [1, 64]]`.
+//! Any uniform may end with an object setting how the GUI shows it: decimal places and a unit,
+//! e.g. `"frequency": ["f32", 440.0, {"precision": 1, "unit": "Hz"}]`. This only changes the display, not the value.
+//! Numbers and vector components are drag fields in the GUI: ctrl-click one to type an exact value, which is clamped
+//! to the range like a dragged one. Unsigned types never go below 0. Double-click any uniform's control to reset it to
+//! the value in the JSON file, or run `Reset All Uniforms` from the command palette to reset every one.
+//!
+//! ### Expressions
+//! Numbers and bools can be animated by typing an expression in the `=` field under them, e.g. `0.5 + 0.5 * sin(t * 2.0)`.
+//! `t` is seconds since Easel started and `frame` is the canvas frame count; `pi`, `sin`, `cos`, `tan`, `abs`, `sqrt`,
+//! `exp` and `fract` are available along with the [evalexpr](https://docs.rs/evalexpr) builtins such as `min`, `max`
+//! and `floor`. The expression is evaluated every frame and overrides the widget until it is cleared.
+//! Errors are shown below the field. Expressions are saved with the shader settings. A uniform whose automatable toggle
+//! is off keeps its value until the toggle is turned back on.
 //!
 //! ### Binding Order
 //! Easel-provided uniforms are always bound to set 0, binding 0. If you also provide uniforms, they are bound to set 0 binding 1.
@@ -66,6 +95,37 @@
 //! };
 //! layout(set = 0, binding = 1) uniform MyUniforms { bool antialiasing; };
 //!```
+//! User uniforms are laid out in the order of the JSON file with std140 alignment, so declare the block's members in
+//! the same order. The `Uniform Layout` section of the GUI lists the offset and size of every uniform in both bindings,
+//! and warns where the compiled shader expects a member somewhere else. Under it, `User Uniform Bytes` shows the user
+//! uniforms buffer byte by byte as it is uploaded, padding included, next to the value each uniform holds.
+//! To match a block you can't reorder, pin uniforms to byte offsets in the shader settings file; the rest are packed
+//! around them:
+//! ```text
+//! "uniform_offsets": { "antialiasing": 16 }
+//! ```
+//!
+//! ### Random Seeds
+//! Numeric uniforms with `seed` in their name (e.g. `"seed": ["u32", 0]`) get a `Reroll` button in the GUI that replaces their value with a random one.
+//! Pressing `R` in the Dashboard, or the `Reroll Seeds` button, rerolls every seed at once. Float seeds are drawn from [0, 1).
+//!
+//! ### Randomizing
+//! Numeric uniforms can declare bounds next to their display settings, optionally with a step and spread over orders of
+//! magnitude:
+//! ```text
+//! "frequency": ["f32", 440.0, {"min": 20, "max": 20000, "log": true, "unit": "Hz"}],
+//! "bands": ["u32", 8, {"min": 2, "max": 32, "step": 2}]
+//! ```
+//! Dragging stops at the bounds. The `Randomize` button in the Uniforms section, also in the command palette, picks new
+//! values for every automatable uniform with bounds, integer vectors with a range, bools and seeds. The checkbox next
+//! to a uniform's automatable toggle locks it, keeping its value. Uniforms following an expression are left alone.
+//! Each randomization is drawn from a seed shown under the button; type a seed and press `Apply` to get the same values
+//! again.
+//!
+//! ### Control Order
+//! Uniform controls are listed in the order of the uniforms JSON file. Drag the `=` handle in front of a uniform onto
+//! another uniform's handle to move its controls there; `Reset Order` goes back to the file's order. The order is
+//! kept with `Save Shader Settings`. It only changes the GUI, the uniforms are laid out as in the file either way.
 //!
 //! ## Texture Loading
 //! Up to [wgpu::Limits::max_sampled_textures_per_shader_stage] images can be loaded and bound as input textures to the fragment shader using the `-t` option.
@@ -81,14 +141,388 @@
 //!
 //! At this time, only PNG images are supported. Support for other formats is planned to be implemented in the next release.
 //!
+//! ### Input Image
+//! To post-process an existing image, enter its path in the `Input Image` section of the GUI and press `Load`.
+//! The image replaces `texture_0`, so the shader can sample and process it, and the painting resolution is set to the image size
+//! so that `Create` captures the processed result at full resolution.
+//! If no images were loaded with `-t`, the input image is added at binding 1 and any ramps move down one binding.
+//!
+//! ### Color Ramps
+//! Uniforms of type `ramp` are not placed in the uniform buffer. Instead each ramp is baked into a 1D texture and bound in set 1
+//! after the images, in the order they appear in the JSON file. Ramps can be edited live from the GUI.
+//! ```text
+//! "uniforms": {
+//!     "palette": ["ramp", [[0.0, [0.0, 0.0, 0.0, 1.0]], [1.0, [1.0, 0.5, 0.0, 1.0]]]]
+//! }
+//! ```
+//! ```text
+//! layout(set = 1, binding = 2) uniform texture1D palette;
+//! ```
+//!
+//! ### Noise Texture
+//! A 256 x 256 tiling noise texture is always bound in set 1 after the ramps, e.g. at binding 3 with one image and one ramp.
+//! Each channel holds independent noise in linear `[0, 1]` values. The type (`White`, `Blue` or `Value`) and the seed
+//! are chosen in the `Noise Texture` section of the GUI and saved with the shader settings, and the same type and seed always
+//! produce the same texture. `Reseed` picks a random seed.
+//! ```text
+//! layout(set = 1, binding = 3) uniform texture2D noise;
+//! ```
+//!
 //! ## Postprocessing Effects
 //! If you would like to run postprocessing effects and/or chain multiple shaders together, use the `-p` option.
 //! Multiple shaders can be provided and shaders are run in order. Post-processing effects are applied to both on and off screen renders.
 //! These shaders can also be provided as source text, compiled SPIR-V, or both.
 //!
+//! Check `Export Each Pass` under `Painting Options` to write a multi-page TIFF for compositing, with one page per pass:
+//! `Shader` for the shader's own output, then `Post-process 1`, `Post-process 2` and so on, the last being the final painting.
+//! Pages are named with the TIFF PageName tag. Without post-processing shaders the painting is a single image as usual.
+//! PNG and JPEG paintings have no pages, so only the final painting is written.
+//!
+//! ## Anti-aliasing
+//! The `AA Quality` setting in the GUI is injected into text shaders as the macro `OTIUM_AA_SAMPLES`, e.g. `#define OTIUM_AA_SAMPLES 2`.
+//! It is meant to be used as the number of samples per axis, so a shader can take `OTIUM_AA_SAMPLES * OTIUM_AA_SAMPLES` samples per pixel.
+//! Guard any default you provide with `#ifndef OTIUM_AA_SAMPLES` so that the setting can override it.
+//! The same setting selects the supersampling factor for paintings: each axis is rendered `OTIUM_AA_SAMPLES` times larger and then downsampled.
+//!
+//! ## Session Timeline
+//! Every uniform change made through the GUI is logged with the time it was made, relative to launch. Values driven by
+//! expressions or a replay aren't logged, and only the most recent 100,000 changes are kept.
+//! The `Session Timeline` section of the GUI exports the log as CSV or JSON, and can replay it, re-applying each change at its recorded time.
+//! Replaying from file reads a previously exported JSON timeline, so a session can be reproduced later. Replays leave
+//! uniforms whose automatable toggle is off alone.
+//!
+//! ## Mouse Capture
+//! Press `C` in the canvas window to grab and hide the cursor, e.g. for first-person look controls.
+//! While captured, `u_mouse_delta.xy` holds the mouse movement in pixels since the previous frame and `u_mouse_delta.z` is 1.
+//! Press `C` again or Escape to release the mouse.
+//!
+//! ## Mouse Smoothing
+//! Raise `Mouse Smoothing` under `Stats & Controls` to filter the position in `u_mouse_info.xy` with a one-euro filter:
+//! slow, jittery movement is smoothed the most while fast strokes barely lag, which suits drawing and pointer shaders.
+//! `u_mouse_info.zw` is then the smoothed position of the previous frame. The Dashboard keeps showing the raw position.
+//!
+//! ## Time Scale
+//! `Time Scale` under `Stats & Controls` makes `u_time` and `u_time_delta` advance from 0.1 to 10 times as fast as real time,
+//! for reviewing an animation slower or faster. Only time is scaled, the canvas renders at the same frame rate.
+//! Recordings run at real time whatever the scale, unless `Time Scale in Recordings` is checked.
+//! An external clock sets time directly and is not scaled.
+//!
+//! ## Sub-pixel Jitter
+//! Enable `Sub-pixel Jitter` in the GUI to have `u_jitter.xy` step through an R2 low-discrepancy sequence of offsets in [-0.5, 0.5) pixels, one per frame.
+//! `u_jitter.z` is the index into the sequence, which restarts whenever the shader is reloaded.
+//! This is meant for temporal anti-aliasing and progressive accumulation. Check `Freeze` to hold the current offset, e.g. for stills.
+//!
+//! ## Progressive Accumulation
+//! For noisy raymarched or path-traced shaders, enable `Progressive Accumulation` together with `Freeze Time` and `Sub-pixel Jitter`.
+//! Each frame is then blended into a running 16-bit float average, which is what gets displayed, and the GUI shows the number of samples so far.
+//! Accumulation restarts whenever the shader, a uniform, a ramp or the window size changes, or when `Reset` is pressed.
+//! Pressing `Create` while accumulating captures the accumulated average at canvas resolution instead of rendering a new frame.
+//!
+//! ## Variation Sheet
+//! To explore a parameter, pick a numeric uniform in the `Variation Sheet` section with a count and a `Min` and `Max` value,
+//! then press `Create Variation Sheet`. Each evenly spaced value is rendered like a painting, at the cell width and the painting's
+//! aspect ratio, and the cells are stitched into one 16-bit TIFF, `<painting filename>_variations.tiff`, filled row by row.
+//! Each cell is labeled with the value it was rendered with. Integer uniforms are rounded.
+//!
+//! ## Burned In Values
+//! Check `Burn In Uniform Values` in the painting options to draw each uniform's `name: value` into a corner of the
+//! painting, in a small built-in pixel font on a darkened box, e.g. for tutorials and parameter studies. `Text Size`
+//! sets how many image pixels each font pixel covers and the corner is chosen below it. Every page of a multi-pass TIFF
+//! gets the text. The option is never saved and is off on every launch, so clean exports can't pick it up by accident.
+//!
+//! ## Capture at Time
+//! `Create at Time` in the painting options paints the frame at the `u_time` next to it instead of the live one, so a
+//! specific moment of an animation can be reproduced at painting resolution. Only `u_time` is replaced: the frame
+//! number, mouse and uniforms driven by expressions keep their live values. Progressive accumulation is skipped for
+//! these paintings, since its samples would span other moments. The Canvas keeps running while the painting renders.
+//!
+//! ## Painting Warm-up
+//! `Warm-up Frames` in the painting options renders that many frames before the painting's own and writes the average of
+//! all of them, for shaders that need to settle, e.g. noise seeded by the frame number. Like accumulation, `u_time` stays at
+//! the painting's and only `u_frame_num` steps back one frame each, so a painting at a given time comes out the same every
+//! time. The count is sent to the Canvas, so paintings from the canvas window shortcut and batch renders warm up too, and
+//! saved with the shader settings.
+//! Paintings of the accumulated average need no warm-up and skip it.
+//!
+//! ## Clipboard
+//! Press `K` in the Dashboard, or `Copy Frame to Clipboard` in the GUI, to put the frame shown on the canvas on the system clipboard
+//! at its on-screen resolution, without writing a file. While paused, this is exactly the frame on screen.
+//!
+//! ## Shader Settings
+//! Press `Save Shader Settings` in the GUI to store the painting and recording options, AA quality and uniform values next to the shader,
+//! e.g. in `shader.frag.otium.json`. The file is loaded automatically the next time the shader is opened.
+//! Without one, the usual defaults are used.
+//!
+//! Uniform values are also saved automatically on exit, in `shader.frag.otium.autosave.json`, and restored the next time
+//! the shader is opened, replacing those in the shader settings. Uncheck `Autosave Uniforms` to start from the shader's
+//! defaults next time, or set `EASEL_AUTOSAVE_UNIFORMS=0` (see below) to never restore them.
+//!
+//! ## Piece Name
+//! `Piece Name` under `Stats & Controls` names the piece, e.g. for an installation. Painting and recording filenames that
+//! still have the default basename, or the previous piece's, take the new name, keeping their directory and extension.
+//! The name is written into TIFF paintings as their DocumentName and into movies as their title. PNG and JPEG
+//! paintings have no room for it. Check `Show in Window Title` to title the canvas window with it. Both are saved
+//! with the shader settings.
+//!
+//! ## Defaults
+//! The usual defaults can be replaced by pointing `EASEL_DEFAULTS` at a JSON file with the same keys as the shader settings,
+//! e.g. `{"painting_resolution": [3840, 2160], "movie_framerate": 30}`. Only the painting and recording resolutions,
+//! filenames and framerate are read, plus `autosave_uniforms`, `maintain_strategy`, `theme`, `log_dropped_frames` and the idle settings. Individual variables
+//! override the file: `EASEL_PAINTING_RESOLUTION=3840x2160`, `EASEL_RECORDING_RESOLUTION`, `EASEL_MOVIE_FRAMERATE`,
+//! `EASEL_PAINTING_FILENAME`, `EASEL_RECORDING_FILENAME`, `EASEL_AUTOSAVE_UNIFORMS=0`, `EASEL_MAINTAIN=wait`, `EASEL_THEME=default` and
+//! `EASEL_LOG_DROPPED_FRAMES=0`.
+//! Shader settings saved next to a shader still take precedence.
+//!
+//! ## Power Use
+//! `Device Maintenance` in the `Display` section sets how the Dashboard waits on its GPU work. `Poll` is the most responsive,
+//! `Wait` uses the least power, e.g. on battery. The default, `Auto`, waits while the canvas is paused or the Dashboard is unfocused.
+//!
+//! Frames the Dashboard drops, e.g. with the Mailbox present mode on a busy display, are logged at most once a second as a count.
+//! Uncheck `Log Dropped Frames` in the same section to mute them. Timeouts are still counted in `Frame Timeouts`.
+//!
+//! `Canvas FPS` and `Canvas Dropped Frames` under `Stats & Controls` are the canvas's own rates over the last second,
+//! while `Render Time` is the Dashboard's. A low FPS without drops points at a slow shader, drops at presentation.
+//!
+//! `Theme` in the `Display` section sets the Dashboard's colors. `Default` leaves imgui's own darker, higher-contrast
+//! styling alone instead of Otium's `Pastel` palette. Set it for every session with `EASEL_THEME=default` or the `theme`
+//! key of the defaults file.
+//!
+//! ## Idle
+//! For unattended installations, set `Idle Timeout` in the `Display` section, or `EASEL_IDLE_TIMEOUT=10` (see Defaults),
+//! to the minutes without input after which the canvas goes idle. While idle it renders at `Idle Framerate` and dims the
+//! output towards the master fade color by `Idle Dim`. Any key, mouse or touch input in any window wakes it. A recording in
+//! progress keeps the canvas awake. `idle_timeout`, `idle_framerate` and `idle_dim` can be set in the defaults file,
+//! and `EASEL_IDLE_FRAMERATE` and `EASEL_IDLE_DIM` override them.
+//!
+//! ## Uniform Snapshot
+//! Press `A` in the Dashboard, or `Snapshot Uniforms` in the GUI, to store the current uniform values.
+//! Keep tweaking, then press `A` again to swap between the tweaked values and the snapshot for a quick A/B comparison.
+//! `Clear Snapshot` drops the values not currently shown. Snapshots are not saved.
+//!
+//! ## Scenes
+//! For a shader that switches between scenes with an integer or bool uniform, pick that uniform as the `Selector` under
+//! `Uniforms > Scenes`. Whenever its value changes, by hand, from an expression or a replay, the values of the other
+//! uniforms are stored for the scene left and those stored for the scene switched to are recalled. A scene not visited
+//! before keeps the current values. Scenes can be named, and are kept with `Save Shader Settings`.
+//!
+//! ## Reference Compare
+//! `Capture Reference` in the `Reference Compare` section stores the frame on the canvas, to compare later tweaks against.
+//! The reference is overlaid on the canvas window either as a vertical wipe, showing it left of the slider position,
+//! or blended over the live render as an onion skin. The overlay is never included in paintings, movies or clipboard copies,
+//! and only shown on mirrors set to `Show Overlays`.
+//!
+//! ## Borderless Canvas
+//! `Borderless Canvas` in the Dashboard hides the canvas window's border and titlebar, whatever `Hide Titlebar` is set to,
+//! and the cursor while it's over the window, so screen recordings of the window show only the output.
+//! Launch with `--transparent-window` to have areas of the output with alpha below 1 show what's behind the window, e.g. to
+//! composite the canvas over other content. Letterboxing is then transparent too. Whether the alpha is honored depends on the
+//! platform's compositor and graphics backend. The window can't be made click-through.
+//!
+//! ## Debug Views
+//! The `Debug View` dropdown replaces what the canvas window shows: `Wireframe` draws the edges of the canvas geometry over the output,
+//! `Normals` treats the output's luminance as a height field, `UV` shows the geometry's texture coordinates and `Overdraw`
+//! brightens each pixel by a quarter per time it's shaded. Wireframe needs a GPU with non-fill polygon mode support and is disabled otherwise.
+//! Like the reference overlay, debug views are never exported.
+//!
+//! ## Transparency
+//! With `Transparency Checkerboard` checked, the canvas window shows a checkerboard behind the output, blended by its alpha,
+//! to tell transparent areas from black ones. It is drawn in the canvas window only, so paintings, movies, mirrors and
+//! clipboard copies keep their true alpha.
+//!
+//! ## Reproducing Exports
+//! After each painting or movie, the command that relaunches Easel with the same shader, files and export settings is
+//! logged and shown under `Reproduce Last Export`, with a button copying it to the clipboard, e.g.
+//! ```text
+//! EASEL_PAINTING_RESOLUTION=3840x2160 EASEL_PAINTING_FILENAME=Painting easel art.frag --uniforms art.json # aa 2, noise white seed 0
+//! ```
+//! The resolution, framerate and filename are passed as [session defaults](#defaults), which the shader's saved
+//! settings take precedence over. Settings that can't be passed at launch, such as the noise seed, seed uniforms and
+//! movie codec, follow as a comment to set by hand.
+//!
+//! ## Flip
+//! Check `Flip Horizontally` and/or `Flip Vertically` in the GUI to mirror the output in the canvas window, e.g. for
+//! rear projection or projecting via a mirror. Paintings and movies keep the unflipped image unless `Flip Captures` is
+//! checked too. Mirror windows are not flipped, swap the corners of their warp to mirror them instead.
+//!
+//! ## Stereo Capture
+//! Check `Capture in Stereo` in the `Stereo Capture` section of the GUI to render paintings and movie frames once per
+//! eye, e.g. for VR. The eyes are packed `Side by Side` or `Top Bottom` into the painting or recording resolution, so a
+//! 3840 x 1920 side by side painting holds two 1920 x 1920 eyes. Each eye renders at its own resolution with
+//! `vec4 u_stereo` set, bound after `u_touches`: `x` is the eye's horizontal offset from the center in scene units,
+//! i.e. minus or plus half the `Interaxial Distance`, `y` is 0 for the left eye and 1 for the right, `z` is 1 while an
+//! eye renders and `w` is the interaxial distance. Outside stereo captures it is all zero, so shaders can offset their
+//! camera by `u_stereo.x` unconditionally. Post-processing runs on the packed frame. The canvas window keeps showing a
+//! single view, and stereo paintings render their eyes rather than capture the accumulated average.
+//!
+//! ## Mirror Outputs
+//! For multi-projector installations, `Add Mirror Window` in the `Mirror Outputs` section opens another window showing the canvas.
+//! Each mirror can show a cropped region of the canvas, given in normalized coordinates, and be made fullscreen on its current monitor.
+//! Overlapping the crops of neighbouring mirrors gives the overlap needed for edge-blended projection.
+//! Closing a mirror window only stops that mirror.
+//!
+//! For a program and preview split, check `Show Overlays` on the operator's mirror to show the debug view and reference overlay
+//! the canvas window shows, while the others show the clean output. Each mirror also has its own `Present Mode`, and a
+//! `Max Framerate` to draw fewer frames than the canvas, e.g. for a secondary monitor. 0 draws every frame.
+//!
+//! For projection mapping, each mirror also has a `Warp`, made of a corner pin followed by a grid of movable control points,
+//! and an `Edge Blend` that fades the image out towards each edge over the given width, shaped by an exponent.
+//! Open mirrors and their settings are stored with `Save Shader Settings` and reopened when the shader is opened again.
+//!
+//! ## Closing the Canvas
+//! By default, closing the canvas window or pressing `Escape` in it quits Easel. Run with
+//! `--on-canvas-close keep-dashboard` to keep the Dashboard open instead. It then shows the canvas as disconnected, with
+//! a `Relaunch Canvas` button that opens the window again with the launch arguments, then reopens the shader that was
+//! open. Uniform values carry over through the autosave. The same happens if the Canvas stops on an error. Closing the
+//! Dashboard always quits.
+//!
+//! ## Resolution Lock
+//! While recording, the canvas renders at the recording resolution and is letterboxed in its window,
+//! so `u_resolution` matches the movie and resizing the window can't change the composition mid-take.
+//! The window follows its own size again once the recording stops. Uncheck `Lock Canvas Resolution` to disable this.
+//!
+//! ## Framing
+//! The `Framing` presets (16:9, 1:1, 9:16 and 4:5) letterbox the canvas to that aspect, so `u_resolution` is the framed region,
+//! and conform the painting and recording resolutions to it, keeping their long edge. Each preset previews the part of the
+//! canvas window it fills. `Window` fills the whole window again. The framing is saved with the shader settings.
+//!
+//! ## Exact Resolution
+//! Check `Exact Resolution` under `Framing` to render the canvas at exactly `Width` x `Height` pixels, whatever the window's
+//! size and the display's scale factor. The window only shows the result, scaled to fit and letterboxed, and `u_resolution`
+//! holds the exact size. This overrides the framing preset, and while recording with `Lock Canvas Resolution` the recording
+//! resolution takes over. The exact resolution is saved with the shader settings.
+//!
+//! ## Aspect Warning
+//! When the recording resolution's aspect differs by more than 1% from what the canvas window shows, the framing's aspect or
+//! else the window's, the recording options warn that movies will be framed differently, and offer a resolution with the
+//! canvas' aspect and the recording's long edge.
+//!
+//! ## Fixed-Duration Recording
+//! Check `Fixed Duration` in the recording options to stop recording automatically after `Duration` seconds.
+//! A movie is a whole number of frames long, so the duration is rounded to the nearest frame at the chosen framerate.
+//! When that changes the length, the adjusted duration is shown below the field.
+//! While recording, the options show how much movie time has been recorded and, for a fixed duration, a progress bar
+//! and the time left, estimated from how fast frames have been rendered and encoded so far.
+//!
+//! ## PNG Sequences
+//! The `PNG Sequence` codec writes each frame to its own lossless PNG file instead of a movie, named like `name.####.png`
+//! after the recording filename. `Start Frame` sets the first frame's number, `Padding` the number of digits numbers are
+//! zero-padded to, and `Separator` what goes between the name and the number. The numbering is saved with the shader's settings.
+//! With a fixed duration, the recorder refuses to start if the last frame's number needs more digits than the padding,
+//! since those files would sort out of order.
+//!
+//! ## Holding Time
+//! `Hold Time` in the recording options, or `H` in the Dashboard, holds `u_time` still while the canvas keeps rendering and a
+//! recording keeps capturing frames at its framerate, for a freeze frame in the movie. `Release Time` carries on from the held
+//! moment. Unlike `Freeze Time`, `u_frame_num` keeps counting and progressive accumulation stays off.
+//!
+//! ## Dry Run Recording
+//! Check `Dry Run` before initializing the recorder to go through a recording without FFmpeg: frames are requested,
+//! rendered and read back at the movie framerate and counted towards progress and fixed durations as usual, but nothing
+//! is written. Useful to check pacing or how a piece behaves while recording without filling the disk.
+//!
+//! ## Recording Only Changes
+//! For slow-moving pieces, check `Only Record Changes` before initializing the recorder. A frame whose colors differ from the
+//! last frame written by less than `Change Threshold` 8-bit levels on average is replaced with an exact repeat of that frame.
+//! The difference is measured on every 8th pixel across and down. The movie keeps one frame per tick, so its timing is
+//! unchanged, and the repeats cost almost nothing with HEVC and VP9. ProRes and FFV1 store every frame whole, so they save nothing.
+//!
+//! ## Master Fade
+//! To end a performance cleanly, press `Fade Out` in the GUI, or `B` in the Dashboard, to fade the canvas and all mirrors to the
+//! `Fade Color` over `Fade Duration` seconds. Press it again to fade back in. The `Master Fade` slider sets the level directly.
+//! The fade is separate from pausing: the shader keeps running underneath. Paintings and recordings are not faded.
+//!
+//! ## Embedding
+//! The shader rendering core is also available as the `easel_rs` library, for use in other wgpu applications.
+//! [easel_rs::ShaderRenderer] takes an existing `wgpu::Device` and `wgpu::Queue`, renders a shader with its uniforms,
+//! textures and ramps into any texture view, and can read a frame back as an image. It has no window, GUI or threads.
+//!
+//! ## Histogram
+//! Check `Show Histogram` in the GUI to plot the red, green and blue distribution of the on-screen output, as displayed.
+//! It is computed from a 128 x 128 copy of the output read back from the GPU, so it is off unless enabled.
+//! The share of pixels in the top bin of each channel is shown below it, to help spot clipped highlights before capturing.
+//!
+//! ## Preview
+//! Check `Show Preview` to see a live thumbnail of the canvas output in the Dashboard, e.g. while the canvas window is on
+//! another display. The Canvas box filters its output down to `Thumbnail Size` pixels on the longer edge on the GPU, and
+//! only that small image is read back and sent to the Dashboard, so the preview stays cheap at 4K. Like the histogram,
+//! it shows the clean output as displayed and is off unless enabled.
+//!
+//! ## Render Format
+//! The canvas renders and post-processes in 8-bit linear color by default. Run with `--render-format rgba16f` to use 16-bit
+//! float instead, like paintings do, so post-processing doesn't band and values above 1 survive until the output is
+//! converted for the display. Movie frames are rendered the same way and only converted to 8-bit sRGB at the end.
+//! Paintings always render at 16-bit precision.
+//!
+//! ## Entry Point
+//! The fragment shader's function is expected to be called `main`. Run with `--entry-point fs_main` to use another name
+//! without renaming it: GLSL shaders have it renamed to `main` while compiling, and SPIR-V blobs are run from that entry
+//! point directly. A shader without a function of that name fails to load with a missing entry point error.
+//! Post-processing shaders always use `main`.
+//!
+//! ## Adapter Fallback
+//! The canvas asks for a high-performance GPU and the Dashboard for a low-power one. If none matches, Easel tries the
+//! other kind and then any adapter that can draw to the window, e.g. a software one, logging which adapter each window
+//! ended up on. If there is no such adapter, Easel exits with an error instead of crashing.
+//!
+//! ## Optional GPU Features
+//! Easel asks for the optional GPU features it can use, currently only non-fill polygon mode for the wireframe debug
+//! view. If the adapter refuses the device, it retries without them and then with default limits, which may lower the
+//! maximum painting size. What was dropped is logged, and listed under `Unavailable` in the Dashboard's `GPU Info`.
+//!
+//! ## Includes
+//! Text shaders can include other files, e.g. a library of noise functions. `#include "file.glsl"` is looked up next to
+//! the including file, then next to the shader, then in each `--include-dir` (or `-I`) in the order given, which allows
+//! sharing a library between shaders. `#include <file.glsl>` skips the including file's directory. Includes are
+//! resolved again whenever the shader is recompiled, and one that can't be found is reported like any other compile
+//! error, listing the paths searched.
+//!
+//! ## Touch Input
+//! Touches on the canvas window are bound after `u_aspect`, as `uint u_touch_count` followed by `vec4 u_touches[10]`,
+//! oldest first. `xy` is the position in pixels, like the mouse's, `z` the touch id and `w` is 0 when the point has
+//! just landed and 1 once it has moved. Run with `--max-touches <N>` to track fewer points at once; further touches are
+//! ignored until one lifts. The Dashboard shows how many points are down, which helps when setting up a touchscreen.
+//! The Shadertoy-style template names these `iTouchCount` and `iTouch`.
+//!
+//! ## Batch Render
+//! The `Batch Render` section of the GUI renders every `.frag` and `.spv` shader in a directory to a still, one after
+//! the other, e.g. for a contact sheet. Each shader is opened, compiled and painted at the painting resolution with the
+//! values from the uniforms file, ignoring its saved settings, then written to the output directory as `000_name`,
+//! `001_name` and so on, in the format of the painting filename. A shader that fails to compile or render is listed
+//! with the reason and skipped, without stopping the batch. The shader that was open before is reopened at the end.
+//!
+//! ## Playlist
+//! For unattended installations, the `Playlist` section of the GUI cycles through a list of shaders on a timer. Type
+//! the path of a playlist file and press `Start`, or run with `--playlist <FILE>` to start one at launch:
+//! ```text
+//! {
+//!     "duration": 60,
+//!     "fade": 2,
+//!     "shuffle": false,
+//!     "shaders": [
+//!         "tunnel.frag",
+//!         { "path": "clouds.frag", "duration": 300 }
+//!     ]
+//! }
+//! ```
+//! Each shader is shown for its own `duration`, or the playlist's (60 seconds if neither is given), in seconds. Relative
+//! paths are relative to the playlist file. With a `fade`, the output fades out to the master fade color and back in
+//! over that many seconds between shaders, otherwise it cuts. Shuffling picks the next shader at random. `Previous`,
+//! `Next` and `Pause` step through or hold the playlist, and are in the command palette too. Each shader opens with its
+//! saved settings. A shader that fails to compile is skipped with a warning and listed in the GUI; the playlist stops if
+//! every shader fails.
+//!
+//! ## External Clock
+//! Run with `--clock-port <PORT>` to advance frames on ticks received over UDP instead of the local timer,
+//! e.g. to keep the instances of a multi-machine installation in sync. Any datagram is a tick, and advances time by
+//! `1 / --clock-rate` seconds (60 ticks per second by default). A payload that is a number, e.g. `12.5`, sets the time
+//! to that many seconds instead, so late joiners line up. Pausing and freezing still apply, the clock keeps counting.
+//!
 //! ## Live Coding
 //! If you would like to live-code your shaders, Easel also supports auto-loading of both the shader file and the JSON file.
 //! This works for both text shaders and SPIR-V blobs. Auto-reloading of postprocessing shaders is not supported at this time.
+//! When a reload fails to compile, the canvas pauses and keeps showing the last frame of the shader that last compiled,
+//! even when its window is resized, until the error is fixed. Uncheck `Hold Last Frame on Error` in the Dashboard's
+//! `Display` section to clear the window instead.
 //!
 //! # Help
 //! Run `easel --help` to see all options and instructions.
@@ -108,29 +542,35 @@ mod postprocessing;
 // mod push_constants;
 mod recording;
 mod skeletons;
-mod texture;
-mod uniforms;
-mod utils;
-mod vector;
+
+use easel_rs::{layout, overlay, renderer, texture, uniforms, utils, vector};
 
 use clap::{App, Arg};
 use futures::executor::block_on;
 use log::{error, info};
 use winit::{
     event::*,
-    event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+    window::{WindowBuilder, WindowId},
 };
 
 use crate::{
     canvas::CanvasMessage,
-    dashboard::{Dashboard, DashboardMessage},
+    dashboard::{CanvasCloseBehavior, Dashboard, DashboardMessage},
 };
 use canvas::Canvas;
-use std::sync::mpsc::channel;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::{channel, Receiver, Sender},
+    Arc,
+};
 use std::{cmp::max, thread, time::Instant};
-use std::{collections::HashMap, fs, path::Path};
-use winit::dpi::PhysicalSize;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+use winit::dpi::{LogicalSize, PhysicalSize};
 
 static UPDATE_INTERVAL_MS: u128 = 16;
 
@@ -161,14 +601,6 @@ fn main() {
         std::fs::write(&path, skeletons::SHADER_SKELETON).unwrap();
     }
 
-    // Get textures to load, if any
-    let mut images_to_load: Vec<String> = Vec::new();
-    if let Some(files) = matches.values_of("textures") {
-        for a_file in files {
-            images_to_load.push(String::from(a_file));
-        }
-    }
-
     // Set width & height, if specified.
     let mut canvas_width = 1920;
     let mut canvas_height = 1280;
@@ -179,11 +611,198 @@ fn main() {
         canvas_height = height.parse::<i32>().unwrap()
     }
 
-    // Setup the render window.
+    // Setup channels for Dashboard <--> Canvas communication
+    let (dashboard_tx, state_rx) = channel::<DashboardMessage>();
+    let (state_tx, dashboard_rx) = channel::<CanvasMessage>();
     let event_loop = EventLoop::new();
-    let render_window = WindowBuilder::new().build(&event_loop).unwrap();
+    let canvas_size = PhysicalSize::new(canvas_width, canvas_height);
+    let canvas = match launch_canvas(&matches, &event_loop, canvas_size, state_tx, state_rx) {
+        Ok(canvas) => canvas,
+        Err(e) => {
+            error!("{}", e);
+            return;
+        }
+    };
+    let close_behavior = matches
+        .value_of("on-canvas-close")
+        .and_then(CanvasCloseBehavior::from_name)
+        .expect("Invalid canvas close behavior provided. Must be quit or keep-dashboard");
+    let launched_shader = String::from(shader_file);
+    let mut drawables = HashMap::new();
+    drawables.insert(canvas.window_id, canvas.events);
+    let mut canvas_mouse_captured = canvas.mouse_captured;
+
+    // Setup another window for Dashboard
+    let dashboard_window_builder = WindowBuilder::new()
+        .with_resizable(true)
+        .with_min_inner_size(LogicalSize::new(
+            dashboard::MIN_WINDOW_SIZE[0],
+            dashboard::MIN_WINDOW_SIZE[1],
+        ));
+    let dashboard_window = dashboard_window_builder.build(&event_loop).unwrap();
+    dashboard_window.set_title("Dashboard");
+    dashboard_window.set_inner_size(PhysicalSize::new(500, 1250));
+    dashboard_window.set_always_on_top(true);
+
+    // Setup Dashboard
+    let mut dashboard = block_on(Dashboard::new(dashboard_window, dashboard_tx, dashboard_rx));
+    if matches.is_present("dev") {
+        dashboard.enable_developer_mode();
+    }
+    dashboard.set_launch_args(reproduction_args(&matches));
+    if let Some(playlist_file) = matches.value_of("playlist") {
+        dashboard.play_playlist_file(playlist_file);
+    }
+    let mut last_render_time = Instant::now();
+    let mut mirror_windows = HashSet::new();
+    event_loop.run(move |event, window_target, control_flow| {
+        *control_flow = match drawables.is_empty() && close_behavior == CanvasCloseBehavior::Quit {
+            true => ControlFlow::Exit,
+            false => ControlFlow::Wait,
+        };
+        dashboard.imgui_input(&event);
+        match event {
+            Event::RedrawRequested(_) => {}
+            Event::MainEventsCleared => {
+                let now = Instant::now();
+                let delta = (now - last_render_time).as_millis();
+                if delta >= UPDATE_INTERVAL_MS {
+                    dashboard.update();
+                    dashboard.render_dashboard();
+                    dashboard.post_render();
+                    last_render_time = now;
+                }
+                for _ in 0..dashboard.take_mirror_window_requests() {
+                    let mirror_window = WindowBuilder::new()
+                        .with_title(format!("Mirror {}", mirror_windows.len() + 1))
+                        .with_inner_size(PhysicalSize::new(canvas_width, canvas_height))
+                        .build(window_target);
+                    match mirror_window {
+                        Ok(window) => {
+                            mirror_windows.insert(window.id());
+                            dashboard.add_mirror_window(window);
+                        }
+                        Err(e) => error!("Error creating mirror window: {}", e),
+                    }
+                }
+                if dashboard.take_canvas_relaunch_request() {
+                    let (dashboard_tx, state_rx) = channel::<DashboardMessage>();
+                    let (state_tx, dashboard_rx) = channel::<CanvasMessage>();
+                    match launch_canvas(&matches, window_target, canvas_size, state_tx, state_rx) {
+                        Ok(canvas) => {
+                            drawables.insert(canvas.window_id, canvas.events);
+                            canvas_mouse_captured = canvas.mouse_captured;
+                            dashboard.reconnect_canvas(
+                                dashboard_tx,
+                                dashboard_rx,
+                                &launched_shader,
+                            );
+                        }
+                        Err(e) => error!("Error relaunching the canvas: {}", e),
+                    }
+                }
+                // A Canvas can also stop without its window being closed, e.g. if it panicked.
+                if !dashboard.canvas_connected() && close_behavior == CanvasCloseBehavior::Quit {
+                    *control_flow = ControlFlow::Exit;
+                }
+            }
+            Event::WindowEvent { event, window_id } => match event {
+                // While the canvas has the mouse captured, Escape releases it instead of quitting.
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Released,
+                            virtual_keycode: Some(VirtualKeyCode::Escape),
+                            ..
+                        },
+                    ..
+                } if window_id != dashboard.window.id()
+                    && canvas_mouse_captured.load(Ordering::SeqCst) =>
+                {
+                    forward_window_event(&mut drawables, window_id, event.to_static().unwrap());
+                }
+                // Closing a mirror only stops mirroring to it.
+                WindowEvent::CloseRequested | WindowEvent::Destroyed
+                    if mirror_windows.contains(&window_id) =>
+                {
+                    mirror_windows.remove(&window_id);
+                    dashboard.mirror_window_closed(window_id);
+                }
+                WindowEvent::CloseRequested
+                | WindowEvent::Destroyed
+                | WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Released,
+                            virtual_keycode: Some(VirtualKeyCode::Escape),
+                            ..
+                        },
+                    ..
+                } => {
+                    if window_id == dashboard.window.id() {
+                        // clear out all windows.
+                        drawables.clear();
+                        *control_flow = ControlFlow::Exit;
+                    } else {
+                        // Dropping its sender stops the Canvas, which the Dashboard then notices.
+                        drawables.remove(&window_id);
+                        if close_behavior == CanvasCloseBehavior::Quit {
+                            *control_flow = ControlFlow::Exit;
+                        }
+                    }
+                }
+                _ => {
+                    if drawables.contains_key(&window_id) {
+                        if let Some(window_event) = event.to_static() {
+                            forward_window_event(&mut drawables, window_id, window_event);
+                        }
+                    } else if dashboard.window.id() == window_id {
+                        dashboard.window_input(event.to_static().unwrap())
+                    }
+                }
+            },
+            Event::LoopDestroyed => dashboard.save_uniform_autosave(),
+            _ => (),
+            // _ => {}
+        }
+    });
+}
+
+/// A Canvas rendering on a thread of its own, see [launch_canvas].
+struct LaunchedCanvas {
+    window_id: WindowId,
+    /// Forwards events of the canvas window to the Canvas. Dropping it stops the Canvas.
+    events: Sender<WindowEvent<'static>>,
+    mouse_captured: Arc<AtomicBool>,
+}
+
+/// Build the canvas window, load the shader and files given in `matches` and start a Canvas rendering them on a
+/// thread of its own, talking to the Dashboard over `transmitter` and `receiver`.
+/// Called at launch, and again whenever the Dashboard relaunches a Canvas that went away.
+fn launch_canvas(
+    matches: &clap::ArgMatches,
+    window_target: &EventLoopWindowTarget<()>,
+    size: PhysicalSize<i32>,
+    transmitter: Sender<CanvasMessage>,
+    receiver: Receiver<DashboardMessage>,
+) -> Result<LaunchedCanvas, String> {
+    let shader_file = matches.value_of("shader").unwrap();
+    // Get textures to load, if any
+    let mut images_to_load: Vec<String> = Vec::new();
+    if let Some(files) = matches.values_of("textures") {
+        for a_file in files {
+            images_to_load.push(String::from(a_file));
+        }
+    }
+
+    // Setup the render window.
+    let transparent = matches.is_present("transparent-window");
+    let render_window = WindowBuilder::new()
+        .with_transparent(transparent)
+        .build(window_target)
+        .unwrap();
     render_window.set_title("Canvas");
-    render_window.set_inner_size(PhysicalSize::new(canvas_width, canvas_height));
+    render_window.set_inner_size(size);
     render_window.set_decorations(true);
     render_window.set_resizable(true);
     let mut images: Vec<image::DynamicImage> = Vec::new();
@@ -191,31 +810,25 @@ fn main() {
         let an_image = image::open(Path::new(a_file));
         match an_image {
             Ok(img) => images.push(img),
-            Err(error) => {
-                error!("Error loading image: {}", error);
-                return;
-            }
+            Err(error) => return Err(format!("Error loading image: {}", error)),
         }
     }
 
-    // Setup channels for Dashboard <--> Canvas communication
-    let (dashboard_tx, state_rx) = channel::<DashboardMessage>();
-    let (state_tx, dashboard_rx) = channel::<CanvasMessage>();
-
-    let mut drawables = HashMap::new();
     // Make channels for sending events to Canvas
     let (canvas_event_tx, canvas_event_rx) = channel();
-    drawables.insert(render_window.id(), canvas_event_tx);
-    let fs_spv_data = match utils::load_shader(shader_file) {
+    let window_id = render_window.id();
+    let entry_point = matches.value_of("entry-point").unwrap();
+    let include_dirs: Vec<String> = matches
+        .values_of("include-dir")
+        .map_or(vec![], |dirs| dirs.map(String::from).collect());
+    let fs_spv_data = match utils::load_shader(shader_file, 1, entry_point, &include_dirs) {
         Ok(data) => data,
-        Err(e) => {
-            error!("Error compiling/loading shader: {}", e);
-            return;
-        }
+        Err(e) => return Err(format!("Error compiling/loading shader: {}", e)),
     };
 
     // Load custom uniforms from JSON file if specified.
     let mut custom_uniforms = None;
+    let mut ramps = Vec::new();
     // let mut push_constants = None;
     if let Some(uniforms_file) = matches.value_of("uniforms") {
         let text = fs::read_to_string(uniforms_file).expect("Error reading uniforms from file.");
@@ -224,27 +837,42 @@ fn main() {
         if !cu.is_empty() {
             custom_uniforms = Some(cu);
         }
+        ramps = uniforms::load_ramps_from_json(&json_data);
         // let pc = push_constants::load_push_constants_from_json(&json_data);
         // if !pc.is_empty() {
         //     push_constants = Some(pc);
         // }
     }
+    let render_format = matches
+        .value_of("render-format")
+        .and_then(canvas::RenderFormat::from_name)
+        .expect("Invalid render format provided. Must be rgba8 or rgba16f");
     // Setup render state.
     let mut canvas = Box::new(block_on(Canvas::new(
         render_window,
+        String::from(shader_file),
         fs_spv_data,
         Some(images),
         custom_uniforms,
+        ramps,
+        render_format,
+        String::from(entry_point),
+        include_dirs.clone(),
         // push_constants,
-        state_tx,
-        state_rx,
+        transmitter,
+        receiver,
     )));
+    if transparent {
+        canvas.set_window_transparent();
+    }
 
     // Setup post-processing shaders if specified
     if let Some(postprocess_shaders) = matches.values_of("postprocess") {
         let mut postprocess_shader_modules = Vec::with_capacity(postprocess_shaders.len());
         for shader in postprocess_shaders {
-            postprocess_shader_modules.push(utils::load_shader(shader).unwrap());
+            postprocess_shader_modules.push(
+                utils::load_shader(shader, 1, utils::DEFAULT_ENTRY_POINT, &include_dirs).unwrap(),
+            );
         }
         for module in postprocess_shader_modules {
             canvas.add_post_processing_shader(module);
@@ -265,6 +893,26 @@ fn main() {
             canvas.watch_uniforms_file(uniforms_file, interval);
         }
     }
+    if let Some(max) = matches.value_of("max-touches") {
+        canvas.set_max_touch_points(
+            max.parse::<usize>()
+                .expect("Invalid touch point count provided. Must be an integer"),
+        );
+    }
+    if let Some(port) = matches.value_of("clock-port") {
+        let port = port
+            .parse::<u16>()
+            .expect("Invalid clock port provided. Must be an integer");
+        let rate = matches
+            .value_of("clock-rate")
+            .unwrap()
+            .parse::<f32>()
+            .expect("Invalid clock rate provided. Must be a number");
+        canvas
+            .sync_to_external_clock(port, rate)
+            .expect("Unable to listen for external clock ticks");
+    }
+    let mouse_captured = canvas.mouse_capture_flag();
     let mut last_render_time = Instant::now();
     thread::spawn(move || {
         loop {
@@ -281,79 +929,74 @@ fn main() {
             }
             let now = Instant::now();
             let delta = (now - last_render_time).as_millis();
-            if delta >= UPDATE_INTERVAL_MS {
+            // With an external clock, frames only advance when it ticks.
+            let frame_due = match canvas.external_clock_active() {
+                true => canvas.external_clock_ticked(),
+                false => delta >= canvas.frame_interval_ms(UPDATE_INTERVAL_MS),
+            };
+            if frame_due {
                 canvas.update();
                 canvas.render_canvas();
                 canvas.post_render();
                 last_render_time = now;
             }
+            // Polled on every pass, so paintings finish while paused or waiting on external ticks.
+            canvas.poll_painting();
         }
         canvas.exit_requested()
     });
+    Ok(LaunchedCanvas {
+        window_id,
+        events: canvas_event_tx,
+        mouse_captured,
+    })
+}
 
-    // Setup another window for Dashboard
-    let dashboard_window_builder = WindowBuilder::new().with_resizable(true);
-    let dashboard_window = dashboard_window_builder.build(&event_loop).unwrap();
-    dashboard_window.set_title("Dashboard");
-    dashboard_window.set_inner_size(PhysicalSize::new(500, 1250));
-    dashboard_window.set_always_on_top(true);
+/// Pass `event` on to the Canvas drawing into `window_id`, if any. A Canvas that stopped is forgotten.
+fn forward_window_event(
+    drawables: &mut HashMap<WindowId, Sender<WindowEvent<'static>>>,
+    window_id: WindowId,
+    event: WindowEvent<'static>,
+) {
+    let sent = drawables.get(&window_id).map(|tx| tx.send(event).is_ok());
+    if sent == Some(false) {
+        drawables.remove(&window_id);
+    }
+}
 
-    // Setup Dashboard
-    let mut dashboard = block_on(Dashboard::new(dashboard_window, dashboard_tx, dashboard_rx));
-    let mut last_render_time = Instant::now();
-    event_loop.run(move |event, _event_loop, control_flow| {
-        *control_flow = match !drawables.is_empty() {
-            true => ControlFlow::Wait,
-            false => ControlFlow::Exit,
-        };
-        dashboard.imgui_input(&event);
-        match event {
-            Event::RedrawRequested(_) => {}
-            Event::MainEventsCleared => {
-                let now = Instant::now();
-                let delta = (now - last_render_time).as_millis();
-                if delta >= UPDATE_INTERVAL_MS {
-                    dashboard.update();
-                    dashboard.render_dashboard();
-                    dashboard.post_render();
-                    last_render_time = now;
-                }
-            }
-            Event::WindowEvent { event, window_id } => match event {
-                WindowEvent::CloseRequested
-                | WindowEvent::Destroyed
-                | WindowEvent::KeyboardInput {
-                    input:
-                        KeyboardInput {
-                            state: ElementState::Released,
-                            virtual_keycode: Some(VirtualKeyCode::Escape),
-                            ..
-                        },
-                    ..
-                } => {
-                    if window_id == dashboard.window.id() {
-                        // clear out all windows.
-                        drawables.clear();
-                    } else {
-                        drawables.remove(&window_id);
-                    }
-                    // For now exit entire program if any window is closed.
-                    *control_flow = ControlFlow::Exit;
-                }
-                _ => {
-                    if let Some(tx) = drawables.get(&window_id) {
-                        if let Some(window_event) = event.to_static() {
-                            tx.send(window_event).unwrap();
-                        }
-                    } else if dashboard.window.id() == window_id {
-                        dashboard.window_input(event.to_static().unwrap())
-                    }
-                }
-            },
-            _ => (),
-            // _ => {}
+/// Arguments that relaunch Easel with the same shader, files and render settings.
+/// `--generate` is left out, as it would overwrite the shader.
+fn reproduction_args(matches: &clap::ArgMatches) -> Vec<String> {
+    let mut args = vec![String::from(matches.value_of("shader").unwrap())];
+    for (name, flag) in [
+        ("textures", "-t"),
+        ("postprocess", "-p"),
+        ("include-dir", "--include-dir"),
+    ]
+    .iter()
+    {
+        if let Some(values) = matches.values_of(name) {
+            args.push(String::from(*flag));
+            args.extend(values.map(String::from));
         }
-    });
+    }
+    let single_values = [
+        ("uniforms", "--uniforms"),
+        ("width", "--width"),
+        ("height", "--height"),
+        ("render-format", "--render-format"),
+        ("entry-point", "--entry-point"),
+        ("clock-port", "--clock-port"),
+        ("clock-rate", "--clock-rate"),
+        ("max-touches", "--max-touches"),
+    ];
+    for (name, flag) in single_values.iter() {
+        if matches.occurrences_of(name) > 0 {
+            args.push(String::from(*flag));
+            args.push(String::from(matches.value_of(name).unwrap()));
+        }
+    }
+    args
 }
 
 /// Sets up all arguments to be parsed by Easel
@@ -424,5 +1067,70 @@ fn setup_program_args() -> clap::ArgMatches {
             .short('g')
             .long("generate")
         )
+        .arg(Arg::new("clock-port")
+            .long_about("Advance frames on ticks received on this UDP port instead of the local timer, to keep several instances in sync. Any datagram is a tick. A payload that is a number sets the time in seconds.")
+            .required(false)
+            .takes_value(true)
+            .long("clock-port")
+        )
+        .arg(Arg::new("clock-rate")
+            .long_about("Ticks per second expected on the clock port. Each tick advances time by its inverse.")
+            .required(false)
+            .takes_value(true)
+            .long("clock-rate")
+            .default_value("60")
+        )
+        .arg(Arg::new("render-format")
+            .long_about("Precision the canvas renders and post-processes in on screen and for movies: rgba8 or rgba16f. Output is converted to the display's or movie's 8 bits only as the last step.")
+            .required(false)
+            .takes_value(true)
+            .long("render-format")
+            .default_value("rgba8")
+        )
+        .arg(Arg::new("entry-point")
+            .long_about("Name of the shader's fragment function, for shaders that don't use main. Post-processing shaders always use main.")
+            .required(false)
+            .takes_value(true)
+            .long("entry-point")
+            .default_value("main")
+        )
+        .arg(Arg::new("on-canvas-close")
+            .long_about("What closing the canvas window does: quit, or keep-dashboard to keep the Dashboard open with the option to relaunch the canvas.")
+            .required(false)
+            .takes_value(true)
+            .long("on-canvas-close")
+            .default_value("quit")
+        )
+        .arg(Arg::new("transparent-window")
+            .long_about("Make the canvas window see-through where the output's alpha is below 1, on platforms whose compositor supports it. Pair with the Dashboard's borderless canvas for compositing the canvas over other content.")
+            .required(false)
+            .long("transparent-window")
+        )
+        .arg(Arg::new("max-touches")
+            .long_about("Most points touching the canvas window that are tracked at once, at most 10. Further touches are ignored until one lifts.")
+            .required(false)
+            .takes_value(true)
+            .long("max-touches")
+        )
+        .arg(Arg::new("playlist")
+            .long_about("Playlist file of shaders to cycle through on a timer, started at launch. See the Playlist section of the docs for the format.")
+            .required(false)
+            .takes_value(true)
+            .long("playlist")
+        )
+        .arg(Arg::new("include-dir")
+            .long_about("Directory to search for files included by text shaders, after the including file's directory and the shader's own. Multiple can be provided and are searched in the order given.")
+            .required(false)
+            .takes_value(true)
+            .multiple(true)
+            .short('I')
+            .long("include-dir")
+        )
+        .arg(Arg::new("dev")
+            .long_about("Offer the imgui demo and metrics windows in the Dashboard, for developing new widgets.")
+            .required(false)
+            .hidden(true)
+            .long("dev")
+        )
         .get_matches()
 }
@@ -0,0 +1,186 @@
+//! Text burned into exported images, e.g. the uniform values a painting was made with.
+
+/// Rows of a 3 x 5 pixel glyph, most significant of the three bits on the left.
+/// Letters are uppercase only, lowercase ones are drawn as uppercase. `None` for characters without a glyph.
+pub fn glyph(c: char) -> Option<[u8; 5]> {
+    Some(match c.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        '[' => [0b011, 0b010, 0b010, 0b010, 0b011],
+        ']' => [0b110, 0b010, 0b010, 0b010, 0b110],
+        _ => return None,
+    })
+}
+
+/// Corner of the image a [TextOverlay] is drawn in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverlayCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl OverlayCorner {
+    pub const ALL: [OverlayCorner; 4] = [
+        OverlayCorner::TopLeft,
+        OverlayCorner::TopRight,
+        OverlayCorner::BottomLeft,
+        OverlayCorner::BottomRight,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OverlayCorner::TopLeft => "Top Left",
+            OverlayCorner::TopRight => "Top Right",
+            OverlayCorner::BottomLeft => "Bottom Left",
+            OverlayCorner::BottomRight => "Bottom Right",
+        }
+    }
+}
+
+/// Lines of text in the built-in 3 x 5 pixel font, drawn on a darkened box in a corner of an image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextOverlay {
+    pub lines: Vec<String>,
+    /// Image pixels per font pixel.
+    pub scale: u32,
+    pub corner: OverlayCorner,
+}
+
+impl TextOverlay {
+    /// Burn the text into `pixels`, native-endian 16-bit RGBA as produced by
+    /// [crate::utils::transcode_painting_data], `width` x `height` pixels. Text that doesn't fit is cut off.
+    pub fn draw(&self, pixels: &mut [u8], width: u32, height: u32) {
+        let scale = self.scale.max(1);
+        let columns = self
+            .lines
+            .iter()
+            .map(|l| l.chars().count())
+            .max()
+            .unwrap_or(0) as u32;
+        if columns == 0 {
+            return;
+        }
+        // Each glyph is followed by a pixel of spacing, each line by one more, and the box has a pixel of padding.
+        let box_width = ((columns * 4 + 1) * scale).min(width);
+        let box_height = ((self.lines.len() as u32 * 6 + 1) * scale).min(height);
+        let margin = 2 * scale;
+        let left = match self.corner {
+            OverlayCorner::TopLeft | OverlayCorner::BottomLeft => margin.min(width - box_width),
+            _ => width.saturating_sub(box_width + margin),
+        };
+        let top = match self.corner {
+            OverlayCorner::TopLeft | OverlayCorner::TopRight => margin.min(height - box_height),
+            _ => height.saturating_sub(box_height + margin),
+        };
+        let mut canvas = Pixels {
+            pixels,
+            width,
+            height,
+        };
+        canvas.darken(left, top, box_width, box_height);
+        for (line_index, line) in self.lines.iter().enumerate() {
+            let line_top = top + (line_index as u32 * 6 + 1) * scale;
+            for (i, c) in line.chars().enumerate() {
+                let rows = match glyph(c) {
+                    Some(rows) => rows,
+                    None => continue,
+                };
+                let glyph_left = left + (i as u32 * 4 + 1) * scale;
+                for (row, bits) in rows.iter().enumerate() {
+                    for column in 0..3 {
+                        if bits & (0b100 >> column) != 0 {
+                            canvas.fill(
+                                glyph_left + column * scale,
+                                line_top + row as u32 * scale,
+                                scale,
+                                scale,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Native-endian 16-bit RGBA pixels being drawn into.
+struct Pixels<'a> {
+    pixels: &'a mut [u8],
+    width: u32,
+    height: u32,
+}
+
+impl Pixels<'_> {
+    fn for_each(&mut self, x: u32, y: u32, width: u32, height: u32, f: impl Fn(usize, u16) -> u16) {
+        for row in y..(y + height).min(self.height) {
+            for column in x..(x + width).min(self.width) {
+                let i = ((row * self.width + column) * 8) as usize;
+                for channel in 0..4 {
+                    let at = i + channel * 2;
+                    let value = u16::from_ne_bytes([self.pixels[at], self.pixels[at + 1]]);
+                    self.pixels[at..at + 2].copy_from_slice(&f(channel, value).to_ne_bytes());
+                }
+            }
+        }
+    }
+
+    /// Opaque white.
+    fn fill(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        self.for_each(x, y, width, height, |_, _| u16::MAX);
+    }
+
+    /// A quarter of the brightness, fully opaque, so the text reads on any background.
+    fn darken(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        self.for_each(x, y, width, height, |channel, value| match channel {
+            3 => u16::MAX,
+            _ => value / 4,
+        });
+    }
+}
@@ -23,10 +23,12 @@ pub struct PostProcess {
 
 impl PostProcess {
     /// Construct a new object using the provided compiled shader data.
+    /// `render_format` is the canvas' on-screen format, see [crate::canvas::RenderFormat].
     pub fn new(
         device: &wgpu::Device,
         shader_module: Vec<u8>,
         custom_uniforms_provided: bool,
+        render_format: wgpu::TextureFormat,
     ) -> Self {
         // Load shaders
         let vs_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
@@ -97,7 +99,7 @@ impl PostProcess {
             module: &fs_module,
             entry_point: "main",
             targets: &[wgpu::ColorTargetState {
-                format: crate::canvas::RENDER_TEXTURE_FORMAT,
+                format: render_format,
                 blend: Some(BlendState {
                     color: wgpu::BlendComponent::REPLACE,
                     alpha: wgpu::BlendComponent::REPLACE,
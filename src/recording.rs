@@ -1,6 +1,8 @@
-use crate::{utils, vector::UIntVector2};
+use crate::utils::{self, ExportEvent, ExportFormat, ExportNotifier};
+use crate::vector::UIntVector2;
 use futures::executor::block_on;
-use log::info;
+use log::{info, warn};
+use std::fmt;
 use std::io::Write;
 use std::process::{Command, Stdio};
 use std::thread::JoinHandle;
@@ -8,13 +10,319 @@ use wgpu::TextureFormat;
 
 pub static MOVIE_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
 
+/// Only every this many pixels, across and down, are compared when looking for changes between frames.
+const CHANGE_SAMPLE_STRIDE: usize = 8;
+
+/// Mean absolute difference of the color channels of two RGBA8 frames, in 8-bit levels.
+/// Compares a grid of every [CHANGE_SAMPLE_STRIDE]th pixel, which is plenty to notice motion.
+fn frame_difference(a: &[u8], b: &[u8], resolution: UIntVector2) -> f32 {
+    let (width, height) = (resolution.x as usize, resolution.y as usize);
+    let mut total = 0u64;
+    let mut samples = 0u64;
+    for y in (0..height).step_by(CHANGE_SAMPLE_STRIDE) {
+        for x in (0..width).step_by(CHANGE_SAMPLE_STRIDE) {
+            let i = (y * width + x) * 4;
+            for c in i..i + 3 {
+                total += a[c].abs_diff(b[c]) as u64;
+            }
+            samples += 3;
+        }
+    }
+    match samples {
+        0 => 0.0,
+        _ => total as f32 / samples as f32,
+    }
+}
+
+/// Encoder used by FFmpeg when recording.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MovieCodec {
+    /// Lossless HEVC, or NVENC HEVC on Windows. Compact, for delivery.
+    Hevc,
+    /// ProRes 4444. Editing-friendly intermediate that keeps alpha.
+    ProRes4444,
+    /// FFV1. Mathematically lossless RGBA archive master.
+    Ffv1,
+    /// Lossless VP9 with alpha, for the web.
+    Vp9,
+    /// One PNG per frame, numbered as the [FrameNumbering] says, for compositing and editing software.
+    Png,
+}
+
+impl MovieCodec {
+    pub const ALL: [MovieCodec; 5] = [
+        MovieCodec::Hevc,
+        MovieCodec::ProRes4444,
+        MovieCodec::Ffv1,
+        MovieCodec::Vp9,
+        MovieCodec::Png,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MovieCodec::Hevc => "HEVC",
+            MovieCodec::ProRes4444 => "ProRes 4444",
+            MovieCodec::Ffv1 => "FFV1",
+            MovieCodec::Vp9 => "VP9",
+            MovieCodec::Png => "PNG Sequence",
+        }
+    }
+
+    /// Containers this codec can be written to, the usual one first.
+    pub fn containers(&self) -> &'static [MovieContainer] {
+        match self {
+            MovieCodec::Hevc => &[
+                MovieContainer::Mp4,
+                MovieContainer::Mov,
+                MovieContainer::Mkv,
+            ],
+            MovieCodec::ProRes4444 => &[MovieContainer::Mov, MovieContainer::Mkv],
+            MovieCodec::Ffv1 => &[MovieContainer::Mkv],
+            MovieCodec::Vp9 => &[
+                MovieContainer::Webm,
+                MovieContainer::Mkv,
+                MovieContainer::Mp4,
+            ],
+            MovieCodec::Png => &[MovieContainer::PngSequence],
+        }
+    }
+
+    pub fn supports(&self, container: MovieContainer) -> bool {
+        self.containers().contains(&container)
+    }
+}
+
+/// File format FFmpeg writes the encoded movie to. Independent of the [MovieCodec], within [MovieCodec::containers].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MovieContainer {
+    Mp4,
+    Mov,
+    Mkv,
+    Webm,
+    /// Numbered PNG files rather than a movie, see [FrameNumbering].
+    PngSequence,
+}
+
+impl MovieContainer {
+    pub const ALL: [MovieContainer; 5] = [
+        MovieContainer::Mp4,
+        MovieContainer::Mov,
+        MovieContainer::Mkv,
+        MovieContainer::Webm,
+        MovieContainer::PngSequence,
+    ];
+
+    /// File extension, without the dot. FFmpeg picks the container from it.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            MovieContainer::Mp4 => "mp4",
+            MovieContainer::Mov => "mov",
+            MovieContainer::Mkv => "mkv",
+            MovieContainer::Webm => "webm",
+            MovieContainer::PngSequence => "png",
+        }
+    }
+
+    /// `filename` without any container extension, e.g. the name the files of a sequence are numbered after.
+    pub fn stem(filename: &str) -> &str {
+        MovieContainer::ALL
+            .iter()
+            .find_map(|c| {
+                let split = filename.len().checked_sub(c.extension().len() + 1)?;
+                match filename
+                    .get(split..)?
+                    .eq_ignore_ascii_case(&format!(".{}", c.extension()))
+                {
+                    true => Some(&filename[..split]),
+                    false => None,
+                }
+            })
+            .unwrap_or(filename)
+    }
+
+    /// `filename` with this container's extension, replacing any container extension it already has.
+    pub fn filename(&self, filename: &str) -> String {
+        format!("{}.{}", MovieContainer::stem(filename), self.extension())
+    }
+
+    fn export_format(&self) -> ExportFormat {
+        match self {
+            MovieContainer::Mp4 => ExportFormat::Mp4,
+            MovieContainer::Mov => ExportFormat::Mov,
+            MovieContainer::Mkv => ExportFormat::Mkv,
+            MovieContainer::Webm => ExportFormat::Webm,
+            MovieContainer::PngSequence => ExportFormat::Png,
+        }
+    }
+}
+
+/// How the files of a [MovieContainer::PngSequence] are numbered, e.g. `name.0001.png` with a `.` separator,
+/// a padding of 4 digits and a start of 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameNumbering {
+    /// Number of the first frame.
+    pub start: u32,
+    /// Digits frame numbers are zero-padded to.
+    pub padding: u32,
+    /// Between the name and the frame number.
+    pub separator: String,
+}
+
+impl Default for FrameNumbering {
+    fn default() -> Self {
+        FrameNumbering {
+            start: 1,
+            padding: 4,
+            separator: String::from("."),
+        }
+    }
+}
+
+impl FrameNumbering {
+    /// Most digits a frame number is padded to. More than enough for any frame count.
+    pub const MAX_PADDING: u32 = 9;
+
+    /// Read `{"start": 1001, "padding": 4, "separator": "_"}`, keeping the defaults for anything missing.
+    pub fn from_json(data: &json::JsonValue) -> FrameNumbering {
+        let default = FrameNumbering::default();
+        FrameNumbering {
+            start: data["start"].as_u32().unwrap_or(default.start),
+            padding: data["padding"]
+                .as_u32()
+                .unwrap_or(default.padding)
+                .clamp(1, Self::MAX_PADDING),
+            separator: data["separator"]
+                .as_str()
+                .map(String::from)
+                .unwrap_or(default.separator),
+        }
+    }
+
+    pub fn to_json(&self) -> json::JsonValue {
+        let mut data = json::JsonValue::new_object();
+        data["start"] = self.start.into();
+        data["padding"] = self.padding.into();
+        data["separator"] = self.separator.as_str().into();
+        data
+    }
+
+    /// File of the frame at `index`, counted from 0, of the sequence named `stem`.
+    pub fn frame_filename(&self, stem: &str, index: u64) -> String {
+        format!(
+            "{}{}{:0width$}.png",
+            stem,
+            self.separator,
+            self.start as u64 + index,
+            width = self.padding as usize
+        )
+    }
+
+    /// The sequence named `stem` with `#` in place of each digit, e.g. `name.####.png`.
+    pub fn template(&self, stem: &str) -> String {
+        format!(
+            "{}{}{}.png",
+            stem,
+            self.separator,
+            "#".repeat(self.padding as usize)
+        )
+    }
+
+    /// FFmpeg's output pattern for the sequence named `stem`, e.g. `name.%04d.png`.
+    fn ffmpeg_pattern(&self, stem: &str) -> String {
+        format!(
+            "{}{}%0{}d.png",
+            stem.replace('%', "%%"),
+            self.separator.replace('%', "%%"),
+            self.padding
+        )
+    }
+
+    /// Number of frames whose numbers fit in the padding. Later frames get longer names, which sort out of order.
+    pub fn capacity(&self) -> u64 {
+        10u64.pow(self.padding).saturating_sub(self.start as u64)
+    }
+
+    /// Fails with a message saying how much padding is needed if `frames` frames don't fit in it.
+    pub fn check(&self, frames: u64) -> Result<(), String> {
+        if frames <= self.capacity() {
+            return Ok(());
+        }
+        let last = self.start as u64 + frames.max(1) - 1;
+        Err(format!(
+            "Frame {} needs {} digits, the padding is {}.",
+            last,
+            last.to_string().len(),
+            self.padding
+        ))
+    }
+}
+
+/// Why a recording could not be started or continued.
+#[derive(Debug)]
+pub enum RecorderError {
+    /// Only [MOVIE_TEXTURE_FORMAT] can be recorded.
+    UnsupportedFormat(TextureFormat),
+    /// The codec can't be written to the container, see [MovieCodec::containers].
+    IncompatibleContainer(MovieCodec, MovieContainer),
+    /// FFmpeg could not be started, or stopped accepting frames.
+    Encoder(String),
+    /// The output can't be written, see [utils::check_writable].
+    Unwritable(String),
+}
+
+impl fmt::Display for RecorderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecorderError::UnsupportedFormat(format) => write!(
+                f,
+                "Unsupported texture format {:?}, only Rgba8UnormSrgb can be recorded.",
+                format
+            ),
+            RecorderError::IncompatibleContainer(codec, container) => write!(
+                f,
+                "{} can't be written to {}.",
+                codec.name(),
+                container.extension()
+            ),
+            RecorderError::Encoder(msg) => write!(f, "{}", msg),
+            RecorderError::Unwritable(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for RecorderError {}
+
+/// Pixels of one frame sent to a [Recorder], read on the recording thread.
+pub trait MovieFrame: Send {
+    /// Append the frame's RGBA8 pixels to `pixel_data`, waiting for the GPU if need be.
+    fn read_into(self: Box<Self>, resolution: UIntVector2, pixel_data: &mut Vec<u8>);
+}
+
+/// Staging buffer the frame was copied to on the GPU.
+impl MovieFrame for wgpu::Buffer {
+    fn read_into(self: Box<Self>, resolution: UIntVector2, pixel_data: &mut Vec<u8>) {
+        block_on(utils::transcode_frame_data_for_movie(
+            *self, resolution, pixel_data,
+        ));
+    }
+}
+
+/// RGBA8 pixels already in memory.
+impl MovieFrame for Vec<u8> {
+    fn read_into(self: Box<Self>, _resolution: UIntVector2, pixel_data: &mut Vec<u8>) {
+        pixel_data.extend_from_slice(&self);
+    }
+}
+
 enum RecorderToThreadSignal {
     Stop,
-    Frame(wgpu::Buffer, UIntVector2),
+    Frame(Box<dyn MovieFrame>, UIntVector2),
 }
 
 enum ThreadToRecorderSignal {
     Ready,
+    /// FFmpeg stopped accepting frames. The thread finishes without waiting for a stop signal.
+    Failed(String),
     Finished,
 }
 
@@ -24,26 +332,66 @@ pub struct Recorder {
     receiver: std::sync::mpsc::Receiver<ThreadToRecorderSignal>,
     pub done: bool,
     pub ready: bool,
+    /// Frames sent to the encoder so far.
+    pub frames_added: u64,
+    /// Frames rejected because they did not have the recording's size.
+    pub frames_dropped: u64,
+    /// Size every frame must have.
+    resolution: UIntVector2,
+    /// Frames per second of the movie.
+    framerate: u32,
     stop_signal_received: bool,
+    /// Created by [Self::dry_run], writes nothing.
+    dry_run: bool,
 }
 
 impl Recorder {
+    /// Start FFmpeg, encoding with `codec` to `filename` with the extension of `container`.
+    /// * `change_threshold` - Frames that differ from the last one written by less than this many 8-bit levels on average
+    ///   are replaced with an exact repeat of it, which inter-frame codecs store almost for free. `None` writes every frame.
+    /// * `title` - Name of the piece, stored as the movie's title metadata.
+    /// * `numbering` - Names of the files of a [MovieContainer::PngSequence], unused for movies.
     pub fn new(
         width: u32,
         height: u32,
         texture_format: TextureFormat,
         framerate: u32,
         filename: String,
-    ) -> Recorder {
-        let pix_fmt = match texture_format{
+        notifier: ExportNotifier,
+        codec: MovieCodec,
+        container: MovieContainer,
+        change_threshold: Option<f32>,
+        title: Option<String>,
+        numbering: &FrameNumbering,
+    ) -> Result<Recorder, RecorderError> {
+        let pix_fmt = match texture_format {
             TextureFormat::Rgba8UnormSrgb => "rgba",
-            _ => panic!("Unsupported texture format. Only the following texture formats are supported: Rgba8UnormSrgb")
+            _ => return Err(RecorderError::UnsupportedFormat(texture_format)),
         };
+        if !codec.supports(container) {
+            return Err(RecorderError::IncompatibleContainer(codec, container));
+        }
+        // A sequence is reported by its first file, FFmpeg writes the rest following its pattern.
+        let (filename, output) = match container {
+            MovieContainer::PngSequence => {
+                let stem = MovieContainer::stem(&filename);
+                (
+                    numbering.frame_filename(stem, 0),
+                    numbering.ffmpeg_pattern(stem),
+                )
+            }
+            _ => (container.filename(&filename), container.filename(&filename)),
+        };
+        // FFmpeg would only fail to open the output once it gets the first frame, and quietly.
+        utils::check_writable(std::path::Path::new(&filename))
+            .map_err(RecorderError::Unwritable)?;
+        let start_number = numbering.start.to_string();
         let resolution_string = format!("{}x{}", width.to_string(), height.to_string());
         let (our_sender, thread_receiver) = std::sync::mpsc::channel();
         let (thread_sender, our_receiver) = std::sync::mpsc::channel();
         let framerate_str = framerate.to_string();
-        let join_handle = std::thread::spawn(move || {
+        let title_metadata = title.map(|title| format!("title={}", title));
+        let mut ffmpeg_process = {
             let mut args = vec![
                 "-hide_banner",
                 "-y",
@@ -56,8 +404,34 @@ impl Recorder {
                 "-pixel_format",
                 pix_fmt,
             ];
-            if cfg!(target_os = "windows") {
-                args.extend_from_slice(&[
+            match codec {
+                MovieCodec::ProRes4444 => args.extend_from_slice(&[
+                    "-i",
+                    "-",
+                    "-c:v",
+                    "prores_ks",
+                    "-profile:v",
+                    "4444",
+                    "-pix_fmt",
+                    "yuva444p10le",
+                    "-r",
+                    &framerate_str,
+                    &output,
+                ]),
+                MovieCodec::Ffv1 => args.extend_from_slice(&[
+                    "-i",
+                    "-",
+                    "-c:v",
+                    "ffv1",
+                    "-level",
+                    "3",
+                    "-pix_fmt",
+                    "bgra",
+                    "-r",
+                    &framerate_str,
+                    &output,
+                ]),
+                MovieCodec::Hevc if cfg!(target_os = "windows") => args.extend_from_slice(&[
                     "-hwaccel",
                     "cuda",
                     "-i",
@@ -70,10 +444,24 @@ impl Recorder {
                     "yuv420p",
                     "-r",
                     &framerate_str,
-                    &filename,
-                ]);
-            } else {
-                args.extend_from_slice(&[
+                    &output,
+                ]),
+                MovieCodec::Vp9 => args.extend_from_slice(&[
+                    "-i",
+                    "-",
+                    "-c:v",
+                    "libvpx-vp9",
+                    "-lossless",
+                    "1",
+                    "-row-mt",
+                    "1",
+                    "-pix_fmt",
+                    "yuva420p",
+                    "-r",
+                    &framerate_str,
+                    &output,
+                ]),
+                MovieCodec::Hevc => args.extend_from_slice(&[
                     "-i",
                     "-",
                     "-c:v",
@@ -84,102 +472,250 @@ impl Recorder {
                     "lossless=1",
                     "-r",
                     &framerate_str,
-                    &filename,
-                ]);
+                    &output,
+                ]),
+                MovieCodec::Png => args.extend_from_slice(&[
+                    "-i",
+                    "-",
+                    "-c:v",
+                    "png",
+                    "-pix_fmt",
+                    "rgba",
+                    "-start_number",
+                    &start_number,
+                    "-r",
+                    &framerate_str,
+                    &output,
+                ]),
             }
-            let mut ffmpeg_process = Command::new("ffmpeg")
+            if let Some(metadata) = &title_metadata {
+                // Output options go before the output filename, which every codec puts last.
+                let at = args.len() - 1;
+                args.insert(at, metadata);
+                args.insert(at, "-metadata");
+            }
+            Command::new("ffmpeg")
                 .args(&args)
                 .stdin(Stdio::piped())
                 .spawn()
-                .unwrap();
-
+                .map_err(|e| RecorderError::Encoder(format!("Could not start FFmpeg: {}", e)))?
+        };
+        let join_handle = std::thread::spawn(move || {
             // Notify Recorder struct that we are ready to start receiving frames.
-            thread_sender.send(ThreadToRecorderSignal::Ready).unwrap();
+            thread_sender.send(ThreadToRecorderSignal::Ready).ok();
 
             let mut pixel_data = Vec::<u8>::new();
+            // Last frame written, repeated in place of frames that haven't changed enough.
+            let mut last_written = Vec::<u8>::new();
             let mut frame_count: usize = 0;
+            let mut repeated_count: usize = 0;
+            let mut failure = None;
             loop {
-                let msg = thread_receiver.recv().unwrap();
+                // A dropped Recorder stops the recording like a stop signal.
+                let msg = match thread_receiver.recv() {
+                    Ok(msg) => msg,
+                    Err(_) => break,
+                };
                 match msg {
                     RecorderToThreadSignal::Stop => {
                         info!("Stop signal received.");
                         break;
                     }
-                    RecorderToThreadSignal::Frame(buffer, resolution) => {
+                    RecorderToThreadSignal::Frame(frame, resolution) => {
                         let pipe_in = ffmpeg_process.stdin.as_mut().unwrap();
-                        block_on(utils::transcode_frame_data_for_movie(
-                            buffer,
-                            resolution,
-                            &mut pixel_data,
-                        ));
-                        pipe_in.write_all(&pixel_data).unwrap();
+                        frame.read_into(resolution, &mut pixel_data);
+                        let unchanged = match change_threshold {
+                            Some(threshold) if last_written.len() == pixel_data.len() => {
+                                frame_difference(&pixel_data, &last_written, resolution) < threshold
+                            }
+                            _ => false,
+                        };
+                        if unchanged {
+                            repeated_count += 1;
+                        } else {
+                            std::mem::swap(&mut pixel_data, &mut last_written);
+                        }
+                        if let Err(e) = pipe_in.write_all(&last_written) {
+                            let msg = format!("FFmpeg stopped accepting frames: {}", e);
+                            thread_sender
+                                .send(ThreadToRecorderSignal::Failed(msg.clone()))
+                                .ok();
+                            failure = Some(msg);
+                            break;
+                        }
                         frame_count += 1;
                         pixel_data.clear();
                     }
                 }
             }
 
-            ffmpeg_process.stdin.as_mut().unwrap().flush().unwrap();
-            let output = ffmpeg_process
-                .wait_with_output()
-                .expect("Failed to wait on FFmpeg process");
+            ffmpeg_process.stdin.as_mut().unwrap().flush().ok();
+            let error = match ffmpeg_process.wait_with_output() {
+                Ok(output) => {
+                    info!(
+                        "FFMpeg processed {} frames ({} repeated unchanged) and finished with status: {}",
+                        frame_count, repeated_count, output.status
+                    );
+                    match output.status.success() {
+                        true => failure,
+                        false => Some(format!("FFmpeg exited with status {}", output.status)),
+                    }
+                }
+                Err(e) => Some(format!("Failed to wait on FFmpeg process: {}", e)),
+            };
 
-            info!(
-                "FFMpeg processed {} frames and finished with status: {}",
-                frame_count, output.status
-            );
-            thread_sender
-                .send(ThreadToRecorderSignal::Finished)
-                .unwrap();
+            notifier.notify(&ExportEvent {
+                path: std::path::PathBuf::from(&filename),
+                resolution: UIntVector2::new(width, height),
+                format: container.export_format(),
+                error,
+            });
+            thread_sender.send(ThreadToRecorderSignal::Finished).ok();
             // std::io::stdout().write_all(&output.stdout).unwrap();
             // std::io::stderr().write_all(&output.stderr).unwrap();
         });
 
+        Ok(Recorder::with_thread(
+            join_handle,
+            our_sender,
+            our_receiver,
+            UIntVector2::new(width, height),
+            framerate,
+            false,
+        ))
+    }
+
+    /// Recorder that goes through the motions without FFmpeg, to try out recording without writing anything.
+    /// Frames are read back from the GPU and counted like a real recording's, so pacing and progress behave the same,
+    /// then thrown away. Stopping, polling and finishing work as usual, but no export is announced.
+    /// Separate from [Self::new] since it has no output, codec or notifier to check, and no FFmpeg to start.
+    pub fn dry_run(width: u32, height: u32, framerate: u32) -> Recorder {
+        let (our_sender, thread_receiver) = std::sync::mpsc::channel();
+        let (thread_sender, our_receiver) = std::sync::mpsc::channel();
+        let join_handle = std::thread::spawn(move || {
+            thread_sender.send(ThreadToRecorderSignal::Ready).ok();
+            let mut pixel_data = Vec::<u8>::new();
+            let mut frame_count: usize = 0;
+            // A dropped Recorder stops the recording like a stop signal.
+            while let Ok(RecorderToThreadSignal::Frame(frame, resolution)) = thread_receiver.recv()
+            {
+                frame.read_into(resolution, &mut pixel_data);
+                pixel_data.clear();
+                frame_count += 1;
+            }
+            info!(
+                "Dry run received {} frames, nothing was written.",
+                frame_count
+            );
+            thread_sender.send(ThreadToRecorderSignal::Finished).ok();
+        });
+        Recorder::with_thread(
+            join_handle,
+            our_sender,
+            our_receiver,
+            UIntVector2::new(width, height),
+            framerate,
+            true,
+        )
+    }
+
+    /// Recorder for a recording thread just spawned, which has yet to report being ready.
+    fn with_thread(
+        join_handle: JoinHandle<()>,
+        sender: std::sync::mpsc::Sender<RecorderToThreadSignal>,
+        receiver: std::sync::mpsc::Receiver<ThreadToRecorderSignal>,
+        resolution: UIntVector2,
+        framerate: u32,
+        dry_run: bool,
+    ) -> Recorder {
         Recorder {
             join_handle,
-            sender: our_sender,
-            receiver: our_receiver,
+            sender,
+            receiver,
             done: false,
             ready: false,
+            frames_added: 0,
+            frames_dropped: 0,
+            resolution,
+            framerate,
             stop_signal_received: false,
+            dry_run,
         }
     }
 
+    /// Whether this recorder writes nothing, see [Self::dry_run].
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
     /// Whether this recorder has finished processing all frames.
-    pub fn poll(&mut self) -> bool {
+    /// Fails if FFmpeg stopped accepting frames, in which case the recording should be abandoned.
+    pub fn poll(&mut self) -> Result<bool, RecorderError> {
         let msg_result = self.receiver.try_recv();
         match msg_result {
             Ok(signal) => match signal {
                 ThreadToRecorderSignal::Finished => self.done = true,
                 ThreadToRecorderSignal::Ready => self.ready = true,
+                ThreadToRecorderSignal::Failed(msg) => return Err(RecorderError::Encoder(msg)),
             },
             Err(_) => {}
         }
-        self.done
+        Ok(self.done)
     }
 
+    /// Size every frame of this recording is expected to have.
+    pub fn resolution(&self) -> UIntVector2 {
+        self.resolution
+    }
+
+    /// Frames per second the movie is encoded at, fixed when the recorder is created.
+    pub fn framerate(&self) -> u32 {
+        self.framerate
+    }
+
+    /// Length of the movie recorded so far, unit: seconds.
+    pub fn recorded_duration(&self) -> f64 {
+        self.frames_added as f64 / self.framerate.max(1) as f64
+    }
+
+    /// Send a frame to the encoder. Frames that don't have the recording's size are dropped with a warning,
+    /// since FFmpeg would misinterpret their pixel data and corrupt the rest of the movie.
     pub fn add_frame(
-        &self,
-        buffer: wgpu::Buffer,
+        &mut self,
+        frame: impl MovieFrame + 'static,
         resolution: UIntVector2,
         _timestamp: std::time::Instant,
-    ) {
+    ) -> Result<(), RecorderError> {
+        if resolution.x != self.resolution.x || resolution.y != self.resolution.y {
+            warn!(
+                "Dropping {}x{} movie frame, the recording is {}x{}.",
+                resolution.x, resolution.y, self.resolution.x, self.resolution.y
+            );
+            self.frames_dropped += 1;
+            return Ok(());
+        }
         self.sender
-            .send(RecorderToThreadSignal::Frame(buffer, resolution))
-            .unwrap();
+            .send(RecorderToThreadSignal::Frame(Box::new(frame), resolution))
+            .map_err(|_| RecorderError::Encoder(String::from("FFmpeg is no longer running.")))?;
+        self.frames_added += 1;
+        Ok(())
     }
 
-    pub fn stop(&mut self) {
+    pub fn stop(&mut self) -> Result<(), RecorderError> {
         if self.stop_signal_received {
             panic!("Attempting to request stop on recorder that has already stopped!");
         }
         info!("Sending stop signal to FFMpeg.");
-        self.sender.send(RecorderToThreadSignal::Stop).unwrap();
         self.stop_signal_received = true;
+        self.sender
+            .send(RecorderToThreadSignal::Stop)
+            .map_err(|_| RecorderError::Encoder(String::from("FFmpeg is no longer running.")))
     }
 
-    pub fn finish(self) {
-        self.join_handle.join().unwrap();
+    pub fn finish(self) -> Result<(), RecorderError> {
+        self.join_handle
+            .join()
+            .map_err(|_| RecorderError::Encoder(String::from("The recording thread panicked.")))
     }
 }
 
@@ -188,3 +724,37 @@ impl Recorder {
 
 //     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {}
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn dry_run_counts_frames_until_finished() {
+        let mut recorder = Recorder::dry_run(2, 2, 30);
+        let size = UIntVector2::new(2, 2);
+        for _ in 0..3 {
+            let frame = vec![0u8; 16];
+            recorder.add_frame(frame, size, Instant::now()).unwrap();
+        }
+        let wrong_size = UIntVector2::new(4, 4);
+        let frame = vec![0u8; 64];
+        recorder
+            .add_frame(frame, wrong_size, Instant::now())
+            .unwrap();
+        assert_eq!(recorder.frames_added, 3);
+        assert_eq!(recorder.frames_dropped, 1);
+        assert!(recorder.is_dry_run());
+
+        recorder.stop().unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !recorder.poll().unwrap() {
+            assert!(Instant::now() < deadline, "The dry run never finished.");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(recorder.ready);
+        assert_eq!(recorder.frames_added, 3);
+        recorder.finish().unwrap();
+    }
+}
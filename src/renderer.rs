@@ -0,0 +1,594 @@
+use crate::layout::UniformLayout;
+use crate::texture::{default_color_sampler, AssetTexture, NoiseTexture2D, NoiseType, RampTexture};
+use crate::uniforms::{RampUniform, Uniforms, UserUniform};
+use crate::vector::UIntVector2;
+use futures::executor::block_on;
+use std::collections::{HashMap, HashSet};
+use std::num::{NonZeroU32, NonZeroU64};
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+use wgpu::{BindGroupEntry, BindGroupLayoutEntry, BindingResource, BufferBinding, Extent3d};
+
+/// Pre-compile vertex shader that renders a full-screen quad.
+pub static VS_MODULE_BYTES: &[u8] = include_bytes!("../shaders/vert.spv");
+/// The [wgpu::TextureFormat] of the images returned by [ShaderRenderer::render_image].
+pub static IMAGE_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Renders an Easel shader with its uniforms, textures and ramps, without any window, GUI or threads.
+/// The caller owns the [wgpu::Device] and [wgpu::Queue], so the renderer can be embedded in another wgpu application.
+pub struct ShaderRenderer {
+    /// Uniforms provided by Easel to every shader, e.g. [Uniforms::time]. Written to the GPU on every render.
+    pub uniforms: Uniforms,
+    uniforms_buffer: wgpu::Buffer,
+    /// Uniforms declared in the shader's JSON file.
+    user_uniforms: Vec<UserUniform>,
+    /// Where each of [Self::user_uniforms] goes in the custom uniforms buffer, as the Easel app lays them out.
+    layout: UniformLayout,
+    user_uniforms_buffer: Option<wgpu::Buffer>,
+    textures: Vec<AssetTexture>,
+    ramps: Vec<RampTexture>,
+    /// Starts as white noise with seed 0, see [Self::set_noise].
+    noise: NoiseTexture2D,
+    /// Uniforms are set 0, textures, ramps and noise are set 1.
+    bind_groups: [wgpu::BindGroup; 2],
+    pipeline_layout: wgpu::PipelineLayout,
+    vs_module: wgpu::ShaderModule,
+    fs_module: wgpu::ShaderModule,
+    /// One pipeline per target format, built the first time a target of that format is rendered to.
+    pipelines: HashMap<wgpu::TextureFormat, wgpu::RenderPipeline>,
+    /// Like [Self::pipelines], for blending warm-up frames into the target, see [encode_average_blend].
+    average_pipelines: HashMap<wgpu::TextureFormat, wgpu::RenderPipeline>,
+}
+
+impl ShaderRenderer {
+    /// `fs_spirv_data` is a compiled fragment shader, see [crate::utils::compile_builtin_shader] for compiling GLSL.
+    /// `user_uniforms` are laid out like the Easel app lays out those of a uniforms JSON file, see [UniformLayout].
+    /// `images` and `ramps` are bound in the given order, as the Easel app binds them.
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        fs_spirv_data: &[u8],
+        user_uniforms: Vec<UserUniform>,
+        images: &[image::DynamicImage],
+        ramps: &[RampUniform],
+    ) -> ShaderRenderer {
+        let mut uniforms = Uniforms::new();
+        uniforms.num_textures = images.len() as u32;
+        let uniforms_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Uniforms Buffer"),
+            contents: bytemuck::bytes_of(&uniforms),
+            usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+        });
+        let user_set: HashSet<UserUniform> = user_uniforms.iter().cloned().collect();
+        let layout = UniformLayout::new(&user_set, &HashMap::new());
+        let user_bytes = layout.pack_iter(&user_uniforms);
+        let user_uniforms_buffer = if user_bytes.is_empty() {
+            None
+        } else {
+            Some(device.create_buffer_init(&BufferInitDescriptor {
+                label: Some("Custom Uniforms Buffer"),
+                contents: &user_bytes,
+                usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+            }))
+        };
+
+        let textures: Vec<AssetTexture> = images
+            .iter()
+            .map(|an_image| AssetTexture::new_with_image(an_image, device, queue))
+            .collect();
+        let ramps: Vec<RampTexture> = ramps
+            .iter()
+            .map(|a_ramp| RampTexture::new(a_ramp, device, queue))
+            .collect();
+        let noise = NoiseTexture2D::new(device, queue, NoiseType::White, 0);
+
+        let (primary_layout, primary_bind_group) = create_uniform_bindings(
+            device,
+            (&uniforms_buffer, std::mem::size_of::<Uniforms>()),
+            user_uniforms_buffer
+                .as_ref()
+                .map(|buffer| (buffer, user_bytes.len())),
+        );
+        let (secondary_layout, secondary_bind_group) =
+            create_texture_bindings(device, &textures, &ramps, &noise);
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shader Renderer Pipeline Layout"),
+            bind_group_layouts: &[&primary_layout, &secondary_layout],
+            push_constant_ranges: &[],
+        });
+
+        let vs_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Vertex Shader"),
+            source: wgpu::util::make_spirv(VS_MODULE_BYTES),
+            flags: wgpu::ShaderFlags::VALIDATION,
+        });
+        let fs_module = create_fragment_module(device, fs_spirv_data);
+
+        ShaderRenderer {
+            uniforms,
+            uniforms_buffer,
+            user_uniforms,
+            layout,
+            user_uniforms_buffer,
+            textures,
+            ramps,
+            noise,
+            bind_groups: [primary_bind_group, secondary_bind_group],
+            pipeline_layout,
+            vs_module,
+            fs_module,
+            pipelines: HashMap::new(),
+            average_pipelines: HashMap::new(),
+        }
+    }
+
+    /// Replace the fragment shader. It must declare the same bindings as the one it replaces.
+    pub fn load_shader(&mut self, device: &wgpu::Device, fs_spirv_data: &[u8]) {
+        self.fs_module = create_fragment_module(device, fs_spirv_data);
+        self.pipelines.clear();
+    }
+
+    /// Regenerate the noise texture bound after the ramps.
+    pub fn set_noise(&mut self, queue: &wgpu::Queue, noise_type: NoiseType, seed: u32) {
+        self.noise.update(noise_type, seed, queue);
+    }
+
+    pub fn user_uniforms(&self) -> &[UserUniform] {
+        &self.user_uniforms
+    }
+
+    /// Replace the user uniform with the same name.
+    /// Returns false if there is no such uniform, or if the new value does not have the same size in the buffer.
+    pub fn set_user_uniform(&mut self, uniform: UserUniform) -> bool {
+        match self
+            .user_uniforms
+            .iter_mut()
+            .find(|existing| existing.name == uniform.name)
+        {
+            Some(existing) if existing.bytes.len() == uniform.bytes.len() => {
+                *existing = uniform;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn num_textures(&self) -> usize {
+        self.textures.len() + self.ramps.len()
+    }
+
+    /// Encode a render of the shader into `target`, which must have the given resolution and format.
+    /// [Uniforms::resolution] and [Uniforms::aspect] are set from `resolution`, all other uniforms are used as they are.
+    ///
+    /// With `warm_up_frames`, like the Easel app's painting warm-up, `target` gets the average of that many frames
+    /// before this one and this one, each a [Uniforms::frame_num] earlier at the same time, for shaders that need
+    /// several frames to settle, e.g. noise seeded by the frame number.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        format: wgpu::TextureFormat,
+        resolution: UIntVector2,
+        warm_up_frames: u32,
+    ) {
+        self.uniforms.set_resolution(resolution.x, resolution.y);
+        if let Some(buffer) = &self.user_uniforms_buffer {
+            queue.write_buffer(buffer, 0, &self.layout.pack_iter(&self.user_uniforms));
+        }
+
+        let (layout, vs_module, fs_module) =
+            (&self.pipeline_layout, &self.vs_module, &self.fs_module);
+        let pipeline = self.pipelines.entry(format).or_insert_with(|| {
+            crate::utils::create_shader_pipeline(
+                device,
+                layout,
+                vs_module,
+                fs_module,
+                crate::utils::DEFAULT_ENTRY_POINT,
+                format,
+                "Shader Renderer Pipeline",
+            )
+        });
+        if warm_up_frames == 0 {
+            encode_uniforms_upload(device, encoder, &self.uniforms_buffer, &self.uniforms);
+            encode_shader_pass(
+                encoder,
+                target,
+                pipeline,
+                &self.bind_groups,
+                wgpu::Color::BLACK,
+            );
+            return;
+        }
+
+        let average_pipeline = self.average_pipelines.entry(format).or_insert_with(|| {
+            crate::utils::create_blit_pipeline(
+                device,
+                vs_module,
+                format,
+                accumulation_blend_state(),
+            )
+        });
+        let frame = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Shader Renderer Warm-up Frame"),
+            size: Extent3d {
+                width: resolution.x,
+                height: resolution.y,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+        });
+        let frame_view = frame.create_view(&wgpu::TextureViewDescriptor::default());
+        for (samples, frames_before) in (0..=warm_up_frames).rev().enumerate() {
+            let mut uniforms = self.uniforms;
+            uniforms.frame_num = uniforms.frame_num.saturating_sub(frames_before);
+            encode_uniforms_upload(device, encoder, &self.uniforms_buffer, &uniforms);
+            encode_shader_pass(
+                encoder,
+                &frame_view,
+                pipeline,
+                &self.bind_groups,
+                wgpu::Color::BLACK,
+            );
+            encode_average_blend(
+                device,
+                encoder,
+                average_pipeline,
+                &frame_view,
+                target,
+                samples as u32,
+            );
+        }
+    }
+
+    /// Render one frame off-screen and read it back. Blocks until the GPU has finished.
+    /// `warm_up_frames` are averaged in as in [Self::render].
+    pub fn render_image(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        resolution: UIntVector2,
+        warm_up_frames: u32,
+    ) -> image::RgbaImage {
+        let (width, height) = (resolution.x, resolution.y);
+        let size = Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Shader Renderer Image"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: IMAGE_TEXTURE_FORMAT,
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+        });
+        // Buffer rows must be aligned, so they may be longer than the image rows.
+        let row_bytes = width * 4;
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row_bytes = row_bytes.div_ceil(alignment) * alignment;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shader Renderer Readback Buffer"),
+            size: (padded_row_bytes * height) as u64,
+            usage: wgpu::BufferUsage::COPY_DST | wgpu::BufferUsage::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Shader Renderer Command Encoder"),
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.render(
+            device,
+            queue,
+            &mut encoder,
+            &view,
+            IMAGE_TEXTURE_FORMAT,
+            resolution,
+            warm_up_frames,
+        );
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                texture: &texture,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    bytes_per_row: NonZeroU32::new(padded_row_bytes),
+                    offset: 0,
+                    rows_per_image: NonZeroU32::new(height),
+                },
+            },
+            size,
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let mapping = slice.map_async(wgpu::MapMode::Read);
+        device.poll(wgpu::Maintain::Wait);
+        block_on(mapping).unwrap();
+        let mut pixels = Vec::with_capacity((row_bytes * height) as usize);
+        {
+            let mapped = slice.get_mapped_range();
+            for row in mapped.chunks(padded_row_bytes as usize) {
+                pixels.extend_from_slice(&row[..row_bytes as usize]);
+            }
+        }
+        buffer.unmap();
+        image::RgbaImage::from_raw(width, height, pixels).unwrap()
+    }
+}
+
+fn create_fragment_module(device: &wgpu::Device, fs_spirv_data: &[u8]) -> wgpu::ShaderModule {
+    device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+        label: Some("Painting Fragment Shader"),
+        source: wgpu::util::make_spirv(fs_spirv_data),
+        flags: wgpu::ShaderFlags::VALIDATION,
+    })
+}
+
+/// Encode a copy of `uniforms` to `buffer`, ordered with the passes encoded around it.
+/// Unlike [wgpu::Queue::write_buffer], passes in the same encoder can each see different uniforms.
+pub fn encode_uniforms_upload(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    buffer: &wgpu::Buffer,
+    uniforms: &Uniforms,
+) {
+    // Copy uniforms from CPU to staging buffer, then copy from staging buffer to main buf.
+    let staging_buffer = device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("Uniforms Buffer"),
+        contents: bytemuck::bytes_of(uniforms),
+        usage: wgpu::BufferUsage::COPY_SRC,
+    });
+    encoder.copy_buffer_to_buffer(
+        &staging_buffer,
+        0,
+        buffer,
+        0,
+        std::mem::size_of::<Uniforms>() as u64,
+    );
+}
+
+/// Encode a pass running the shader with `pipeline` over all of `target`, cleared to `clear_color` first.
+/// `bind_groups` are set in order, see [create_uniform_bindings] and [create_texture_bindings].
+pub fn encode_shader_pass(
+    encoder: &mut wgpu::CommandEncoder,
+    target: &wgpu::TextureView,
+    pipeline: &wgpu::RenderPipeline,
+    bind_groups: &[wgpu::BindGroup],
+    clear_color: wgpu::Color,
+) {
+    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Shader Render Pass"),
+        color_attachments: &[wgpu::RenderPassColorAttachment {
+            view: target,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(clear_color),
+                store: true,
+            },
+        }],
+        depth_stencil_attachment: None,
+    });
+    for (index, bind_group) in bind_groups.iter().enumerate() {
+        render_pass.set_bind_group(index as u32, bind_group, &[]);
+    }
+    render_pass.set_pipeline(pipeline);
+    render_pass.draw(0..3, 0..1);
+}
+
+/// Blend state that mixes the incoming frame into an average by the blend constant, i.e. `1 / (samples + 1)`.
+pub fn accumulation_blend_state() -> wgpu::BlendState {
+    let component = wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Constant,
+        dst_factor: wgpu::BlendFactor::OneMinusConstant,
+        operation: wgpu::BlendOperation::Add,
+    };
+    wgpu::BlendState {
+        color: component,
+        alpha: component,
+    }
+}
+
+/// Encode a pass blending `frame` into `average`, which holds the average of `samples` frames so far and holds the
+/// average of `samples + 1` after. `pipeline` is a [crate::utils::create_blit_pipeline] with
+/// [accumulation_blend_state] for the format of `average`.
+pub fn encode_average_blend(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    pipeline: &wgpu::RenderPipeline,
+    frame: &wgpu::TextureView,
+    average: &wgpu::TextureView,
+    samples: u32,
+) {
+    let weight = 1.0 / (samples + 1) as f64;
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Average Bind Group"),
+        layout: &pipeline.get_bind_group_layout(0),
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::Sampler(&default_color_sampler(device)),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: BindingResource::TextureView(frame),
+            },
+        ],
+    });
+    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Average Blend Pass"),
+        color_attachments: &[wgpu::RenderPassColorAttachment {
+            view: average,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: true,
+            },
+        }],
+        depth_stencil_attachment: None,
+    });
+    render_pass.set_bind_group(0, &bind_group, &[]);
+    render_pass.set_pipeline(pipeline);
+    render_pass.set_blend_constant(wgpu::Color {
+        r: weight,
+        g: weight,
+        b: weight,
+        a: weight,
+    });
+    render_pass.draw(0..3, 0..1);
+}
+
+/// Layout and bind group for set 0: Easel's uniforms at binding 0, followed by the user uniforms at binding 1, if any.
+/// Buffers are given with their size in bytes.
+pub fn create_uniform_bindings(
+    device: &wgpu::Device,
+    uniforms: (&wgpu::Buffer, usize),
+    user_uniforms: Option<(&wgpu::Buffer, usize)>,
+) -> (wgpu::BindGroupLayout, wgpu::BindGroup) {
+    let mut buffers = vec![uniforms];
+    buffers.extend(user_uniforms);
+    let bind_group_layout_entries: Vec<BindGroupLayoutEntry> = (0..buffers.len())
+        .map(|binding| BindGroupLayoutEntry {
+            binding: binding as u32,
+            visibility: wgpu::ShaderStage::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        })
+        .collect();
+    let primary_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &bind_group_layout_entries,
+        });
+    let primary_bind_group_entries: Vec<BindGroupEntry> = buffers
+        .iter()
+        .enumerate()
+        .map(|(binding, (buffer, size))| BindGroupEntry {
+            binding: binding as u32,
+            resource: BindingResource::Buffer(BufferBinding {
+                buffer,
+                offset: 0,
+                size: Some(NonZeroU64::new(*size as u64).unwrap()),
+            }),
+        })
+        .collect();
+    let primary_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Primary Bind Group"),
+        layout: &primary_bind_group_layout,
+        entries: &primary_bind_group_entries,
+    });
+    (primary_bind_group_layout, primary_bind_group)
+}
+
+/// Layout and bind group for set 1: a sampler at binding 0, followed by the textures, the ramps and the noise texture.
+pub fn create_texture_bindings(
+    device: &wgpu::Device,
+    textures: &[AssetTexture],
+    ramps: &[RampTexture],
+    noise: &NoiseTexture2D,
+) -> (wgpu::BindGroupLayout, wgpu::BindGroup) {
+    let secondary_bind_group_layout: wgpu::BindGroupLayout;
+    {
+        let mut bind_group_layout_entries = Vec::<wgpu::BindGroupLayoutEntry>::new();
+        // For now, we only have 1 sampler per set
+        bind_group_layout_entries.push(BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStage::FRAGMENT,
+            ty: wgpu::BindingType::Sampler {
+                filtering: true,
+                comparison: false,
+            },
+            count: None,
+        });
+        for i in 1..=textures.len() {
+            bind_group_layout_entries.push(BindGroupLayoutEntry {
+                binding: i as u32,
+                visibility: wgpu::ShaderStage::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            });
+        }
+        // Ramps follow the images.
+        for i in 1..=ramps.len() {
+            bind_group_layout_entries.push(BindGroupLayoutEntry {
+                binding: (textures.len() + i) as u32,
+                visibility: wgpu::ShaderStage::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D1,
+                    multisampled: false,
+                },
+                count: None,
+            });
+        }
+        // The noise texture is always bound last.
+        bind_group_layout_entries.push(BindGroupLayoutEntry {
+            binding: (textures.len() + ramps.len() + 1) as u32,
+            visibility: wgpu::ShaderStage::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        });
+        // Create the Bind Group Layout.
+        secondary_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &bind_group_layout_entries,
+            });
+    }
+    let secondary_bind_group: wgpu::BindGroup;
+    {
+        let mut secondary_bind_group_entries: Vec<BindGroupEntry> = Vec::new();
+        let default_sampler = default_color_sampler(device);
+        secondary_bind_group_entries.push(BindGroupEntry {
+            binding: 0,
+            resource: BindingResource::Sampler(&default_sampler),
+        });
+        // Create texture views.
+        let mut tex_views = Vec::<wgpu::TextureView>::new();
+        for tex in textures.iter() {
+            let texture_view = tex.get_view(0);
+            tex_views.push(texture_view);
+        }
+        for ramp in ramps.iter() {
+            tex_views.push(ramp.get_view());
+        }
+        tex_views.push(noise.get_view());
+        // Add texture view bindings.
+        for tex_bind_idx in 1..=tex_views.len() {
+            secondary_bind_group_entries.push(BindGroupEntry {
+                binding: tex_bind_idx as u32,
+                resource: BindingResource::TextureView(&tex_views[tex_bind_idx - 1]),
+            });
+        }
+        secondary_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Secondary Bind Group"),
+            layout: &secondary_bind_group_layout,
+            entries: &secondary_bind_group_entries,
+        });
+    }
+    (secondary_bind_group_layout, secondary_bind_group)
+}
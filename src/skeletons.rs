@@ -11,6 +11,12 @@ layout(set = 0, binding = 0) uniform Uniforms {
     float u_time_delta;
     uint u_frame_num;
     uint u_num_textures;
+    vec4 u_mouse_delta;
+    vec4 u_jitter;
+    float u_aspect;
+    uint u_touch_count;
+    vec4 u_touches[10];
+    vec4 u_stereo;
 };
 
 layout(set = 0, binding = 1) uniform CustomUniforms { bool vertical_wipe; };
@@ -33,3 +39,150 @@ void main() {
 }
 
 "#;
+
+/// Uniforms Easel binds to every shader, shared by the [ShaderTemplate]s.
+static BUILTIN_UNIFORMS: &str = r#"#version 450
+
+layout(set = 0, binding = 0) uniform Uniforms {
+    vec4 u_resolution;
+    vec4 u_mouse_info;
+    ivec4 u_mouse_button_pressed;
+    ivec4 u_date;
+    float u_time;
+    float u_time_delta;
+    uint u_frame_num;
+    uint u_num_textures;
+    vec4 u_mouse_delta;
+    vec4 u_jitter;
+    float u_aspect;
+    uint u_touch_count;
+    vec4 u_touches[10];
+    vec4 u_stereo;
+};
+
+layout(location = 0) in vec2 tex_coords;
+layout(location = 0) out vec4 f_color;
+"#;
+
+static PLAIN_TEMPLATE: &str = r#"
+void main() {
+    vec2 uv = tex_coords;
+    vec3 color = 0.5 + 0.5 * cos(u_time + uv.xyx + vec3(0.0, 2.0, 4.0));
+    f_color = vec4(color, 1.0);
+}
+"#;
+
+static SHADERTOY_TEMPLATE: &str = r#"
+// Shadertoy names for Easel's uniforms. fragCoord has its origin at the bottom left, as on Shadertoy.
+#define iResolution vec3(u_resolution.xy, 1.0)
+#define iTime u_time
+#define iTimeDelta u_time_delta
+#define iFrame int(u_frame_num)
+#define iTouchCount int(u_touch_count)
+#define iTouch u_touches
+#define iMouse vec4(u_mouse_info.x, u_resolution.y - u_mouse_info.y, u_mouse_button_pressed.x != 0 ? 1.0 : -1.0, 0.0)
+
+void mainImage(out vec4 fragColor, in vec2 fragCoord) {
+    vec2 uv = fragCoord / iResolution.xy;
+    vec3 col = 0.5 + 0.5 * cos(iTime + uv.xyx + vec3(0.0, 2.0, 4.0));
+    fragColor = vec4(col, 1.0);
+}
+
+void main() {
+    vec2 frag_coord = vec2(gl_FragCoord.x, u_resolution.y - gl_FragCoord.y);
+    mainImage(f_color, frag_coord);
+}
+"#;
+
+static RAYMARCH_TEMPLATE: &str = r#"
+const int MAX_STEPS = 128;
+const float MAX_DISTANCE = 100.0;
+const float SURFACE_DISTANCE = 0.001;
+
+float scene(vec3 p) {
+    float sphere = length(p - vec3(0.0, 1.0, 0.0)) - 1.0;
+    float ground = p.y;
+    return min(sphere, ground);
+}
+
+vec3 normal(vec3 p) {
+    vec2 e = vec2(0.001, 0.0);
+    return normalize(vec3(scene(p + e.xyy) - scene(p - e.xyy),
+                          scene(p + e.yxy) - scene(p - e.yxy),
+                          scene(p + e.yyx) - scene(p - e.yyx)));
+}
+
+float march(vec3 origin, vec3 direction) {
+    float travelled = 0.0;
+    for (int i = 0; i < MAX_STEPS; i++) {
+        float d = scene(origin + direction * travelled);
+        travelled += d;
+        if (d < SURFACE_DISTANCE || travelled > MAX_DISTANCE) {
+            break;
+        }
+    }
+    return travelled;
+}
+
+void main() {
+    vec2 uv = (tex_coords - 0.5) * vec2(u_aspect, -1.0);
+    vec3 origin = vec3(3.0 * sin(0.3 * u_time), 1.5, 3.0 * cos(0.3 * u_time) - 1.0);
+    vec3 forward = normalize(vec3(0.0, 1.0, 0.0) - origin);
+    vec3 right = normalize(cross(forward, vec3(0.0, 1.0, 0.0)));
+    vec3 up = cross(right, forward);
+    vec3 direction = normalize(forward + uv.x * right + uv.y * up);
+
+    vec3 color = vec3(0.6, 0.7, 0.9) - 0.5 * direction.y;
+    float hit = march(origin, direction);
+    if (hit < MAX_DISTANCE) {
+        vec3 p = origin + direction * hit;
+        vec3 light = normalize(vec3(1.0, 2.0, -1.0));
+        float diffuse = max(dot(normal(p), light), 0.0);
+        float shadow = march(p + normal(p) * 0.01, light) < MAX_DISTANCE ? 0.2 : 1.0;
+        color = vec3(0.9, 0.6, 0.4) * (0.1 + diffuse * shadow);
+    }
+    f_color = vec4(color, 1.0);
+}
+"#;
+
+/// Starting points for a new shader. Each only uses the uniforms Easel always binds, so it runs as is.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ShaderTemplate {
+    Plain,
+    Shadertoy,
+    Raymarch,
+}
+
+impl ShaderTemplate {
+    pub const ALL: [ShaderTemplate; 3] = [
+        ShaderTemplate::Plain,
+        ShaderTemplate::Shadertoy,
+        ShaderTemplate::Raymarch,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShaderTemplate::Plain => "Plain Fragment",
+            ShaderTemplate::Shadertoy => "Shadertoy-style",
+            ShaderTemplate::Raymarch => "Raymarch Starter",
+        }
+    }
+
+    /// Complete GLSL source, ready to compile.
+    pub fn source(&self) -> String {
+        let body = match self {
+            ShaderTemplate::Plain => PLAIN_TEMPLATE,
+            ShaderTemplate::Shadertoy => SHADERTOY_TEMPLATE,
+            ShaderTemplate::Raymarch => RAYMARCH_TEMPLATE,
+        };
+        format!("{}{}", BUILTIN_UNIFORMS, body)
+    }
+
+    /// Write the template to `file`, refusing to overwrite an existing file.
+    pub fn write(&self, file: &str) -> Result<(), String> {
+        if std::path::Path::new(file).exists() {
+            return Err(format!("There is already a file at {}", file));
+        }
+        std::fs::write(file, self.source()).map_err(|e| e.to_string())
+    }
+}
@@ -1,125 +1,209 @@
-use crate::texture::Texture;
-use byteorder::{NativeEndian, WriteBytesExt};
-use noise::{utils::*, Perlin};
-use std::vec::Vec;
-use wgpu::{Extent3d, ImageCopyTexture, Origin3d, TextureDataLayout};
-
-pub enum GenerativeTextureType {
-    // Perlin(usize, usize, bool),
-    Perlin(usize, usize, bool),
+use std::num::NonZeroU32;
+use wgpu::{Extent3d, ImageCopyTexture, ImageDataLayout, Origin3d};
+
+/// Width and height of the noise texture. It tiles seamlessly.
+pub const NOISE_TEXTURE_SIZE: u32 = 256;
+/// Lattice cells per side for [NoiseType::Value].
+const VALUE_NOISE_CELLS: u32 = 16;
+/// Radius of the box blur subtracted from white noise to make [NoiseType::Blue].
+const BLUE_NOISE_BLUR_RADIUS: i32 = 2;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum NoiseType {
+    /// Independent random value per texel.
+    White,
+    /// Random values without low frequencies, so samples spread evenly. Approximated by high-pass filtering white noise.
+    Blue,
+    /// Random values on a coarse lattice, smoothly interpolated.
+    Value,
+}
+
+impl NoiseType {
+    pub const ALL: [NoiseType; 3] = [NoiseType::White, NoiseType::Blue, NoiseType::Value];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NoiseType::White => "White",
+            NoiseType::Blue => "Blue",
+            NoiseType::Value => "Value",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<NoiseType> {
+        NoiseType::ALL
+            .iter()
+            .copied()
+            .find(|t| t.name().eq_ignore_ascii_case(name))
+    }
 }
 
+/// Seeded noise bound to the shader after the images and ramps.
+/// Each of the four channels holds independent noise. The same type and seed always produce the same texture.
 pub struct NoiseTexture2D {
-    pub noise_handle: Perlin,
-    pub texture_handle: wgpu::Texture,
-    pub dimension: wgpu::TextureDimension,
+    handle: wgpu::Texture,
+    pub noise_type: NoiseType,
+    pub seed: u32,
 }
 
 impl NoiseTexture2D {
+    /// Noise is data rather than color, so it isn't sRGB encoded.
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
     pub fn new(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-        variant: GenerativeTextureType,
+        noise_type: NoiseType,
+        seed: u32,
     ) -> NoiseTexture2D {
-        // Create perlin noise fn.
-        let (noise_handle, width, height, seamless) = match variant {
-            GenerativeTextureType::Perlin(w, h, s) => (Perlin::new(), w, h, s),
-        };
-        let noise_map = PlaneMapBuilder::new(&noise_handle)
-            .set_size(width, height)
-            .set_x_bounds(-5.0, 5.0)
-            .set_y_bounds(-5.0, 5.0)
-            // .set_x_bounds(-(width as f64) / 50.0, (width as f64) / 50.0)
-            // .set_y_bounds(-(height as f64) / 50.0, (height as f64) / 50.0)
-            .set_is_seamless(seamless)
-            .build();
-        // Generate texture.
-        let mut noise_data: Vec<u8> =
-            Vec::with_capacity(width * height * std::mem::size_of::<f32>());
-        for y in 0..height {
-            for x in 0..width {
-                let noise_val = noise_map.get_value(x, y) as f32;
-                noise_data.write_f32::<NativeEndian>(noise_val).unwrap();
-            }
-        }
-
-        // Create device texture handle.
-        let bytes_per_row = width as u32 * 4;
-        let tex_desc = wgpu::TextureDescriptor {
-            label: None,
-            size: wgpu::Extent3d {
-                width: width as u32,
-                height: height as u32,
+        let handle = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Noise"),
+            size: Extent3d {
+                width: NOISE_TEXTURE_SIZE,
+                height: NOISE_TEXTURE_SIZE,
                 depth_or_array_layers: 1,
             },
             mip_level_count: 1,
             usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::R32Float,
+            format: Self::FORMAT,
             sample_count: 1,
+        });
+        let mut texture = NoiseTexture2D {
+            handle,
+            noise_type,
+            seed,
         };
-        let texture_handle = device.create_texture(&tex_desc);
+        texture.update(noise_type, seed, queue);
+        texture
+    }
 
-        // Copy data over.
+    /// Regenerate the noise and copy it over the existing texture contents.
+    pub fn update(&mut self, noise_type: NoiseType, seed: u32, queue: &wgpu::Queue) {
+        self.noise_type = noise_type;
+        self.seed = seed;
         queue.write_texture(
             ImageCopyTexture {
                 origin: Origin3d::ZERO,
                 mip_level: 0,
-                texture: &texture_handle,
+                texture: &self.handle,
             },
-            &noise_data,
-            TextureDataLayout {
-                bytes_per_row,
+            &generate_noise(noise_type, seed),
+            ImageDataLayout {
+                bytes_per_row: NonZeroU32::new(NOISE_TEXTURE_SIZE * 4),
                 offset: 0,
-                rows_per_image: height as u32,
+                rows_per_image: NonZeroU32::new(NOISE_TEXTURE_SIZE),
             },
             Extent3d {
-                width: width as u32,
-                height: height as u32,
+                width: NOISE_TEXTURE_SIZE,
+                height: NOISE_TEXTURE_SIZE,
                 depth_or_array_layers: 1,
             },
         );
+    }
 
-        NoiseTexture2D {
-            noise_handle,
-            texture_handle,
-            dimension: wgpu::TextureDimension::D2,
-        }
+    pub fn byte_size(&self) -> u64 {
+        crate::utils::texture_bytes(NOISE_TEXTURE_SIZE, NOISE_TEXTURE_SIZE, Self::FORMAT)
     }
 
-    pub fn get_color_sampler(device: &wgpu::Device) -> wgpu::Sampler {
-        device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("Perlin Default"),
-            address_mode_u: wgpu::AddressMode::Repeat,
-            address_mode_v: wgpu::AddressMode::Repeat,
-            address_mode_w: wgpu::AddressMode::Repeat,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            lod_min_clamp: 0.0,
-            lod_max_clamp: std::f32::MAX,
-            compare: None,
-            anisotropy_clamp: None,
+    pub fn get_view(&self) -> wgpu::TextureView {
+        self.handle.create_view(&wgpu::TextureViewDescriptor {
+            format: Some(Self::FORMAT),
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            aspect: wgpu::TextureAspect::All,
+            ..Default::default()
         })
     }
 }
 
-impl Texture for NoiseTexture2D {
-    fn get_view(&self, mip_level: u32) -> wgpu::TextureView {
-        self.texture_handle
-            .create_view(&wgpu::TextureViewDescriptor {
-                label: None,
-                format: Some(wgpu::TextureFormat::R32Float),
-                dimension: Some(wgpu::TextureViewDimension::D2), // TODO: Match descriptor's dimension.
-                aspect: wgpu::TextureAspect::All,
-                base_mip_level: mip_level,
-                level_count: None,
-                base_array_layer: 0,
-                array_layer_count: None,
-            })
+/// RGBA8 pixels of a [NOISE_TEXTURE_SIZE] square noise texture.
+pub fn generate_noise(noise_type: NoiseType, seed: u32) -> Vec<u8> {
+    let size = NOISE_TEXTURE_SIZE as usize;
+    let mut pixels = vec![0u8; size * size * 4];
+    for channel in 0..4 {
+        let channel_seed = (seed as u64) << 2 | channel as u64;
+        let values = match noise_type {
+            NoiseType::White => white_noise(channel_seed),
+            NoiseType::Blue => blue_noise(channel_seed),
+            NoiseType::Value => value_noise(channel_seed),
+        };
+        for (i, value) in values.iter().enumerate() {
+            pixels[i * 4 + channel] = (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        }
+    }
+    pixels
+}
+
+/// SplitMix64, a small generator that is fully determined by its seed, unlike [crate::utils::random_u64].
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1).
+    fn next_f32(&mut self) -> f32 {
+        (self.next() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+fn white_noise(seed: u64) -> Vec<f32> {
+    let mut rng = SplitMix64(seed);
+    let count = (NOISE_TEXTURE_SIZE * NOISE_TEXTURE_SIZE) as usize;
+    (0..count).map(|_| rng.next_f32()).collect()
+}
+
+/// White noise minus its blurred self, then remapped by rank so values stay uniformly distributed.
+fn blue_noise(seed: u64) -> Vec<f32> {
+    let size = NOISE_TEXTURE_SIZE as i32;
+    let white = white_noise(seed);
+    let at = |x: i32, y: i32| white[(y.rem_euclid(size) * size + x.rem_euclid(size)) as usize];
+    let radius = BLUE_NOISE_BLUR_RADIUS;
+    let taps = ((2 * radius + 1) * (2 * radius + 1)) as f32;
+    let mut high_pass = Vec::with_capacity(white.len());
+    for y in 0..size {
+        for x in 0..size {
+            let mut blurred = 0.0;
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    blurred += at(x + dx, y + dy);
+                }
+            }
+            high_pass.push(at(x, y) - blurred / taps);
+        }
+    }
+    let mut order: Vec<usize> = (0..high_pass.len()).collect();
+    order.sort_by(|a, b| high_pass[*a].partial_cmp(&high_pass[*b]).unwrap());
+    let mut ranked = vec![0.0; high_pass.len()];
+    let last = (order.len() - 1) as f32;
+    for (rank, index) in order.into_iter().enumerate() {
+        ranked[index] = rank as f32 / last;
     }
+    ranked
+}
 
-    fn get_handle(&self) -> &wgpu::Texture {
-        &self.texture_handle
+fn value_noise(seed: u64) -> Vec<f32> {
+    let cells = VALUE_NOISE_CELLS;
+    let mut rng = SplitMix64(seed);
+    let lattice: Vec<f32> = (0..cells * cells).map(|_| rng.next_f32()).collect();
+    // Wrapping the lattice makes the texture tile.
+    let at = |x: u32, y: u32| lattice[((y % cells) * cells + x % cells) as usize];
+    let cell_size = (NOISE_TEXTURE_SIZE / cells) as f32;
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let mut values = Vec::with_capacity((NOISE_TEXTURE_SIZE * NOISE_TEXTURE_SIZE) as usize);
+    for y in 0..NOISE_TEXTURE_SIZE {
+        for x in 0..NOISE_TEXTURE_SIZE {
+            let (fx, fy) = (x as f32 / cell_size, y as f32 / cell_size);
+            let (cx, cy) = (fx.floor() as u32, fy.floor() as u32);
+            let (tx, ty) = (smooth(fx.fract()), smooth(fy.fract()));
+            let top = at(cx, cy) + (at(cx + 1, cy) - at(cx, cy)) * tx;
+            let bottom = at(cx, cy + 1) + (at(cx + 1, cy + 1) - at(cx, cy + 1)) * tx;
+            values.push(top + (bottom - top) * ty);
+        }
     }
+    values
 }
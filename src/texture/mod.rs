@@ -1,7 +1,11 @@
+use crate::uniforms::{RampUniform, RAMP_TEXTURE_WIDTH};
 use image::{DynamicImage, GenericImageView};
 use std::num::NonZeroU32;
 use wgpu::{Extent3d, ImageCopyTexture, ImageDataLayout, Origin3d};
 
+mod generative;
+pub use self::generative::*;
+
 /// Construct a [wgpu::Sampler] object using our defaults.
 pub fn default_color_sampler(device: &wgpu::Device) -> wgpu::Sampler {
     device.create_sampler(&wgpu::SamplerDescriptor {
@@ -25,6 +29,8 @@ pub fn default_color_sampler(device: &wgpu::Device) -> wgpu::Sampler {
 pub struct AssetTexture {
     handle: wgpu::Texture,
     pub format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
 }
 
 impl AssetTexture {
@@ -74,9 +80,16 @@ impl AssetTexture {
         AssetTexture {
             handle: texture,
             format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width: image.width(),
+            height: image.height(),
         }
     }
 
+    /// Size of the texture on the GPU.
+    pub fn byte_size(&self) -> u64 {
+        crate::utils::texture_bytes(self.width, self.height, self.format)
+    }
+
     pub fn get_view(&self, mip_level: u32) -> wgpu::TextureView {
         self.handle.create_view(&wgpu::TextureViewDescriptor {
             format: Some(self.format),
@@ -91,3 +104,69 @@ impl AssetTexture {
     //     &self.handle
     // }
 }
+
+/// A [RampUniform] baked into a 1D [wgpu::Texture] that can be re-uploaded whenever the ramp is edited.
+pub struct RampTexture {
+    handle: wgpu::Texture,
+    pub name: String,
+}
+
+impl RampTexture {
+    /// The [wgpu::TextureFormat] ramps are baked into.
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+    /// Allocate a 1D texture on the GPU device and upload the baked ramp into it.
+    pub fn new(ramp: &RampUniform, device: &wgpu::Device, queue: &wgpu::Queue) -> RampTexture {
+        let handle = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Ramp"),
+            size: Extent3d {
+                width: RAMP_TEXTURE_WIDTH,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+            dimension: wgpu::TextureDimension::D1,
+            format: Self::FORMAT,
+            sample_count: 1,
+        });
+        let texture = RampTexture {
+            handle,
+            name: ramp.name.clone(),
+        };
+        texture.update(ramp, queue);
+        texture
+    }
+
+    /// Re-bake the ramp and copy it over the existing texture contents.
+    pub fn update(&self, ramp: &RampUniform, queue: &wgpu::Queue) {
+        let bytes_per_row = RAMP_TEXTURE_WIDTH * 4 * std::mem::size_of::<half::f16>() as u32;
+        queue.write_texture(
+            ImageCopyTexture {
+                origin: Origin3d::ZERO,
+                mip_level: 0,
+                texture: &self.handle,
+            },
+            &ramp.bake(),
+            ImageDataLayout {
+                bytes_per_row: NonZeroU32::new(bytes_per_row),
+                offset: 0,
+                rows_per_image: NonZeroU32::new(1),
+            },
+            Extent3d {
+                width: RAMP_TEXTURE_WIDTH,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    pub fn get_view(&self) -> wgpu::TextureView {
+        self.handle.create_view(&wgpu::TextureViewDescriptor {
+            format: Some(Self::FORMAT),
+            dimension: Some(wgpu::TextureViewDimension::D1),
+            aspect: wgpu::TextureAspect::All,
+            ..Default::default()
+        })
+    }
+}
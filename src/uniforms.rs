@@ -1,12 +1,19 @@
 use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 
-use crate::utils::{convert_bytes_to_value, convert_value_to_bytes};
-use crate::vector::{IntVector4, Vector4};
+use crate::utils::{convert_bytes_to_value, convert_value_to_bytes, random_u64, SeededRandom};
+use crate::vector::{IntVector2, IntVector3, IntVector4, Vector4};
 use bytemuck::{Pod, Zeroable};
-use imgui::ImString;
+use half::f16;
+use imgui::{im_str, ImStr, ImString};
 use log::{debug, error};
 
+/// Number of texels a [RampUniform] is baked into when uploaded to the GPU.
+pub static RAMP_TEXTURE_WIDTH: u32 = 256;
+
+/// Length of the [Uniforms::touches] array.
+pub const MAX_TOUCH_POINTS: usize = 10;
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone, Pod, Zeroable)]
 /// A struct of uniforms provided by Easel and bound to every shader.
@@ -29,6 +36,25 @@ pub struct Uniforms {
     pub frame_num: u32,
     /// Number of textures bound.
     pub num_textures: u32,
+    /// xy: mouse movement in pixels since the last frame while the mouse is captured.
+    /// z: 1 if the mouse is captured, 0 otherwise. w is unused.
+    pub mouse_delta: Vector4,
+    /// xy: sub-pixel jitter offset in [-0.5, 0.5) pixels from an R2 sequence, zero when jitter is disabled.
+    /// z: index into the sequence, reset on shader reload. w is unused.
+    pub jitter: Vector4,
+    /// Width / height of [Self::resolution], i.e. of the canvas or of the painting or movie frame being captured.
+    pub aspect: f32,
+    /// Number of points touching the canvas window, i.e. of valid [Self::touches].
+    pub touch_count: u32,
+    /// Aligns [Self::touches] to 16 bytes, as uniform blocks do.
+    _padding: [f32; 2],
+    /// Points touching the canvas window, oldest first. xy: position in pixels like the mouse's, z: touch id,
+    /// w: 0 if the point just landed, 1 once it has moved.
+    pub touches: [Vector4; MAX_TOUCH_POINTS],
+    /// Set while rendering an eye of a stereo capture, all zero otherwise, see [crate::canvas::StereoSettings].
+    /// x: horizontal offset of the eye from the center in scene units, y: 0 for the left eye, 1 for the right,
+    /// z: 1 while rendering an eye, w: interaxial distance.
+    pub stereo: Vector4,
 }
 
 impl Uniforms {
@@ -47,8 +73,22 @@ impl Uniforms {
             mouse_button: IntVector4::zero(),
             num_textures: 0,
             date: IntVector4::zero(),
+            mouse_delta: Vector4::zero(),
+            jitter: Vector4::zero(),
+            aspect: 1.0,
+            touch_count: 0,
+            _padding: [0.0; 2],
+            touches: [Vector4::zero(); MAX_TOUCH_POINTS],
+            stereo: Vector4::zero(),
         }
     }
+
+    /// Set [Self::resolution] and the [Self::aspect] that goes with it, unit: pixels.
+    pub fn set_resolution(&mut self, width: u32, height: u32) {
+        self.resolution.x = width as f32;
+        self.resolution.y = height as f32;
+        self.aspect = width as f32 / height.max(1) as f32;
+    }
 }
 #[derive(Clone, Copy)]
 pub enum UserUniformType {
@@ -59,6 +99,21 @@ pub enum UserUniformType {
     Int32,
     Int64,
     Bool,
+    /// Integer vectors, e.g. grid sizes. Clamped to the inclusive range, if one was declared.
+    IntVec2(Option<(i32, i32)>),
+    IntVec3(Option<(i32, i32)>),
+}
+
+impl UserUniformType {
+    /// Alignment of the type in a std140 uniform block, unit: bytes.
+    pub fn std140_alignment(&self) -> usize {
+        match self {
+            UserUniformType::Float64 | UserUniformType::UInt64 | UserUniformType::Int64 => 8,
+            UserUniformType::IntVec2(_) => 8,
+            UserUniformType::IntVec3(_) => 16,
+            _ => 4,
+        }
+    }
 }
 
 #[repr(C)]
@@ -66,12 +121,305 @@ pub struct UserUniform {
     pub bytes: Vec<u8>,
     pub name: String,
     pub inherent_type: UserUniformType,
+    /// Whether external control (e.g. MIDI or OSC mappings) may drive this uniform.
+    pub automatable: bool,
+    pub display: UniformDisplay,
+    /// Value from the uniforms JSON file, restored by [Self::reset].
+    pub default_bytes: Vec<u8>,
+    /// Position in the uniforms JSON file, which is the order uniforms are laid out in, see [crate::layout].
+    pub order: usize,
+    /// Bounds of scalar values, if declared.
+    pub range: Option<UniformRange>,
+    /// Kept as is by [Self::randomize].
+    pub locked: bool,
+}
+
+/// How a uniform's value is shown in the GUI. Display only, the value bound to the shader is unchanged.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UniformDisplay {
+    /// Decimal places shown for floats.
+    pub precision: Option<u32>,
+    /// Shown after the value, e.g. `Hz`, `px` or `°`.
+    pub unit: Option<String>,
+}
+
+impl UniformDisplay {
+    /// Read `{"precision": 2, "unit": "Hz"}`, as written after the value in the uniforms JSON file.
+    pub fn from_json(data: &json::JsonValue) -> UniformDisplay {
+        UniformDisplay {
+            precision: data["precision"].as_u32().map(|p| p.min(10)),
+            unit: data["unit"].as_str().map(String::from),
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.precision.is_none() && self.unit.is_none()
+    }
+
+    /// printf-style format for an imgui widget, e.g. `%.2f Hz`. Integers show no decimals.
+    pub fn imgui_format(&self, integer: bool) -> ImString {
+        let precision = match integer {
+            true => 0,
+            false => self.precision.unwrap_or(3),
+        };
+        ImString::new(format!(
+            "%.{}f{}",
+            precision,
+            self.unit_suffix().replace('%', "%%")
+        ))
+    }
+
+    /// Unit with a leading space, or nothing.
+    pub fn unit_suffix(&self) -> String {
+        match &self.unit {
+            Some(unit) => format!(" {}", unit),
+            None => String::new(),
+        }
+    }
+}
+
+/// Declared bounds of a scalar uniform. Dragging in the GUI stops at them and [UserUniform::randomize] picks values
+/// within them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UniformRange {
+    pub min: f64,
+    pub max: f64,
+    /// Randomized values are [Self::min] plus a multiple of the step, if set.
+    pub step: Option<f64>,
+    /// Spread randomized values evenly over orders of magnitude instead of linearly. Only with a positive minimum.
+    pub log: bool,
+}
+
+impl UniformRange {
+    /// Read `{"min": 20, "max": 20000, "step": 1, "log": true}`, the same object as the [UniformDisplay].
+    /// `None` unless both bounds are given.
+    pub fn from_json(data: &json::JsonValue) -> Option<UniformRange> {
+        let (a, b) = (data["min"].as_f64()?, data["max"].as_f64()?);
+        let (min, max) = (a.min(b), a.max(b));
+        Some(UniformRange {
+            min,
+            max,
+            step: data["step"].as_f64().filter(|step| *step > 0.0),
+            log: data["log"].as_bool().unwrap_or(false) && min > 0.0,
+        })
+    }
+
+    /// Value a fraction `t` in [0, 1] of the way through the range, on the step if there is one.
+    pub fn value_at(&self, t: f64) -> f64 {
+        let value = match self.log {
+            true => self.min * (self.max / self.min).powf(t),
+            false => self.min + (self.max - self.min) * t,
+        };
+        match self.step {
+            Some(step) => {
+                let snapped = self.min + ((value - self.min) / step).round() * step;
+                if snapped > self.max {
+                    snapped - step
+                } else {
+                    snapped
+                }
+            }
+            None => value,
+        }
+    }
 }
 
 impl UserUniform {
+    /// A uniform whose default is its initial value. Uniforms are automatable unless turned off in the GUI.
+    /// `order` is its position in the uniforms JSON file, see [Self::order].
+    pub fn new(
+        name: &str,
+        bytes: Vec<u8>,
+        inherent_type: UserUniformType,
+        display: UniformDisplay,
+        order: usize,
+    ) -> UserUniform {
+        UserUniform {
+            default_bytes: bytes.clone(),
+            bytes,
+            name: String::from(name),
+            inherent_type,
+            automatable: true,
+            display,
+            order,
+            range: None,
+            locked: false,
+        }
+    }
+
+    /// Declare the bounds of a scalar uniform, see [Self::range].
+    pub fn with_range(mut self, range: Option<UniformRange>) -> UserUniform {
+        self.range = range;
+        self
+    }
+
+    /// Restore the value from the uniforms JSON file.
+    pub fn reset(&mut self) {
+        self.bytes = self.default_bytes.clone();
+    }
+
     pub fn get_value<T: Copy>(&self) -> Result<T, &str> {
         convert_bytes_to_value(&self.bytes)
     }
+
+    /// Numeric uniforms with "seed" in their name are treated as random seeds that can be rerolled.
+    pub fn is_seed(&self) -> bool {
+        match self.inherent_type {
+            UserUniformType::Bool | UserUniformType::IntVec2(_) | UserUniformType::IntVec3(_) => {
+                false
+            }
+            _ => self.name.to_lowercase().contains("seed"),
+        }
+    }
+
+    /// Replace the value with a new random one.
+    /// Floats are drawn from [0, 1), integers are non-negative so they display sensibly in the GUI.
+    pub fn reroll(&mut self) {
+        self.reroll_with(random_u64());
+    }
+
+    /// [Self::reroll] with the given random bits.
+    fn reroll_with(&mut self, random: u64) {
+        self.bytes = match self.inherent_type {
+            UserUniformType::Float32 => {
+                convert_value_to_bytes((random >> 40) as f32 / (1u64 << 24) as f32)
+            }
+            UserUniformType::Float64 => {
+                convert_value_to_bytes((random >> 11) as f64 / (1u64 << 53) as f64)
+            }
+            UserUniformType::UInt32 => convert_value_to_bytes((random >> 33) as u32),
+            UserUniformType::UInt64 => convert_value_to_bytes(random >> 1),
+            UserUniformType::Int32 => convert_value_to_bytes((random >> 33) as i32),
+            UserUniformType::Int64 => convert_value_to_bytes((random >> 1) as i64),
+            UserUniformType::Bool | UserUniformType::IntVec2(_) | UserUniformType::IntVec3(_) => {
+                return
+            }
+        };
+    }
+
+    /// Whether [Self::randomize] can pick a value. Bools and seeds always can, other numbers need a range.
+    pub fn is_randomizable(&self) -> bool {
+        match self.inherent_type {
+            UserUniformType::Bool => true,
+            UserUniformType::IntVec2(range) | UserUniformType::IntVec3(range) => range.is_some(),
+            _ => self.range.is_some() || self.is_seed(),
+        }
+    }
+
+    /// Replace the value with one drawn from `random` within the uniform's range. Seeds without a range are rerolled
+    /// from `random` instead. Returns false, leaving the value as is, if the uniform is not randomizable.
+    pub fn randomize(&mut self, random: &mut SeededRandom) -> bool {
+        if !self.is_randomizable() {
+            return false;
+        }
+        match self.inherent_type {
+            UserUniformType::Bool => {
+                self.bytes = convert_value_to_bytes((random.next_u64() >> 63) as u32);
+            }
+            UserUniformType::IntVec2(Some((min, max)))
+            | UserUniformType::IntVec3(Some((min, max))) => {
+                let span = (max as i64 - min as i64 + 1) as u64;
+                let mut component = || (min as i64 + (random.next_u64() % span) as i64) as i32;
+                self.bytes = match self.inherent_type {
+                    UserUniformType::IntVec2(_) => {
+                        convert_value_to_bytes(IntVector2::new(component(), component()))
+                    }
+                    _ => convert_value_to_bytes(IntVector3::new(
+                        component(),
+                        component(),
+                        component(),
+                    )),
+                };
+            }
+            _ => match self.range {
+                Some(range) => {
+                    self.set_scalar_value(range.value_at(random.next_f64()));
+                }
+                None => self.reroll_with(random.next_u64()),
+            },
+        }
+        true
+    }
+
+    /// Current value as a single number. `None` for bools and vectors.
+    pub fn scalar_value(&self) -> Option<f64> {
+        match self.inherent_type {
+            UserUniformType::Float32 => self.get_value::<f32>().ok().map(|v| v as f64),
+            UserUniformType::Float64 => self.get_value::<f64>().ok(),
+            UserUniformType::UInt32 => self.get_value::<u32>().ok().map(|v| v as f64),
+            UserUniformType::UInt64 => self.get_value::<u64>().ok().map(|v| v as f64),
+            UserUniformType::Int32 => self.get_value::<i32>().ok().map(|v| v as f64),
+            UserUniformType::Int64 => self.get_value::<i64>().ok().map(|v| v as f64),
+            UserUniformType::Bool | UserUniformType::IntVec2(_) | UserUniformType::IntVec3(_) => {
+                None
+            }
+        }
+    }
+
+    /// Replace the value of a scalar uniform, rounding for integer types. Returns false for bools and vectors.
+    pub fn set_scalar_value(&mut self, value: f64) -> bool {
+        self.bytes = match self.inherent_type {
+            UserUniformType::Float32 => convert_value_to_bytes(value as f32),
+            UserUniformType::Float64 => convert_value_to_bytes(value),
+            UserUniformType::UInt32 => convert_value_to_bytes(value.round().max(0.0) as u32),
+            UserUniformType::UInt64 => convert_value_to_bytes(value.round().max(0.0) as u64),
+            UserUniformType::Int32 => convert_value_to_bytes(value.round() as i32),
+            UserUniformType::Int64 => convert_value_to_bytes(value.round() as i64),
+            UserUniformType::Bool | UserUniformType::IntVec2(_) | UserUniformType::IntVec3(_) => {
+                return false
+            }
+        };
+        true
+    }
+
+    /// Name of the type as written in the uniforms JSON file.
+    pub fn type_name(&self) -> &'static str {
+        match self.inherent_type {
+            UserUniformType::Float32 => "f32",
+            UserUniformType::Float64 => "f64",
+            UserUniformType::UInt32 => "u32",
+            UserUniformType::UInt64 => "u64",
+            UserUniformType::Int32 => "i32",
+            UserUniformType::Int64 => "i64",
+            UserUniformType::Bool => "bool",
+            UserUniformType::IntVec2(_) => "ivec2",
+            UserUniformType::IntVec3(_) => "ivec3",
+        }
+    }
+
+    /// Current value as shown in the GUI, with its unit and floats rounded to the display precision.
+    pub fn display_value(&self) -> String {
+        let value = match self.inherent_type {
+            UserUniformType::Float32 | UserUniformType::Float64 => format!(
+                "{:.*}",
+                self.display.precision.unwrap_or(3) as usize,
+                self.scalar_value().unwrap_or_default()
+            ),
+            _ => self.value_json().dump(),
+        };
+        value + &self.display.unit_suffix()
+    }
+
+    /// Current value as JSON, in the same form the uniforms JSON file uses.
+    pub fn value_json(&self) -> json::JsonValue {
+        match self.inherent_type {
+            UserUniformType::Float32 => self.get_value::<f32>().unwrap().into(),
+            UserUniformType::Float64 => self.get_value::<f64>().unwrap().into(),
+            UserUniformType::UInt32 => self.get_value::<u32>().unwrap().into(),
+            UserUniformType::UInt64 => self.get_value::<u64>().unwrap().into(),
+            UserUniformType::Int32 => self.get_value::<i32>().unwrap().into(),
+            UserUniformType::Int64 => self.get_value::<i64>().unwrap().into(),
+            UserUniformType::Bool => (self.get_value::<u32>().unwrap() != 0).into(),
+            UserUniformType::IntVec2(_) => {
+                let value = self.get_value::<IntVector2>().unwrap();
+                json::array![value.x, value.y]
+            }
+            UserUniformType::IntVec3(_) => {
+                let value = self.get_value::<IntVector3>().unwrap();
+                json::array![value.x, value.y, value.z]
+            }
+        }
+    }
 }
 
 impl Clone for UserUniform {
@@ -80,6 +428,12 @@ impl Clone for UserUniform {
             bytes: self.bytes.clone(),
             name: self.name.clone(),
             inherent_type: self.inherent_type,
+            automatable: self.automatable,
+            display: self.display.clone(),
+            default_bytes: self.default_bytes.clone(),
+            order: self.order,
+            range: self.range,
+            locked: self.locked,
         }
     }
 }
@@ -106,13 +460,19 @@ impl Hash for UserUniform {
 ///   - i32
 ///   - i64
 ///   - bool (bound as u32 in shader)
+///   - ivec2, ivec3 (bound as ivec2/ivec3 in shader)
 ///
 /// The JSON file must follow a specific format, where each uniform is given a name followed by the type and value.
+/// Integer vectors take an array of components and may be followed by an inclusive `[min, max]` range.
+/// Any uniform may end with a [UniformDisplay] object, setting the decimal places and unit shown in the GUI. For numbers,
+/// the same object may declare a [UniformRange].
 /// Example valid format:
 /// ```text
 /// "uniforms": {
 ///     "dynamic": ["bool", false],
-///     "ground_truth": ["f32", 4.0]
+///     "ground_truth": ["f32", 4.0],
+///     "frequency": ["f32", 440.0, {"precision": 1, "unit": "Hz"}],
+///     "tiles": ["ivec2", [8, 8], [1, 64], {"unit": "px"}]
 /// }
 /// ```
 /// Returns a vector of [UserUniform] objects that provided everything needed to bind to a shader.
@@ -121,58 +481,124 @@ pub fn load_uniforms_from_json(data: &json::JsonValue) -> HashSet<UserUniform> {
     let uniforms_json = &data["uniforms"];
     if !uniforms_json.is_null() {
         let entries = uniforms_json.entries();
-        for entry in entries {
+        for (order, entry) in entries.enumerate() {
             let name = entry.0;
             let mut array_itr = entry.1.members();
             let type_str = array_itr.next().unwrap().as_str().unwrap();
             let value = array_itr.next().unwrap();
+            let metadata = entry.1.members().skip(2).find(|m| m.is_object());
+            let display = metadata.map(UniformDisplay::from_json).unwrap_or_default();
+            let range = metadata.and_then(UniformRange::from_json);
             if type_str == "f32" {
-                uniforms.insert(UserUniform {
-                    bytes: convert_value_to_bytes(value.as_f32().unwrap()),
-                    name: String::from(name),
-                    inherent_type: UserUniformType::Float32,
-                });
+                uniforms.insert(
+                    UserUniform::new(
+                        name,
+                        convert_value_to_bytes(value.as_f32().unwrap()),
+                        UserUniformType::Float32,
+                        display.clone(),
+                        order,
+                    )
+                    .with_range(range),
+                );
             } else if type_str == "f64" {
-                uniforms.insert(UserUniform {
-                    bytes: convert_value_to_bytes(value.as_f64().unwrap()),
-                    name: String::from(name),
-                    inherent_type: UserUniformType::Float64,
-                });
+                uniforms.insert(
+                    UserUniform::new(
+                        name,
+                        convert_value_to_bytes(value.as_f64().unwrap()),
+                        UserUniformType::Float64,
+                        display.clone(),
+                        order,
+                    )
+                    .with_range(range),
+                );
             } else if type_str == "u32" {
-                uniforms.insert(UserUniform {
-                    bytes: convert_value_to_bytes(value.as_u32().unwrap()),
-                    name: String::from(name),
-                    inherent_type: UserUniformType::UInt32,
-                });
+                uniforms.insert(
+                    UserUniform::new(
+                        name,
+                        convert_value_to_bytes(value.as_u32().unwrap()),
+                        UserUniformType::UInt32,
+                        display.clone(),
+                        order,
+                    )
+                    .with_range(range),
+                );
             } else if type_str == "u64" {
-                uniforms.insert(UserUniform {
-                    bytes: convert_value_to_bytes(value.as_u64().unwrap()),
-                    name: String::from(name),
-                    inherent_type: UserUniformType::UInt64,
-                });
+                uniforms.insert(
+                    UserUniform::new(
+                        name,
+                        convert_value_to_bytes(value.as_u64().unwrap()),
+                        UserUniformType::UInt64,
+                        display.clone(),
+                        order,
+                    )
+                    .with_range(range),
+                );
             } else if type_str == "i32" {
-                uniforms.insert(UserUniform {
-                    bytes: convert_value_to_bytes(value.as_i32().unwrap()),
-                    name: String::from(name),
-                    inherent_type: UserUniformType::Int32,
-                });
+                uniforms.insert(
+                    UserUniform::new(
+                        name,
+                        convert_value_to_bytes(value.as_i32().unwrap()),
+                        UserUniformType::Int32,
+                        display.clone(),
+                        order,
+                    )
+                    .with_range(range),
+                );
             } else if type_str == "i64" {
-                uniforms.insert(UserUniform {
-                    bytes: convert_value_to_bytes(value.as_i64().unwrap()),
-                    name: String::from(name),
-                    inherent_type: UserUniformType::Int64,
-                });
+                uniforms.insert(
+                    UserUniform::new(
+                        name,
+                        convert_value_to_bytes(value.as_i64().unwrap()),
+                        UserUniformType::Int64,
+                        display.clone(),
+                        order,
+                    )
+                    .with_range(range),
+                );
             } else if type_str == "bool" {
                 // Note we bind booleans as u32
                 let uint_value: u32 = match value.as_bool().unwrap() {
                     true => 1,
                     false => 0,
                 };
-                uniforms.insert(UserUniform {
-                    bytes: convert_value_to_bytes(uint_value),
-                    name: String::from(name),
-                    inherent_type: UserUniformType::Bool,
-                });
+                uniforms.insert(UserUniform::new(
+                    name,
+                    convert_value_to_bytes(uint_value),
+                    UserUniformType::Bool,
+                    display.clone(),
+                    order,
+                ));
+            } else if type_str == "ivec2" || type_str == "ivec3" {
+                let range = array_itr
+                    .next()
+                    .map(|r| (r[0].as_i32().unwrap(), r[1].as_i32().unwrap()));
+                let mut components: Vec<i32> =
+                    value.members().map(|c| c.as_i32().unwrap()).collect();
+                clamp_to_range(&mut components, range);
+                let (bytes, inherent_type) = if type_str == "ivec2" {
+                    (
+                        convert_value_to_bytes(IntVector2::new(components[0], components[1])),
+                        UserUniformType::IntVec2(range),
+                    )
+                } else {
+                    (
+                        convert_value_to_bytes(IntVector3::new(
+                            components[0],
+                            components[1],
+                            components[2],
+                        )),
+                        UserUniformType::IntVec3(range),
+                    )
+                };
+                uniforms.insert(UserUniform::new(
+                    name,
+                    bytes,
+                    inherent_type,
+                    display.clone(),
+                    order,
+                ));
+            } else if type_str == "ramp" {
+                // Ramps are bound as textures, see [load_ramps_from_json].
             } else {
                 error!("Uniform with invalid type {} found, ignoring.", type_str);
             }
@@ -183,57 +609,309 @@ pub fn load_uniforms_from_json(data: &json::JsonValue) -> HashSet<UserUniform> {
 }
 
 /// Builds the UI element for the given uniform and updates it with the latest value.
+/// Returns whether the uniform was modified this frame.
 ///
 /// * `ui` - Reference to [imgui::Ui] object.
 /// * `uniform` - The [UserUniform] object to visualise and update.
-pub fn update_user_uniform_ui(ui: &imgui::Ui, uniform: &mut UserUniform) {
-    match uniform.inherent_type {
-        // 32 bit types
-        UserUniformType::Float32 => {
-            let mut value = uniform.get_value::<f32>().unwrap();
-            ui.input_float(&ImString::from(uniform.name.clone()), &mut value)
-                .build();
-            uniform.bytes = convert_value_to_bytes(value);
-        }
-        UserUniformType::Int32 => {
-            let mut value = uniform.get_value::<i32>().unwrap();
-            ui.input_int(&ImString::from(uniform.name.clone()), &mut value)
-                .build();
-            uniform.bytes = convert_value_to_bytes(value);
-        }
-        UserUniformType::UInt32 => {
-            let value = uniform.get_value::<u32>().unwrap();
-            let mut value_i32 = value as i32;
-            ui.input_int(&ImString::from(uniform.name.clone()), &mut value_i32)
-                .build();
-            uniform.bytes = convert_value_to_bytes(value);
-        }
-        // 64 bit types
-        UserUniformType::Float64 => {
-            let mut value = uniform.get_value::<f32>().unwrap();
-            ui.input_float(&ImString::from(uniform.name.clone()), &mut value)
-                .build();
-            uniform.bytes = convert_value_to_bytes(value as f64);
-        }
-        UserUniformType::Int64 => {
-            let mut value = uniform.get_value::<i32>().unwrap();
-            ui.input_int(&ImString::from(uniform.name.clone()), &mut value)
-                .build();
-            uniform.bytes = convert_value_to_bytes(value as i64);
-        }
-        UserUniformType::UInt64 => {
-            let value = uniform.get_value::<u32>().unwrap();
-            let mut value_i32 = value as i32;
-            ui.input_int(&ImString::from(uniform.name.clone()), &mut value_i32)
-                .build();
-            uniform.bytes = convert_value_to_bytes(value_i32 as u64);
-        }
-        // Bool is a special case
-        UserUniformType::Bool => {
-            let value = uniform.get_value::<u32>().unwrap();
-            let mut value_bool = value != 0;
-            ui.checkbox(&ImString::from(uniform.name.clone()), &mut value_bool);
-            uniform.bytes = convert_value_to_bytes(value_bool as u32);
+pub fn update_user_uniform_ui(ui: &imgui::Ui, uniform: &mut UserUniform) -> bool {
+    let previous = (uniform.bytes.clone(), uniform.automatable);
+    let id_token = ui.push_id(uniform.name.as_str());
+    automatable_toggle_ui(ui, &mut uniform.automatable);
+    ui.same_line(0.0);
+    if uniform.is_randomizable() {
+        ui.checkbox(im_str!("##Locked"), &mut uniform.locked);
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Locked: keep this value when randomizing uniforms.");
+        }
+        ui.same_line(0.0);
+    }
+    // Numbers are drag fields: drag to adjust, ctrl-click to type an exact value, double-click to reset.
+    if let Some(mut value) = uniform.scalar_value() {
+        let integer = !matches!(
+            uniform.inherent_type,
+            UserUniformType::Float32 | UserUniformType::Float64
+        );
+        let speed = match integer {
+            true => 1.0,
+            false => 0.1f32.powi(uniform.display.precision.unwrap_or(2).min(2) as i32),
+        };
+        let format = uniform.display.imgui_format(integer);
+        let label = ImString::from(uniform.name.clone());
+        let mut drag = imgui::Drag::new(&label)
+            .speed(speed)
+            .display_format(&format);
+        let mut flags = typing_flags(ui);
+        if let Some(range) = uniform.range {
+            drag = drag.range(range.min..=range.max);
+            if range.log {
+                flags |= imgui::SliderFlags::LOGARITHMIC;
+            }
+        }
+        if drag.flags(flags).build(ui, &mut value) {
+            if let Some(range) = uniform.range {
+                value = value.max(range.min).min(range.max);
+            }
+            // Unsigned types clamp at 0, whatever was typed.
+            uniform.set_scalar_value(value);
+        }
+    } else {
+        // Bools and vectors have no format of their own, so their unit goes in the label.
+        let label = match &uniform.display.unit {
+            Some(unit) => ImString::new(format!("{} ({})", uniform.name, unit)),
+            None => ImString::from(uniform.name.clone()),
+        };
+        match uniform.inherent_type {
+            UserUniformType::IntVec2(range) => {
+                let value = uniform.get_value::<IntVector2>().unwrap();
+                let mut components = [value.x, value.y];
+                int_vector_drag(ui, &label, &mut components, range);
+                uniform.bytes =
+                    convert_value_to_bytes(IntVector2::new(components[0], components[1]));
+            }
+            UserUniformType::IntVec3(range) => {
+                let value = uniform.get_value::<IntVector3>().unwrap();
+                let mut components = [value.x, value.y, value.z];
+                int_vector_drag(ui, &label, &mut components, range);
+                uniform.bytes = convert_value_to_bytes(IntVector3::new(
+                    components[0],
+                    components[1],
+                    components[2],
+                ));
+            }
+            // Bool is the only other type
+            _ => {
+                let value = uniform.get_value::<u32>().unwrap();
+                let mut value_bool = value != 0;
+                ui.checkbox(&label, &mut value_bool);
+                uniform.bytes = convert_value_to_bytes(value_bool as u32);
+            }
+        }
+    }
+    if ui.is_item_hovered() && ui.is_mouse_double_clicked(imgui::MouseButton::Left) {
+        uniform.reset();
+    }
+    if uniform.is_seed() {
+        ui.same_line(0.0);
+        if ui.small_button(im_str!("Reroll")) {
+            uniform.reroll();
+        }
+    }
+    id_token.pop(ui);
+    previous != (uniform.bytes.clone(), uniform.automatable)
+}
+
+/// Flags keeping a double-click on a drag field from opening its text input, so it can reset the value instead.
+/// Ctrl-click still types an exact value.
+fn typing_flags(ui: &imgui::Ui) -> imgui::SliderFlags {
+    match ui.io().key_ctrl {
+        true => imgui::SliderFlags::empty(),
+        false => imgui::SliderFlags::NO_INPUT,
+    }
+}
+
+/// One drag field per component, each of which can be ctrl-clicked to type a value.
+/// Dragging stops at the range, and typed values are clamped to it.
+fn int_vector_drag(
+    ui: &imgui::Ui,
+    label: &ImStr,
+    components: &mut [i32],
+    range: Option<(i32, i32)>,
+) {
+    let mut drag = imgui::Drag::new(label).speed(0.25).flags(typing_flags(ui));
+    if let Some((min, max)) = range {
+        drag = drag.range(min..=max);
+    }
+    drag.build_array(ui, components);
+    clamp_to_range(components, range);
+}
+
+/// Clamp each component to the inclusive `(min, max)` range, if there is one.
+fn clamp_to_range(components: &mut [i32], range: Option<(i32, i32)>) {
+    if let Some((min, max)) = range {
+        for c in components.iter_mut() {
+            *c = (*c).max(min).min(max);
+        }
+    }
+}
+
+/// A single color stop of a [RampUniform].
+#[derive(Debug, Copy, Clone)]
+pub struct RampStop {
+    /// Location of the stop along the ramp, in [0, 1].
+    pub position: f32,
+    /// Linear RGBA color at this stop.
+    pub color: [f32; 4],
+}
+
+/// A color gradient authored in the GUI and bound to the shader as a 1D texture.
+#[derive(Debug, Clone)]
+pub struct RampUniform {
+    pub name: String,
+    pub stops: Vec<RampStop>,
+    /// Whether external control may drive this ramp. Off by default as ramps are not a single value.
+    pub automatable: bool,
+}
+
+impl RampUniform {
+    /// Evaluate the gradient at `t`, linearly interpolating between the neighbouring stops.
+    pub fn sample(&self, t: f32) -> [f32; 4] {
+        if self.stops.is_empty() {
+            return [0.0, 0.0, 0.0, 1.0];
+        }
+        let mut sorted = self.stops.clone();
+        sorted.sort_by(|a, b| a.position.partial_cmp(&b.position).unwrap());
+
+        let first = sorted.first().unwrap();
+        let last = sorted.last().unwrap();
+        if t <= first.position {
+            return first.color;
         }
+        if t >= last.position {
+            return last.color;
+        }
+        for pair in sorted.windows(2) {
+            let (lo, hi) = (pair[0], pair[1]);
+            if t >= lo.position && t <= hi.position {
+                let span = hi.position - lo.position;
+                let alpha = if span > 0.0 {
+                    (t - lo.position) / span
+                } else {
+                    0.0
+                };
+                let mut color = lo.color;
+                for (c, h) in color.iter_mut().zip(hi.color.iter()) {
+                    *c += (h - *c) * alpha;
+                }
+                return color;
+            }
+        }
+        last.color
+    }
+
+    /// Bake the gradient into [RAMP_TEXTURE_WIDTH] texels of `Rgba16Float` data, ready for upload.
+    pub fn bake(&self) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity(RAMP_TEXTURE_WIDTH as usize * 4 * std::mem::size_of::<f16>());
+        for i in 0..RAMP_TEXTURE_WIDTH {
+            let t = i as f32 / (RAMP_TEXTURE_WIDTH - 1) as f32;
+            for component in self.sample(t).iter() {
+                bytes.extend_from_slice(&f16::from_f32(*component).to_bits().to_ne_bytes());
+            }
+        }
+        bytes
+    }
+}
+
+/// Loads color ramps from the `uniforms` section of the given JSON file.
+/// Each ramp is given a name followed by the `ramp` type and a list of `[position, [r, g, b, a]]` stops.
+/// Example valid format:
+/// ```text
+/// "uniforms": {
+///     "palette": ["ramp", [[0.0, [0.0, 0.0, 0.0, 1.0]], [1.0, [1.0, 0.5, 0.0, 1.0]]]]
+/// }
+/// ```
+/// Ramps are returned in the order they appear in the file, which is also their binding order.
+pub fn load_ramps_from_json(data: &json::JsonValue) -> Vec<RampUniform> {
+    let mut ramps = Vec::new();
+    let uniforms_json = &data["uniforms"];
+    if !uniforms_json.is_null() {
+        for entry in uniforms_json.entries() {
+            let mut array_itr = entry.1.members();
+            if array_itr.next().unwrap().as_str().unwrap() != "ramp" {
+                continue;
+            }
+            let mut stops = Vec::new();
+            for stop in array_itr.next().unwrap().members() {
+                let mut color = [0.0; 4];
+                for (i, component) in stop[1].members().take(4).enumerate() {
+                    color[i] = component.as_f32().unwrap();
+                }
+                stops.push(RampStop {
+                    position: stop[0].as_f32().unwrap(),
+                    color,
+                });
+            }
+            ramps.push(RampUniform {
+                name: String::from(entry.0),
+                stops,
+                automatable: false,
+            });
+        }
+    }
+
+    ramps
+}
+
+/// Builds the gradient editor for the given ramp: a preview strip followed by one row per stop.
+/// Returns whether the ramp was modified this frame.
+///
+/// * `ui` - Reference to [imgui::Ui] object.
+/// * `ramp` - The [RampUniform] object to visualise and update.
+pub fn update_ramp_uniform_ui(ui: &imgui::Ui, ramp: &mut RampUniform) -> bool {
+    let mut changed = false;
+    let id_token = ui.push_id(ramp.name.as_str());
+    ui.text(&ramp.name);
+
+    // Draw a preview strip of the gradient.
+    {
+        let origin = ui.cursor_screen_pos();
+        let width = ui.content_region_avail()[0];
+        let height = 20.0;
+        let segments = 32;
+        let draw_list = ui.get_window_draw_list();
+        for i in 0..segments {
+            let t0 = i as f32 / segments as f32;
+            let t1 = (i + 1) as f32 / segments as f32;
+            let c0 = ramp.sample(t0);
+            let c1 = ramp.sample(t1);
+            draw_list.add_rect_filled_multicolor(
+                [origin[0] + width * t0, origin[1]],
+                [origin[0] + width * t1, origin[1] + height],
+                c0,
+                c1,
+                c1,
+                c0,
+            );
+        }
+        ui.dummy([width, height]);
+    }
+
+    let mut remove_idx = None;
+    for (i, stop) in ramp.stops.iter_mut().enumerate() {
+        let stop_token = ui.push_id(i as i32);
+        changed |= imgui::Drag::new(im_str!("Position"))
+            .range(0.0..=1.0)
+            .speed(0.005)
+            .build(ui, &mut stop.position);
+        changed |= imgui::ColorEdit::new(im_str!("Color"), &mut stop.color).build(ui);
+        // Keep the first stop around so the ramp always stays well defined.
+        if i > 0 && ui.small_button(im_str!("Remove Stop")) {
+            remove_idx = Some(i);
+        }
+        stop_token.pop(ui);
+    }
+    if let Some(idx) = remove_idx {
+        ramp.stops.remove(idx);
+        changed = true;
+    }
+    if ui.small_button(im_str!("Add Stop")) {
+        ramp.stops.push(RampStop {
+            position: 1.0,
+            color: ramp.sample(1.0),
+        });
+        changed = true;
+    }
+    changed |= automatable_toggle_ui(ui, &mut ramp.automatable);
+    id_token.pop(ui);
+    changed
+}
+
+/// Small checkbox shown on each uniform row that controls whether it is offered as an automation target.
+/// Expects the caller to have pushed a unique ID for the uniform.
+fn automatable_toggle_ui(ui: &imgui::Ui, automatable: &mut bool) -> bool {
+    let changed = ui.checkbox(im_str!("##Automatable"), automatable);
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Automatable: allow expressions, timeline replays, randomizing and external control to drive this uniform.");
     }
+    changed
 }
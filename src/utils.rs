@@ -1,14 +1,16 @@
+use crate::overlay::TextOverlay;
 use crate::vector::UIntVector2;
 use byteorder::{NativeEndian, WriteBytesExt};
-use futures::executor::block_on;
 use half::prelude::*;
 use image::ImageEncoder;
-use image::{codecs::png::PngEncoder, tiff::TiffEncoder};
-use log::info;
-use std::fs::File;
-use std::io::BufWriter;
-use std::path::Path;
-use std::sync::mpsc::{channel, Receiver};
+use image::{codecs::jpeg::JpegEncoder, codecs::png::PngEncoder, tiff::TiffEncoder};
+use log::{error, info, warn};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
 use std::vec::Vec;
 use wgpu::{BindGroupLayoutDescriptor, BindGroupLayoutEntry, BlendState};
 
@@ -30,10 +32,25 @@ fn load_shader_source(
     )
 }
 
+/// Name of the macro injected into text shaders holding the anti-aliasing quality.
+pub static AA_SAMPLES_DEFINE: &str = "OTIUM_AA_SAMPLES";
+
+/// Name of the fragment shader entry point unless configured otherwise.
+pub static DEFAULT_ENTRY_POINT: &str = "main";
+
 /// Loads a shader from the given file. Can be either text source or compiled SPIR-V blob.
 /// Returns a Result with the binary data of the loaded/compiled shader or an error from ShaderC
 /// if unable to compile.
-pub fn load_shader(shader_file: &str) -> Result<Vec<u8>, shaderc::Error> {
+/// * `aa_samples` - Value of the [AA_SAMPLES_DEFINE] macro. Ignored for SPIR-V blobs.
+/// * `entry_point` - Name of the fragment function. Text shaders are compiled with it renamed to `main`,
+///   see [fragment_entry_point].
+/// * `include_dirs` - Directories searched for `#include`d files, see [resolve_include].
+pub fn load_shader(
+    shader_file: &str,
+    aa_samples: u32,
+    entry_point: &str,
+    include_dirs: &[String],
+) -> Result<Vec<u8>, shaderc::Error> {
     // Determine if shader text file provided or SPIR-V binary blob.
     let tokens = shader_file.split(".").collect::<Vec<&str>>();
     assert!(
@@ -48,42 +65,241 @@ pub fn load_shader(shader_file: &str) -> Result<Vec<u8>, shaderc::Error> {
     if *tokens.last().unwrap() == "frag" {
         let mut shader_compile_options = shaderc::CompileOptions::new().unwrap();
         shader_compile_options.set_include_callback(
-            |source_name: &str,
+            |requested: &str,
              include_type: shaderc::IncludeType,
-             _shader_name: &str,
+             requesting_source: &str,
              _include_depth: usize| {
-                // We only support relative includes for now.
-                if include_type == shaderc::IncludeType::Standard {
-                    return Err("Standard include type (#include <..>) found in shader. Only relative includes (#include \"..\")are currently supported".to_string());
-                }
-                // Read text data from include file.
-                let path_to_file = shader_dir.join(Path::new(source_name));
-                let include_src = std::fs::read_to_string(path_to_file.to_str().unwrap()).expect("Unable to find include file.");
-                // Return info.
-                Ok(shaderc::ResolvedInclude{
-                    resolved_name: path_to_file.to_str().unwrap().to_string(),
-                    content: include_src
-                })
+                resolve_include(
+                    requested,
+                    include_type,
+                    requesting_source,
+                    shader_dir,
+                    include_dirs,
+                )
             },
         );
+        shader_compile_options
+            .add_macro_definition(AA_SAMPLES_DEFINE, Some(&aa_samples.to_string()));
+        // GLSL requires a main function, so a custom entry point is renamed by the preprocessor.
+        if entry_point != DEFAULT_ENTRY_POINT {
+            shader_compile_options.add_macro_definition(entry_point, Some(DEFAULT_ENTRY_POINT));
+        }
         let fs_src = std::fs::read_to_string(fpath).expect("Unable to find shader");
         fs_compilation_artifact = match load_shader_source(
             &fs_src,
             shaderc::ShaderKind::Fragment,
             shader_file,
-            "main",
+            DEFAULT_ENTRY_POINT,
             Some(&shader_compile_options),
         ) {
             Ok(artifact) => artifact,
+            Err(shaderc::Error::CompilationError(count, msg))
+                if msg.contains("Missing entry point") =>
+            {
+                return Result::Err(shaderc::Error::CompilationError(
+                    count,
+                    missing_entry_point_message(shader_file, entry_point),
+                ))
+            }
             Err(e) => return Result::Err(e),
         };
         fs_spv_data = fs_compilation_artifact.as_binary_u8().to_vec();
     } else {
         fs_spv_data = std::fs::read(fpath).unwrap();
+        if !spirv_fragment_entry_points(&fs_spv_data)
+            .iter()
+            .any(|name| name == entry_point)
+        {
+            return Result::Err(shaderc::Error::CompilationError(
+                1,
+                missing_entry_point_message(shader_file, entry_point),
+            ));
+        }
     }
     Result::Ok(fs_spv_data)
 }
 
+/// Find and read a file `#include`d by a text shader.
+/// `#include "file"` is looked up next to the file including it, then next to the shader, then in `include_dirs` in
+/// order. `#include <file>` skips the including file's directory. A file that can't be found fails the compilation.
+fn resolve_include(
+    requested: &str,
+    include_type: shaderc::IncludeType,
+    requesting_source: &str,
+    shader_dir: &Path,
+    include_dirs: &[String],
+) -> Result<shaderc::ResolvedInclude, String> {
+    let mut candidates = vec![];
+    if include_type == shaderc::IncludeType::Relative {
+        if let Some(dir) = Path::new(requesting_source).parent() {
+            candidates.push(dir.join(requested));
+        }
+    }
+    candidates.push(shader_dir.join(requested));
+    candidates.extend(
+        include_dirs
+            .iter()
+            .map(|dir| Path::new(dir).join(requested)),
+    );
+    candidates.dedup();
+    for path in &candidates {
+        if let Ok(content) = std::fs::read_to_string(path) {
+            return Ok(shaderc::ResolvedInclude {
+                resolved_name: path.to_string_lossy().into_owned(),
+                content,
+            });
+        }
+    }
+    let searched: Vec<_> = candidates.iter().map(|p| p.to_string_lossy()).collect();
+    Err(format!(
+        "Cannot find include \"{}\" requested by {}. Searched: {}. Add directories with --include-dir.",
+        requested,
+        requesting_source,
+        searched.join(", ")
+    ))
+}
+
+/// Entry point of the SPIR-V returned by [load_shader] for `shader_file`.
+/// Text shaders always compile to `main`, SPIR-V blobs keep the name they were built with.
+pub fn fragment_entry_point<'a>(shader_file: &str, entry_point: &'a str) -> &'a str {
+    match shader_file.ends_with(".spv") {
+        true => entry_point,
+        false => DEFAULT_ENTRY_POINT,
+    }
+}
+
+fn missing_entry_point_message(shader_file: &str, entry_point: &str) -> String {
+    format!(
+        "{}: missing entry point, no fragment function named `{}`. Use --entry-point to set its name.",
+        shader_file, entry_point
+    )
+}
+
+/// Names of the fragment entry points declared in a SPIR-V module, i.e. of its `OpEntryPoint Fragment` instructions.
+fn spirv_fragment_entry_points(spirv: &[u8]) -> Vec<String> {
+    const OP_ENTRY_POINT: u32 = 15;
+    const EXECUTION_MODEL_FRAGMENT: u32 = 4;
+    let words: Vec<u32> = spirv
+        .chunks_exact(4)
+        .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+        .collect();
+    let mut names = vec![];
+    // Skip the 5 word header.
+    let mut i = 5;
+    while i < words.len() {
+        let (count, opcode) = ((words[i] >> 16) as usize, words[i] & 0xffff);
+        if count == 0 || i + count > words.len() {
+            break;
+        }
+        if opcode == OP_ENTRY_POINT && count > 3 && words[i + 1] == EXECUTION_MODEL_FRAGMENT {
+            names.push(spirv_string(&words[i + 3..i + count]));
+        }
+        i += count;
+    }
+    names
+}
+
+/// Decode a nul-terminated SPIR-V literal string.
+fn spirv_string(words: &[u32]) -> String {
+    let bytes: Vec<u8> = words
+        .iter()
+        .flat_map(|w| w.to_le_bytes().to_vec())
+        .take_while(|b| *b != 0)
+        .collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Names and byte offsets of the members of the uniform block at `set` and `binding` in a SPIR-V module, in
+/// declaration order. Names are empty if the module was stripped of them. `None` if there is no such block.
+pub fn spirv_uniform_block_members(
+    spirv: &[u8],
+    set: u32,
+    binding: u32,
+) -> Option<Vec<(String, u32)>> {
+    const OP_MEMBER_NAME: u32 = 6;
+    const OP_TYPE_POINTER: u32 = 32;
+    const OP_VARIABLE: u32 = 59;
+    const OP_DECORATE: u32 = 71;
+    const OP_MEMBER_DECORATE: u32 = 72;
+    const DECORATION_BINDING: u32 = 33;
+    const DECORATION_DESCRIPTOR_SET: u32 = 34;
+    const DECORATION_OFFSET: u32 = 35;
+    const STORAGE_CLASS_UNIFORM: u32 = 2;
+    let words: Vec<u32> = spirv
+        .chunks_exact(4)
+        .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+        .collect();
+    let mut sets = HashMap::new();
+    let mut bindings = HashMap::new();
+    let mut member_offsets: HashMap<u32, Vec<(u32, u32)>> = HashMap::new();
+    let mut member_names = HashMap::new();
+    let mut pointees = HashMap::new();
+    let mut variables = vec![];
+    // Skip the 5 word header.
+    let mut i = 5;
+    while i < words.len() {
+        let (count, opcode) = ((words[i] >> 16) as usize, words[i] & 0xffff);
+        if count == 0 || i + count > words.len() {
+            break;
+        }
+        let operands = &words[i + 1..i + count];
+        match opcode {
+            OP_DECORATE if operands.len() > 2 => match operands[1] {
+                DECORATION_DESCRIPTOR_SET => {
+                    sets.insert(operands[0], operands[2]);
+                }
+                DECORATION_BINDING => {
+                    bindings.insert(operands[0], operands[2]);
+                }
+                _ => {}
+            },
+            OP_MEMBER_DECORATE if operands.len() > 3 && operands[2] == DECORATION_OFFSET => {
+                member_offsets
+                    .entry(operands[0])
+                    .or_default()
+                    .push((operands[1], operands[3]));
+            }
+            OP_MEMBER_NAME if operands.len() > 2 => {
+                member_names.insert((operands[0], operands[1]), spirv_string(&operands[2..]));
+            }
+            OP_TYPE_POINTER if operands.len() > 2 => {
+                pointees.insert(operands[0], operands[2]);
+            }
+            OP_VARIABLE if operands.len() > 2 && operands[2] == STORAGE_CLASS_UNIFORM => {
+                variables.push((operands[1], operands[0]));
+            }
+            _ => {}
+        }
+        i += count;
+    }
+    let (_, pointer_type) = variables
+        .into_iter()
+        .find(|(id, _)| sets.get(id) == Some(&set) && bindings.get(id) == Some(&binding))?;
+    let block = pointees.get(&pointer_type)?;
+    let mut members = member_offsets.remove(block).unwrap_or_default();
+    members.sort();
+    Some(
+        members
+            .into_iter()
+            .map(|(index, offset)| {
+                let name = member_names.remove(&(*block, index)).unwrap_or_default();
+                (name, offset)
+            })
+            .collect(),
+    )
+}
+
+/// Compiles GLSL source bundled with Easel, e.g. via [include_str], into SPIR-V.
+/// * `name` - Used in error messages to identify the shader.
+pub fn compile_builtin_shader(
+    source: &str,
+    kind: shaderc::ShaderKind,
+    name: &str,
+) -> Result<Vec<u8>, shaderc::Error> {
+    let artifact = load_shader_source(source, kind, name, DEFAULT_ENTRY_POINT, None)?;
+    Ok(artifact.as_binary_u8().to_vec())
+}
+
 pub async fn transcode_frame_data_for_movie(
     painting: wgpu::Buffer,
     resolution: UIntVector2,
@@ -106,14 +322,14 @@ pub async fn transcode_frame_data_for_movie(
     }
 }
 
-pub async fn transcode_painting_data(
+/// Convert a painting's half-float pixels to 16-bit uint. `painting` must already be mapped for reading.
+pub fn transcode_painting_data(
     painting: wgpu::Buffer,
     resolution: UIntVector2,
     pixel_data: &mut Vec<u8>,
 ) {
     let (width, height) = (resolution.x, resolution.y);
     let slice = painting.slice(0..);
-    slice.map_async(wgpu::MapMode::Read).await.unwrap();
     let buf_view = slice.get_mapped_range();
     pixel_data.reserve((width * height * 4) as usize * std::mem::size_of::<u16>());
     for i in 0..(width * height) {
@@ -159,9 +375,179 @@ pub fn encode_image_buffer_to_png(
         .unwrap();
 }
 
+/// File format of an exported painting or movie.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ExportFormat {
+    Tiff,
+    Png,
+    Jpeg,
+    Mp4,
+    Mov,
+    Mkv,
+    Webm,
+}
+
+/// Image formats that can be typed as the extension of a painting filename but aren't written, so are warned about.
+const UNSUPPORTED_PAINTING_EXTENSIONS: [&str; 6] = ["exr", "hdr", "bmp", "gif", "webp", "tga"];
+
+/// A painting filename as typed in the GUI, split into the name and the format the extension asks for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaintingFile {
+    /// Filename without a recognized extension, e.g. for deriving the names of related exports.
+    pub stem: String,
+    /// Path to write to, keeping the extension as typed.
+    pub path: String,
+    pub format: ExportFormat,
+}
+
+impl PaintingFile {
+    /// `tiff`/`tif`, `png` and `jpg`/`jpeg` extensions select the format, anything else is written as `.tiff`.
+    /// Extensions of other image formats, e.g. `exr`, are replaced with `.tiff` and warned about.
+    pub fn new(filename: &str) -> PaintingFile {
+        let (stem, extension) = match filename.rfind('.') {
+            Some(dot) if dot > 0 => (&filename[..dot], filename[dot + 1..].to_lowercase()),
+            _ => (filename, String::new()),
+        };
+        let format = match extension.as_str() {
+            "tiff" | "tif" => Some(ExportFormat::Tiff),
+            "png" => Some(ExportFormat::Png),
+            "jpg" | "jpeg" => Some(ExportFormat::Jpeg),
+            _ => None,
+        };
+        match format {
+            Some(format) => PaintingFile {
+                stem: String::from(stem),
+                path: String::from(filename),
+                format,
+            },
+            None if UNSUPPORTED_PAINTING_EXTENSIONS.contains(&extension.as_str()) => {
+                warn!(
+                    "Paintings can't be written as .{}, only .tiff, .png and .jpg. Writing {}.tiff instead.",
+                    extension, stem
+                );
+                PaintingFile {
+                    stem: String::from(stem),
+                    path: format!("{}.tiff", stem),
+                    format: ExportFormat::Tiff,
+                }
+            }
+            None => PaintingFile {
+                stem: String::from(filename),
+                path: format!("{}.tiff", filename),
+                format: ExportFormat::Tiff,
+            },
+        }
+    }
+}
+
+/// Describes a finished export: where the file landed and what it contains.
+#[derive(Debug, Clone)]
+pub struct ExportEvent {
+    pub path: PathBuf,
+    pub resolution: UIntVector2,
+    pub format: ExportFormat,
+    /// Set if the export failed, in which case the file at [Self::path] may be missing or incomplete.
+    pub error: Option<String>,
+}
+
+/// Fails with a message if `path` can't be written, e.g. since its directory is read-only, missing or on a full disk.
+/// Probes by creating and removing a small file next to it, so exports fail up front rather than once rendered.
+/// An existing file at `path` is opened for writing to check it, but left as it is.
+pub fn check_writable(path: &Path) -> Result<(), String> {
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let probe = directory.join(format!(".easel-write-probe-{}", std::process::id()));
+    let result = File::create(&probe).and_then(|mut file| file.write_all(&[0]));
+    std::fs::remove_file(&probe).ok();
+    result.map_err(|e| format!("Can't write to {}: {}", directory.display(), e))?;
+    if path.exists() {
+        OpenOptions::new()
+            .write(true)
+            .open(path)
+            .map_err(|e| format!("Can't overwrite {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
 /// An enum used by the [AsyncTiffWriter] class to signify a write operation has finished.
 pub enum WriteFinished {
-    Finished,
+    Finished(ExportEvent),
+}
+
+/// Broadcasts [ExportEvent]s to any number of subscribers.
+/// Clones share the same subscriber list, so one can be handed to each exporter.
+#[derive(Clone, Default)]
+pub struct ExportNotifier {
+    subscribers: Arc<Mutex<Vec<Sender<ExportEvent>>>>,
+}
+
+impl ExportNotifier {
+    /// Returns a receiver that gets every export event sent after this call.
+    pub fn subscribe(&self) -> Receiver<ExportEvent> {
+        let (tx, rx) = channel();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    /// Send the event to all subscribers, dropping the ones that have hung up.
+    pub fn notify(&self, event: &ExportEvent) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.send(event.clone()).is_ok());
+    }
+}
+
+/// Estimated GPU memory use, since wgpu can't report the real figure.
+/// Each allocation site registers its size under a name, replacing its previous size.
+/// Clones share the same record, so the Canvas and Dashboard can both report to it.
+#[derive(Clone, Default)]
+pub struct VramTracker {
+    allocations: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl VramTracker {
+    /// Record the current size of an allocation site. Zero removes it.
+    pub fn set(&self, site: &str, bytes: u64) {
+        let mut allocations = self.allocations.lock().unwrap();
+        if bytes == 0 {
+            allocations.remove(site);
+        } else {
+            allocations.insert(String::from(site), bytes);
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.allocations.lock().unwrap().values().sum()
+    }
+
+    /// Every allocation site and its size, largest first.
+    pub fn allocations(&self) -> Vec<(String, u64)> {
+        let mut allocations: Vec<(String, u64)> = self
+            .allocations
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(site, bytes)| (site.clone(), *bytes))
+            .collect();
+        allocations.sort_by_key(|(_, bytes)| std::cmp::Reverse(*bytes));
+        allocations
+    }
+}
+
+/// Size in bytes of a single-mip 2D texture.
+pub fn texture_bytes(width: u32, height: u32, format: wgpu::TextureFormat) -> u64 {
+    width as u64 * height as u64 * format.describe().block_size as u64
+}
+
+/// One pass of a painting read back from GPU memory, written as a page of the TIFF.
+pub struct PaintingLayer {
+    /// Stored as the page name, e.g. `Post-process 1`.
+    pub name: String,
+    /// Half-float RGBA pixels, already mapped for reading.
+    pub buffer: wgpu::Buffer,
 }
 
 /// A struct used to write a painting to disk after rendering.
@@ -169,24 +555,57 @@ pub struct AsyncTiffWriter {}
 
 impl AsyncTiffWriter {
     /// Private helper method called by [AsyncTiffWriter::write]
-    async fn write_painting_to_disk(
-        painting: wgpu::Buffer,
+    fn write_painting_to_disk(
+        mut layers: Vec<PaintingLayer>,
         resolution: UIntVector2,
         filename: &str,
+        format: ExportFormat,
+        overlay: Option<&TextOverlay>,
+        title: Option<&str>,
         _open_external_app: bool,
-    ) {
+    ) -> Result<(), String> {
         let width = resolution.x;
         let height = resolution.y;
-        let mut pixel_data = Vec::<u8>::new();
-        transcode_painting_data(painting, resolution, &mut pixel_data).await;
-
-        {
-            let file = File::create(Path::new(filename)).unwrap();
-            let buf_writer = BufWriter::new(file);
-            TiffEncoder::new(buf_writer)
-                .write_image(&pixel_data, width, height, image::ColorType::Rgba16)
-                .unwrap();
+        let file = File::create(Path::new(filename)).map_err(|e| e.to_string())?;
+        let mut buf_writer = BufWriter::new(file);
+        // Only the tiff crate can write the title, so a titled TIFF is written like a painting of several layers.
+        if (layers.len() > 1 || title.is_some()) && format == ExportFormat::Tiff {
+            AsyncTiffWriter::write_pages(layers, resolution, overlay, title, &mut buf_writer)?;
+        } else {
+            if layers.len() > 1 {
+                warn!("Only TIFF paintings can hold every pass, writing the final output only.");
+            }
+            let mut pixel_data = Vec::<u8>::new();
+            transcode_painting_data(layers.pop().unwrap().buffer, resolution, &mut pixel_data);
+            if let Some(overlay) = overlay {
+                overlay.draw(&mut pixel_data, width, height);
+            }
+            match format {
+                ExportFormat::Png => PngEncoder::new(&mut buf_writer)
+                    .encode(&pixel_data, width, height, image::ColorType::Rgba16)
+                    .map_err(|e| e.to_string())?,
+                ExportFormat::Jpeg => {
+                    // JPEG has no alpha and only 8 bits per channel, keep the high byte of each color channel.
+                    let rgb: Vec<u8> = pixel_data
+                        .chunks_exact(8)
+                        .flat_map(|pixel| {
+                            let channel = |i: usize| {
+                                (u16::from_ne_bytes([pixel[i], pixel[i + 1]]) >> 8) as u8
+                            };
+                            vec![channel(0), channel(2), channel(4)]
+                        })
+                        .collect();
+                    JpegEncoder::new_with_quality(&mut buf_writer, 95)
+                        .encode(&rgb, width, height, image::ColorType::Rgb8)
+                        .map_err(|e| e.to_string())?
+                }
+                _ => TiffEncoder::new(&mut buf_writer)
+                    .write_image(&pixel_data, width, height, image::ColorType::Rgba16)
+                    .map_err(|e| e.to_string())?,
+            }
         }
+        // Dropping the writer would flush the rest silently, losing e.g. a full disk.
+        buf_writer.flush().map_err(|e| e.to_string())?;
         // Once writing has finished, open in external app if specified.
         #[cfg(target_os = "macos")]
         if _open_external_app {
@@ -195,43 +614,212 @@ impl AsyncTiffWriter {
                 .spawn()
                 .expect("Error launching external app to display painting.");
         }
+        Ok(())
+    }
+
+    /// Write each layer as a page of a multi-page TIFF, named with the TIFF PageName tag.
+    /// The title, if any, is stored in the DocumentName tag of every page.
+    fn write_pages(
+        layers: Vec<PaintingLayer>,
+        resolution: UIntVector2,
+        overlay: Option<&TextOverlay>,
+        title: Option<&str>,
+        writer: &mut BufWriter<File>,
+    ) -> Result<(), String> {
+        /// DocumentName and PageName, which the `tiff` crate has no names for.
+        const DOCUMENT_NAME_TAG: u16 = 269;
+        const PAGE_NAME_TAG: u16 = 285;
+        let mut encoder = tiff::encoder::TiffEncoder::new(writer).map_err(|e| e.to_string())?;
+        for layer in layers {
+            let mut pixel_data = Vec::<u8>::new();
+            transcode_painting_data(layer.buffer, resolution, &mut pixel_data);
+            if let Some(overlay) = overlay {
+                overlay.draw(&mut pixel_data, resolution.x, resolution.y);
+            }
+            let pixels: Vec<u16> = pixel_data
+                .chunks_exact(2)
+                .map(|bytes| u16::from_ne_bytes([bytes[0], bytes[1]]))
+                .collect();
+            let mut image = encoder
+                .new_image::<tiff::encoder::colortype::RGBA16>(resolution.x, resolution.y)
+                .map_err(|e| e.to_string())?;
+            image
+                .encoder()
+                .write_tag(tiff::tags::Tag::Unknown(PAGE_NAME_TAG), layer.name.as_str())
+                .map_err(|e| e.to_string())?;
+            if let Some(title) = title {
+                image
+                    .encoder()
+                    .write_tag(tiff::tags::Tag::Unknown(DOCUMENT_NAME_TAG), title)
+                    .map_err(|e| e.to_string())?;
+            }
+            image.write_data(&pixels).map_err(|e| e.to_string())?;
+        }
+        Ok(())
     }
 
-    /// Given a painting present in GPU memory, copy to CPU, construct a TIFF painting and write to disk.
-    /// Paintings are written with uncompressed 16-bit uint TIFF encoding.
+    /// Given a painting read back from GPU memory, construct a TIFF painting and write to disk.
+    /// Paintings are written with uncompressed 16-bit uint TIFF encoding, or as a 16-bit PNG or 8-bit JPEG.
+    /// A painting of several layers is written as a multi-page TIFF, one page per layer in order.
+    /// Other formats only get the final layer.
     /// **Note:** This function launches an async task and returns immediately.
     /// Use the returned [std::sync::mpsc::Receiver] object which can be used to poll for status updates.
-    /// * `painting` - WGPU buffer holding the image data.
+    /// * `layers` - Passes of the painting, the final output last. A single layer is written as a plain TIFF.
     /// * `resolution` - The width and height of the image.
-    /// * `filename` - File will be written relative to working directory and with .tiff extension.
+    /// * `file` - Written relative to working directory, in the format its extension asks for.
+    /// * `overlay` - Text burned into every layer, if any.
+    /// * `title` - Name of the piece, stored in the metadata of TIFFs. PNG and JPEG paintings are written without it.
     /// * `open_external_app` - Optionally launch external program to view the image. Only supported on macOS and Windows.
+    /// * `notifier` - Also informs these subscribers once the write has finished.
     pub fn write(
-        buffer: wgpu::Buffer,
+        layers: Vec<PaintingLayer>,
         resolution: UIntVector2,
-        filename: String,
+        file: PaintingFile,
+        overlay: Option<TextOverlay>,
+        title: Option<String>,
         open_external_app: bool,
+        notifier: ExportNotifier,
     ) -> Receiver<WriteFinished> {
         let (tx, rx) = channel();
         std::thread::spawn(move || {
-            block_on(AsyncTiffWriter::write_painting_to_disk(
-                buffer,
+            let filename = file.path;
+            let result = AsyncTiffWriter::write_painting_to_disk(
+                layers,
                 resolution,
                 &filename,
+                file.format,
+                overlay.as_ref(),
+                title.as_deref(),
                 open_external_app,
-            ));
-            info!("Wrote painting {} to disk", filename);
-            tx.send(WriteFinished::Finished).unwrap();
+            );
+            match &result {
+                Ok(_) => info!("Wrote painting {} to disk", filename),
+                Err(e) => error!("Error writing painting {}: {}", filename, e),
+            }
+            let event = ExportEvent {
+                path: PathBuf::from(&filename),
+                resolution,
+                format: file.format,
+                error: result.err(),
+            };
+            notifier.notify(&event);
+            // The Dashboard may have stopped listening.
+            tx.send(WriteFinished::Finished(event)).ok();
         });
         rx
     }
 }
 
+/// Request an adapter that can present to `surface`, preferring `power_preference`. Should none match, fall back to
+/// the other power preference and then to any other adapter that can present to it, such as a software one. `None` if
+/// there is no such adapter.
+pub async fn request_adapter(
+    instance: &wgpu::Instance,
+    surface: &wgpu::Surface,
+    power_preference: wgpu::PowerPreference,
+    label: &str,
+) -> Option<wgpu::Adapter> {
+    let other_preference = match power_preference {
+        wgpu::PowerPreference::LowPower => wgpu::PowerPreference::HighPerformance,
+        _ => wgpu::PowerPreference::LowPower,
+    };
+    let mut adapter = None;
+    for preference in [power_preference, other_preference].iter() {
+        let options = wgpu::RequestAdapterOptions {
+            compatible_surface: Some(surface),
+            power_preference: *preference,
+        };
+        adapter = instance.request_adapter(&options).await;
+        if adapter.is_some() {
+            break;
+        }
+        warn!("{}: no {:?} adapter found", label, preference);
+    }
+    if adapter.is_none() {
+        warn!(
+            "{}: no adapter matches the window, trying any available adapter",
+            label
+        );
+        // wgpu has no preferred format for a surface the adapter can't present to.
+        adapter = instance
+            .enumerate_adapters(wgpu::BackendBit::all())
+            .find(|adapter| adapter.get_swap_chain_preferred_format(surface).is_some());
+    }
+    if let Some(adapter) = &adapter {
+        let adapter_info = adapter.get_info();
+        info!(
+            "{}: using {} ({:?}, {:?})",
+            label, adapter_info.name, adapter_info.device_type, adapter_info.backend
+        );
+    }
+    adapter
+}
+
+/// Device features Easel can do without, paired with the functionality that is disabled when they are missing.
+pub const OPTIONAL_FEATURES: [(wgpu::Features, &str); 1] = [(
+    wgpu::Features::NON_FILL_POLYGON_MODE,
+    "Wireframe debug view",
+)];
+
+/// Request a device with the adapter's optional features and the given limits. Should the adapter refuse, as weaker
+/// and software adapters sometimes do, retry without optional features and then with default limits as well.
+pub async fn request_device(
+    adapter: &wgpu::Adapter,
+    limits: wgpu::Limits,
+    label: &str,
+) -> (wgpu::Device, wgpu::Queue) {
+    let wanted = OPTIONAL_FEATURES
+        .iter()
+        .fold(wgpu::Features::empty(), |features, (feature, _)| {
+            features | *feature
+        });
+    let attempts = [
+        (adapter.features() & wanted, limits.clone()),
+        (wgpu::Features::empty(), limits),
+        (wgpu::Features::empty(), wgpu::Limits::default()),
+    ];
+    let mut last_error = None;
+    for (features, limits) in attempts.iter().cloned() {
+        let desc = wgpu::DeviceDescriptor {
+            label: Some(label),
+            features,
+            limits,
+        };
+        match adapter.request_device(&desc, None).await {
+            Ok((device, queue)) => {
+                for capability in missing_capabilities(&device) {
+                    warn!("{}: {} unavailable on this adapter", label, capability);
+                }
+                return (device, queue);
+            }
+            Err(err) => {
+                warn!(
+                    "{}: device request with {:?} failed ({}), retrying with fewer features",
+                    label, features, err
+                );
+                last_error = Some(err);
+            }
+        }
+    }
+    panic!("{}: could not create a device: {:?}", label, last_error);
+}
+
+/// Functionality from [OPTIONAL_FEATURES] that `device` cannot provide.
+pub fn missing_capabilities(device: &wgpu::Device) -> Vec<&'static str> {
+    OPTIONAL_FEATURES
+        .iter()
+        .filter(|(feature, _)| !device.features().contains(*feature))
+        .map(|(_, capability)| *capability)
+        .collect()
+}
+
 /// Convenience method for constructing render and painting pipelines.
 pub fn create_pipelines(
     device: &wgpu::Device,
     layout: &wgpu::PipelineLayout,
     vs_module: &wgpu::ShaderModule,
     fs_module: &wgpu::ShaderModule,
+    fs_entry_point: &str,
     texture_formats: (
         wgpu::TextureFormat,
         wgpu::TextureFormat,
@@ -242,74 +830,60 @@ pub fn create_pipelines(
     wgpu::RenderPipeline,
     wgpu::RenderPipeline,
 ) {
-    let vertex_state = wgpu::VertexState {
-        module: &vs_module,
-        entry_point: "main",
-        buffers: &[],
-    };
-    let primitive_state = wgpu::PrimitiveState {
-        topology: wgpu::PrimitiveTopology::TriangleList,
-        cull_mode: None,
-        polygon_mode: wgpu::PolygonMode::Fill,
-        conservative: false,
-        ..Default::default()
-    };
-    let multisample_state = wgpu::MultisampleState {
-        count: 1,
-        mask: !0,
-        alpha_to_coverage_enabled: false,
-    };
-    let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label: Some("Canvas Pipeline"),
-        layout: Some(&layout),
-        vertex: vertex_state.clone(),
-        fragment: Some(wgpu::FragmentState {
-            module: &fs_module,
-            entry_point: "main",
-            targets: &[wgpu::ColorTargetState {
-                format: texture_formats.0,
-                blend: Some(BlendState {
-                    color: wgpu::BlendComponent::REPLACE,
-                    alpha: wgpu::BlendComponent::REPLACE,
-                }),
-                write_mask: wgpu::ColorWrite::ALL,
-            }],
-        }),
-        primitive: primitive_state.clone(),
-        depth_stencil: None,
-        multisample: multisample_state.clone(),
-    });
+    let render_pipeline = create_shader_pipeline(
+        device,
+        layout,
+        vs_module,
+        fs_module,
+        fs_entry_point,
+        texture_formats.0,
+        "Canvas Pipeline",
+    );
+    let painting_pipeline = create_shader_pipeline(
+        device,
+        layout,
+        vs_module,
+        fs_module,
+        fs_entry_point,
+        texture_formats.1,
+        "Painting Pipeline",
+    );
+    let movie_pipeline = create_shader_pipeline(
+        device,
+        layout,
+        vs_module,
+        fs_module,
+        fs_entry_point,
+        texture_formats.2,
+        "Movie Pipeline",
+    );
 
-    let painting_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label: Some("Painting Pipeline"),
-        layout: Some(&layout),
-        vertex: vertex_state.clone(),
-        fragment: Some(wgpu::FragmentState {
-            module: &fs_module,
-            entry_point: "main",
-            targets: &[wgpu::ColorTargetState {
-                format: texture_formats.1,
-                blend: Some(BlendState {
-                    color: wgpu::BlendComponent::REPLACE,
-                    alpha: wgpu::BlendComponent::REPLACE,
-                }),
-                write_mask: wgpu::ColorWrite::ALL,
-            }],
-        }),
-        primitive: primitive_state.clone(),
-        depth_stencil: None,
-        multisample: multisample_state.clone(),
-    });
+    (render_pipeline, painting_pipeline, movie_pipeline)
+}
 
-    let movie_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label: Some("Movie Pipeline"),
-        layout: Some(&layout),
-        vertex: vertex_state.clone(),
-        fragment: Some(wgpu::FragmentState {
-            module: &fs_module,
+/// Pipeline drawing the full-screen triangle with the painting shader into a target of the given format.
+pub fn create_shader_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    vs_module: &wgpu::ShaderModule,
+    fs_module: &wgpu::ShaderModule,
+    fs_entry_point: &str,
+    format: wgpu::TextureFormat,
+    label: &str,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: vs_module,
             entry_point: "main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: fs_module,
+            entry_point: fs_entry_point,
             targets: &[wgpu::ColorTargetState {
-                format: texture_formats.2,
+                format,
                 blend: Some(BlendState {
                     color: wgpu::BlendComponent::REPLACE,
                     alpha: wgpu::BlendComponent::REPLACE,
@@ -317,12 +891,20 @@ pub fn create_pipelines(
                 write_mask: wgpu::ColorWrite::ALL,
             }],
         }),
-        primitive: primitive_state,
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+            ..Default::default()
+        },
         depth_stencil: None,
-        multisample: multisample_state.clone(),
-    });
-
-    (render_pipeline, painting_pipeline, movie_pipeline)
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+    })
 }
 
 static RENDER_TO_SWAP_CHAIN_TEX_SHADER_BYTES: &[u8] =
@@ -331,6 +913,41 @@ pub fn create_swap_chain_pipeline(
     device: &wgpu::Device,
     vs_module: &wgpu::ShaderModule,
     sc_tex_format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    create_blit_pipeline(
+        device,
+        vs_module,
+        sc_tex_format,
+        BlendState {
+            color: wgpu::BlendComponent::REPLACE,
+            alpha: wgpu::BlendComponent::REPLACE,
+        },
+    )
+}
+
+/// Pipeline that draws a sampled texture over a full-screen quad, combined with the target using `blend`.
+pub fn create_blit_pipeline(
+    device: &wgpu::Device,
+    vs_module: &wgpu::ShaderModule,
+    sc_tex_format: wgpu::TextureFormat,
+    blend: BlendState,
+) -> wgpu::RenderPipeline {
+    let fs_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+        label: Some("Painting Fragment Shader"),
+        source: wgpu::util::make_spirv(RENDER_TO_SWAP_CHAIN_TEX_SHADER_BYTES),
+        flags: wgpu::ShaderFlags::VALIDATION,
+    });
+    create_sampling_pipeline(device, vs_module, &fs_module, sc_tex_format, blend)
+}
+
+/// Like [create_blit_pipeline], with a fragment shader of its own that samples the texture with the same bindings:
+/// the sampler at binding 0 and the texture at binding 1 of set 0.
+pub fn create_sampling_pipeline(
+    device: &wgpu::Device,
+    vs_module: &wgpu::ShaderModule,
+    fs_module: &wgpu::ShaderModule,
+    sc_tex_format: wgpu::TextureFormat,
+    blend: BlendState,
 ) -> wgpu::RenderPipeline {
     let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some("Swap Chain Pipeline Layout"),
@@ -382,25 +999,16 @@ pub fn create_swap_chain_pipeline(
         alpha_to_coverage_enabled: false,
     };
 
-    let fs_module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
-        label: Some("Painting Fragment Shader"),
-        source: wgpu::util::make_spirv(RENDER_TO_SWAP_CHAIN_TEX_SHADER_BYTES),
-        flags: wgpu::ShaderFlags::VALIDATION,
-    });
-
     let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
         label: Some("Swap Chain Pipeline"),
         layout: Some(&layout),
         vertex: vertex_state,
         fragment: Some(wgpu::FragmentState {
-            module: &fs_module,
+            module: fs_module,
             entry_point: "main",
             targets: &[wgpu::ColorTargetState {
                 format: sc_tex_format,
-                blend: Some(BlendState {
-                    color: wgpu::BlendComponent::REPLACE,
-                    alpha: wgpu::BlendComponent::REPLACE,
-                }),
+                blend: Some(blend),
                 write_mask: wgpu::ColorWrite::ALL,
             }],
         }),
@@ -412,6 +1020,115 @@ pub fn create_swap_chain_pipeline(
     pipeline
 }
 
+/// Memory available to a new allocation, unit: bytes. `None` where it can't be told.
+#[cfg(target_os = "linux")]
+pub fn available_memory() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo
+        .lines()
+        .find(|line| line.starts_with("MemAvailable:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Memory installed, unit: bytes. macOS pages memory out rather than reporting a smaller amount as available.
+#[cfg(target_os = "macos")]
+pub fn available_memory() -> Option<u64> {
+    let output = std::process::Command::new("sysctl")
+        .args(&["-n", "hw.memsize"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// Not known on this platform.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn available_memory() -> Option<u64> {
+    None
+}
+
+/// Convert tightly packed straight-alpha RGBA8 pixels to premultiplied alpha, in place.
+pub fn premultiply_alpha(pixels: &mut [u8]) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let alpha = pixel[3] as u32;
+        for component in &mut pixel[0..3] {
+            *component = ((*component as u32 * alpha + 127) / 255) as u8;
+        }
+    }
+}
+
+/// Convert tightly packed premultiplied-alpha RGBA8 pixels to straight alpha, in place.
+/// Fully transparent pixels are left black.
+pub fn unpremultiply_alpha(pixels: &mut [u8]) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let alpha = pixel[3] as u32;
+        if alpha == 0 {
+            continue;
+        }
+        for component in &mut pixel[0..3] {
+            *component = ((*component as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+        }
+    }
+}
+
+/// Element `index` of the R2 low-discrepancy sequence, centered on zero so each component is in [-0.5, 0.5).
+/// See <http://extremelearning.com.au/unreasonable-effectiveness-of-quasirandom-sequences/>
+pub fn r2_sequence(index: u32) -> (f32, f32) {
+    // The plastic number, the 2D analogue of the golden ratio.
+    const G: f64 = 1.324_717_957_244_746;
+    let n = index as f64;
+    let x = (0.5 + n / G).fract();
+    let y = (0.5 + n / (G * G)).fract();
+    (x as f32 - 0.5, y as f32 - 0.5)
+}
+
+/// Random 64-bit value, drawn from the standard library's randomly keyed hasher so we don't need a `rand` dependency.
+pub fn random_u64() -> u64 {
+    use std::hash::{BuildHasher, Hasher};
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    hasher.write_u128(now.as_nanos());
+    hasher.finish()
+}
+
+/// Random numbers that follow from a seed (SplitMix64), for randomness that can be reproduced.
+pub struct SeededRandom {
+    state: u64,
+}
+
+impl SeededRandom {
+    pub fn new(seed: u64) -> SeededRandom {
+        SeededRandom { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed in [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Format a byte count with a binary unit suffix for display, e.g. `1.5 GiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, units[unit])
+}
+
 pub fn convert_bytes_to_value<'a, T: Copy>(bytes: &'a [u8]) -> Result<T, &str> {
     if bytes.len() != std::mem::size_of::<T>() {
         return Err("Amount of bytes in slice incorrect for size of given type.");